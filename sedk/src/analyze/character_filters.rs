@@ -0,0 +1,106 @@
+use super::AnalysisError;
+use crate::dsl::character_filters::{CharacterFilter, CharacterFilterType};
+
+/// Text after character filtering, along with the offset in the original text
+/// of every boundary between characters, so that tokens can report offsets
+/// into what the user actually sent.
+pub(super) struct FilteredText {
+    pub chars: Vec<char>,
+    pub offsets: Vec<usize>,
+}
+
+impl FilteredText {
+    fn new(text: &str) -> FilteredText {
+        let mut offsets = Vec::with_capacity(text.len() + 1);
+        let mut offset = 0;
+        for c in text.chars() {
+            offsets.push(offset);
+            offset += c.len_utf16();
+        }
+        offsets.push(offset);
+        FilteredText {
+            chars: text.chars().collect(),
+            offsets,
+        }
+    }
+}
+
+pub(super) fn apply(
+    filters: &[CharacterFilter],
+    text: &str,
+) -> Result<FilteredText, AnalysisError> {
+    let mut filtered = FilteredText::new(text);
+    for filter in filters {
+        filtered = match &filter.character_filter_type {
+            CharacterFilterType::Mapping { mappings } => map(filtered, mappings),
+            other => return Err(AnalysisError::Unsupported(format!("{:?}", other))),
+        };
+    }
+    Ok(filtered)
+}
+
+// like lucene's MappingCharFilter, the longest key wins at each position and
+// replaced characters report the offsets of the text they replaced.
+fn map(text: FilteredText, mappings: &[(String, String)]) -> FilteredText {
+    let keys: Vec<(Vec<char>, &str)> = mappings
+        .iter()
+        .filter(|(from, _)| !from.is_empty())
+        .map(|(from, to)| (from.chars().collect(), to.as_str()))
+        .collect();
+    let mut chars = Vec::with_capacity(text.chars.len());
+    let mut offsets = Vec::with_capacity(text.offsets.len());
+    let mut i = 0;
+    while i < text.chars.len() {
+        let longest = keys
+            .iter()
+            .filter(|(key, _)| text.chars[i..].starts_with(key))
+            .max_by_key(|(key, _)| key.len());
+        match longest {
+            Some((key, replacement)) => {
+                for c in replacement.chars() {
+                    chars.push(c);
+                    offsets.push(text.offsets[i]);
+                }
+                i += key.len();
+            }
+            None => {
+                chars.push(text.chars[i]);
+                offsets.push(text.offsets[i]);
+                i += 1;
+            }
+        }
+    }
+    offsets.push(text.offsets[text.chars.len()]);
+    FilteredText { chars, offsets }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(mappings: &[(&str, &str)]) -> CharacterFilter {
+        CharacterFilter {
+            name: "mapping".to_string(),
+            character_filter_type: CharacterFilterType::Mapping {
+                mappings: mappings
+                    .iter()
+                    .map(|(from, to)| (from.to_string(), to.to_string()))
+                    .collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn longest_key_wins() {
+        let filtered = apply(&[mapping(&[("a", "x"), ("ab", "yz")])], "abc").unwrap();
+        assert_eq!(filtered.chars.iter().collect::<String>(), "yzc");
+        assert_eq!(filtered.offsets, vec![0, 0, 2, 3]);
+    }
+
+    #[test]
+    fn offsets_survive_chaining() {
+        let filtered = apply(&[mapping(&[("&", " and ")]), mapping(&[(" ", "")])], "a&b").unwrap();
+        assert_eq!(filtered.chars.iter().collect::<String>(), "aandb");
+        assert_eq!(filtered.offsets, vec![0, 1, 1, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,144 @@
+use super::Token;
+use std::fmt;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TokenChange {
+    Same(Token),
+    /// Expected but not produced.
+    Missing(Token),
+    /// Produced but not expected.
+    Unexpected(Token),
+}
+
+/// A token-level diff between the tokens something was expected to produce
+/// and the tokens it did produce, aligned on their longest common run.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TokenDiff {
+    pub changes: Vec<TokenChange>,
+}
+
+impl TokenDiff {
+    pub fn new(expected: &[Token], actual: &[Token]) -> TokenDiff {
+        let changes = align(expected, actual, |e, a| e == a)
+            .into_iter()
+            .map(|aligned| match aligned {
+                Aligned::Both(i, _) => TokenChange::Same(expected[i].clone()),
+                Aligned::Expected(i) => TokenChange::Missing(expected[i].clone()),
+                Aligned::Actual(j) => TokenChange::Unexpected(actual[j].clone()),
+            })
+            .collect();
+        TokenDiff { changes }
+    }
+
+    pub fn is_match(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|change| matches!(change, TokenChange::Same(_)))
+    }
+}
+
+impl fmt::Display for TokenDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            let (marker, token) = match change {
+                TokenChange::Same(token) => (' ', token),
+                TokenChange::Missing(token) => ('-', token),
+                TokenChange::Unexpected(token) => ('+', token),
+            };
+            writeln!(f, "{} {}", marker, describe(token))?;
+        }
+        Ok(())
+    }
+}
+
+/// One step through two sequences aligned on their longest common
+/// subsequence, as indices into either side.
+pub(crate) enum Aligned {
+    Both(usize, usize),
+    Expected(usize),
+    Actual(usize),
+}
+
+pub(crate) fn align<E, A, F>(expected: &[E], actual: &[A], eq: F) -> Vec<Aligned>
+where
+    F: Fn(&E, &A) -> bool,
+{
+    // lengths of the longest common subsequence of the suffixes
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if eq(&expected[i], &actual[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut aligned = Vec::with_capacity(expected.len().max(actual.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && eq(&expected[i], &actual[j]) {
+            aligned.push(Aligned::Both(i, j));
+            i += 1;
+            j += 1;
+        } else if j == actual.len() || (i < expected.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            aligned.push(Aligned::Expected(i));
+            i += 1;
+        } else {
+            aligned.push(Aligned::Actual(j));
+            j += 1;
+        }
+    }
+    aligned
+}
+
+pub(crate) fn describe(token: &Token) -> String {
+    let length = if token.position_length == 1 {
+        String::new()
+    } else {
+        format!("+{}", token.position_length)
+    };
+    format!(
+        "{:?} @{}{} [{}..{}] {}",
+        token.term, token.position, length, token.start_offset, token.end_offset, token.token_type
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(term: &str, position: usize) -> Token {
+        Token {
+            term: term.to_string(),
+            start_offset: 0,
+            end_offset: term.len(),
+            token_type: "word".to_string(),
+            position,
+            position_length: 1,
+        }
+    }
+
+    #[test]
+    fn aligned_diff() {
+        let expected = vec![token("the", 0), token("quick", 1), token("fox", 2)];
+        let actual = vec![token("the", 0), token("quik", 1), token("fox", 2)];
+        let diff = TokenDiff::new(&expected, &actual);
+        assert!(!diff.is_match());
+        assert_eq!(
+            diff.to_string(),
+            "  \"the\" @0 [0..3] word
+- \"quick\" @1 [0..5] word
++ \"quik\" @1 [0..4] word
+  \"fox\" @2 [0..3] word
+"
+        );
+    }
+
+    #[test]
+    fn identical() {
+        let tokens = vec![token("a", 0), token("b", 1)];
+        assert!(TokenDiff::new(&tokens, &tokens).is_match());
+        assert!(!TokenDiff::new(&tokens, &tokens[..1]).is_match());
+    }
+}
@@ -0,0 +1,104 @@
+use super::Token;
+use std::collections::{BTreeSet, HashMap};
+
+/// Squashes a token graph so that parallel paths share positions, the way
+/// the `flatten_graph` filter prepares graphs for indexing. Each node moves
+/// to one past the furthest node leading into it, and nodes nothing leads
+/// into keep their distance from the node before them so that gaps survive.
+pub(super) fn flatten(mut tokens: Vec<Token>) -> Vec<Token> {
+    let mut nodes = BTreeSet::new();
+    let mut incoming: HashMap<usize, Vec<usize>> = HashMap::new();
+    for token in &tokens {
+        nodes.insert(token.position);
+        nodes.insert(token.end_position());
+        incoming
+            .entry(token.end_position())
+            .or_default()
+            .push(token.position);
+    }
+
+    let mut flattened: HashMap<usize, usize> = HashMap::new();
+    let mut previous: Option<(usize, usize)> = None;
+    for node in nodes {
+        let position = match (incoming.get(&node), previous) {
+            (Some(starts), _) => starts.iter().map(|s| flattened[s] + 1).max().unwrap_or(0),
+            (None, Some((node_before, position_before))) => position_before + node - node_before,
+            (None, None) => node,
+        };
+        flattened.insert(node, position);
+        previous = Some((node, position));
+    }
+
+    for token in &mut tokens {
+        let start = flattened[&token.position];
+        let end = flattened[&token.end_position()];
+        token.position = start;
+        token.position_length = end.saturating_sub(start).max(1);
+    }
+    tokens.sort_by_key(|t| t.position);
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(term: &str, position: usize, position_length: usize) -> Token {
+        Token {
+            term: term.to_string(),
+            start_offset: 0,
+            end_offset: 0,
+            token_type: "word".to_string(),
+            position,
+            position_length,
+        }
+    }
+
+    fn summary(tokens: &[Token]) -> Vec<(&str, usize, usize)> {
+        tokens
+            .iter()
+            .map(|t| (t.term.as_str(), t.position, t.position_length))
+            .collect()
+    }
+
+    #[test]
+    fn merges_parallel_paths() {
+        let tokens = vec![
+            token("what", 0, 1),
+            token("wow", 0, 3),
+            token("wtf", 0, 5),
+            token("the", 1, 1),
+            token("fudge", 2, 3),
+            token("that's", 3, 1),
+            token("fun", 4, 1),
+            token("happened", 5, 1),
+        ];
+        assert_eq!(
+            summary(&flatten(tokens)),
+            vec![
+                ("what", 0, 1),
+                ("wow", 0, 1),
+                ("wtf", 0, 3),
+                ("the", 1, 1),
+                ("that's", 1, 1),
+                ("fudge", 2, 1),
+                ("fun", 2, 1),
+                ("happened", 3, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_linear_graphs_and_gaps() {
+        let tokens = vec![
+            token("ny", 0, 2),
+            token("new", 0, 1),
+            token("york", 1, 1),
+            token("city", 4, 1),
+        ];
+        assert_eq!(
+            summary(&flatten(tokens)),
+            vec![("ny", 0, 2), ("new", 0, 1), ("york", 1, 1), ("city", 4, 1)]
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+// Lucene's ASCIIFoldingFilter carries a table of several thousand characters.
+// Compatibility decomposition with the accents dropped covers most of it, and
+// the letters that don't decompose are listed here.
+fn special(c: char) -> Option<&'static str> {
+    Some(match c {
+        'ß' => "ss",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ø' => "o",
+        'Ø' => "O",
+        'ł' => "l",
+        'Ł' => "L",
+        'đ' | 'ð' => "d",
+        'Đ' | 'Ð' => "D",
+        'þ' => "th",
+        'Þ' => "TH",
+        'ı' => "i",
+        '‘' | '’' | '‚' | '‛' => "'",
+        '“' | '”' | '„' | '«' | '»' => "\"",
+        '–' | '—' => "-",
+        _ => return None,
+    })
+}
+
+/// Folds `term` to ascii the way the asciifolding filter would. Characters
+/// without an ascii equivalent are kept.
+pub(super) fn fold(term: &str) -> String {
+    let mut folded = String::with_capacity(term.len());
+    for c in term.chars() {
+        if c.is_ascii() {
+            folded.push(c);
+        } else if let Some(replacement) = special(c) {
+            folded.push_str(replacement);
+        } else {
+            let decomposed: String = c.nfkd().filter(|d| !is_combining_mark(*d)).collect();
+            if !decomposed.is_empty() && decomposed.is_ascii() {
+                folded.push_str(&decomposed);
+            } else {
+                folded.push(c);
+            }
+        }
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_to_ascii() {
+        assert_eq!(fold("crème brûlée"), "creme brulee");
+        assert_eq!(fold("Straße Øresund"), "Strasse Oresund");
+        assert_eq!(fold("ﬁne ²"), "fine 2");
+        assert_eq!(fold("東京"), "東京");
+    }
+}
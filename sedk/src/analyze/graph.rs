@@ -0,0 +1,68 @@
+use super::Token;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Renders a token stream as a Graphviz dot digraph. Nodes are positions and
+/// every token is an edge spanning its position length, so the paths created
+/// by multi-word synonyms show up side by side.
+pub fn to_dot(tokens: &[Token]) -> String {
+    let nodes: BTreeSet<usize> = tokens
+        .iter()
+        .flat_map(|t| vec![t.position, t.end_position()])
+        .collect();
+    let mut dot = String::from("digraph tokens {\n  rankdir = LR;\n");
+    for node in nodes {
+        writeln!(dot, "  {};", node).unwrap();
+    }
+    for token in tokens {
+        writeln!(
+            dot,
+            "  {} -> {} [label=\"{}\"];",
+            token.position,
+            token.end_position(),
+            escape(&token.term)
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn escape(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot() {
+        let token = |term: &str, position, position_length| Token {
+            term: term.to_string(),
+            start_offset: 0,
+            end_offset: 2,
+            token_type: "SYNONYM".to_string(),
+            position,
+            position_length,
+        };
+        let tokens = vec![
+            token("ny", 0, 2),
+            token("new", 0, 1),
+            token("\"york\"", 1, 1),
+        ];
+        assert_eq!(
+            to_dot(&tokens),
+            "digraph tokens {
+  rankdir = LR;
+  0;
+  1;
+  2;
+  0 -> 2 [label=\"ny\"];
+  0 -> 1 [label=\"new\"];
+  1 -> 2 [label=\"\\\"york\\\"\"];
+}
+"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Local emulation of the elasticsearch analysis chain, so that analyzers can
+//! be exercised without a cluster. Tokens carry a position length, which makes
+//! the token graphs produced by multi-word synonyms and shingles visible.
+mod character_filters;
+mod diff;
+mod flatten;
+mod folding;
+mod graph;
+mod shingle;
+mod stemmers;
+mod synonyms;
+mod token_filters;
+mod tokenizers;
+mod word_delimiter;
+
+#[cfg(any(test, feature = "testing"))]
+pub(crate) use diff::{align, describe, Aligned};
+pub use diff::{TokenChange, TokenDiff};
+pub use graph::to_dot;
+pub use stemmers::{stem, Emulation};
+
+use crate::dsl::{
+    analysis::{Analyzer, Normalizer},
+    character_filters::CharacterFilter,
+    token_processing::TokenFilter,
+    tokenizers::Tokenizer,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A single token as reported by the `_analyze` api. Offsets are in UTF-16
+/// code units of the original text, matching elasticsearch.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Token {
+    #[serde(rename = "token")]
+    pub term: String,
+    pub start_offset: usize,
+    pub end_offset: usize,
+    #[serde(rename = "type")]
+    pub token_type: String,
+    pub position: usize,
+    #[serde(
+        rename = "positionLength",
+        default = "default_position_length",
+        skip_serializing_if = "is_default_position_length"
+    )]
+    pub position_length: usize,
+}
+
+fn default_position_length() -> usize {
+    1
+}
+
+fn is_default_position_length(length: &usize) -> bool {
+    *length == 1
+}
+
+impl Token {
+    /// The node of the token graph at which this token ends.
+    pub fn end_position(&self) -> usize {
+        self.position + self.position_length
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum AnalysisError {
+    /// The component has no local emulation.
+    Unsupported(String),
+    /// A synonym rule could not be parsed by the preceding analysis chain.
+    InvalidSynonym { rule: String, reason: String },
+    /// The component's settings are ones elasticsearch refuses.
+    InvalidSettings(String),
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnalysisError::Unsupported(component) => {
+                write!(f, "{} cannot be emulated locally", component)
+            }
+            AnalysisError::InvalidSynonym { rule, reason } => {
+                write!(f, "invalid synonym rule [{}]: {}", rule, reason)
+            }
+            AnalysisError::InvalidSettings(reason) => write!(f, "invalid settings: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+impl Analyzer {
+    /// Runs `text` through a local emulation of this analyzer.
+    pub fn analyze(&self, text: &str) -> Result<Vec<Token>, AnalysisError> {
+        analyze_chain(
+            &self.character_filters,
+            &self.tokenizer,
+            &self.token_filters,
+            text,
+        )
+    }
+}
+
+impl Normalizer {
+    /// Runs `text` through a local emulation of this normalizer. Like in
+    /// elasticsearch the whole text is kept as a single term, so the
+    /// tokenizer plays no part.
+    pub fn normalize(&self, text: &str) -> Result<String, AnalysisError> {
+        let filtered = character_filters::apply(&self.character_filters, text)?;
+        let mut term: String = filtered.chars.into_iter().collect();
+        let no_synonyms = |_: &str| -> Result<Vec<Token>, AnalysisError> {
+            Err(AnalysisError::Unsupported(
+                "synonyms in a normalizer".to_string(),
+            ))
+        };
+        for filter in &self.token_filters {
+            let token = Token {
+                term,
+                start_offset: 0,
+                end_offset: 0,
+                token_type: "word".to_string(),
+                position: 0,
+                position_length: 1,
+            };
+            term = token_filters::apply(&filter.filter_type, vec![token], &no_synonyms)?
+                .into_iter()
+                .next()
+                .map(|t| t.term)
+                .unwrap_or_default();
+        }
+        Ok(term)
+    }
+}
+
+fn analyze_chain(
+    character_filters: &[CharacterFilter],
+    tokenizer: &Tokenizer,
+    token_filters: &[TokenFilter],
+    text: &str,
+) -> Result<Vec<Token>, AnalysisError> {
+    let filtered = character_filters::apply(character_filters, text)?;
+    let mut tokens = tokenizers::tokenize(&tokenizer.tokenizer_type, &filtered)?;
+    for (i, filter) in token_filters.iter().enumerate() {
+        // synonym rules are parsed with everything that precedes the filter
+        let preceding =
+            |phrase: &str| analyze_chain(character_filters, tokenizer, &token_filters[..i], phrase);
+        tokens = token_filters::apply(&filter.filter_type, tokens, &preceding)?;
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::{
+        character_filters::CharacterFilterType,
+        token_processing::{Stopwords, Synonym, TokenFilterType},
+    };
+    use serde_json::{from_value, json, to_value};
+
+    fn analyzer(token_filters: Vec<TokenFilterType>) -> Analyzer {
+        Analyzer {
+            name: "my_analyzer".to_string(),
+            character_filters: vec![CharacterFilter {
+                name: "dashes".to_string(),
+                character_filter_type: CharacterFilterType::Mapping {
+                    mappings: vec![("-".to_string(), " ".to_string())],
+                },
+            }],
+            tokenizer: Tokenizer::default(),
+            token_filters: token_filters
+                .into_iter()
+                .enumerate()
+                .map(|(i, filter_type)| TokenFilter {
+                    name: format!("filter_{}", i),
+                    filter_type,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn token_round_trip() {
+        let value = json!({
+            "token": "ny",
+            "start_offset": 0,
+            "end_offset": 2,
+            "type": "SYNONYM",
+            "position": 0,
+            "positionLength": 2
+        });
+        let token: Token = from_value(value.clone()).unwrap();
+        assert_eq!(token.end_position(), 2);
+        assert_eq!(to_value(&token).unwrap(), value);
+
+        let unit: Token = from_value(json!({
+            "token": "york",
+            "start_offset": 0,
+            "end_offset": 2,
+            "type": "word",
+            "position": 1
+        }))
+        .unwrap();
+        assert_eq!(unit.position_length, 1);
+    }
+
+    #[test]
+    fn synonyms_see_preceding_filters() {
+        let analyzer = analyzer(vec![
+            TokenFilterType::Lowercase,
+            TokenFilterType::SynonymGraph {
+                expand: true,
+                lenient: false,
+                synonyms: vec![Synonym::explicit(&["New York"], &["NYC"])],
+            },
+        ]);
+        let terms: Vec<String> = analyzer
+            .analyze("New-York city")
+            .unwrap()
+            .into_iter()
+            .map(|t| t.term)
+            .collect();
+        assert_eq!(terms, vec!["nyc", "city"]);
+    }
+
+    fn shingle(min_shingle_size: u8, max_shingle_size: u8) -> TokenFilterType {
+        TokenFilterType::Shingle {
+            max_shingle_size,
+            min_shingle_size,
+            output_unigrams: true,
+            output_unigrams_if_no_shingles: false,
+            token_separator: " ".to_string(),
+            filler_token: "_".to_string(),
+        }
+    }
+
+    #[test]
+    fn shingles_across_stopwords() {
+        let analyzer = analyzer(vec![
+            TokenFilterType::Stop {
+                stopwords: Stopwords::Predefined("english".to_string()),
+                ignore_case: false,
+            },
+            shingle(2, 3),
+        ]);
+        let terms: Vec<(String, usize)> = analyzer
+            .analyze("over the lazy dog")
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.term, t.position))
+            .collect();
+        let expected = [
+            ("over", 0),
+            ("over _", 0),
+            ("over _ lazy", 0),
+            ("_ lazy", 1),
+            ("_ lazy dog", 1),
+            ("lazy", 2),
+            ("lazy dog", 2),
+            ("dog", 3),
+        ];
+        let expected: Vec<(String, usize)> = expected
+            .iter()
+            .map(|(term, position)| (term.to_string(), *position))
+            .collect();
+        assert_eq!(terms, expected);
+    }
+
+    #[test]
+    fn shingles_refuse_stacked_synonyms() {
+        let analyzer = analyzer(vec![
+            TokenFilterType::SynonymGraph {
+                expand: true,
+                lenient: false,
+                synonyms: vec![Synonym::equivalent(&["ny", "new york"])],
+            },
+            shingle(2, 2),
+        ]);
+        assert!(matches!(
+            analyzer.analyze("ny city"),
+            Err(AnalysisError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_settings() {
+        for (min, max) in &[(1, 3), (3, 2)] {
+            assert!(matches!(
+                analyzer(vec![shingle(*min, *max)]).analyze("anything"),
+                Err(AnalysisError::InvalidSettings(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn normalize() {
+        let normalizer = Normalizer {
+            name: "my_normalizer".to_string(),
+            character_filters: analyzer(vec![]).character_filters,
+            tokenizer: Tokenizer::default(),
+            token_filters: vec![],
+        };
+        assert_eq!(
+            normalizer.normalize("New-York city").unwrap(),
+            "New York city"
+        );
+    }
+
+    #[test]
+    fn unsupported_components() {
+        let analyzer = analyzer(vec![TokenFilterType::Stemmer {
+            language: crate::dsl::token_processing::StemmerLanguage::Lovins,
+        }]);
+        assert!(matches!(
+            analyzer.analyze("anything"),
+            Err(AnalysisError::Unsupported(_))
+        ));
+    }
+}
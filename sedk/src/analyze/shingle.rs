@@ -0,0 +1,209 @@
+use super::{AnalysisError, Token};
+
+pub(super) struct Shingle<'a> {
+    pub max_shingle_size: usize,
+    pub min_shingle_size: usize,
+    pub output_unigrams: bool,
+    pub output_unigrams_if_no_shingles: bool,
+    pub token_separator: &'a str,
+    pub filler_token: &'a str,
+}
+
+impl Shingle<'_> {
+    /// Emits, for every token, its unigram followed by the shingles starting
+    /// at it in increasing size. Positions left empty by earlier filters (a
+    /// stop filter, say) take part in shingles as the filler token, and start
+    /// shingles of their own as long as those hold a token. The sizes are
+    /// expected to be valid, `2 <= min_shingle_size <= max_shingle_size`.
+    ///
+    /// Lucene's shingle filter reads stacked tokens, the ones synonym_graph
+    /// or preserve_original put at the same position, as if they followed
+    /// each other. That is not emulated, and such streams are refused.
+    pub fn apply(&self, tokens: Vec<Token>) -> Result<Vec<Token>, AnalysisError> {
+        let mut slots: Vec<Option<&Token>> = Vec::with_capacity(tokens.len());
+        for token in &tokens {
+            if token.position < slots.len() || token.position_length != 1 {
+                return Err(AnalysisError::Unsupported(
+                    "shingles over stacked tokens".to_string(),
+                ));
+            }
+            while slots.len() < token.position {
+                slots.push(None);
+            }
+            slots.push(Some(token));
+        }
+
+        let mut output = Vec::new();
+        let mut shingles = 0;
+        for (position, slot) in slots.iter().enumerate() {
+            if let (Some(token), true) = (slot, self.output_unigrams) {
+                output.push((*token).clone());
+            }
+            for size in self.min_shingle_size..=self.max_shingle_size {
+                let window = match slots.get(position..position + size) {
+                    Some(window) => window,
+                    None => break,
+                };
+                // a filler takes the offsets of the token after it, and
+                // shingles of nothing but fillers are left out
+                let (first, last) = match (
+                    window.iter().flatten().next(),
+                    window.iter().rev().flatten().next(),
+                ) {
+                    (Some(first), Some(last)) => (first, last),
+                    _ => continue,
+                };
+                let term = window
+                    .iter()
+                    .map(|slot| match slot {
+                        Some(token) => token.term.as_str(),
+                        None => self.filler_token,
+                    })
+                    .collect::<Vec<&str>>()
+                    .join(self.token_separator);
+                output.push(Token {
+                    term,
+                    start_offset: first.start_offset,
+                    end_offset: last.end_offset,
+                    token_type: "shingle".to_string(),
+                    position,
+                    position_length: size,
+                });
+                shingles += 1;
+            }
+        }
+
+        if shingles == 0 && !self.output_unigrams && self.output_unigrams_if_no_shingles {
+            Ok(tokens)
+        } else {
+            Ok(output)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(term: &str, position: usize, start_offset: usize) -> Token {
+        Token {
+            term: term.to_string(),
+            start_offset,
+            end_offset: start_offset + term.len(),
+            token_type: "word".to_string(),
+            position,
+            position_length: 1,
+        }
+    }
+
+    fn shingle() -> Shingle<'static> {
+        Shingle {
+            max_shingle_size: 3,
+            min_shingle_size: 2,
+            output_unigrams: true,
+            output_unigrams_if_no_shingles: false,
+            token_separator: " ",
+            filler_token: "_",
+        }
+    }
+
+    fn summary(tokens: &[Token]) -> Vec<(&str, usize, usize)> {
+        tokens
+            .iter()
+            .map(|t| (t.term.as_str(), t.position, t.position_length))
+            .collect()
+    }
+
+    #[test]
+    fn unigrams_then_shingles() {
+        let tokens = vec![token("a", 0, 0), token("b", 1, 2), token("c", 2, 4)];
+        let output = shingle().apply(tokens).unwrap();
+        assert_eq!(
+            summary(&output),
+            vec![
+                ("a", 0, 1),
+                ("a b", 0, 2),
+                ("a b c", 0, 3),
+                ("b", 1, 1),
+                ("b c", 1, 2),
+                ("c", 2, 1),
+            ]
+        );
+        assert_eq!(output[2].start_offset, 0);
+        assert_eq!(output[2].end_offset, 5);
+        assert_eq!(output[2].token_type, "shingle");
+    }
+
+    #[test]
+    fn filler_for_gaps() {
+        let tokens = vec![token("please", 0, 0), token("divide", 2, 11)];
+        let output = Shingle {
+            output_unigrams: false,
+            ..shingle()
+        }
+        .apply(tokens)
+        .unwrap();
+        assert_eq!(
+            summary(&output),
+            vec![
+                ("please _", 0, 2),
+                ("please _ divide", 0, 3),
+                ("_ divide", 1, 2)
+            ]
+        );
+        assert_eq!(output[0].end_offset, 6);
+        assert_eq!((output[2].start_offset, output[2].end_offset), (11, 17));
+    }
+
+    #[test]
+    fn unigrams_if_no_shingles() {
+        let tokens = vec![token("alone", 0, 0)];
+        let settings = Shingle {
+            output_unigrams: false,
+            output_unigrams_if_no_shingles: true,
+            ..shingle()
+        };
+        assert_eq!(
+            summary(&settings.apply(tokens.clone()).unwrap()),
+            vec![("alone", 0, 1)]
+        );
+        let settings = Shingle {
+            output_unigrams_if_no_shingles: false,
+            ..settings
+        };
+        assert_eq!(summary(&settings.apply(tokens).unwrap()), vec![]);
+    }
+
+    #[test]
+    fn separator_and_minimum_size() {
+        let tokens = vec![
+            token("a", 0, 0),
+            token("b", 1, 2),
+            token("c", 2, 4),
+            token("d", 3, 6),
+        ];
+        let output = Shingle {
+            min_shingle_size: 3,
+            max_shingle_size: 3,
+            output_unigrams: false,
+            token_separator: "+",
+            ..shingle()
+        }
+        .apply(tokens)
+        .unwrap();
+        assert_eq!(summary(&output), vec![("a+b+c", 0, 3), ("b+c+d", 1, 3)]);
+    }
+
+    #[test]
+    fn stacked_tokens_are_refused() {
+        let stacked = vec![
+            token("cafe", 0, 0),
+            token("café", 0, 0),
+            token("noir", 1, 6),
+        ];
+        assert!(matches!(
+            shingle().apply(stacked),
+            Err(AnalysisError::Unsupported(_))
+        ));
+    }
+}
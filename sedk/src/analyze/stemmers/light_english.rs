@@ -0,0 +1,116 @@
+// An approximation of kstem, the light_english stemmer. kstem only strips an
+// inflectional suffix when the result is in its lexicon, so without that
+// lexicon we apply its plural, past tense and aspect rules to any lowercase
+// ascii word and accept that irregular words will come out differently.
+
+pub(super) fn stem(term: &str) -> String {
+    if term.len() < 3 || !term.bytes().all(|b| b.is_ascii_lowercase()) {
+        return term.to_string();
+    }
+    let mut word = term.to_string();
+    plural(&mut word);
+    past_tense(&mut word);
+    aspect(&mut word);
+    word
+}
+
+fn is_vowel(c: u8) -> bool {
+    matches!(c, b'a' | b'e' | b'i' | b'o' | b'u')
+}
+
+fn has_vowel(stem: &str) -> bool {
+    stem.bytes().any(is_vowel)
+}
+
+fn plural(word: &mut String) {
+    if word.ends_with("ies") && word.len() > 4 {
+        word.truncate(word.len() - 3);
+        word.push('y');
+    } else if ["sses", "xes", "zes", "ches", "shes"]
+        .iter()
+        .any(|suffix| word.ends_with(suffix))
+    {
+        word.truncate(word.len() - 2);
+    } else if word.ends_with('s')
+        && !["ss", "us", "is"]
+            .iter()
+            .any(|suffix| word.ends_with(suffix))
+    {
+        word.truncate(word.len() - 1);
+    }
+}
+
+fn past_tense(word: &mut String) {
+    if word.ends_with("ied") && word.len() > 4 {
+        word.truncate(word.len() - 3);
+        word.push('y');
+    } else if word.ends_with("ed") && !word.ends_with("eed") && strip(word, 2) {
+        restore_stem(word);
+    }
+}
+
+fn aspect(word: &mut String) {
+    if word.ends_with("ing") && word.len() > 5 && strip(word, 3) {
+        restore_stem(word);
+    }
+}
+
+// removes the last `length` bytes if what remains still has a vowel
+fn strip(word: &mut String, length: usize) -> bool {
+    let stem = &word[..word.len() - length];
+    if has_vowel(stem) {
+        word.truncate(stem.len());
+        true
+    } else {
+        false
+    }
+}
+
+// undoes the doubled consonant of stopped or running, and puts back the e
+// dropped from hoped
+fn restore_stem(word: &mut String) {
+    let b = word.as_bytes();
+    let n = b.len();
+    if n >= 2
+        && b[n - 1] == b[n - 2]
+        && !is_vowel(b[n - 1])
+        && !matches!(b[n - 1], b'l' | b's' | b'z')
+    {
+        word.truncate(n - 1);
+    } else if n == 3
+        && !is_vowel(b[0])
+        && is_vowel(b[1])
+        && !is_vowel(b[2])
+        && !matches!(b[2], b'w' | b'x' | b'y')
+    {
+        word.push('e');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflections() {
+        let cases = [
+            ("books", "book"),
+            ("ponies", "pony"),
+            ("boxes", "box"),
+            ("churches", "church"),
+            ("glass", "glass"),
+            ("walked", "walk"),
+            ("carried", "carry"),
+            ("stopped", "stop"),
+            ("hoped", "hope"),
+            ("walking", "walk"),
+            ("running", "run"),
+            ("falling", "fall"),
+            ("sing", "sing"),
+            ("Books", "Books"),
+        ];
+        for (term, expected) in &cases {
+            assert_eq!(&stem(term), expected, "stemming {}", term);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Stemmers for the `stemmer` token filter. The snowball languages use the
+//! same snowball algorithms lucene compiles, everything else is ported from
+//! the lucene filter elasticsearch picks for the language.
+mod light_english;
+mod porter;
+
+use super::AnalysisError;
+use crate::dsl::token_processing::StemmerLanguage;
+use rust_stemmers::{Algorithm, Stemmer};
+
+/// How closely a stemmer language is reproduced locally.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Emulation {
+    /// The algorithm elasticsearch runs.
+    Exact,
+    /// A stand-in which agrees on regular inflections but not on every term.
+    Approximate,
+    /// Not available locally. Analysis fails with `AnalysisError::Unsupported`.
+    Unsupported,
+}
+
+impl StemmerLanguage {
+    pub fn emulation(&self) -> Emulation {
+        match self {
+            StemmerLanguage::English
+            | StemmerLanguage::Porter2
+            | StemmerLanguage::MinimalEnglish
+            | StemmerLanguage::PossessiveEnglish
+            | StemmerLanguage::German
+            | StemmerLanguage::German2
+            | StemmerLanguage::French
+            | StemmerLanguage::Spanish => Emulation::Exact,
+            // kstem leans on a lexicon of several thousand words which we
+            // don't ship, so only its suffix rules are reproduced.
+            StemmerLanguage::LightEnglish => Emulation::Approximate,
+            _ => Emulation::Unsupported,
+        }
+    }
+}
+
+/// Stems a single term the way the `stemmer` filter would for `language`.
+pub fn stem(language: &StemmerLanguage, term: &str) -> Result<String, AnalysisError> {
+    Ok(match language {
+        StemmerLanguage::English => porter::stem(term),
+        StemmerLanguage::Porter2 => snowball(Algorithm::English, term),
+        StemmerLanguage::LightEnglish => light_english::stem(term),
+        StemmerLanguage::MinimalEnglish => minimal_english(term),
+        StemmerLanguage::PossessiveEnglish => possessive_english(term),
+        StemmerLanguage::German => snowball(Algorithm::German, term),
+        StemmerLanguage::German2 => snowball(Algorithm::German, &german2_prelude(term)),
+        StemmerLanguage::French => snowball(Algorithm::French, term),
+        StemmerLanguage::Spanish => snowball(Algorithm::Spanish, term),
+        other => {
+            return Err(AnalysisError::Unsupported(format!(
+                "Stemmer {{ language: {:?} }}",
+                other
+            )))
+        }
+    })
+}
+
+fn snowball(algorithm: Algorithm, term: &str) -> String {
+    Stemmer::create(algorithm).stem(term).into_owned()
+}
+
+// lucene's EnglishMinimalStemmer, an s-stemmer
+fn minimal_english(term: &str) -> String {
+    let s: Vec<char> = term.chars().collect();
+    let len = s.len();
+    if len < 3 || s[len - 1] != 's' {
+        return term.to_string();
+    }
+    let keep = match s[len - 2] {
+        'u' | 's' => len,
+        'e' if len > 3 && s[len - 3] == 'i' && s[len - 4] != 'a' && s[len - 4] != 'e' => {
+            return s[..len - 3].iter().collect::<String>() + "y";
+        }
+        'e' if matches!(s[len - 3], 'i' | 'a' | 'o' | 'e') => len,
+        _ => len - 1,
+    };
+    s[..keep].iter().collect()
+}
+
+// lucene's EnglishPossessiveFilter
+fn possessive_english(term: &str) -> String {
+    let s: Vec<char> = term.chars().collect();
+    let len = s.len();
+    if len >= 2
+        && matches!(s[len - 2], '\'' | '\u{2019}' | '\u{FF07}')
+        && matches!(s[len - 1], 's' | 'S')
+    {
+        s[..len - 2].iter().collect()
+    } else {
+        term.to_string()
+    }
+}
+
+// german2 differs from german only in its prelude, which spells out umlauts
+// written as two letters. u and y between vowels are protected first, like
+// snowball marks them, so the substitution doesn't touch them.
+fn german2_prelude(term: &str) -> String {
+    let vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'ä' | 'ö' | 'ü');
+    let s: Vec<char> = term.chars().collect();
+    let mut protected = vec![false; s.len()];
+    for i in 1..s.len().saturating_sub(1) {
+        if vowel(s[i - 1]) && !protected[i - 1] && matches!(s[i], 'u' | 'y') && vowel(s[i + 1]) {
+            protected[i] = true;
+        }
+    }
+    let mut output = String::with_capacity(term.len());
+    let mut i = 0;
+    while i < s.len() {
+        let next = s.get(i + 1).copied();
+        match (s[i], next) {
+            ('q', Some('u')) => {
+                output.push_str("qu");
+                i += 2;
+            }
+            ('a', Some('e')) => {
+                output.push('ä');
+                i += 2;
+            }
+            ('o', Some('e')) => {
+                output.push('ö');
+                i += 2;
+            }
+            ('u', Some('e')) if !protected[i] => {
+                output.push('ü');
+                i += 2;
+            }
+            (c, _) => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stems(language: StemmerLanguage, cases: &[(&str, &str)]) {
+        for (term, expected) in cases {
+            assert_eq!(
+                &stem(&language, term).unwrap(),
+                expected,
+                "stemming {}",
+                term
+            );
+        }
+    }
+
+    #[test]
+    fn minimal_english() {
+        stems(
+            StemmerLanguage::MinimalEnglish,
+            &[
+                ("queries", "query"),
+                ("phrases", "phrase"),
+                ("corpus", "corpus"),
+                ("stress", "stress"),
+                ("kings", "king"),
+                ("panels", "panel"),
+                ("aerodynamics", "aerodynamic"),
+                ("congress", "congress"),
+                ("serious", "serious"),
+                ("series", "sery"),
+                ("toes", "toes"),
+                ("bees", "bees"),
+            ],
+        );
+    }
+
+    #[test]
+    fn possessive_english() {
+        stems(
+            StemmerLanguage::PossessiveEnglish,
+            &[
+                ("elasticsearch's", "elasticsearch"),
+                ("DOG'S", "DOG"),
+                ("dog\u{2019}s", "dog"),
+                ("dogs'", "dogs'"),
+                ("s", "s"),
+            ],
+        );
+    }
+
+    #[test]
+    fn german2() {
+        stems(
+            StemmerLanguage::German2,
+            &[
+                ("schoen", "schon"),
+                ("schön", "schon"),
+                ("fuer", "fur"),
+                ("quelle", "quell"),
+                ("blaue", "blau"),
+            ],
+        );
+    }
+
+    #[test]
+    fn emulation() {
+        assert_eq!(StemmerLanguage::Porter2.emulation(), Emulation::Exact);
+        assert_eq!(
+            StemmerLanguage::LightEnglish.emulation(),
+            Emulation::Approximate
+        );
+        assert_eq!(StemmerLanguage::Lovins.emulation(), Emulation::Unsupported);
+        assert!(matches!(
+            stem(&StemmerLanguage::Lovins, "nationally"),
+            Err(AnalysisError::Unsupported(_))
+        ));
+    }
+}
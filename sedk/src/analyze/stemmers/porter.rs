@@ -0,0 +1,371 @@
+// The original porter algorithm, as in lucene's PorterStemmer which follows
+// Martin Porter's reference implementation, including its departures from
+// the paper (bli -> ble and logi -> log in step 2).
+
+pub(super) fn stem(term: &str) -> String {
+    let mut stemmer = Porter {
+        b: term.chars().collect(),
+        k: 0,
+        j: 0,
+    };
+    if stemmer.b.len() <= 2 {
+        return term.to_string();
+    }
+    stemmer.k = stemmer.b.len() - 1;
+    stemmer.step1();
+    stemmer.step2();
+    stemmer.step3();
+    stemmer.step4();
+    stemmer.step5();
+    stemmer.step6();
+    stemmer.b[..=stemmer.k].iter().collect()
+}
+
+// b[..=k] is the current word, and j marks the end of the stem once a suffix
+// has been matched by `ends`.
+struct Porter {
+    b: Vec<char>,
+    k: usize,
+    j: usize,
+}
+
+impl Porter {
+    fn cons(&self, i: usize) -> bool {
+        match self.b[i] {
+            'a' | 'e' | 'i' | 'o' | 'u' => false,
+            'y' => i == 0 || !self.cons(i - 1),
+            _ => true,
+        }
+    }
+
+    // the number of vowel-consonant sequences in b[..=j]
+    fn m(&self) -> usize {
+        let mut n = 0;
+        let mut i = 0;
+        loop {
+            if i > self.j {
+                return n;
+            }
+            if !self.cons(i) {
+                break;
+            }
+            i += 1;
+        }
+        i += 1;
+        loop {
+            loop {
+                if i > self.j {
+                    return n;
+                }
+                if self.cons(i) {
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+            n += 1;
+            loop {
+                if i > self.j {
+                    return n;
+                }
+                if !self.cons(i) {
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+        }
+    }
+
+    fn vowel_in_stem(&self) -> bool {
+        (0..=self.j).any(|i| !self.cons(i))
+    }
+
+    fn double_c(&self, j: usize) -> bool {
+        j >= 1 && self.b[j] == self.b[j - 1] && self.cons(j)
+    }
+
+    fn cvc(&self, i: usize) -> bool {
+        i >= 2
+            && self.cons(i)
+            && !self.cons(i - 1)
+            && self.cons(i - 2)
+            && !matches!(self.b[i], 'w' | 'x' | 'y')
+    }
+
+    fn ends(&mut self, s: &str) -> bool {
+        let s: Vec<char> = s.chars().collect();
+        if s.len() > self.k + 1 || self.b[self.k + 1 - s.len()..=self.k] != s[..] {
+            return false;
+        }
+        // j may wrap when the suffix is the whole word, in which case nothing
+        // that reads it can match
+        self.j = (self.k + 1 - s.len()).wrapping_sub(1);
+        true
+    }
+
+    fn set_to(&mut self, s: &str) {
+        let start = self.j.wrapping_add(1);
+        self.b.truncate(start);
+        self.b.extend(s.chars());
+        self.k = (start + s.chars().count()).wrapping_sub(1);
+    }
+
+    fn r(&mut self, s: &str) {
+        if self.m() > 0 {
+            self.set_to(s);
+        }
+    }
+
+    fn whole_word_suffix(&self) -> bool {
+        self.j == usize::MAX
+    }
+
+    // plurals, -ed and -ing
+    fn step1(&mut self) {
+        if self.b[self.k] == 's' {
+            if self.ends("sses") {
+                self.k -= 2;
+            } else if self.ends("ies") {
+                self.set_to("i");
+            } else if self.b[self.k - 1] != 's' {
+                self.k -= 1;
+            }
+        }
+        if self.ends("eed") {
+            if !self.whole_word_suffix() && self.m() > 0 {
+                self.k -= 1;
+            }
+        } else if (self.ends("ed") || self.ends("ing"))
+            && !self.whole_word_suffix()
+            && self.vowel_in_stem()
+        {
+            self.k = self.j;
+            if self.ends("at") {
+                self.set_to("ate");
+            } else if self.ends("bl") {
+                self.set_to("ble");
+            } else if self.ends("iz") {
+                self.set_to("ize");
+            } else if self.double_c(self.k) {
+                if !matches!(self.b[self.k], 'l' | 's' | 'z') {
+                    self.k -= 1;
+                }
+            } else if self.m() == 1 && self.cvc(self.k) {
+                self.set_to("e");
+            }
+        }
+    }
+
+    // y -> i when there is another vowel in the stem
+    fn step2(&mut self) {
+        if self.ends("y") && !self.whole_word_suffix() && self.vowel_in_stem() {
+            self.b[self.k] = 'i';
+        }
+    }
+
+    // double suffixes map to single ones
+    fn step3(&mut self) {
+        if self.k == 0 {
+            return;
+        }
+        let replacements: &[(&str, &str)] = match self.b[self.k - 1] {
+            'a' => &[("ational", "ate"), ("tional", "tion")],
+            'c' => &[("enci", "ence"), ("anci", "ance")],
+            'e' => &[("izer", "ize")],
+            'l' => &[
+                ("bli", "ble"),
+                ("alli", "al"),
+                ("entli", "ent"),
+                ("eli", "e"),
+                ("ousli", "ous"),
+            ],
+            'o' => &[("ization", "ize"), ("ation", "ate"), ("ator", "ate")],
+            's' => &[
+                ("alism", "al"),
+                ("iveness", "ive"),
+                ("fulness", "ful"),
+                ("ousness", "ous"),
+            ],
+            't' => &[("aliti", "al"), ("iviti", "ive"), ("biliti", "ble")],
+            'g' => &[("logi", "log")],
+            _ => &[],
+        };
+        self.replace_first(replacements);
+    }
+
+    // -ic-, -full, -ness etc.
+    fn step4(&mut self) {
+        let replacements: &[(&str, &str)] = match self.b[self.k] {
+            'e' => &[("icate", "ic"), ("ative", ""), ("alize", "al")],
+            'i' => &[("iciti", "ic")],
+            'l' => &[("ical", "ic"), ("ful", "")],
+            's' => &[("ness", "")],
+            _ => &[],
+        };
+        self.replace_first(replacements);
+    }
+
+    fn replace_first(&mut self, replacements: &[(&str, &str)]) {
+        for (suffix, replacement) in replacements {
+            if self.ends(suffix) {
+                if !self.whole_word_suffix() {
+                    self.r(replacement);
+                }
+                return;
+            }
+        }
+    }
+
+    // -ant, -ence etc. in context <c>vcvc<v>
+    fn step5(&mut self) {
+        if self.k == 0 {
+            return;
+        }
+        let suffixes: &[&str] = match self.b[self.k - 1] {
+            'a' => &["al"],
+            'c' => &["ance", "ence"],
+            'e' => &["er"],
+            'i' => &["ic"],
+            'l' => &["able", "ible"],
+            'n' => &["ant", "ement", "ment", "ent"],
+            'o' => &["ion", "ou"],
+            's' => &["ism"],
+            't' => &["ate", "iti"],
+            'u' => &["ous"],
+            'v' => &["ive"],
+            'z' => &["ize"],
+            _ => &[],
+        };
+        let mut matched = false;
+        for suffix in suffixes {
+            if self.ends(suffix) {
+                // -ion only goes after s or t
+                if *suffix == "ion"
+                    && (self.whole_word_suffix() || !matches!(self.b[self.j], 's' | 't'))
+                {
+                    continue;
+                }
+                matched = true;
+                break;
+            }
+        }
+        if matched && !self.whole_word_suffix() && self.m() > 1 {
+            self.k = self.j;
+        }
+    }
+
+    // removes a final -e and -ll when the stem is long enough
+    fn step6(&mut self) {
+        self.j = self.k;
+        if self.b[self.k] == 'e' {
+            let a = self.m();
+            if a > 1 || a == 1 && !self.cvc(self.k - 1) {
+                self.k -= 1;
+            }
+        }
+        if self.b[self.k] == 'l' && self.double_c(self.k) && self.m() > 1 {
+            self.k -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porter_paper_examples() {
+        let cases = [
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("ties", "ti"),
+            ("caress", "caress"),
+            ("cats", "cat"),
+            ("feed", "feed"),
+            ("agreed", "agre"),
+            ("plastered", "plaster"),
+            ("bled", "bled"),
+            ("motoring", "motor"),
+            ("sing", "sing"),
+            ("conflated", "conflat"),
+            ("troubled", "troubl"),
+            ("sized", "size"),
+            ("hopping", "hop"),
+            ("tanned", "tan"),
+            ("falling", "fall"),
+            ("hissing", "hiss"),
+            ("fizzed", "fizz"),
+            ("failing", "fail"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("sky", "sky"),
+            ("relational", "relat"),
+            ("conditional", "condit"),
+            ("rational", "ration"),
+            ("valenci", "valenc"),
+            ("hesitanci", "hesit"),
+            ("digitizer", "digit"),
+            ("conformabli", "conform"),
+            ("radicalli", "radic"),
+            ("differentli", "differ"),
+            ("vileli", "vile"),
+            ("analogousli", "analog"),
+            ("vietnamization", "vietnam"),
+            ("predication", "predic"),
+            ("operator", "oper"),
+            ("feudalism", "feudal"),
+            ("decisiveness", "decis"),
+            ("hopefulness", "hope"),
+            ("callousness", "callous"),
+            ("formaliti", "formal"),
+            ("sensitiviti", "sensit"),
+            ("sensibiliti", "sensibl"),
+            ("triplicate", "triplic"),
+            ("formative", "form"),
+            ("formalize", "formal"),
+            ("electriciti", "electr"),
+            ("electrical", "electr"),
+            ("hopeful", "hope"),
+            ("goodness", "good"),
+            ("revival", "reviv"),
+            ("allowance", "allow"),
+            ("inference", "infer"),
+            ("airliner", "airlin"),
+            ("gyroscopic", "gyroscop"),
+            ("adjustable", "adjust"),
+            ("defensible", "defens"),
+            ("irritant", "irrit"),
+            ("replacement", "replac"),
+            ("adjustment", "adjust"),
+            ("dependent", "depend"),
+            ("adoption", "adopt"),
+            ("homologou", "homolog"),
+            ("communism", "commun"),
+            ("activate", "activ"),
+            ("angulariti", "angular"),
+            ("homologous", "homolog"),
+            ("effective", "effect"),
+            ("bowdlerize", "bowdler"),
+            ("probate", "probat"),
+            ("rate", "rate"),
+            ("cease", "ceas"),
+            ("controll", "control"),
+            ("roll", "roll"),
+            ("generalizations", "gener"),
+            ("oscillators", "oscil"),
+            ("archaeology", "archaeolog"),
+        ];
+        for (term, expected) in &cases {
+            assert_eq!(&stem(term), expected, "stemming {}", term);
+        }
+    }
+
+    #[test]
+    fn degenerate_words() {
+        for term in &["", "a", "is", "ed", "ing", "eed", "ies", "y", "ion", "sses"] {
+            stem(term);
+        }
+        assert_eq!(stem("as"), "as");
+    }
+}
@@ -0,0 +1,287 @@
+use super::{AnalysisError, Token};
+use crate::dsl::token_processing::Synonym;
+use std::collections::HashMap;
+
+const SYNONYM_TYPE: &str = "SYNONYM";
+
+/// Parsed synonym rules, keyed by the analyzed terms of each input phrase.
+pub(super) struct SynonymMap {
+    rules: HashMap<Vec<String>, Vec<Vec<String>>>,
+    longest: usize,
+}
+
+impl SynonymMap {
+    pub fn build<F>(
+        synonyms: &[Synonym],
+        expand: bool,
+        lenient: bool,
+        analyze: &F,
+    ) -> Result<SynonymMap, AnalysisError>
+    where
+        F: Fn(&str) -> Result<Vec<Token>, AnalysisError>,
+    {
+        let mut map = SynonymMap {
+            rules: HashMap::new(),
+            longest: 0,
+        };
+        for synonym in synonyms {
+            let parse = |phrases: &[String]| -> Result<Vec<Vec<String>>, AnalysisError> {
+                let mut parsed = Vec::new();
+                for phrase in phrases {
+                    let terms: Vec<String> = analyze(phrase)?.into_iter().map(|t| t.term).collect();
+                    if !terms.is_empty() {
+                        parsed.push(terms);
+                    } else if !lenient {
+                        return Err(AnalysisError::InvalidSynonym {
+                            rule: serde_json::to_string(synonym).unwrap_or_default(),
+                            reason: format!(
+                                "term: {} was completely eliminated by analyzer",
+                                phrase
+                            ),
+                        });
+                    }
+                }
+                Ok(parsed)
+            };
+            let from = parse(&synonym.from)?;
+            if synonym.to.is_empty() {
+                for input in &from {
+                    let outputs = if expand { &from[..] } else { &from[..1] };
+                    map.add(input, outputs);
+                }
+            } else {
+                let to = parse(&synonym.to)?;
+                for input in &from {
+                    map.add(input, &to);
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    fn add(&mut self, input: &[String], outputs: &[Vec<String>]) {
+        self.longest = self.longest.max(input.len());
+        let existing = self.rules.entry(input.to_vec()).or_default();
+        for output in outputs {
+            if !existing.contains(output) {
+                existing.push(output.clone());
+            }
+        }
+    }
+
+    /// Replaces the longest matching run of tokens at each position with a
+    /// graph holding one path per output. Every path starts and ends on the
+    /// same nodes, so tokens after the match shift to make room for the
+    /// internal nodes of multi-token paths.
+    pub fn apply(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut output = Vec::with_capacity(tokens.len());
+        let mut shift: isize = 0;
+        let mut i = 0;
+        while i < tokens.len() {
+            let shifted = |position: usize| (position as isize + shift) as usize;
+            let (matched, paths) = match self.longest_match(&tokens[i..]) {
+                Some(found) => found,
+                None => {
+                    output.push(Token {
+                        position: shifted(tokens[i].position),
+                        ..tokens[i].clone()
+                    });
+                    i += 1;
+                    continue;
+                }
+            };
+            let matched = &tokens[i..i + matched];
+            let start = shifted(matched[0].position);
+            let end = start + 1 + paths.iter().map(|p| p.len() - 1).sum::<usize>();
+            let mut next_node = start + 1;
+            let mut emitted = Vec::new();
+            for path in paths {
+                let original = path.len() == matched.len()
+                    && path.iter().zip(matched).all(|(term, t)| *term == t.term);
+                let mut from = start;
+                for (j, term) in path.iter().enumerate() {
+                    let to = if j + 1 == path.len() {
+                        end
+                    } else {
+                        next_node += 1;
+                        next_node - 1
+                    };
+                    let (start_offset, end_offset, token_type) = if original {
+                        (
+                            matched[j].start_offset,
+                            matched[j].end_offset,
+                            matched[j].token_type.clone(),
+                        )
+                    } else {
+                        (
+                            matched[0].start_offset,
+                            matched[matched.len() - 1].end_offset,
+                            SYNONYM_TYPE.to_string(),
+                        )
+                    };
+                    emitted.push(Token {
+                        term: term.clone(),
+                        start_offset,
+                        end_offset,
+                        token_type,
+                        position: from,
+                        position_length: to - from,
+                    });
+                    from = to;
+                }
+            }
+            emitted.sort_by_key(|t| t.position);
+            output.append(&mut emitted);
+            shift += (end - start) as isize - matched.len() as isize;
+            i += matched.len();
+        }
+        output
+    }
+
+    fn longest_match(&self, tokens: &[Token]) -> Option<(usize, &Vec<Vec<String>>)> {
+        let mut consecutive = 1;
+        while consecutive < tokens.len().min(self.longest)
+            && tokens[consecutive].position == tokens[consecutive - 1].position + 1
+        {
+            consecutive += 1;
+        }
+        (1..=consecutive).rev().find_map(|length| {
+            let key: Vec<String> = tokens[..length].iter().map(|t| t.term.clone()).collect();
+            self.rules.get(&key).map(|paths| (length, paths))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whitespace(text: &str) -> Result<Vec<Token>, AnalysisError> {
+        Ok(tokens(&text.split_whitespace().collect::<Vec<&str>>()))
+    }
+
+    fn tokens(terms: &[&str]) -> Vec<Token> {
+        let mut offset = 0;
+        terms
+            .iter()
+            .enumerate()
+            .map(|(position, term)| {
+                let token = Token {
+                    term: term.to_string(),
+                    start_offset: offset,
+                    end_offset: offset + term.len(),
+                    token_type: "word".to_string(),
+                    position,
+                    position_length: 1,
+                };
+                offset += term.len() + 1;
+                token
+            })
+            .collect()
+    }
+
+    fn graph(synonyms: &[Synonym], expand: bool, text: &[&str]) -> Vec<(String, usize, usize)> {
+        SynonymMap::build(synonyms, expand, false, &whitespace)
+            .unwrap()
+            .apply(tokens(text))
+            .into_iter()
+            .map(|t| (t.term, t.position, t.position_length))
+            .collect()
+    }
+
+    fn summary(tokens: &[(&str, usize, usize)]) -> Vec<(String, usize, usize)> {
+        tokens
+            .iter()
+            .map(|(term, position, length)| (term.to_string(), *position, *length))
+            .collect()
+    }
+
+    #[test]
+    fn single_to_multi_token() {
+        assert_eq!(
+            graph(
+                &[Synonym::equivalent(&["ny", "new york"])],
+                true,
+                &["ny", "city"]
+            ),
+            summary(&[("ny", 0, 2), ("new", 0, 1), ("york", 1, 1), ("city", 2, 1)])
+        );
+    }
+
+    #[test]
+    fn multi_to_single_token() {
+        assert_eq!(
+            graph(
+                &[Synonym::explicit(&["new york"], &["ny"])],
+                true,
+                &["new", "york", "city"]
+            ),
+            summary(&[("ny", 0, 1), ("city", 1, 1)])
+        );
+    }
+
+    #[test]
+    fn without_expand_maps_to_first() {
+        assert_eq!(
+            graph(
+                &[Synonym::equivalent(&["ny", "new york"])],
+                false,
+                &["new", "york"]
+            ),
+            summary(&[("ny", 0, 1)])
+        );
+    }
+
+    #[test]
+    fn parallel_multi_token_paths() {
+        assert_eq!(
+            graph(
+                &[Synonym::explicit(
+                    &["wtf"],
+                    &["what the fudge", "wow that's fun"]
+                )],
+                true,
+                &["wtf", "happened"]
+            ),
+            summary(&[
+                ("what", 0, 1),
+                ("wow", 0, 3),
+                ("the", 1, 1),
+                ("fudge", 2, 3),
+                ("that's", 3, 1),
+                ("fun", 4, 1),
+                ("happened", 5, 1),
+            ])
+        );
+    }
+
+    #[test]
+    fn original_keeps_offsets() {
+        let output = SynonymMap::build(
+            &[Synonym::equivalent(&["new york", "nyc"])],
+            true,
+            false,
+            &whitespace,
+        )
+        .unwrap()
+        .apply(tokens(&["new", "york"]));
+        let york = output.iter().find(|t| t.term == "york").unwrap();
+        assert_eq!((york.start_offset, york.end_offset), (4, 8));
+        assert_eq!(york.token_type, "word");
+        let nyc = output.iter().find(|t| t.term == "nyc").unwrap();
+        assert_eq!((nyc.start_offset, nyc.end_offset), (0, 8));
+        assert_eq!(nyc.token_type, SYNONYM_TYPE);
+        assert_eq!(nyc.position_length, 2);
+    }
+
+    #[test]
+    fn lenient_skips_eliminated_phrases() {
+        let rules = [Synonym::equivalent(&["a", "   "])];
+        assert!(matches!(
+            SynonymMap::build(&rules, true, false, &whitespace),
+            Err(AnalysisError::InvalidSynonym { .. })
+        ));
+        let map = SynonymMap::build(&rules, true, true, &whitespace).unwrap();
+        assert_eq!(map.apply(tokens(&["a"])).len(), 1);
+    }
+}
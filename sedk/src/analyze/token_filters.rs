@@ -0,0 +1,243 @@
+use super::{
+    flatten::flatten, folding::fold, shingle::Shingle, stemmers::stem, synonyms::SynonymMap,
+    word_delimiter::WordDelimiter, AnalysisError, Token,
+};
+use crate::dsl::token_processing::{Stopwords, TokenFilterType};
+
+// lucene's EnglishAnalyzer.ENGLISH_STOP_WORDS_SET, which _english_ refers to
+const ENGLISH_STOPWORDS: [&str; 33] = [
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it",
+    "no", "not", "of", "on", "or", "such", "that", "the", "their", "then", "there", "these",
+    "they", "this", "to", "was", "will", "with",
+];
+
+/// Applies a single filter. `preceding` analyzes text with the part of the
+/// chain that comes before the filter, which is how synonym rules are parsed.
+pub(super) fn apply<F>(
+    filter: &TokenFilterType,
+    tokens: Vec<Token>,
+    preceding: &F,
+) -> Result<Vec<Token>, AnalysisError>
+where
+    F: Fn(&str) -> Result<Vec<Token>, AnalysisError>,
+{
+    if let Some(reason) = filter.problem() {
+        return Err(AnalysisError::InvalidSettings(reason));
+    }
+    match filter {
+        TokenFilterType::Lowercase => Ok(map_terms(tokens, |term| term.to_lowercase())),
+        TokenFilterType::Reverse => Ok(map_terms(tokens, |term| term.chars().rev().collect())),
+        TokenFilterType::Asciifolding { preserve_original } => {
+            Ok(asciifolding(tokens, *preserve_original))
+        }
+        TokenFilterType::EdgeNgram {
+            min_gram,
+            max_gram,
+            preserve_original,
+        } => Ok(edge_ngram(
+            tokens,
+            *min_gram as usize,
+            *max_gram as usize,
+            *preserve_original,
+        )),
+        TokenFilterType::Stop {
+            stopwords,
+            ignore_case,
+        } => stop(tokens, stopwords, *ignore_case),
+        TokenFilterType::WordDelimiterGraph {
+            generate_word_parts,
+            generate_number_parts,
+            catenate_all,
+            split_on_case_change,
+            split_on_numerics,
+            preserve_original,
+        } => Ok(WordDelimiter {
+            generate_word_parts: *generate_word_parts,
+            generate_number_parts: *generate_number_parts,
+            catenate_all: *catenate_all,
+            split_on_case_change: *split_on_case_change,
+            split_on_numerics: *split_on_numerics,
+            preserve_original: *preserve_original,
+        }
+        .apply(tokens)),
+        TokenFilterType::Shingle {
+            max_shingle_size,
+            min_shingle_size,
+            output_unigrams,
+            output_unigrams_if_no_shingles,
+            token_separator,
+            filler_token,
+        } => Shingle {
+            max_shingle_size: *max_shingle_size as usize,
+            min_shingle_size: *min_shingle_size as usize,
+            output_unigrams: *output_unigrams,
+            output_unigrams_if_no_shingles: *output_unigrams_if_no_shingles,
+            token_separator,
+            filler_token,
+        }
+        .apply(tokens),
+        TokenFilterType::SynonymGraph {
+            expand,
+            lenient,
+            synonyms,
+        } => Ok(SynonymMap::build(synonyms, *expand, *lenient, preceding)?.apply(tokens)),
+        TokenFilterType::FlattenGraph => Ok(flatten(tokens)),
+        TokenFilterType::Stemmer { language } => tokens
+            .into_iter()
+            .map(|token| {
+                Ok(Token {
+                    term: stem(language, &token.term)?,
+                    ..token
+                })
+            })
+            .collect(),
+        // components of analysis plugins, when their features are enabled
+        #[allow(unreachable_patterns)]
+        other => Err(AnalysisError::Unsupported(format!("{:?}", other))),
+    }
+}
+
+// the folded token comes first, then the original at the same position
+fn asciifolding(tokens: Vec<Token>, preserve_original: bool) -> Vec<Token> {
+    let mut output = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let folded = fold(&token.term);
+        if preserve_original && folded != token.term {
+            output.push(Token {
+                term: folded,
+                ..token.clone()
+            });
+            output.push(token);
+        } else {
+            output.push(Token {
+                term: folded,
+                ..token
+            });
+        }
+    }
+    output
+}
+
+// grams keep the offsets of the whole token. A token outside the gram sizes
+// is dropped unless the original is preserved.
+fn edge_ngram(tokens: Vec<Token>, min: usize, max: usize, preserve_original: bool) -> Vec<Token> {
+    let mut output = Vec::new();
+    for token in tokens {
+        let chars: Vec<char> = token.term.chars().collect();
+        for length in min..=max.min(chars.len()) {
+            output.push(Token {
+                term: chars[..length].iter().collect(),
+                ..token.clone()
+            });
+        }
+        if preserve_original && (chars.len() < min || chars.len() > max) {
+            output.push(token);
+        }
+    }
+    output
+}
+
+fn stop(
+    tokens: Vec<Token>,
+    stopwords: &Stopwords,
+    ignore_case: bool,
+) -> Result<Vec<Token>, AnalysisError> {
+    let words: Vec<String> = match stopwords {
+        Stopwords::Predefined(language) if language == "english" => {
+            ENGLISH_STOPWORDS.iter().map(|w| w.to_string()).collect()
+        }
+        Stopwords::Predefined(language) if language == "none" => Vec::new(),
+        Stopwords::Predefined(language) => {
+            return Err(AnalysisError::Unsupported(format!(
+                "_{}_ stopwords",
+                language
+            )))
+        }
+        Stopwords::List(words) => words.clone(),
+    };
+    let is_stopword = |term: &str| {
+        words.iter().any(|w| {
+            if ignore_case {
+                w.to_lowercase() == term.to_lowercase()
+            } else {
+                w == term
+            }
+        })
+    };
+    // removed tokens leave their positions empty
+    Ok(tokens
+        .into_iter()
+        .filter(|token| !is_stopword(&token.term))
+        .collect())
+}
+
+fn map_terms<F>(tokens: Vec<Token>, f: F) -> Vec<Token>
+where
+    F: Fn(&str) -> String,
+{
+    tokens
+        .into_iter()
+        .map(|token| Token {
+            term: f(&token.term),
+            ..token
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(terms: &[&str]) -> Vec<Token> {
+        terms
+            .iter()
+            .enumerate()
+            .map(|(position, term)| Token {
+                term: term.to_string(),
+                start_offset: 0,
+                end_offset: term.len(),
+                token_type: "word".to_string(),
+                position,
+                position_length: 1,
+            })
+            .collect()
+    }
+
+    fn summary(tokens: &[Token]) -> Vec<(&str, usize)> {
+        tokens
+            .iter()
+            .map(|t| (t.term.as_str(), t.position))
+            .collect()
+    }
+
+    #[test]
+    fn folding_preserves_original_after() {
+        let output = asciifolding(tokens(&["café", "bar"]), true);
+        assert_eq!(summary(&output), vec![("cafe", 0), ("café", 0), ("bar", 1)]);
+    }
+
+    #[test]
+    fn edge_ngrams() {
+        let output = edge_ngram(tokens(&["quick", "a"]), 2, 4, false);
+        assert_eq!(summary(&output), vec![("qu", 0), ("qui", 0), ("quic", 0)]);
+        let output = edge_ngram(tokens(&["quick", "a"]), 2, 4, true);
+        assert_eq!(output.len(), 5);
+        assert_eq!(output[3].term, "quick");
+        assert_eq!(output[3].end_offset, 5);
+    }
+
+    #[test]
+    fn stopwords_leave_gaps() {
+        let english = Stopwords::Predefined("english".to_string());
+        let output = stop(tokens(&["The", "king", "of", "rock"]), &english, false).unwrap();
+        assert_eq!(summary(&output), vec![("The", 0), ("king", 1), ("rock", 3)]);
+        let output = stop(tokens(&["The", "king"]), &english, true).unwrap();
+        assert_eq!(summary(&output), vec![("king", 1)]);
+        assert!(stop(
+            tokens(&["le"]),
+            &Stopwords::Predefined("french".to_string()),
+            false
+        )
+        .is_err());
+    }
+}
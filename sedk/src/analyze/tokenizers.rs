@@ -0,0 +1,267 @@
+use super::{character_filters::FilteredText, AnalysisError, Token};
+use crate::dsl::tokenizers::{CharacterGroups, TokenizerType};
+use unicode_segmentation::UnicodeSegmentation;
+
+// the default max_token_length of the char_group tokenizer
+const MAX_TOKEN_LENGTH: usize = 255;
+
+pub(super) fn tokenize(
+    tokenizer: &TokenizerType,
+    text: &FilteredText,
+) -> Result<Vec<Token>, AnalysisError> {
+    match tokenizer {
+        TokenizerType::CharacterGroup { tokenize_on_chars } => {
+            Ok(char_group(tokenize_on_chars, text))
+        }
+        TokenizerType::Standard => Ok(standard(text)),
+        TokenizerType::Keyword => Ok(keyword(text)),
+        TokenizerType::PathHierarchy { delimiter, reverse } => {
+            Ok(path_hierarchy(*delimiter, *reverse, text))
+        }
+        // tokenizers of analysis plugins, when their features are enabled
+        #[allow(unreachable_patterns)]
+        other => Err(AnalysisError::Unsupported(format!("{:?}", other))),
+    }
+}
+
+fn char_group(groups: &[CharacterGroups], text: &FilteredText) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for i in 0..=text.chars.len() {
+        let boundary = match (text.chars.get(i), start) {
+            (None, _) => true,
+            (Some(c), _) if groups.iter().any(|g| in_group(g, *c)) => true,
+            (Some(_), Some(s)) => i - s == MAX_TOKEN_LENGTH,
+            (Some(_), None) => false,
+        };
+        if boundary {
+            if let Some(s) = start.take() {
+                tokens.push(Token {
+                    term: text.chars[s..i].iter().collect(),
+                    start_offset: text.offsets[s],
+                    end_offset: text.offsets[i],
+                    token_type: "word".to_string(),
+                    position: tokens.len(),
+                    position_length: 1,
+                });
+            }
+        }
+        match text.chars.get(i) {
+            Some(c) if start.is_none() && !groups.iter().any(|g| in_group(g, *c)) => {
+                start = Some(i)
+            }
+            _ => (),
+        }
+    }
+    tokens
+}
+
+// java's notions of these classes, restricted to what std can tell us. Non
+// ascii punctuation and symbols are not recognised.
+fn in_group(group: &CharacterGroups, c: char) -> bool {
+    match group {
+        CharacterGroups::Whitespace => {
+            c.is_whitespace() && !matches!(c, '\u{00A0}' | '\u{2007}' | '\u{202F}')
+        }
+        CharacterGroups::Letter => c.is_alphabetic(),
+        CharacterGroups::Digit => c.is_numeric(),
+        CharacterGroups::Punctuation => c.is_ascii_punctuation() && !is_ascii_symbol(c),
+        CharacterGroups::Symbol => is_ascii_symbol(c),
+        CharacterGroups::Arbitrary(a) => *a == c,
+    }
+}
+
+fn is_ascii_symbol(c: char) -> bool {
+    matches!(c, '$' | '+' | '<' | '=' | '>' | '^' | '`' | '|' | '~')
+}
+
+fn token(
+    text: &FilteredText,
+    start: usize,
+    end: usize,
+    token_type: &str,
+    position: usize,
+) -> Token {
+    Token {
+        term: text.chars[start..end].iter().collect(),
+        start_offset: text.offsets[start],
+        end_offset: text.offsets[end],
+        token_type: token_type.to_string(),
+        position,
+        position_length: 1,
+    }
+}
+
+// Words are split on the unicode word boundaries the standard tokenizer is
+// built on, keeping the segments with a letter or digit in them. Lucene also
+// types ideographs and kana separately and cuts words at 255 characters,
+// which isn't reproduced.
+fn standard(text: &FilteredText) -> Vec<Token> {
+    let source: String = text.chars.iter().collect();
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    for segment in source.split_word_bounds() {
+        let end = start + segment.chars().count();
+        if segment.chars().any(char::is_alphanumeric) {
+            let numeric = segment
+                .chars()
+                .all(|c| c.is_numeric() || c == '.' || c == ',');
+            let token_type = if numeric { "<NUM>" } else { "<ALPHANUM>" };
+            tokens.push(token(text, start, end, token_type, tokens.len()));
+        }
+        start = end;
+    }
+    tokens
+}
+
+fn keyword(text: &FilteredText) -> Vec<Token> {
+    if text.chars.is_empty() {
+        Vec::new()
+    } else {
+        vec![token(text, 0, text.chars.len(), "word", 0)]
+    }
+}
+
+// Every prefix ending at a delimiter, then the whole path, all stacked at the
+// first position. Reversed, every suffix starting after a delimiter.
+fn path_hierarchy(delimiter: char, reverse: bool, text: &FilteredText) -> Vec<Token> {
+    let length = text.chars.len();
+    if length == 0 {
+        return Vec::new();
+    }
+    let delimiters = (1..length).filter(|&i| text.chars[i] == delimiter);
+    let spans: Vec<(usize, usize)> = if reverse {
+        std::iter::once(0)
+            .chain(
+                (0..length - 1)
+                    .filter(|&i| text.chars[i] == delimiter)
+                    .map(|i| i + 1),
+            )
+            .map(|start| (start, length))
+            .collect()
+    } else {
+        delimiters
+            .chain(std::iter::once(length))
+            .map(|end| (0, end))
+            .collect()
+    };
+    spans
+        .into_iter()
+        .map(|(start, end)| token(text, start, end, "word", 0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(groups: Vec<CharacterGroups>, text: &str) -> Vec<(String, usize, usize)> {
+        let filtered = super::super::character_filters::apply(&[], text).unwrap();
+        tokenize(
+            &TokenizerType::CharacterGroup {
+                tokenize_on_chars: groups,
+            },
+            &filtered,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| (t.term, t.start_offset, t.end_offset))
+        .collect()
+    }
+
+    #[test]
+    fn splits_on_groups() {
+        assert_eq!(
+            terms(
+                vec![CharacterGroups::Whitespace, CharacterGroups::Punctuation],
+                " the quick-brown  fox "
+            ),
+            vec![
+                ("the".to_string(), 1, 4),
+                ("quick".to_string(), 5, 10),
+                ("brown".to_string(), 11, 16),
+                ("fox".to_string(), 18, 21),
+            ]
+        );
+    }
+
+    #[test]
+    fn symbols_are_not_punctuation() {
+        assert_eq!(
+            terms(vec![CharacterGroups::Punctuation], "a+b.c"),
+            vec![("a+b".to_string(), 0, 3), ("c".to_string(), 4, 5)]
+        );
+    }
+
+    #[test]
+    fn offsets_are_utf16() {
+        assert_eq!(
+            terms(vec![CharacterGroups::Whitespace], "𝄞 clef"),
+            vec![("𝄞".to_string(), 0, 2), ("clef".to_string(), 3, 7)]
+        );
+    }
+
+    fn analyzed(tokenizer: TokenizerType, text: &str) -> Vec<(String, usize, usize, usize)> {
+        let filtered = super::super::character_filters::apply(&[], text).unwrap();
+        tokenize(&tokenizer, &filtered)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.term, t.position, t.start_offset, t.end_offset))
+            .collect()
+    }
+
+    fn owned(tokens: &[(&str, usize, usize, usize)]) -> Vec<(String, usize, usize, usize)> {
+        tokens
+            .iter()
+            .map(|(term, position, start, end)| (term.to_string(), *position, *start, *end))
+            .collect()
+    }
+
+    #[test]
+    fn standard_words() {
+        assert_eq!(
+            analyzed(
+                TokenizerType::Standard,
+                "The 2 QUICK brown-foxes, 3.5 can't!"
+            ),
+            owned(&[
+                ("The", 0, 0, 3),
+                ("2", 1, 4, 5),
+                ("QUICK", 2, 6, 11),
+                ("brown", 3, 12, 17),
+                ("foxes", 4, 18, 23),
+                ("3.5", 5, 25, 28),
+                ("can't", 6, 29, 34),
+            ])
+        );
+    }
+
+    #[test]
+    fn keyword_is_whole_text() {
+        assert_eq!(
+            analyzed(TokenizerType::Keyword, "New York"),
+            owned(&[("New York", 0, 0, 8)])
+        );
+        assert_eq!(analyzed(TokenizerType::Keyword, ""), vec![]);
+    }
+
+    #[test]
+    fn path_prefixes() {
+        let path = |reverse| TokenizerType::PathHierarchy {
+            delimiter: '/',
+            reverse,
+        };
+        assert_eq!(
+            analyzed(path(false), "/usr/local/bin"),
+            owned(&[
+                ("/usr", 0, 0, 4),
+                ("/usr/local", 0, 0, 10),
+                ("/usr/local/bin", 0, 0, 14),
+            ])
+        );
+        assert_eq!(
+            analyzed(path(true), "a/b/c"),
+            owned(&[("a/b/c", 0, 0, 5), ("b/c", 0, 2, 5), ("c", 0, 4, 5)])
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use super::Token;
+
+pub(super) struct WordDelimiter {
+    pub generate_word_parts: bool,
+    pub generate_number_parts: bool,
+    pub catenate_all: bool,
+    pub split_on_case_change: bool,
+    pub split_on_numerics: bool,
+    pub preserve_original: bool,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Class {
+    Lower,
+    Upper,
+    // letters without case, which never split on a case change
+    Letter,
+    Digit,
+    Delimiter,
+}
+
+fn class(c: char) -> Class {
+    if c.is_lowercase() {
+        Class::Lower
+    } else if c.is_uppercase() {
+        Class::Upper
+    } else if c.is_alphabetic() {
+        Class::Letter
+    } else if c.is_numeric() {
+        Class::Digit
+    } else {
+        Class::Delimiter
+    }
+}
+
+fn is_alpha(class: Class) -> bool {
+    matches!(class, Class::Lower | Class::Upper | Class::Letter)
+}
+
+impl WordDelimiter {
+    // mirrors WordDelimiterIterator.isBreak
+    fn is_break(&self, last: Class, current: Class) -> bool {
+        if last == current {
+            false
+        } else if is_alpha(last) && is_alpha(current) {
+            self.split_on_case_change && last == Class::Lower && current == Class::Upper
+        } else {
+            // a letter next to a digit
+            self.split_on_numerics
+        }
+    }
+
+    /// The character ranges of the subwords of `term`. A trailing english
+    /// possessive is dropped, as the filter does by default.
+    fn subwords(&self, term: &[char]) -> Vec<(usize, usize)> {
+        let mut end = term.len();
+        if end > 2 && term[end - 2] == '\'' && matches!(term[end - 1], 's' | 'S') {
+            end -= 2;
+        }
+        let mut subwords = Vec::new();
+        let mut start: Option<usize> = None;
+        for i in 0..end {
+            let current = class(term[i]);
+            match start {
+                Some(s) if current == Class::Delimiter => {
+                    subwords.push((s, i));
+                    start = None;
+                }
+                Some(s) if self.is_break(class(term[i - 1]), current) => {
+                    subwords.push((s, i));
+                    start = Some(i);
+                }
+                None if current != Class::Delimiter => start = Some(i),
+                _ => (),
+            }
+        }
+        if let Some(s) = start {
+            subwords.push((s, end));
+        }
+        subwords
+    }
+
+    /// Splits every token into its subwords, which take consecutive positions.
+    /// The original and the catenation of all subwords span them, and the
+    /// tokens that follow shift along to make room.
+    pub fn apply(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut output = Vec::with_capacity(tokens.len());
+        let mut shift = 0;
+        for token in tokens {
+            let position = token.position + shift;
+            let term: Vec<char> = token.term.chars().collect();
+            let subwords = self.subwords(&term);
+            if subwords == [(0, term.len())] {
+                output.push(Token { position, ..token });
+                continue;
+            }
+
+            // offsets can only be split when they still match the term
+            let exact = token.end_offset - token.start_offset == token.term.encode_utf16().count();
+            let offset = |i: usize| -> usize {
+                if exact {
+                    token.start_offset + term[..i].iter().map(|c| c.len_utf16()).sum::<usize>()
+                } else if i == 0 {
+                    token.start_offset
+                } else {
+                    token.end_offset
+                }
+            };
+            let part = |start: usize, end: usize, from: usize, to: usize| Token {
+                term: term[start..end].iter().collect(),
+                start_offset: offset(start),
+                end_offset: offset(end).max(offset(start)),
+                token_type: token.token_type.clone(),
+                position: position + from,
+                position_length: to - from,
+            };
+
+            let parts: Vec<(usize, usize)> = subwords
+                .iter()
+                .copied()
+                .filter(|(start, _)| {
+                    if class(term[*start]) == Class::Digit {
+                        self.generate_number_parts
+                    } else {
+                        self.generate_word_parts
+                    }
+                })
+                .collect();
+            let span = parts.len().max(1);
+            let mut emitted = Vec::new();
+            if self.preserve_original {
+                emitted.push(Token {
+                    position,
+                    position_length: span,
+                    ..token.clone()
+                });
+            }
+            if self.catenate_all && !subwords.is_empty() {
+                let mut catenated = part(subwords[0].0, subwords[subwords.len() - 1].1, 0, span);
+                catenated.term = subwords
+                    .iter()
+                    .flat_map(|(start, end)| term[*start..*end].iter())
+                    .collect();
+                emitted.push(catenated);
+            }
+            for (i, (start, end)) in parts.iter().enumerate() {
+                emitted.push(part(*start, *end, i, i + 1));
+            }
+            // longer tokens first among those starting at a node, as lucene
+            emitted.sort_by(|a, b| {
+                (a.position, b.end_position()).cmp(&(b.position, a.end_position()))
+            });
+            output.append(&mut emitted);
+            shift += span - 1;
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word_delimiter() -> WordDelimiter {
+        WordDelimiter {
+            generate_word_parts: true,
+            generate_number_parts: true,
+            catenate_all: false,
+            split_on_case_change: true,
+            split_on_numerics: true,
+            preserve_original: false,
+        }
+    }
+
+    fn token(term: &str, position: usize, start_offset: usize) -> Token {
+        Token {
+            term: term.to_string(),
+            start_offset,
+            end_offset: start_offset + term.len(),
+            token_type: "word".to_string(),
+            position,
+            position_length: 1,
+        }
+    }
+
+    fn summary(tokens: &[Token]) -> Vec<(&str, usize, usize, usize, usize)> {
+        tokens
+            .iter()
+            .map(|t| {
+                (
+                    t.term.as_str(),
+                    t.position,
+                    t.position_length,
+                    t.start_offset,
+                    t.end_offset,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_into_parts() {
+        let output =
+            word_delimiter().apply(vec![token("Wi-Fi", 0, 0), token("PowerShot500", 1, 6)]);
+        assert_eq!(
+            summary(&output),
+            vec![
+                ("Wi", 0, 1, 0, 2),
+                ("Fi", 1, 1, 3, 5),
+                ("Power", 2, 1, 6, 11),
+                ("Shot", 3, 1, 11, 15),
+                ("500", 4, 1, 15, 18),
+            ]
+        );
+    }
+
+    #[test]
+    fn original_and_catenation_span_parts() {
+        let output = WordDelimiter {
+            catenate_all: true,
+            preserve_original: true,
+            ..word_delimiter()
+        }
+        .apply(vec![token("SD-500", 0, 0), token("camera", 1, 7)]);
+        assert_eq!(
+            summary(&output),
+            vec![
+                ("SD-500", 0, 2, 0, 6),
+                ("SD500", 0, 2, 0, 6),
+                ("SD", 0, 1, 0, 2),
+                ("500", 1, 1, 3, 6),
+                ("camera", 2, 1, 7, 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn options() {
+        let keep_together = WordDelimiter {
+            split_on_case_change: false,
+            split_on_numerics: false,
+            ..word_delimiter()
+        };
+        assert_eq!(
+            summary(&keep_together.apply(vec![token("iPhone12", 0, 0)])),
+            vec![("iPhone12", 0, 1, 0, 8)]
+        );
+        let words_only = WordDelimiter {
+            generate_number_parts: false,
+            ..word_delimiter()
+        };
+        assert_eq!(
+            summary(&words_only.apply(vec![token("abc-123", 0, 0)])),
+            vec![("abc", 0, 1, 0, 3)]
+        );
+        assert_eq!(
+            summary(&word_delimiter().apply(vec![token("O'Neil's", 0, 0)])),
+            vec![("O", 0, 1, 0, 1), ("Neil", 1, 1, 2, 6)]
+        );
+    }
+}
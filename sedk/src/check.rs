@@ -0,0 +1,670 @@
+//! Checks documents against a mapping before they're indexed, so that a bulk
+//! request doesn't fail part way through on a value elasticsearch rejects:
+//!
+//! ```
+//! use sedk::{Dynamic, Field, FieldType, IndexMapping, IndexSettings};
+//! use serde_json::json;
+//!
+//! let mapping = IndexMapping {
+//!     fields: vec![Field::new("stars", FieldType::Byte)],
+//!     plugins: vec![],
+//!     settings: IndexSettings::default(),
+//!     aliases: vec![],
+//!     dynamic: Some(Dynamic::Strict),
+//! };
+//! let violations = mapping.check_document(&json!({"stars": 500, "by": "ann"}));
+//! let pointers: Vec<&str> = violations.iter().map(|v| v.pointer.as_str()).collect();
+//! assert_eq!(pointers, ["/by", "/stars"]);
+//! ```
+use crate::dsl::date_format::DateFormat;
+use crate::dsl::field::{Dynamic, Field, FieldType, IndexMapping};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::net::IpAddr;
+
+/// A value in a document that doesn't fit the mapping.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Violation {
+    /// Where the value is in the document, as a JSON pointer.
+    pub pointer: String,
+    /// The field the value was checked against, or the path it would have had
+    /// when no field maps it.
+    pub field: String,
+    pub problem: Problem,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Problem {
+    /// No field maps the value and the mapping is strict.
+    Unmapped,
+    /// A value of the wrong JSON type, such as an object in a keyword field or
+    /// a word in an integer field.
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// A date that doesn't match any of the field's formats.
+    UnparseableDate { format: String },
+    /// A string stored but not indexed, since it's longer than the limit.
+    IgnoredAbove { limit: u32, length: usize },
+    /// A number that doesn't fit the field's type.
+    OutOfRange { field_type: &'static str },
+    /// A vector of the wrong number of dimensions.
+    Dimensions { expected: u32, found: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.pointer)?;
+        match &self.problem {
+            Problem::Unmapped => write!(f, "`{}` isn't mapped", self.field),
+            Problem::TypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "`{}` is {}, but {} was found",
+                    self.field, expected, found
+                )
+            }
+            Problem::UnparseableDate { format } => {
+                write!(f, "`{}` takes dates in `{}`", self.field, format)
+            }
+            Problem::IgnoredAbove { limit, length } => write!(
+                f,
+                "`{}` ignores strings over {} characters, but this has {}",
+                self.field, limit, length
+            ),
+            Problem::OutOfRange { field_type } => {
+                write!(
+                    f,
+                    "the value is out of range for `{}`, a {}",
+                    self.field, field_type
+                )
+            }
+            Problem::Dimensions { expected, found } => write!(
+                f,
+                "`{}` has {} dimensions, but this has {}",
+                self.field, expected, found
+            ),
+        }
+    }
+}
+
+impl IndexMapping {
+    /// The values of `document` elasticsearch would reject or not index, in
+    /// the order `serde_json` keeps the keys of objects.
+    pub fn check_document(&self, document: &Value) -> Vec<Violation> {
+        let mut check = Check {
+            violations: Vec::new(),
+        };
+        let dynamic = self.dynamic.unwrap_or(Dynamic::True);
+        match document {
+            Value::Object(object) => check.object(&self.fields, "", object, "", dynamic),
+            other => check.violation("", "", mismatch("an object", other)),
+        }
+        check.violations
+    }
+}
+
+struct Check {
+    violations: Vec<Violation>,
+}
+
+impl Check {
+    fn violation(&mut self, pointer: &str, field: &str, problem: Problem) {
+        self.violations.push(Violation {
+            pointer: pointer.to_string(),
+            field: field.to_string(),
+            problem,
+        });
+    }
+
+    // the keys of an object, whose dotted names start with `prefix` among
+    // `fields`. Nested fields start again from their own properties. Objects
+    // take `dynamic` from the one they're in, except for maps, which add
+    // their keys whatever the mapping's setting.
+    fn object(
+        &mut self,
+        fields: &[Field],
+        prefix: &str,
+        object: &Map<String, Value>,
+        pointer: &str,
+        dynamic: Dynamic,
+    ) {
+        for (key, value) in object {
+            let name = format!("{}{}", prefix, key);
+            let pointer = format!("{}/{}", pointer, escape(key));
+            self.member(fields, &name, value, &pointer, dynamic);
+        }
+    }
+
+    fn member(
+        &mut self,
+        fields: &[Field],
+        name: &str,
+        value: &Value,
+        pointer: &str,
+        dynamic: Dynamic,
+    ) {
+        let within = name.to_string() + ".";
+        match fields.iter().find(|field| field.name == name) {
+            // the keys of maps are matched by the templates beside them
+            Some(field) if matches!(field.field_type, FieldType::Object { .. }) => {
+                return self.objects(fields, &within, value, pointer, Dynamic::True)
+            }
+            Some(field) => return self.field(field, value, pointer, dynamic),
+            None => (),
+        }
+        if fields.iter().any(|field| field.name.starts_with(&within)) {
+            return self.objects(fields, &within, value, pointer, dynamic);
+        }
+        match dynamic {
+            Dynamic::Strict => self.violation(pointer, name, Problem::Unmapped),
+            // templates only map the fields dynamic mapping adds
+            Dynamic::True => match template(fields, name) {
+                Some(template) if matches!(template.field_type, FieldType::Object { .. }) => {
+                    self.objects(fields, &within, value, pointer, Dynamic::True)
+                }
+                Some(template) => self.field(template, value, pointer, dynamic),
+                None => self.added(fields, &within, value, pointer),
+            },
+            Dynamic::False | Dynamic::Runtime => (),
+        }
+    }
+
+    // an object, or an array of them, whose keys continue the dotted `prefix`
+    fn objects(
+        &mut self,
+        fields: &[Field],
+        prefix: &str,
+        value: &Value,
+        pointer: &str,
+        dynamic: Dynamic,
+    ) {
+        match value {
+            Value::Null => (),
+            Value::Object(object) => self.object(fields, prefix, object, pointer, dynamic),
+            Value::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    let pointer = format!("{}/{}", pointer, i);
+                    self.objects(fields, prefix, value, &pointer, dynamic);
+                }
+            }
+            other => {
+                let field = prefix.trim_end_matches('.');
+                self.violation(pointer, field, mismatch("an object", other))
+            }
+        }
+    }
+
+    // a value dynamic mapping adds a field for, whose keys, when it's an
+    // object, may still be mapped by templates
+    fn added(&mut self, fields: &[Field], prefix: &str, value: &Value, pointer: &str) {
+        match value {
+            Value::Object(object) => self.object(fields, prefix, object, pointer, Dynamic::True),
+            Value::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.added(fields, prefix, value, &format!("{}/{}", pointer, i));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn field(&mut self, field: &Field, value: &Value, pointer: &str, dynamic: Dynamic) {
+        match (&field.field_type, value) {
+            (_, Value::Null) | (FieldType::Flattened, _) => (),
+            (FieldType::Nested { properties }, _) => {
+                self.objects(properties, "", value, pointer, dynamic)
+            }
+            (FieldType::DenseVector { dims }, Value::Array(values)) => {
+                if values.len() != *dims as usize {
+                    let problem = Problem::Dimensions {
+                        expected: *dims,
+                        found: values.len(),
+                    };
+                    self.violation(pointer, &field.name, problem);
+                }
+                for (i, value) in values.iter().enumerate() {
+                    if !value.is_number() {
+                        let pointer = format!("{}/{}", pointer, i);
+                        self.violation(&pointer, &field.name, mismatch("a number", value));
+                    }
+                }
+            }
+            (FieldType::DenseVector { .. }, other) => {
+                self.violation(pointer, &field.name, mismatch("an array of numbers", other))
+            }
+            (_, Value::Array(values)) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.field(field, value, &format!("{}/{}", pointer, i), dynamic);
+                }
+            }
+            (field_type, value) => {
+                if let Some(problem) = scalar(field_type, value) {
+                    self.violation(pointer, &field.name, problem);
+                }
+                // subfields are named relative to the field they belong to
+                for subfield in &field.fields {
+                    if let Some(problem) = scalar(&subfield.field_type, value) {
+                        let name = format!("{}.{}", field.name, subfield.name);
+                        self.violation(pointer, &name, problem);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// the template elasticsearch gives a new field: the first whose path_match
+// fits, in the order the mapping lists them, where maps within maps come
+// before the map around them
+fn template<'a>(fields: &'a [Field], name: &str) -> Option<&'a Field> {
+    fields.iter().find_map(|field| match &field.field_type {
+        FieldType::Object { templates } => template(templates, name).or_else(|| {
+            templates
+                .iter()
+                .find(|template| path_match(&template.name, name))
+        }),
+        _ => None,
+    })
+}
+
+// `*` stands for any characters, dots included
+fn path_match(pattern: &str, path: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == path,
+        Some((start, rest)) => match path.strip_prefix(start) {
+            Some(path) => path
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(Some(path.len()))
+                .any(|i| path_match(rest, &path[i..])),
+            None => false,
+        },
+    }
+}
+
+// what's wrong with a single value of a field that isn't an object
+fn scalar(field_type: &FieldType, value: &Value) -> Option<Problem> {
+    if let Value::Object(_) = value {
+        return Some(mismatch(expected(field_type), value));
+    }
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    match field_type {
+        FieldType::Keyword { ignore_above, .. } => {
+            // elasticsearch counts characters as java does, in utf-16
+            let length = text.encode_utf16().count();
+            match ignore_above {
+                Some(limit) if length > *limit as usize => Some(Problem::IgnoredAbove {
+                    limit: *limit,
+                    length,
+                }),
+                _ => None,
+            }
+        }
+        FieldType::Text { .. } => None,
+        FieldType::Binary => match value {
+            Value::String(_) => None,
+            other => Some(mismatch("a base64 string", other)),
+        },
+        FieldType::Boolean => match value {
+            Value::Bool(_) => None,
+            Value::String(s) if s == "true" || s == "false" || s.is_empty() => None,
+            other => Some(mismatch("a boolean", other)),
+        },
+        FieldType::Date { format } => {
+            if value.is_boolean() {
+                return Some(mismatch("a date", value));
+            }
+            let parsed = match format {
+                Some(format) => DateFormat::parse(format),
+                None => Ok(DateFormat::default()),
+            };
+            match parsed {
+                // formats sedk can't read are left to elasticsearch
+                Ok(parsed) if !parsed.accepts(&text) => Some(Problem::UnparseableDate {
+                    format: format.clone().unwrap_or_else(|| {
+                        crate::dsl::date_format::DEFAULT_DATE_FORMAT.to_string()
+                    }),
+                }),
+                _ => None,
+            }
+        }
+        FieldType::Ip => match value {
+            Value::String(s) if s.parse::<IpAddr>().is_ok() => None,
+            other => Some(mismatch("an ip address", other)),
+        },
+        numeric => number(numeric, value, &text),
+    }
+}
+
+// numbers can be written as strings, and integer types drop any fraction
+fn number(field_type: &FieldType, value: &Value, text: &str) -> Option<Problem> {
+    let parsed = match value {
+        Value::Number(_) | Value::String(_) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    let parsed = match parsed {
+        Some(parsed) if parsed.is_finite() => parsed,
+        _ => return Some(mismatch(expected(field_type), value)),
+    };
+    // whole numbers are compared exactly, since an f64 can't hold every long
+    let whole = text.trim().parse::<i128>().ok();
+    let integer = |min: i128, max: i128| match whole {
+        Some(whole) => whole >= min && whole <= max,
+        None => parsed.trunc() >= min as f64 && parsed.trunc() <= max as f64,
+    };
+    let in_range = match field_type {
+        FieldType::Byte => integer(i8::MIN.into(), i8::MAX.into()),
+        FieldType::Short => integer(i16::MIN.into(), i16::MAX.into()),
+        FieldType::Integer => integer(i32::MIN.into(), i32::MAX.into()),
+        FieldType::Long => integer(i64::MIN.into(), i64::MAX.into()),
+        FieldType::UnsignedLong => integer(0, u64::MAX.into()),
+        FieldType::HalfFloat => parsed.abs() <= 65504.0,
+        FieldType::Float => parsed.abs() <= f32::MAX.into(),
+        _ => true,
+    };
+    if in_range {
+        None
+    } else {
+        Some(Problem::OutOfRange {
+            field_type: field_type.name(),
+        })
+    }
+}
+
+fn expected(field_type: &FieldType) -> &'static str {
+    match field_type {
+        FieldType::Keyword { .. } | FieldType::Text { .. } => "a string",
+        FieldType::Date { .. } => "a date",
+        FieldType::Ip => "an ip address",
+        FieldType::Boolean => "a boolean",
+        FieldType::Binary => "a base64 string",
+        FieldType::Nested { .. } | FieldType::Object { .. } => "an object",
+        _ => "a number",
+    }
+}
+
+fn mismatch(expected: &'static str, found: &Value) -> Problem {
+    Problem::TypeMismatch {
+        expected,
+        found: match found {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        },
+    }
+}
+
+// keys escaped for JSON pointers, as in RFC 6901
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::field::IndexSettings;
+    use crate::IntoFields;
+    use serde_json::json;
+
+    fn mapping(fields: Vec<Field>, dynamic: Option<Dynamic>) -> IndexMapping {
+        IndexMapping {
+            fields,
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic,
+        }
+    }
+
+    fn problems(mapping: &IndexMapping, document: Value) -> Vec<(String, Problem)> {
+        mapping
+            .check_document(&document)
+            .into_iter()
+            .map(|v| (v.pointer, v.problem))
+            .collect()
+    }
+
+    #[test]
+    fn unmapped_fields_only_matter_when_strict() {
+        let fields = vec![
+            Field::new(
+                "address.city",
+                FieldType::Text {
+                    analyzer: Default::default(),
+                    search_analyzer: None,
+                },
+            ),
+            Field::new(
+                "scores",
+                FieldType::Object {
+                    templates: vec![Field::new("scores.*", FieldType::Short)],
+                },
+            ),
+            Field::new(
+                "reviews",
+                FieldType::Object {
+                    templates: vec![Field::new("reviews.*.stars", FieldType::Byte)],
+                },
+            ),
+        ];
+        let document = json!({
+            "address": {"city": "Oslo", "zip": "0150"},
+            "scores": {"a/b": 3, "c~": {"d": 4}},
+            "reviews": {"ann": {"stars": "many", "by": "ann"}},
+            "extra": true,
+            "a/b~": 1,
+        });
+        // maps take new keys either way, and their templates apply to all of
+        // them, objects included
+        let in_maps = [
+            (
+                "/reviews/ann/stars".to_string(),
+                Problem::TypeMismatch {
+                    expected: "a number",
+                    found: "a string",
+                },
+            ),
+            (
+                "/scores/c~0".to_string(),
+                Problem::TypeMismatch {
+                    expected: "a number",
+                    found: "an object",
+                },
+            ),
+        ];
+        assert_eq!(
+            problems(&mapping(fields.clone(), None), document.clone()),
+            in_maps
+        );
+        let mut strict = vec![
+            ("/a~1b~0".to_string(), Problem::Unmapped),
+            ("/address/zip".to_string(), Problem::Unmapped),
+            ("/extra".to_string(), Problem::Unmapped),
+        ];
+        strict.extend(in_maps.iter().cloned());
+        assert_eq!(
+            problems(&mapping(fields, Some(Dynamic::Strict)), document),
+            strict
+        );
+    }
+
+    #[test]
+    fn templates_only_apply_where_fields_are_added() {
+        let fields = vec![Field::new(
+            "tags",
+            FieldType::Object {
+                templates: vec![Field::new("*.count", FieldType::Byte)],
+            },
+        )];
+        let document = json!({"stock": {"count": 500}, "tags": {"count": 300}});
+        assert_eq!(
+            problems(
+                &mapping(fields.clone(), Some(Dynamic::False)),
+                document.clone()
+            ),
+            [(
+                "/tags/count".to_string(),
+                Problem::OutOfRange { field_type: "byte" }
+            )]
+        );
+        assert_eq!(problems(&mapping(fields, None), document).len(), 2);
+    }
+
+    #[test]
+    fn type_mismatches() {
+        let mapping = mapping(
+            vec![
+                Field::new("count", FieldType::Integer),
+                Field::keyword("tag").build().unwrap(),
+                Field::new("at", FieldType::Date { format: None }),
+                Field::new(
+                    "day",
+                    FieldType::Date {
+                        format: Some("strict_date".to_string()),
+                    },
+                ),
+                Field::new("from", FieldType::Ip),
+                Field::new("done", FieldType::Boolean),
+            ],
+            None,
+        );
+        let fine = json!({
+            "count": ["12", 13.5, null],
+            "tag": 7,
+            "at": [1614556800000i64, "2021-03-01T12:00:00Z"],
+            "day": "2021-03-01",
+            "from": "10.0.0.1",
+            "done": "false",
+        });
+        assert_eq!(problems(&mapping, fine), []);
+        let wrong = json!({
+            "count": "twelve",
+            "tag": {"name": "x"},
+            "at": "yesterday",
+            "day": 1614556800000i64,
+            "from": "10.0.0",
+            "done": 1,
+        });
+        let mismatch = |expected, found| Problem::TypeMismatch { expected, found };
+        let date = |format: &str| Problem::UnparseableDate {
+            format: format.to_string(),
+        };
+        assert_eq!(
+            problems(&mapping, wrong),
+            [
+                (
+                    "/at".to_string(),
+                    date("strict_date_optional_time||epoch_millis")
+                ),
+                ("/count".to_string(), mismatch("a number", "a string")),
+                ("/day".to_string(), date("strict_date")),
+                ("/done".to_string(), mismatch("a boolean", "a number")),
+                ("/from".to_string(), mismatch("an ip address", "a string")),
+                ("/tag".to_string(), mismatch("a string", "an object")),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_strings_in_subfields() {
+        let mapping = mapping(
+            vec![String::into_fields(Some("title"))
+                .remove(0)
+                .subfield(Field::keyword("keyword").ignore_above(5).build().unwrap())],
+            None,
+        );
+        let violations = mapping.check_document(&json!({"title": ["short", "longer"]}));
+        assert_eq!(
+            violations,
+            [Violation {
+                pointer: "/title/1".to_string(),
+                field: "title.keyword".to_string(),
+                problem: Problem::IgnoredAbove {
+                    limit: 5,
+                    length: 6
+                },
+            }]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "/title/1: `title.keyword` ignores strings over 5 characters, but this has 6"
+        );
+    }
+
+    #[test]
+    fn numbers_out_of_range() {
+        let mapping = mapping(
+            vec![
+                Field::new("byte", FieldType::Byte),
+                Field::new("short", FieldType::Short),
+                Field::new("half", FieldType::HalfFloat),
+                Field::new("long", FieldType::Long),
+            ],
+            None,
+        );
+        let fine = json!({
+            "byte": [-128, "127", 127.9],
+            "short": 32767,
+            "half": -65504.0,
+            "long": i64::MIN,
+        });
+        assert_eq!(problems(&mapping, fine), []);
+        let wrong = json!({"byte": [128], "short": "-32769", "half": 70000, "long": u64::MAX});
+        let out = |field_type| Problem::OutOfRange { field_type };
+        assert_eq!(
+            problems(&mapping, wrong),
+            [
+                ("/byte/0".to_string(), out("byte")),
+                ("/half".to_string(), out("half_float")),
+                ("/long".to_string(), out("long")),
+                ("/short".to_string(), out("short")),
+            ]
+        );
+    }
+
+    #[test]
+    fn vectors_in_nested_objects() {
+        let mapping = mapping(
+            vec![Field::new(
+                "chunks",
+                FieldType::Nested {
+                    properties: vec![Field::new("embedding", FieldType::DenseVector { dims: 3 })],
+                },
+            )],
+            Some(Dynamic::Strict),
+        );
+        let document = json!({"chunks": [
+            {"embedding": [0.1, 0.2, 0.3]},
+            {"embedding": [0.1, 0.2]},
+            {"embedding": [0.1, "x", 0.3], "text": "hi"},
+        ]});
+        assert_eq!(
+            problems(&mapping, document),
+            [
+                (
+                    "/chunks/1/embedding".to_string(),
+                    Problem::Dimensions {
+                        expected: 3,
+                        found: 2
+                    }
+                ),
+                (
+                    "/chunks/2/embedding/1".to_string(),
+                    Problem::TypeMismatch {
+                        expected: "a number",
+                        found: "a string"
+                    }
+                ),
+                ("/chunks/2/text".to_string(), Problem::Unmapped),
+            ]
+        );
+    }
+}
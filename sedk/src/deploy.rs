@@ -0,0 +1,760 @@
+//! Deploying mappings behind an alias. Applications use a fixed alias, and
+//! each version of the mapping gets a backing index named after the time it
+//! was deployed and a fingerprint of the mapping, such as
+//! `products-20240301120000-9f86d081`. A deployment creates the new index,
+//! reindexes the current one into it, checks that every document arrived and
+//! then moves the alias over in one atomic update. The previous index is
+//! kept, so rolling back is pointing the alias at it again.
+//!
+//! ```
+//! # #[cfg(feature = "testing")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use sedk::deploy::{ClusterState, Planner};
+//! use sedk::testing::FakeCluster;
+//! use sedk::transport::Client;
+//! use std::time::SystemTime;
+//! # use sedk::{Field, IndexMapping, IndexSettings};
+//! # let mapping = IndexMapping {
+//! #     fields: vec![Field::keyword("sku").build()?],
+//! #     plugins: vec![],
+//! #     settings: IndexSettings::default(),
+//! #     aliases: vec![],
+//! #     dynamic: None,
+//! # };
+//!
+//! let client = Client::new(FakeCluster::new());
+//! let planner = Planner::new("products", mapping);
+//! let plan = planner.plan(&ClusterState::fetch(&client, "products")?, SystemTime::now())?;
+//! println!("{}", plan);
+//! for step in plan.execute(&client) {
+//!     println!("done: {}", step?);
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "testing"))]
+//! # fn main() {}
+//! ```
+use crate::dsl::date_format::DateFormat;
+use crate::dsl::field::{IndexMapping, Indexable};
+use crate::transport::api::{AliasAction, CreateIndex, Endpoint, Script};
+use crate::transport::{ApiError, Body, Client, Transport};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Plans deployments of a mapping behind an alias.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Planner {
+    alias: String,
+    mapping: IndexMapping,
+    script: Option<Script>,
+}
+
+impl Planner {
+    /// Plans deployments of `mapping` behind `alias`. Any aliases the mapping
+    /// has are left out, since the alias is only moved to a new index once
+    /// it holds every document.
+    pub fn new(alias: &str, mut mapping: IndexMapping) -> Planner {
+        mapping.aliases.clear();
+        Planner {
+            alias: alias.to_string(),
+            mapping,
+            script: None,
+        }
+    }
+
+    pub fn of<T: Indexable>(alias: &str) -> Planner {
+        Planner::new(alias, T::index_mapping())
+    }
+
+    /// Runs `script` on each document as it's reindexed, for mappings whose
+    /// fields changed shape.
+    pub fn script(mut self, script: Script) -> Planner {
+        self.script = Some(script);
+        self
+    }
+
+    /// A short hash of everything in the mapping that's sent to the cluster,
+    /// which changes whenever the index has to be rebuilt. Dynamic settings,
+    /// such as the number of replicas, can be changed on the live index and
+    /// are left out.
+    pub fn fingerprint(&self) -> Result<String, ApiError> {
+        let mut mapping = self.mapping.clone();
+        mapping.settings.number_of_replicas = None;
+        let request = CreateIndex {
+            index: &self.alias,
+            mapping: &mapping,
+        }
+        .request()?;
+        let body = match request.body {
+            Some(Body::Json(body)) => body,
+            _ => Value::Null,
+        };
+        let mut canonical = String::new();
+        write_canonical(&body, &mut canonical);
+        // FNV-1a, which unlike std's hashers is the same in every release
+        let hash = canonical.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Ok(format!("{:016x}", hash)[..8].to_string())
+    }
+
+    /// What deploying the mapping at `now` takes, given the state of the
+    /// alias. Nothing is planned when the alias's write index already has
+    /// the mapping's fingerprint.
+    pub fn plan(&self, state: &ClusterState, now: SystemTime) -> Result<Plan, DeployError> {
+        let fingerprint = self.fingerprint()?;
+        let index = format!("{}-{}-{}", self.alias, timestamp(now), fingerprint);
+        let mut plan = Plan {
+            alias: self.alias.clone(),
+            fingerprint: fingerprint.clone(),
+            steps: Vec::new(),
+        };
+        let source = if state.replaces_index {
+            Some(self.alias.clone())
+        } else {
+            state
+                .write_index()
+                .map_err(|indices| DeployError::NoWriteIndex {
+                    alias: self.alias.clone(),
+                    indices,
+                })?
+        };
+        if let Some(source) = &source {
+            if self.fingerprint_of(source) == Some(fingerprint.as_str()) {
+                return Ok(plan);
+            }
+        }
+        plan.steps.push(Step::CreateIndex {
+            index: index.clone(),
+            mapping: self.mapping.clone(),
+        });
+        if let Some(source) = source {
+            plan.steps.extend(vec![
+                Step::Reindex {
+                    source: source.clone(),
+                    dest: index.clone(),
+                    script: self.script.clone(),
+                },
+                Step::WaitForReindex {
+                    source: source.clone(),
+                    dest: index.clone(),
+                },
+                Step::CheckCounts {
+                    source,
+                    dest: index.clone(),
+                },
+            ]);
+        }
+        plan.steps.push(Step::SwapAlias {
+            alias: self.alias.clone(),
+            index,
+            from: state.aliased.keys().cloned().collect(),
+            replaces_index: state.replaces_index,
+        });
+        Ok(plan)
+    }
+
+    // the fingerprint in the name of a backing index
+    fn fingerprint_of<'a>(&self, index: &'a str) -> Option<&'a str> {
+        let rest = index.strip_prefix(&self.alias)?.strip_prefix('-')?;
+        let (_, fingerprint) = rest.rsplit_once('-')?;
+        Some(fingerprint).filter(|f| f.len() == 8 && f.bytes().all(|b| b.is_ascii_hexdigit()))
+    }
+}
+
+// json with the keys of every object sorted, so that the same body is
+// written the same way whatever order its maps kept
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(value, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn timestamp(now: SystemTime) -> String {
+    let millis = match now.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_millis() as i64,
+        Err(before) => -(before.duration().as_millis() as i64),
+    };
+    DateFormat::parse("yyyyMMddHHmmss")
+        .expect("a supported pattern")
+        .write(millis, &mut || false)
+}
+
+/// What's behind an alias in the cluster.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ClusterState {
+    /// The indices the alias points at, with whether each is its write
+    /// index.
+    pub aliased: BTreeMap<String, Option<bool>>,
+    /// Whether there's an index with the alias's name, as there is when an
+    /// application used an index directly before it used an alias. The alias
+    /// then replaces the index.
+    pub replaces_index: bool,
+}
+
+impl ClusterState {
+    pub fn fetch<T: Transport>(client: &Client<T>, alias: &str) -> Result<ClusterState, ApiError> {
+        let aliased = client.get_alias(alias)?;
+        let replaces_index = aliased.is_empty() && client.index_exists(alias)?;
+        Ok(ClusterState {
+            aliased,
+            replaces_index,
+        })
+    }
+
+    /// The index writes to the alias go to, or the indices it points at when
+    /// there's no telling.
+    fn write_index(&self) -> Result<Option<String>, Vec<String>> {
+        let mut indices = self.aliased.iter();
+        match (indices.next(), indices.next()) {
+            (None, _) => Ok(None),
+            (Some((only, is_write_index)), None) if *is_write_index != Some(false) => {
+                Ok(Some(only.clone()))
+            }
+            _ => match self.aliased.iter().find(|(_, w)| **w == Some(true)) {
+                Some((index, _)) => Ok(Some(index.clone())),
+                None => Err(self.aliased.keys().cloned().collect()),
+            },
+        }
+    }
+}
+
+/// The steps of a deployment, in the order they're taken.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Plan {
+    pub alias: String,
+    pub fingerprint: String,
+    pub steps: Vec<Step>,
+}
+
+impl Plan {
+    /// The steps of the plan, each taken as the iterator reaches it. The
+    /// iterator ends after a step fails.
+    pub fn execute<'a, T: Transport>(&'a self, client: &'a Client<T>) -> Execution<'a, T> {
+        Execution {
+            steps: self.steps.iter(),
+            client,
+            task: None,
+            poll_interval: Duration::from_secs(1),
+            failed: false,
+        }
+    }
+
+    /// Takes every step of the plan.
+    pub fn run<T: Transport>(&self, client: &Client<T>) -> Result<(), DeployError> {
+        self.execute(client).try_for_each(|step| step.map(drop))
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.steps.is_empty() {
+            return write!(
+                f,
+                "{} is up to date with mapping {}",
+                self.alias, self.fingerprint
+            );
+        }
+        write!(
+            f,
+            "deploy mapping {} behind {}:",
+            self.fingerprint, self.alias
+        )?;
+        for (i, step) in self.steps.iter().enumerate() {
+            write!(f, "\n  {}. {}", i + 1, step)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Step {
+    CreateIndex {
+        index: String,
+        mapping: IndexMapping,
+    },
+    /// Starts copying the documents of `source` into `dest`.
+    Reindex {
+        source: String,
+        dest: String,
+        script: Option<Script>,
+    },
+    WaitForReindex {
+        source: String,
+        dest: String,
+    },
+    /// Fails unless `dest` holds as many documents as `source`, which a
+    /// script that drops documents would prevent.
+    CheckCounts {
+        source: String,
+        dest: String,
+    },
+    /// Points `alias` at `index` as its write index and away from the
+    /// indices it pointed at, or from the index it replaces, all at once.
+    SwapAlias {
+        alias: String,
+        index: String,
+        from: Vec<String>,
+        replaces_index: bool,
+    },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::CreateIndex { index, .. } => write!(f, "create index {}", index),
+            Step::Reindex {
+                source,
+                dest,
+                script,
+            } => {
+                write!(f, "reindex {} into {}", source, dest)?;
+                match script {
+                    Some(script) => write!(f, " with script `{}`", script.source),
+                    None => Ok(()),
+                }
+            }
+            Step::WaitForReindex { source, dest } => write!(
+                f,
+                "wait for the reindex of {} into {} to complete",
+                source, dest
+            ),
+            Step::CheckCounts { source, dest } => {
+                write!(f, "check {} holds as many documents as {}", dest, source)
+            }
+            Step::SwapAlias {
+                alias,
+                index,
+                from,
+                replaces_index,
+            } => {
+                write!(f, "point {} at {} as its write index", alias, index)?;
+                if !from.is_empty() {
+                    write!(f, ", moving it from {}", from.join(", "))?;
+                }
+                if *replaces_index {
+                    write!(f, ", deleting the index {} it replaces", alias)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A plan being carried out. Each item is a step that was taken, or the
+/// error that stopped it.
+pub struct Execution<'a, T> {
+    steps: std::slice::Iter<'a, Step>,
+    client: &'a Client<T>,
+    // the reindex started and not yet waited for
+    task: Option<String>,
+    poll_interval: Duration,
+    failed: bool,
+}
+
+impl<'a, T: Transport> Execution<'a, T> {
+    /// How long to wait between checks on a reindex, a second by default.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    fn take(&mut self, step: &Step) -> Result<(), DeployError> {
+        let client = self.client;
+        match step {
+            Step::CreateIndex { index, mapping } => client.create_index(index, mapping)?,
+            Step::Reindex {
+                source,
+                dest,
+                script,
+            } => self.task = Some(client.reindex(source, dest, script.as_ref())?),
+            Step::WaitForReindex { source, dest } => {
+                let task = self.task.take().ok_or(DeployError::NoReindex)?;
+                let failed = |reason: String| DeployError::ReindexFailed {
+                    source: source.clone(),
+                    dest: dest.clone(),
+                    reason,
+                };
+                let response = loop {
+                    let status = client.get_task(&task)?;
+                    if let Some(error) = status.error {
+                        return Err(failed(error.to_string()));
+                    }
+                    if status.completed {
+                        break status.response.unwrap_or_default();
+                    }
+                    std::thread::sleep(self.poll_interval);
+                };
+                if let Some(failures) = response["failures"].as_array().filter(|f| !f.is_empty()) {
+                    return Err(failed(format!(
+                        "{} documents failed, the first with {}",
+                        failures.len(),
+                        failures[0]["cause"]["reason"]
+                    )));
+                }
+            }
+            Step::CheckCounts { source, dest } => {
+                let (source_count, dest_count) = (client.count(source)?, client.count(dest)?);
+                if source_count != dest_count {
+                    return Err(DeployError::CountMismatch {
+                        source: source.clone(),
+                        source_count,
+                        dest: dest.clone(),
+                        dest_count,
+                    });
+                }
+            }
+            Step::SwapAlias {
+                alias,
+                index,
+                from,
+                replaces_index,
+            } => {
+                let mut actions = Vec::new();
+                if *replaces_index {
+                    actions.push(AliasAction::RemoveIndex {
+                        index: alias.clone(),
+                    });
+                }
+                actions.extend(from.iter().map(|from| AliasAction::Remove {
+                    index: from.clone(),
+                    alias: alias.clone(),
+                }));
+                actions.push(AliasAction::Add {
+                    index: index.clone(),
+                    alias: alias.clone(),
+                    is_write_index: Some(true),
+                });
+                client.update_aliases(actions)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: Transport> Iterator for Execution<'a, T> {
+    type Item = Result<&'a Step, DeployError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let step = self.steps.next()?;
+        match self.take(step) {
+            Ok(()) => Some(Ok(step)),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum DeployError {
+    Api(ApiError),
+    /// The alias points at several indices and none is its write index, so
+    /// there's no telling which to reindex.
+    NoWriteIndex {
+        alias: String,
+        indices: Vec<String>,
+    },
+    /// A wait for a reindex that wasn't started.
+    NoReindex,
+    ReindexFailed {
+        source: String,
+        dest: String,
+        reason: String,
+    },
+    CountMismatch {
+        source: String,
+        source_count: u64,
+        dest: String,
+        dest_count: u64,
+    },
+}
+
+impl fmt::Display for DeployError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeployError::Api(e) => e.fmt(f),
+            DeployError::NoWriteIndex { alias, indices } => write!(
+                f,
+                "alias {} points at {} without a write index",
+                alias,
+                indices.join(", ")
+            ),
+            DeployError::NoReindex => f.write_str("there's no reindex to wait for"),
+            DeployError::ReindexFailed {
+                source,
+                dest,
+                reason,
+            } => write!(f, "reindexing {} into {} failed: {}", source, dest, reason),
+            DeployError::CountMismatch {
+                source,
+                source_count,
+                dest,
+                dest_count,
+            } => write!(
+                f,
+                "{} holds {} documents but {} holds {}",
+                dest, dest_count, source, source_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeployError {}
+
+impl From<ApiError> for DeployError {
+    fn from(e: ApiError) -> Self {
+        DeployError::Api(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::field::{Field, IndexSettings};
+    use crate::testing::FakeCluster;
+    use crate::transport::api::BulkOperation;
+    use crate::transport::Request;
+    use serde_json::json;
+
+    fn mapping(fields: &[&str]) -> IndexMapping {
+        IndexMapping {
+            fields: fields
+                .iter()
+                .map(|name| Field::keyword(name).build().unwrap())
+                .collect(),
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec!["ignored".to_string()],
+            dynamic: None,
+        }
+    }
+
+    // 2024-03-01T12:00:00Z and a day later
+    fn day(n: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_709_294_400 + n * 86_400)
+    }
+
+    fn deploy(client: &Client<FakeCluster>, planner: &Planner, when: SystemTime) -> Plan {
+        let state = ClusterState::fetch(client, "products").unwrap();
+        let plan = planner.plan(&state, when).unwrap();
+        plan.run(client).unwrap();
+        plan
+    }
+
+    fn index(client: &Client<FakeCluster>, skus: &[&str]) {
+        let operations = skus
+            .iter()
+            .map(|sku| BulkOperation {
+                action: crate::transport::api::BulkAction::Index,
+                index: "products".to_string(),
+                id: Some(sku.to_string()),
+                routing: None,
+                version: None,
+                source: Some(json!({ "sku": sku })),
+            })
+            .collect();
+        assert!(!client.bulk(operations).unwrap().errors);
+    }
+
+    #[test]
+    fn fingerprints_leave_out_dynamic_settings() {
+        let fingerprint = |settings: IndexSettings| {
+            let mapping = IndexMapping {
+                settings,
+                ..mapping(&["sku"])
+            };
+            Planner::new("products", mapping).fingerprint().unwrap()
+        };
+        let unset = fingerprint(IndexSettings::default());
+        let replicated = IndexSettings {
+            number_of_shards: None,
+            number_of_replicas: Some(2),
+        };
+        assert_eq!(fingerprint(replicated), unset);
+        let sharded = IndexSettings {
+            number_of_shards: Some(2),
+            number_of_replicas: None,
+        };
+        assert_ne!(fingerprint(sharded), unset);
+    }
+
+    #[test]
+    fn fingerprints_hash_sorted_keys() {
+        let mut canonical = String::new();
+        let body = json!({"b": [{"d": 1, "c": null}], "a": "\"x\""});
+        write_canonical(&body, &mut canonical);
+        assert_eq!(canonical, r#"{"a":"\"x\"","b":[{"c":null,"d":1}]}"#);
+    }
+
+    #[test]
+    fn first_deployments_create_and_alias() {
+        let client = Client::new(FakeCluster::new());
+        let planner = Planner::new("products", mapping(&["sku"]));
+        let fingerprint = planner.fingerprint().unwrap();
+        let plan = deploy(&client, &planner, day(0));
+        let index = format!("products-20240301120000-{}", fingerprint);
+        assert_eq!(plan.steps.len(), 2);
+        assert_eq!(
+            client.get_alias("products").unwrap(),
+            vec![(index.clone(), Some(true))].into_iter().collect()
+        );
+        assert_eq!(
+            client.get_aliases(Some(&index)).unwrap()[&index],
+            vec!["products"]
+        );
+
+        let replanned = planner
+            .plan(&ClusterState::fetch(&client, "products").unwrap(), day(1))
+            .unwrap();
+        assert!(replanned.steps.is_empty());
+        assert_eq!(
+            replanned.to_string(),
+            format!("products is up to date with mapping {}", fingerprint)
+        );
+    }
+
+    #[test]
+    fn changed_mappings_are_reindexed_behind_the_alias() {
+        let client = Client::new(FakeCluster::new());
+        let first = deploy(
+            &client,
+            &Planner::new("products", mapping(&["sku"])),
+            day(0),
+        );
+        index(&client, &["a1", "b2", "c3"]);
+
+        let planner = Planner::new("products", mapping(&["sku", "colour"]));
+        assert_ne!(planner.fingerprint().unwrap(), first.fingerprint);
+        let state = ClusterState::fetch(&client, "products").unwrap();
+        let plan = planner.plan(&state, day(1)).unwrap();
+        let (old, new) = (
+            format!("products-20240301120000-{}", first.fingerprint),
+            format!("products-20240302120000-{}", plan.fingerprint),
+        );
+        assert_eq!(
+            plan.to_string(),
+            format!(
+                "deploy mapping {fingerprint} behind products:\n  \
+                 1. create index {new}\n  \
+                 2. reindex {old} into {new}\n  \
+                 3. wait for the reindex of {old} into {new} to complete\n  \
+                 4. check {new} holds as many documents as {old}\n  \
+                 5. point products at {new} as its write index, moving it from {old}",
+                fingerprint = plan.fingerprint,
+                old = old,
+                new = new
+            )
+        );
+
+        let taken: Vec<String> = plan
+            .execute(&client)
+            .poll_interval(Duration::from_millis(1))
+            .map(|step| step.unwrap().to_string())
+            .collect();
+        assert_eq!(taken.len(), 5);
+        assert_eq!(
+            client.get_alias("products").unwrap(),
+            vec![(new.clone(), Some(true))].into_iter().collect()
+        );
+        assert_eq!(client.count(&new).unwrap(), 3);
+        // the previous index stays for rolling back
+        assert_eq!(client.count(&old).unwrap(), 3);
+        index(&client, &["d4"]);
+        assert_eq!(client.count(&new).unwrap(), 4);
+    }
+
+    #[test]
+    fn aliases_replace_indices_of_their_name() {
+        let client = Client::new(FakeCluster::new());
+        client
+            .transport()
+            .send(&Request::new(crate::transport::Method::Put, "/products").with_json(json!({})))
+            .unwrap();
+        index(&client, &["a1", "b2"]);
+
+        let planner = Planner::new("products", mapping(&["sku"]));
+        let plan = deploy(&client, &planner, day(0));
+        assert!(plan
+            .to_string()
+            .ends_with("as its write index, deleting the index products it replaces"));
+        let aliased = client.get_alias("products").unwrap();
+        assert_eq!(aliased.len(), 1);
+        assert_eq!(client.count("products").unwrap(), 2);
+    }
+
+    #[test]
+    fn failures_stop_execution() {
+        let client = Client::new(FakeCluster::new());
+        deploy(
+            &client,
+            &Planner::new("products", mapping(&["sku"])),
+            day(0),
+        );
+        index(&client, &["a1"]);
+
+        let planner = Planner::new("products", mapping(&["sku", "colour"])).script(
+            Script::new("ctx._source.colour = params.colour").param("colour", json!("red")),
+        );
+        let state = ClusterState::fetch(&client, "products").unwrap();
+        let plan = planner.plan(&state, day(1)).unwrap();
+        assert!(plan.steps[1]
+            .to_string()
+            .ends_with("with script `ctx._source.colour = params.colour`"));
+        // the fake cluster doesn't run scripts
+        let taken: Vec<Result<&Step, DeployError>> = plan.execute(&client).collect();
+        assert_eq!(taken.len(), 2);
+        assert!(taken[1].is_err());
+        assert_eq!(client.get_alias("products").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn aliases_without_a_write_index_are_ambiguous() {
+        let state = ClusterState {
+            aliased: vec![
+                ("products-1".to_string(), None),
+                ("products-2".to_string(), None),
+            ]
+            .into_iter()
+            .collect(),
+            replaces_index: false,
+        };
+        let error = Planner::new("products", mapping(&["sku"]))
+            .plan(&state, day(0))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "alias products points at products-1, products-2 without a write index"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Data dictionaries, which describe the fields of an index for the people
+//! searching it. A mapping is rendered as a Markdown table with a row for
+//! each field, taking descriptions and other notes from the fields' meta:
+//!
+//! ```text
+//! | Path | Type | Analysis | Sub-fields | Description | Meta |
+//! | --- | --- | --- | --- | --- | --- |
+//! | `title` | text | english | `title.keyword` (keyword), `title.raw` (keyword) | The product's name | owner: search |
+//! ```
+use crate::dsl::field::{Field, FieldType, IndexMapping};
+
+/// The meta key a field's description is kept under.
+pub const DESCRIPTION: &str = "description";
+
+/// The mapping's fields as a Markdown table, nested properties and dynamic
+/// templates included.
+pub fn data_dictionary(mapping: &IndexMapping) -> String {
+    let mut dictionary = String::from(
+        "| Path | Type | Analysis | Sub-fields | Description | Meta |\n\
+         | --- | --- | --- | --- | --- | --- |\n",
+    );
+    rows(&mapping.fields, "", &mut dictionary);
+    dictionary
+}
+
+fn rows(fields: &[Field], prefix: &str, dictionary: &mut String) {
+    for field in fields {
+        let path = format!("{}{}", prefix, field.name);
+        let subfields: Vec<String> = field
+            .fields
+            .iter()
+            .map(|f| format!("`{}.{}` ({})", path, f.name, f.field_type.name()))
+            .collect();
+        let meta: Vec<String> = field
+            .meta
+            .iter()
+            .filter(|(key, _)| key.as_str() != DESCRIPTION)
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        let description = field.meta.get(DESCRIPTION).map_or("", String::as_str);
+        dictionary.push_str(&format!(
+            "| `{}` | {} | {} | {} | {} | {} |\n",
+            path,
+            field.field_type.name(),
+            escape(&analysis(&field.field_type)),
+            subfields.join(", "),
+            escape(description),
+            escape(&meta.join(", ")),
+        ));
+        match &field.field_type {
+            FieldType::Nested { properties } => rows(properties, &format!("{}.", path), dictionary),
+            FieldType::Object { templates } => rows(templates, prefix, dictionary),
+            _ => (),
+        }
+    }
+}
+
+// the analyzers or normalizer of a text or keyword field, or how a date is
+// written
+fn analysis(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Text {
+            analyzer,
+            search_analyzer: Some(search),
+        } => format!("{} (search: {})", analyzer.name, search.name),
+        FieldType::Text { analyzer, .. } => analyzer.name.clone(),
+        FieldType::Keyword { normalizer, .. } => normalizer.name.clone(),
+        FieldType::Date {
+            format: Some(format),
+        } => format!("format: {}", format),
+        _ => String::new(),
+    }
+}
+
+// cells are a single line, and pipes would end them early
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::analysis::Analyzer;
+    use crate::dsl::field::IndexSettings;
+
+    #[test]
+    fn fields_become_rows() {
+        let english = Analyzer {
+            name: "english".to_string(),
+            ..Analyzer::default()
+        };
+        let mut title: Field = Field::text("title")
+            .analyzer(english)
+            .subfield(Field::keyword("keyword"))
+            .subfield(Field::keyword("raw"))
+            .build()
+            .unwrap();
+        let mut views = Field::new("views", FieldType::Long);
+        views.meta.insert("unit".to_string(), "views".to_string());
+        views
+            .meta
+            .insert("owner".to_string(), "analytics".to_string());
+        let mut reviews = Field::new(
+            "reviews",
+            FieldType::Nested {
+                properties: vec![
+                    Field::new("stars", FieldType::Byte),
+                    Field::text("body").subfield(Field::keyword("raw")).build().unwrap(),
+                ],
+            },
+        );
+        set_description(&mut title, "The product's name | title");
+        set_description(&mut reviews, "Reviews, newest first");
+        let prices = Field::new(
+            "prices",
+            FieldType::Object {
+                templates: vec![Field::keyword("prices.*").build().unwrap()],
+            },
+        );
+        let mapping = IndexMapping {
+            fields: vec![title, views, reviews, prices],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        assert_eq!(
+            data_dictionary(&mapping),
+            "| Path | Type | Analysis | Sub-fields | Description | Meta |\n\
+             | --- | --- | --- | --- | --- | --- |\n\
+             | `title` | text | english | `title.keyword` (keyword), `title.raw` (keyword) | The product's name \\| title |  |\n\
+             | `views` | long |  |  |  | owner: analytics, unit: views |\n\
+             | `reviews` | nested |  |  | Reviews, newest first |  |\n\
+             | `reviews.stars` | byte |  |  |  |  |\n\
+             | `reviews.body` | text | sedk_default_normalizer | `reviews.body.raw` (keyword) |  |  |\n\
+             | `prices` | object |  |  |  |  |\n\
+             | `prices.*` | keyword | sedk_default_normalizer |  |  |  |\n"
+        );
+    }
+
+    fn set_description(field: &mut Field, description: &str) {
+        field
+            .meta
+            .insert(DESCRIPTION.to_string(), description.to_string());
+    }
+}
@@ -0,0 +1,105 @@
+//! The identity of the records stored in an index. Indexing a record needs
+//! its `_id`, and may need the shard it's routed to and the external version
+//! that guards against older copies overwriting newer ones.
+use std::borrow::Cow;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub trait Document {
+    /// The record's `_id`.
+    fn id(&self) -> Cow<'_, str>;
+
+    /// The key that decides the record's shard, elasticsearch using the id
+    /// when there's none.
+    fn routing(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// An external version, which must increase for each change to the
+    /// record.
+    fn version(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Values that can be used as an `_id` or routing key, borrowed where they
+/// already are strings.
+pub trait AsId {
+    fn as_id(&self) -> Cow<'_, str>;
+}
+
+impl AsId for str {
+    fn as_id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<T: AsId + ?Sized> AsId for &T {
+    fn as_id(&self) -> Cow<'_, str> {
+        (**self).as_id()
+    }
+}
+
+macro_rules! borrowed_id {
+    ($($t:ty),*) => {
+        $(
+            impl AsId for $t {
+                fn as_id(&self) -> Cow<'_, str> {
+                    Cow::Borrowed(self)
+                }
+            }
+        )*
+    };
+}
+
+borrowed_id!(String, Cow<'_, str>, Box<str>, Rc<str>, Arc<str>);
+
+// numbers are formatted the way they would be serialized
+macro_rules! formatted_id {
+    ($($t:ty),*) => {
+        $(
+            impl AsId for $t {
+                fn as_id(&self) -> Cow<'_, str> {
+                    Cow::Owned(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+formatted_id!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, char);
+formatted_id!(IpAddr, Ipv4Addr, Ipv6Addr);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Product {
+        sku: String,
+        shop: u32,
+    }
+
+    impl Document for Product {
+        fn id(&self) -> Cow<'_, str> {
+            self.sku.as_id()
+        }
+
+        fn routing(&self) -> Option<Cow<'_, str>> {
+            Some(self.shop.as_id())
+        }
+    }
+
+    #[test]
+    fn ids_borrow_strings() {
+        let product = Product {
+            sku: "ab-12".to_string(),
+            shop: 7,
+        };
+        assert!(matches!(product.id(), Cow::Borrowed("ab-12")));
+        assert_eq!(product.routing(), Some(Cow::Owned("7".to_string())));
+        assert_eq!(product.version(), None);
+        assert_eq!('x'.as_id(), "x");
+        assert_eq!((&"nested").as_id(), "nested");
+    }
+}
@@ -1,9 +1,10 @@
-use super::character_filters::CharacterFilter;
-use super::token_processing::TokenFilter;
-use super::tokenizers::Tokenizer;
+use super::character_filters::{CharacterFilter, CharacterFilterType};
+use super::token_processing::{TokenFilter, TokenFilterType};
+use super::tokenizers::{Tokenizer, TokenizerType};
 use serde::ser::Serializer;
 use serde::Serialize;
 use serde_json::json;
+use std::fmt;
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct Analyzer {
@@ -14,18 +15,46 @@ pub struct Analyzer {
 }
 
 impl Analyzer {
+    pub fn builder(name: &str) -> AnalyzerBuilder {
+        AnalyzerBuilder {
+            analyzer: Analyzer {
+                name: name.to_string(),
+                ..Analyzer::default()
+            },
+            components: Components::new(name),
+        }
+    }
+
+    /// An analyzer that treats text like `n` does before applying
+    /// `token_filters`.
     pub fn from_normalizer(
         n: &Normalizer,
         name: String,
         token_filters: Vec<TokenFilter>,
     ) -> Analyzer {
         Analyzer {
-            name: name,
-            token_filters: token_filters,
+            name,
+            token_filters: n.token_filters().into_iter().chain(token_filters).collect(),
             tokenizer: n.tokenizer(),
             character_filters: n.character_filters(),
         }
     }
+
+    /// Elasticsearch's built in `standard` analyzer, which text fields
+    /// without one get. Mappings refer to it by name without defining it.
+    pub fn standard() -> Analyzer {
+        Analyzer {
+            name: "standard".to_string(),
+            character_filters: Vec::new(),
+            tokenizer: Tokenizer::new("standard", TokenizerType::Standard),
+            token_filters: vec![TokenFilter::new("lowercase", TokenFilterType::Lowercase)],
+        }
+    }
+
+    /// Whether elasticsearch already has this analyzer, so it isn't defined.
+    pub(crate) fn is_built_in(&self) -> bool {
+        *self == Analyzer::standard()
+    }
 }
 
 impl Serialize for Analyzer {
@@ -41,7 +70,7 @@ impl Serialize for Analyzer {
     }
 }
 
-const DEFAULT_ANALYZER_NAME: &'static str = "sedk_default_normalizer";
+const DEFAULT_ANALYZER_NAME: &str = "sedk_default_normalizer";
 impl Default for Analyzer {
     fn default() -> Self {
         Analyzer {
@@ -58,9 +87,22 @@ pub struct Normalizer {
     pub name: String,
     pub character_filters: Vec<CharacterFilter>,
     pub tokenizer: Tokenizer,
+    /// Applied to the whole value as a single token, so only filters that
+    /// work a character at a time (lowercase, asciifolding) are allowed.
+    pub token_filters: Vec<TokenFilter>,
 }
 
 impl Normalizer {
+    pub fn builder(name: &str) -> NormalizerBuilder {
+        NormalizerBuilder {
+            normalizer: Normalizer {
+                name: name.to_string(),
+                ..Normalizer::default()
+            },
+            components: Components::new(name),
+        }
+    }
+
     fn tokenizer(&self) -> Tokenizer {
         self.tokenizer.clone()
     }
@@ -68,6 +110,10 @@ impl Normalizer {
     fn character_filters(&self) -> Vec<CharacterFilter> {
         self.character_filters.clone()
     }
+
+    fn token_filters(&self) -> Vec<TokenFilter> {
+        self.token_filters.clone()
+    }
 }
 
 impl Serialize for Normalizer {
@@ -75,20 +121,212 @@ impl Serialize for Normalizer {
     where
         S: Serializer,
     {
-        json!({
+        let mut normalizer = json!({
             "char_filter": self.character_filters.iter().map(|cf| cf.name.clone()).collect::<Vec<String>>(),
             "tokenizer": self.tokenizer.name
-        }).serialize(serializer)
+        });
+        if !self.token_filters.is_empty() {
+            normalizer["filter"] = json!(self
+                .token_filters
+                .iter()
+                .map(|tf| tf.name.clone())
+                .collect::<Vec<String>>());
+        }
+        normalizer.serialize(serializer)
     }
 }
 
-const DEFAULT_NORMALIZER_NAME: &'static str = "sedk_default_normalizer";
+const DEFAULT_NORMALIZER_NAME: &str = "sedk_default_normalizer";
 impl Default for Normalizer {
     fn default() -> Self {
         Normalizer {
             name: DEFAULT_NORMALIZER_NAME.to_owned(),
             character_filters: Vec::new(),
             tokenizer: Tokenizer::default(),
+            token_filters: Vec::new(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum BuildError {
+    EmptyName,
+    /// Two different components were given the same name.
+    NameConflict(String),
+    InvalidSettings {
+        component: String,
+        reason: String,
+    },
+    /// A normalizer was given a filter that does more than change characters.
+    NotNormalizing(String),
+    /// A field was given something elasticsearch would refuse, such as a
+    /// subfield holding an object.
+    InvalidField {
+        field: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::EmptyName => write!(f, "analysis components need a name"),
+            BuildError::NameConflict(name) => {
+                write!(f, "different components are both named {}", name)
+            }
+            BuildError::InvalidSettings { component, reason } => {
+                write!(f, "invalid settings for {}: {}", component, reason)
+            }
+            BuildError::NotNormalizing(name) => {
+                write!(f, "{} can't be used in a normalizer", name)
+            }
+            BuildError::InvalidField { field, reason } => {
+                write!(f, "invalid field {}: {}", field, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+// for building fields from what can't fail along with what can
+impl From<std::convert::Infallible> for BuildError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+// The components of an analyzer or normalizer so far, by name, along with the
+// first problem found with them.
+struct Components {
+    names: Vec<(String, Definition)>,
+    error: Option<BuildError>,
+}
+
+#[derive(PartialEq)]
+enum Definition {
+    CharFilter(CharacterFilterType),
+    Tokenizer(TokenizerType),
+    Filter(TokenFilterType),
+}
+
+impl Components {
+    fn new(name: &str) -> Components {
+        let mut components = Components {
+            names: Vec::new(),
+            error: None,
+        };
+        if name.is_empty() {
+            components.fail(BuildError::EmptyName);
+        }
+        components
+    }
+
+    fn fail(&mut self, error: BuildError) {
+        self.error.get_or_insert(error);
+    }
+
+    // components of one kind share a namespace, so the same name may only be
+    // used again for the same definition
+    fn add(&mut self, name: &str, definition: Definition) {
+        if name.is_empty() {
+            return self.fail(BuildError::EmptyName);
+        }
+        let kind = std::mem::discriminant(&definition);
+        let existing = self
+            .names
+            .iter()
+            .find(|(n, d)| n == name && std::mem::discriminant(d) == kind);
+        match existing {
+            Some((_, existing)) if *existing != definition => {
+                self.fail(BuildError::NameConflict(name.to_string()))
+            }
+            Some(_) => (),
+            None => self.names.push((name.to_string(), definition)),
+        }
+    }
+
+    fn add_filter(&mut self, filter: &TokenFilter) {
+        self.add(&filter.name, Definition::Filter(filter.filter_type.clone()));
+        if let Some(reason) = filter.filter_type.problem() {
+            self.fail(BuildError::InvalidSettings {
+                component: filter.name.clone(),
+                reason,
+            });
+        }
+    }
+}
+
+pub struct AnalyzerBuilder {
+    analyzer: Analyzer,
+    components: Components,
+}
+
+impl AnalyzerBuilder {
+    pub fn char_filter(mut self, char_filter: CharacterFilter) -> Self {
+        self.components.add(
+            &char_filter.name,
+            Definition::CharFilter(char_filter.character_filter_type.clone()),
+        );
+        self.analyzer.character_filters.push(char_filter);
+        self
+    }
+
+    /// Replaces the default whitespace tokenizer.
+    pub fn tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.components.add(
+            &tokenizer.name,
+            Definition::Tokenizer(tokenizer.tokenizer_type.clone()),
+        );
+        self.analyzer.tokenizer = tokenizer;
+        self
+    }
+
+    pub fn filter(mut self, filter: TokenFilter) -> Self {
+        self.components.add_filter(&filter);
+        self.analyzer.token_filters.push(filter);
+        self
+    }
+
+    /// The analyzer, or the first problem found while building it.
+    pub fn build(self) -> Result<Analyzer, BuildError> {
+        match self.components.error {
+            Some(error) => Err(error),
+            None => Ok(self.analyzer),
+        }
+    }
+}
+
+pub struct NormalizerBuilder {
+    normalizer: Normalizer,
+    components: Components,
+}
+
+impl NormalizerBuilder {
+    pub fn char_filter(mut self, char_filter: CharacterFilter) -> Self {
+        self.components.add(
+            &char_filter.name,
+            Definition::CharFilter(char_filter.character_filter_type.clone()),
+        );
+        self.normalizer.character_filters.push(char_filter);
+        self
+    }
+
+    pub fn filter(mut self, filter: TokenFilter) -> Self {
+        self.components.add_filter(&filter);
+        if !filter.filter_type.is_normalizing() {
+            self.components
+                .fail(BuildError::NotNormalizing(filter.name.clone()));
+        }
+        self.normalizer.token_filters.push(filter);
+        self
+    }
+
+    /// The normalizer, or the first problem found while building it.
+    pub fn build(self) -> Result<Normalizer, BuildError> {
+        match self.components.error {
+            Some(error) => Err(error),
+            None => Ok(self.normalizer),
         }
     }
 }
@@ -138,6 +376,7 @@ mod tests {
                     tokenize_on_chars: vec![CharacterGroups::Whitespace],
                 },
             },
+            token_filters: vec![],
         };
         let expected = json!({
             "char_filter": vec!["my_char_filter"],
@@ -145,4 +384,67 @@ mod tests {
         });
         assert_eq!(to_value(&normalizer).unwrap(), expected)
     }
+
+    #[test]
+    fn builder() {
+        use crate::dsl::token_processing::TokenFilterType;
+        let lowercase = TokenFilter::new("lower", TokenFilterType::Lowercase);
+        let analyzer = Analyzer::builder("my_analyzer")
+            .char_filter(CharacterFilter::new(
+                "my_char_filter",
+                CharacterFilterType::Mapping { mappings: vec![] },
+            ))
+            .tokenizer(Tokenizer::new("standard", TokenizerType::Standard))
+            .filter(lowercase.clone())
+            .filter(lowercase)
+            .build()
+            .unwrap();
+        assert_eq!(analyzer.name, "my_analyzer");
+        assert_eq!(analyzer.tokenizer.tokenizer_type, TokenizerType::Standard);
+        assert_eq!(analyzer.token_filters.len(), 2);
+    }
+
+    #[test]
+    fn builder_validates() {
+        use crate::dsl::token_processing::{StemmerLanguage, TokenFilterType};
+        assert_eq!(
+            Analyzer::builder("x")
+                .filter(TokenFilter::new("f", TokenFilterType::Lowercase))
+                .filter(TokenFilter::new("f", TokenFilterType::FlattenGraph))
+                .build(),
+            Err(BuildError::NameConflict("f".to_string()))
+        );
+        assert!(matches!(
+            Analyzer::builder("x")
+                .filter(TokenFilter::new(
+                    "prefixes",
+                    TokenFilterType::EdgeNgram {
+                        min_gram: 3,
+                        max_gram: 2,
+                        preserve_original: false,
+                    },
+                ))
+                .build(),
+            Err(BuildError::InvalidSettings { .. })
+        ));
+        assert_eq!(Analyzer::builder("").build(), Err(BuildError::EmptyName));
+        // each kind of component has its own names
+        assert!(Analyzer::builder("x")
+            .tokenizer(Tokenizer::new("f", TokenizerType::Standard))
+            .filter(TokenFilter::new("f", TokenFilterType::Lowercase))
+            .build()
+            .is_ok());
+        assert_eq!(
+            Normalizer::builder("n")
+                .filter(TokenFilter::new("lower", TokenFilterType::Lowercase))
+                .filter(TokenFilter::new(
+                    "stem",
+                    TokenFilterType::Stemmer {
+                        language: StemmerLanguage::English,
+                    },
+                ))
+                .build(),
+            Err(BuildError::NotNormalizing("stem".to_string()))
+        );
+    }
 }
@@ -1,3 +1,6 @@
+#[cfg(feature = "icu")]
+use super::plugins::IcuNormalization;
+use super::plugins::Plugin;
 use serde::ser::Serializer;
 use serde::Serialize;
 
@@ -7,6 +10,15 @@ pub struct CharacterFilter {
     pub character_filter_type: CharacterFilterType,
 }
 
+impl CharacterFilter {
+    pub fn new(name: &str, character_filter_type: CharacterFilterType) -> CharacterFilter {
+        CharacterFilter {
+            name: name.to_string(),
+            character_filter_type,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Hash, Serialize, Clone, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum CharacterFilterType {
@@ -23,6 +35,28 @@ pub enum CharacterFilterType {
         #[serde(serialize_with = "serialize_flags")]
         flags: Vec<RegexFlag>,
     },
+    #[cfg(feature = "icu")]
+    IcuNormalizer {
+        name: IcuNormalization,
+    },
+    #[cfg(feature = "kuromoji")]
+    KuromojiIterationMark {
+        normalize_kanji: bool,
+        normalize_kana: bool,
+    },
+}
+
+impl CharacterFilterType {
+    /// The plugin that provides this filter, if it isn't built in.
+    pub fn plugin(&self) -> Option<Plugin> {
+        match self {
+            #[cfg(feature = "icu")]
+            CharacterFilterType::IcuNormalizer { .. } => Some(Plugin::Icu),
+            #[cfg(feature = "kuromoji")]
+            CharacterFilterType::KuromojiIterationMark { .. } => Some(Plugin::Kuromoji),
+            _ => None,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
@@ -56,19 +90,19 @@ impl From<&RegexFlag> for String {
     }
 }
 
-fn serialize_flags<S>(flags: &Vec<RegexFlag>, serializer: S) -> Result<S::Ok, S::Error>
+fn serialize_flags<S>(flags: &[RegexFlag], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
     let s = flags
         .iter()
-        .map(|f| String::from(f))
+        .map(String::from)
         .collect::<Vec<String>>()
         .join("|");
     serializer.serialize_str(&s)
 }
 
-fn serialize_mappings<S>(mappings: &Vec<(String, String)>, serializer: S) -> Result<S::Ok, S::Error>
+fn serialize_mappings<S>(mappings: &[(String, String)], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -129,4 +163,17 @@ mod tests {
 
         assert_eq!(to_value(&char_filter).unwrap(), expected)
     }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn icu_normalizer() {
+        let char_filter = CharacterFilterType::IcuNormalizer {
+            name: IcuNormalization::NfkcCf,
+        };
+        let expected = json!({
+            "type": "icu_normalizer",
+            "name": "nfkc_cf"
+        });
+        assert_eq!(to_value(&char_filter).unwrap(), expected)
+    }
 }
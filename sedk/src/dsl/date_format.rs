@@ -0,0 +1,523 @@
+//! The formats of date fields, for checking values locally. Elasticsearch
+//! takes the names of its built in formats and java-style patterns, any
+//! number of which can be given separated by `||`:
+//!
+//! ```
+//! use sedk::dsl::date_format::DateFormat;
+//!
+//! let format = DateFormat::parse("strict_date||epoch_millis").unwrap();
+//! assert!(format.accepts("2021-03-01"));
+//! assert!(format.accepts("1614556800000"));
+//! assert!(!format.accepts("01/03/2021"));
+//! ```
+use std::fmt;
+
+/// The format of date fields that don't set one.
+pub const DEFAULT_DATE_FORMAT: &str = "strict_date_optional_time||epoch_millis";
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DateFormat {
+    alternatives: Vec<Vec<Part>>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum Part {
+    /// A number of between `min` and `max` digits, within `range`.
+    Number {
+        min: usize,
+        max: usize,
+        range: (u32, u32),
+        unit: Unit,
+    },
+    /// A fraction of a second, of between `min` and `max` digits.
+    Fraction {
+        min: usize,
+        max: usize,
+    },
+    /// An offset from UTC, such as `+01:00`, or `Z` when `utc` is set.
+    Offset {
+        utc: bool,
+    },
+    /// Seconds or milliseconds since the epoch, which can be negative and
+    /// have a fraction.
+    Epoch {
+        seconds: bool,
+    },
+    Literal(char),
+    Optional(Vec<Part>),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Unit {
+    Year,
+    YearOfCentury,
+    Month,
+    Day,
+    DayOfYear,
+    Hour,
+    ClockHour,
+    Minute,
+    Second,
+}
+
+impl Unit {
+    // the digits the unit is usually written with
+    fn width(self) -> usize {
+        match self {
+            Unit::Year => 4,
+            Unit::DayOfYear => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// A format that elasticsearch may accept, but sedk can't check values
+/// against.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct UnsupportedFormat(pub String);
+
+impl fmt::Display for UnsupportedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "sedk can't check dates against `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedFormat {}
+
+impl DateFormat {
+    pub fn parse(format: &str) -> Result<DateFormat, UnsupportedFormat> {
+        let alternatives = format
+            .split("||")
+            .map(|alternative| builtin(alternative).or_else(|| pattern(alternative)))
+            .collect::<Option<_>>()
+            .ok_or_else(|| UnsupportedFormat(format.to_string()))?;
+        Ok(DateFormat { alternatives })
+    }
+
+    /// Whether a date written as `value` would be accepted.
+    pub fn accepts(&self, value: &str) -> bool {
+        let value: Vec<char> = value.chars().collect();
+        self.alternatives
+            .iter()
+            .any(|parts| matches(parts, &value, &mut |rest| rest.is_empty()))
+    }
+
+    /// `millis` since the epoch written in the first of the formats, with the
+    /// optional sections `include` picks as long as the result is accepted.
+    pub(crate) fn write(&self, millis: i64, include: &mut dyn FnMut() -> bool) -> String {
+        let moment = Moment::new(millis);
+        let mut written = String::new();
+        write(&self.alternatives[0], &moment, include, &mut written);
+        if !self.accepts(&written) {
+            // sections such as `[.SSS][.SS]` are only valid alone
+            written.clear();
+            write(&self.alternatives[0], &moment, &mut || false, &mut written);
+        }
+        written
+    }
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        DateFormat::parse(DEFAULT_DATE_FORMAT).unwrap()
+    }
+}
+
+// whether a prefix of `input` matches `parts`, leaving a rest that `then`
+// accepts, backtracking through the lengths of numbers and optional parts
+fn matches(parts: &[Part], input: &[char], then: &mut dyn FnMut(&[char]) -> bool) -> bool {
+    let (part, parts) = match parts.split_first() {
+        Some(split) => split,
+        None => return then(input),
+    };
+    let digits = input.iter().take_while(|c| c.is_ascii_digit()).count();
+    match part {
+        Part::Number {
+            min, max, range, ..
+        } => (*min..=(*max).min(digits)).rev().any(|n| {
+            let value: u32 = input[..n]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .unwrap_or(u32::MAX);
+            value >= range.0 && value <= range.1 && matches(parts, &input[n..], then)
+        }),
+        Part::Fraction { min, max } => (*min..=(*max).min(digits))
+            .rev()
+            .any(|n| matches(parts, &input[n..], then)),
+        Part::Offset { utc } => {
+            if *utc && input.first() == Some(&'Z') {
+                return matches(parts, &input[1..], then);
+            }
+            if !matches!(input.first(), Some('+') | Some('-')) {
+                return false;
+            }
+            // +HH, +HHmm, +HH:mm, +HHmmss and +HH:mm:ss
+            let hours = Part::Number {
+                min: 2,
+                max: 2,
+                range: (0, 18),
+                unit: Unit::Hour,
+            };
+            let sixty = || Part::Number {
+                min: 2,
+                max: 2,
+                range: (0, 59),
+                unit: Unit::Minute,
+            };
+            let offsets = [
+                vec![
+                    hours.clone(),
+                    Part::Optional(vec![sixty(), Part::Optional(vec![sixty()])]),
+                ],
+                vec![
+                    hours,
+                    Part::Literal(':'),
+                    sixty(),
+                    Part::Optional(vec![Part::Literal(':'), sixty()]),
+                ],
+            ];
+            offsets
+                .iter()
+                .any(|offset| matches(offset, &input[1..], &mut |rest| matches(parts, rest, then)))
+        }
+        Part::Epoch { .. } => {
+            let sign = usize::from(input.first() == Some(&'-'));
+            let whole = sign
+                + input[sign..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+            if whole == sign {
+                return false;
+            }
+            let fraction = match input.get(whole) {
+                Some('.') => input[whole + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count(),
+                _ => 0,
+            };
+            (fraction > 0 && matches(parts, &input[whole + 1 + fraction..], then))
+                || matches(parts, &input[whole..], then)
+        }
+        Part::Literal(c) => input.first() == Some(c) && matches(parts, &input[1..], then),
+        Part::Optional(optional) => {
+            matches(optional, input, &mut |rest| matches(parts, rest, then))
+                || matches(parts, input, then)
+        }
+    }
+}
+
+// a moment in UTC, split into the units dates are written in
+struct Moment {
+    millis: i64,
+    year: i64,
+    month: u32,
+    day: u32,
+    day_of_year: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanos: u32,
+}
+
+impl Moment {
+    fn new(millis: i64) -> Moment {
+        let days = millis.div_euclid(86_400_000);
+        let of_day = millis.rem_euclid(86_400_000) as u32;
+        // the proleptic gregorian date of a day, as in Howard Hinnant's
+        // `civil_from_days`
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let of_era = z.rem_euclid(146_097);
+        let year_of_era = (of_era - of_era / 1460 + of_era / 36_524 - of_era / 146_096) / 365;
+        let from_march = of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * from_march + 2) / 153;
+        let day = (from_march - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let before = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334][month as usize - 1];
+        Moment {
+            millis,
+            year,
+            month,
+            day,
+            day_of_year: before + u32::from(leap && month > 2) + day,
+            hour: of_day / 3_600_000,
+            minute: of_day / 60_000 % 60,
+            second: of_day / 1000 % 60,
+            nanos: of_day % 1000 * 1_000_000,
+        }
+    }
+
+    fn get(&self, unit: Unit) -> i64 {
+        match unit {
+            Unit::Year => self.year,
+            Unit::YearOfCentury => self.year.rem_euclid(100),
+            Unit::Month => self.month.into(),
+            Unit::Day => self.day.into(),
+            Unit::DayOfYear => self.day_of_year.into(),
+            Unit::Hour => self.hour.into(),
+            Unit::ClockHour => ((self.hour + 11) % 12 + 1).into(),
+            Unit::Minute => self.minute.into(),
+            Unit::Second => self.second.into(),
+        }
+    }
+}
+
+fn write(parts: &[Part], moment: &Moment, include: &mut dyn FnMut() -> bool, out: &mut String) {
+    // the start of the optional section just written, since of sections such
+    // as `[.SSS][.SS]` that start the same way only one is written
+    let mut written: Option<&Part> = None;
+    for part in parts {
+        match part {
+            Part::Number { min, unit, .. } => {
+                let width = unit.width().max(*min);
+                out.push_str(&format!("{:0width$}", moment.get(*unit), width = width));
+            }
+            Part::Fraction { min, max } => {
+                let digits = (*min).max((*max).min(3));
+                let nanos = format!("{:09}", moment.nanos);
+                let fraction = &nanos[..digits.min(9)];
+                out.push_str(&format!("{:0<width$}", fraction, width = digits));
+            }
+            Part::Offset { utc: true } => out.push('Z'),
+            Part::Offset { utc: false } => out.push_str("+00:00"),
+            Part::Epoch { seconds: true } => {
+                out.push_str(&moment.millis.div_euclid(1000).to_string())
+            }
+            Part::Epoch { seconds: false } => out.push_str(&moment.millis.to_string()),
+            Part::Literal(c) => out.push(*c),
+            Part::Optional(optional) => {
+                let start = optional.first();
+                if (start.is_none() || start != written) && include() {
+                    write(optional, moment, include, out);
+                    written = start;
+                }
+                continue;
+            }
+        }
+        written = None;
+    }
+}
+
+// the patterns of the built in formats, which are the same whether or not
+// they're strict apart from how many digits numbers can have
+fn builtin(name: &str) -> Option<Vec<Part>> {
+    match name {
+        "epoch_millis" => return Some(vec![Part::Epoch { seconds: false }]),
+        "epoch_second" => return Some(vec![Part::Epoch { seconds: true }]),
+        _ => (),
+    }
+    let (strict, name) = match name.strip_prefix("strict_") {
+        Some(name) => (true, name),
+        None => (false, name),
+    };
+    let java = match name {
+        "date_optional_time" | "date_optional_time_nanos" => {
+            "yyyy[-MM[-dd]]['T'HH[:mm[:ss[.n]]][XXX]]"
+        }
+        "date" | "year_month_day" => "yyyy-MM-dd",
+        "year_month" => "yyyy-MM",
+        "year" | "weekyear" => "yyyy",
+        "date_time" => "yyyy-MM-dd'T'HH:mm:ss.nXXX",
+        "date_time_no_millis" => "yyyy-MM-dd'T'HH:mm:ssXXX",
+        "date_hour" => "yyyy-MM-dd'T'HH",
+        "date_hour_minute" => "yyyy-MM-dd'T'HH:mm",
+        "date_hour_minute_second" => "yyyy-MM-dd'T'HH:mm:ss",
+        "date_hour_minute_second_fraction" | "date_hour_minute_second_millis" => {
+            "yyyy-MM-dd'T'HH:mm:ss.n"
+        }
+        "basic_date" => "yyyyMMdd",
+        "basic_date_time" => "yyyyMMdd'T'HHmmss.nXXX",
+        "basic_date_time_no_millis" => "yyyyMMdd'T'HHmmssXXX",
+        "hour_minute_second" => "HH:mm:ss",
+        "hour_minute_second_fraction" | "hour_minute_second_millis" => "HH:mm:ss.n",
+        "time" => "HH:mm:ss.nXXX",
+        "time_no_millis" => "HH:mm:ssXXX",
+        _ => return None,
+    };
+    let mut parts = pattern(java)?;
+    if !strict {
+        loosen(&mut parts);
+    }
+    Some(parts)
+}
+
+// lenient formats take numbers with fewer digits, as in `2021-3-1`
+fn loosen(parts: &mut [Part]) {
+    for part in parts {
+        match part {
+            Part::Number { min, .. } => *min = 1,
+            Part::Optional(parts) => loosen(parts),
+            _ => (),
+        }
+    }
+}
+
+// the parts of a java-style pattern, or none if it uses anything that can't
+// be checked, such as the names of months
+fn pattern(pattern: &str) -> Option<Vec<Part>> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut stack: Vec<Vec<Part>> = vec![Vec::new()];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let count = chars[i..].iter().take_while(|&&d| d == c).count();
+        let number = |max_digits: usize, range: (u32, u32), unit: Unit| Part::Number {
+            min: count.min(max_digits),
+            max: count.max(max_digits),
+            range,
+            unit,
+        };
+        let part = match c {
+            '[' => {
+                stack.push(Vec::new());
+                i += 1;
+                continue;
+            }
+            ']' => {
+                let optional = stack.pop()?;
+                stack.last_mut()?.push(Part::Optional(optional));
+                i += 1;
+                continue;
+            }
+            '\'' => {
+                let end = chars[i + 1..].iter().position(|&d| d == '\'')? + i + 1;
+                if end == i + 1 {
+                    stack.last_mut()?.push(Part::Literal('\''));
+                }
+                for &literal in &chars[i + 1..end] {
+                    stack.last_mut()?.push(Part::Literal(literal));
+                }
+                i = end + 1;
+                continue;
+            }
+            'y' | 'u' if count == 2 => number(2, (0, 99), Unit::YearOfCentury),
+            'y' | 'u' => Part::Number {
+                min: count.max(4),
+                max: count.max(9),
+                range: (0, u32::MAX),
+                unit: Unit::Year,
+            },
+            'M' if count <= 2 => number(2, (1, 12), Unit::Month),
+            'd' if count <= 2 => number(2, (1, 31), Unit::Day),
+            'D' if count <= 3 => number(3, (1, 366), Unit::DayOfYear),
+            'H' if count <= 2 => number(2, (0, 23), Unit::Hour),
+            'h' if count <= 2 => number(2, (1, 12), Unit::ClockHour),
+            'm' if count <= 2 => number(2, (0, 59), Unit::Minute),
+            's' if count <= 2 => number(2, (0, 59), Unit::Second),
+            'S' => Part::Fraction {
+                min: count,
+                max: count,
+            },
+            'n' => Part::Fraction { min: 1, max: 9 },
+            'X' | 'Z' => Part::Offset { utc: true },
+            'x' => Part::Offset { utc: false },
+            c if c.is_ascii_alphabetic() => return None,
+            c => {
+                stack.last_mut()?.push(Part::Literal(c));
+                i += 1;
+                continue;
+            }
+        };
+        stack.last_mut()?.push(part);
+        i += count;
+    }
+    match (stack.pop(), stack.is_empty()) {
+        (Some(parts), true) => Some(parts),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_default_format_takes_iso_dates_and_epoch_millis() {
+        let format = DateFormat::default();
+        for date in &[
+            "2021",
+            "2021-03-01",
+            "2021-03-01T12:00",
+            "2021-03-01T12:00:00Z",
+            "2021-03-01T12:00:00.123456789+01:00",
+            "2021-03-01T12:00:00.5-0530",
+            "1614556800000",
+            "-1.5",
+        ] {
+            assert!(format.accepts(date), "{}", date);
+        }
+        for date in &[
+            "",
+            "2021-13-01",
+            "2021-03-01T25:00",
+            "2021-03-01T12:00:00+",
+            "March",
+        ] {
+            assert!(!format.accepts(date), "{}", date);
+        }
+    }
+
+    #[test]
+    fn only_lenient_formats_take_short_numbers() {
+        assert!(DateFormat::parse("date").unwrap().accepts("2021-3-1"));
+        assert!(!DateFormat::parse("strict_date")
+            .unwrap()
+            .accepts("2021-3-1"));
+    }
+
+    #[test]
+    fn patterns() {
+        let format = DateFormat::parse("uuuu-MM-dd HH:mm:ss[.n] xxxxx").unwrap();
+        assert!(format.accepts("2021-03-01 12:00:00.123456789 +00:00:00"));
+        assert!(format.accepts("2021-03-01 12:00:00 -05:30"));
+        assert!(!format.accepts("2021-03-01 12:00:00 Z"));
+        let format = DateFormat::parse("dd/MM/yyyy'T'HH").unwrap();
+        assert!(format.accepts("01/03/2021T09"));
+        assert!(!format.accepts("01/03/2021 09"));
+    }
+
+    #[test]
+    fn writing_dates() {
+        let millis = 1_614_600_000_123;
+        let written = |format: &str, include: bool| {
+            DateFormat::parse(format)
+                .unwrap()
+                .write(millis, &mut || include)
+        };
+        assert_eq!(
+            written("strict_date_optional_time", true),
+            "2021-03-01T12:00:00.123Z"
+        );
+        assert_eq!(written("strict_date_optional_time", false), "2021");
+        assert_eq!(written("date||epoch_millis", true), "2021-03-01");
+        assert_eq!(written("epoch_second", true), "1614600000");
+        assert_eq!(written("dd/MM/yy h:mm, D", true), "01/03/21 12:00, 060");
+        assert_eq!(
+            written("uuuu-MM-dd HH:mm:ss[.SSSSSS][.SSS] xxx", true),
+            "2021-03-01 12:00:00.123000 +00:00"
+        );
+        assert_eq!(
+            DateFormat::default().write(-86_400_000 * 366, &mut || true),
+            "1968-12-31T00:00:00.000Z"
+        );
+    }
+
+    #[test]
+    fn unsupported_patterns() {
+        for format in &["MMM d, yyyy", "yyyy-MM-dd[", "strict_date||EEE", "dd 'of"] {
+            assert_eq!(
+                DateFormat::parse(format),
+                Err(UnsupportedFormat(format.to_string()))
+            );
+        }
+    }
+}
@@ -1,30 +1,106 @@
 use super::{
-    analysis::{Analyzer, Normalizer},
+    analysis::{Analyzer, BuildError, Normalizer},
     character_filters::CharacterFilterType,
+    plugins::{Plugin, UndeclaredPlugins},
     token_processing::TokenFilterType,
     tokenizers::TokenizerType,
 };
-use serde::ser::{SerializeMap, Serializer};
+use serde::ser::{Error, SerializeMap, Serializer};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
 
-#[derive(PartialEq, Eq, Clone, Serialize)]
-#[serde(into = "IndexMappingDTO")]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct IndexMapping {
     pub fields: Vec<Field>,
+    /// The analysis plugins installed on the cluster. A mapping using plugin
+    /// components fails to serialize unless their plugins are listed here.
+    pub plugins: Vec<Plugin>,
+    pub settings: IndexSettings,
+    /// Other names the index can be searched by.
+    pub aliases: Vec<String>,
+    /// How fields missing from the mapping are treated, elasticsearch's
+    /// default of adding them when unset.
+    pub dynamic: Option<Dynamic>,
 }
 
-#[derive(PartialEq, Eq, Clone, Serialize, Debug)]
+/// Index settings left to the cluster's defaults when unset.
+#[derive(PartialEq, Eq, Clone, Default, Debug, Serialize)]
+pub struct IndexSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_shards: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_replicas: Option<u32>,
+}
+
+impl IndexSettings {
+    pub fn is_empty(&self) -> bool {
+        self.number_of_shards.is_none() && self.number_of_replicas.is_none()
+    }
+}
+
+/// What elasticsearch does with fields a document has but the mapping doesn't.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dynamic {
+    /// Add them to the mapping.
+    True,
+    /// Keep them in the source without indexing them.
+    False,
+    /// Reject the document.
+    Strict,
+    /// Map them as runtime fields, computed at query time.
+    Runtime,
+}
+
+impl Serialize for IndexMapping {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.check_plugins().map_err(S::Error::custom)?;
+        self.check_fields().map_err(S::Error::custom)?;
+        self.check_analysis().map_err(S::Error::custom)?;
+        IndexMappingDTO::from(self.clone()).serialize(serializer)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Field {
-    #[serde(skip)]
     pub name: String,
-    #[serde(flatten)]
     pub field_type: FieldType,
-    #[serde(serialize_with = "serialize_fields")]
     pub fields: Vec<Field>,
+    /// Whether the field can be searched, rather than only stored.
+    pub index: bool,
+    /// Fields this field's values are also indexed into, by full path.
+    pub copy_to: Vec<String>,
+    /// Notes about the field for people and tools, which elasticsearch keeps
+    /// but doesn't use.
+    pub meta: BTreeMap<String, String>,
+}
+
+impl Serialize for Field {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        FieldDTO {
+            field_type: &self.field_type,
+            // objects have properties instead of multi-fields
+            fields: match self.field_type {
+                FieldType::Nested { .. } | FieldType::Object { .. } => None,
+                _ => Some(&self.fields),
+            },
+            index: self.index,
+            copy_to: &self.copy_to,
+            meta: &self.meta,
+        }
+        .serialize(serializer)
+    }
 }
 
-#[derive(PartialEq, Eq, Clone, Serialize, Debug)]
+#[derive(PartialEq, Clone, Serialize, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum FieldType {
     Binary,
@@ -32,12 +108,30 @@ pub enum FieldType {
     #[serde(serialize_with = "serialize_keyword")]
     Keyword {
         normalizer: Normalizer,
+        /// Strings longer than this many characters are stored but not
+        /// indexed.
+        ignore_above: Option<u32>,
     },
     #[serde(serialize_with = "serialize_text")]
     Text {
         analyzer: Analyzer,
+        /// Analyzes queries against the field, when they should be treated
+        /// differently from indexed text.
+        search_analyzer: Option<Analyzer>,
+    },
+    /// A date, written in `format` or by default as an ISO 8601 date or
+    /// milliseconds since the epoch. `dsl::date_format` checks values
+    /// against it.
+    Date {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
+    /// An IPv4 or IPv6 address.
+    Ip,
+    /// An array of `dims` floats, for similarity search.
+    DenseVector {
+        dims: u32,
     },
-    Date,
     Long,
     Integer,
     Short,
@@ -45,28 +139,326 @@ pub enum FieldType {
     Double,
     Float,
     HalfFloat,
-    ScaledFloat,
+    /// A number stored as a long, multiplied by `scaling_factor` and rounded.
+    ScaledFloat {
+        scaling_factor: f64,
+    },
     UnsignedLong,
+    /// An array of objects indexed separately, so that queries can match
+    /// fields of the same object together.
+    #[serde(serialize_with = "serialize_nested")]
+    Nested {
+        properties: Vec<Field>,
+    },
+    /// A whole object indexed as keywords, however many keys it has.
+    Flattened,
+    /// An object whose keys aren't known in advance. Its values are mapped by
+    /// dynamic templates, named by the paths they match such as `prices.*`.
+    #[serde(serialize_with = "serialize_object")]
+    Object {
+        templates: Vec<Field>,
+    },
 }
 
-fn serialize_keyword<S>(n: &Normalizer, serializer: S) -> Result<S::Ok, S::Error>
+// scaling factors are positive numbers, never NaN, so types equal themselves
+impl Eq for FieldType {}
+
+impl FieldType {
+    /// The name elasticsearch knows the type by.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldType::Binary => "binary",
+            FieldType::Boolean => "boolean",
+            FieldType::Keyword { .. } => "keyword",
+            FieldType::Text { .. } => "text",
+            FieldType::Date { .. } => "date",
+            FieldType::Ip => "ip",
+            FieldType::DenseVector { .. } => "dense_vector",
+            FieldType::Long => "long",
+            FieldType::Integer => "integer",
+            FieldType::Short => "short",
+            FieldType::Byte => "byte",
+            FieldType::Double => "double",
+            FieldType::Float => "float",
+            FieldType::HalfFloat => "half_float",
+            FieldType::ScaledFloat { .. } => "scaled_float",
+            FieldType::UnsignedLong => "unsigned_long",
+            FieldType::Nested { .. } => "nested",
+            FieldType::Flattened => "flattened",
+            FieldType::Object { .. } => "object",
+        }
+    }
+}
+
+impl Field {
+    pub fn new(name: &str, field_type: FieldType) -> Field {
+        Field {
+            name: name.to_string(),
+            field_type,
+            fields: vec![],
+            index: true,
+            copy_to: vec![],
+            meta: BTreeMap::new(),
+        }
+    }
+
+    /// A text field using the default analyzer until told otherwise.
+    pub fn text(name: &str) -> TextField {
+        TextField {
+            name: name.to_string(),
+            analyzer: Analyzer::default(),
+            search_analyzer: None,
+            fields: vec![],
+            error: named(name),
+        }
+    }
+
+    /// A keyword field using the default normalizer until told otherwise.
+    pub fn keyword(name: &str) -> KeywordField {
+        KeywordField {
+            name: name.to_string(),
+            normalizer: Normalizer::default(),
+            ignore_above: None,
+            fields: vec![],
+            error: named(name),
+        }
+    }
+
+    /// Adds a subfield, replacing any subfield of the same name.
+    pub fn subfield(mut self, field: impl Into<Field>) -> Field {
+        add_subfield(&mut self.fields, field.into());
+        self
+    }
+}
+
+fn named(name: &str) -> Option<BuildError> {
+    if name.is_empty() {
+        Some(BuildError::EmptyName)
+    } else {
+        None
+    }
+}
+
+fn add_subfield(fields: &mut Vec<Field>, field: Field) {
+    match fields.iter_mut().find(|f| f.name == field.name) {
+        Some(existing) => *existing = field,
+        None => fields.push(field),
+    }
+}
+
+// adds a subfield to a field being built, or the first problem with either
+fn build_subfield<F>(fields: &mut Vec<Field>, error: &mut Option<BuildError>, field: F)
+where
+    F: TryInto<Field>,
+    BuildError: From<F::Error>,
+{
+    let problem = match field.try_into() {
+        Ok(field) if field.name.is_empty() => Some(BuildError::EmptyName),
+        // elasticsearch reads a dot as a path into an object
+        Ok(field) if field.name.contains('.') => Some(BuildError::InvalidField {
+            field: field.name,
+            reason: "subfield names can't contain dots".to_string(),
+        }),
+        Ok(Field {
+            name,
+            field_type: FieldType::Nested { .. } | FieldType::Object { .. },
+            ..
+        }) => Some(BuildError::InvalidField {
+            field: name,
+            reason: "subfields can't be objects".to_string(),
+        }),
+        Ok(field) => {
+            add_subfield(fields, field);
+            None
+        }
+        Err(e) => Some(e.into()),
+    };
+    if let Some(problem) = problem {
+        error.get_or_insert(problem);
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TextField {
+    name: String,
+    analyzer: Analyzer,
+    search_analyzer: Option<Analyzer>,
+    fields: Vec<Field>,
+    error: Option<BuildError>,
+}
+
+impl TextField {
+    pub fn analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    pub fn search_analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.search_analyzer = Some(analyzer);
+        self
+    }
+
+    /// Adds a subfield, replacing any subfield of the same name.
+    pub fn subfield<F>(mut self, field: F) -> Self
+    where
+        F: TryInto<Field>,
+        BuildError: From<F::Error>,
+    {
+        build_subfield(&mut self.fields, &mut self.error, field);
+        self
+    }
+
+    /// The field, or the first problem found while building it.
+    pub fn build(self) -> Result<Field, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        // a search analyzer that matches the index one would only be noise
+        let analyzer = self.analyzer;
+        let search_analyzer = self.search_analyzer.filter(|a| *a != analyzer);
+        Ok(Field {
+            name: self.name,
+            field_type: FieldType::Text {
+                analyzer,
+                search_analyzer,
+            },
+            fields: self.fields,
+            index: true,
+            copy_to: vec![],
+            meta: BTreeMap::new(),
+        })
+    }
+}
+
+impl TryFrom<TextField> for Field {
+    type Error = BuildError;
+
+    fn try_from(text: TextField) -> Result<Field, BuildError> {
+        text.build()
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct KeywordField {
+    name: String,
+    normalizer: Normalizer,
+    ignore_above: Option<u32>,
+    fields: Vec<Field>,
+    error: Option<BuildError>,
+}
+
+impl KeywordField {
+    pub fn normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    pub fn ignore_above(mut self, length: u32) -> Self {
+        self.ignore_above = Some(length);
+        self
+    }
+
+    /// Adds a subfield, replacing any subfield of the same name.
+    pub fn subfield<F>(mut self, field: F) -> Self
+    where
+        F: TryInto<Field>,
+        BuildError: From<F::Error>,
+    {
+        build_subfield(&mut self.fields, &mut self.error, field);
+        self
+    }
+
+    /// The field, or the first problem found while building it.
+    pub fn build(self) -> Result<Field, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        Ok(Field {
+            name: self.name,
+            field_type: FieldType::Keyword {
+                normalizer: self.normalizer,
+                ignore_above: self.ignore_above,
+            },
+            fields: self.fields,
+            index: true,
+            copy_to: vec![],
+            meta: BTreeMap::new(),
+        })
+    }
+}
+
+impl TryFrom<KeywordField> for Field {
+    type Error = BuildError;
+
+    fn try_from(keyword: KeywordField) -> Result<Field, BuildError> {
+        keyword.build()
+    }
+}
+
+fn serialize_keyword<S>(
+    n: &Normalizer,
+    ignore_above: &Option<u32>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let mut map = serializer.serialize_map(Some(1))?;
+    let mut map = serializer.serialize_map(None)?;
     map.serialize_entry("normalizer", &n.name)?;
+    if let Some(length) = ignore_above {
+        map.serialize_entry("ignore_above", length)?;
+    }
     map.end()
 }
 
-fn serialize_text<S>(n: &Analyzer, serializer: S) -> Result<S::Ok, S::Error>
+fn serialize_text<S>(
+    n: &Analyzer,
+    search: &Option<Analyzer>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let mut map = serializer.serialize_map(Some(1))?;
+    let mut map = serializer.serialize_map(None)?;
     map.serialize_entry("analyzer", &n.name)?;
+    if let Some(search) = search {
+        map.serialize_entry("search_analyzer", &search.name)?;
+    }
+    map.end()
+}
+
+fn serialize_nested<S>(properties: &[Field], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry("properties", &Properties(properties))?;
+    map.end()
+}
+
+// the templates are part of the mapping's dynamic_templates instead. They
+// only apply to fields elasticsearch may add, so the object takes new keys
+// even when the rest of the mapping is strict.
+fn serialize_object<S>(_templates: &[Field], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry("dynamic", &true)?;
     map.end()
 }
 
+struct Properties<'a>(&'a [Field]);
+
+impl Serialize for Properties<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_fields(self.0, serializer)
+    }
+}
+
 fn serialize_fields<S>(fields: &[Field], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -78,33 +470,253 @@ where
     map.end()
 }
 
+impl IndexMapping {
+    /// The analyzers of every text field, subfields included, by name.
+    pub fn analyzers(&self) -> HashMap<&str, &Analyzer> {
+        let mut analyzers = HashMap::new();
+        visit_fields(&self.fields, &mut |field| {
+            if let FieldType::Text {
+                analyzer,
+                search_analyzer,
+            } = &field.field_type
+            {
+                for a in std::iter::once(analyzer).chain(search_analyzer) {
+                    analyzers.insert(a.name.as_str(), a);
+                }
+            }
+        });
+        analyzers
+    }
+
+    /// The normalizers of every keyword field, subfields included, by name.
+    pub fn normalizers(&self) -> HashMap<&str, &Normalizer> {
+        let mut normalizers = HashMap::new();
+        visit_fields(&self.fields, &mut |field| {
+            if let FieldType::Keyword { normalizer, .. } = &field.field_type {
+                normalizers.insert(normalizer.name.as_str(), normalizer);
+            }
+        });
+        normalizers
+    }
+
+    /// The plugins providing the analysis components this mapping uses.
+    pub fn required_plugins(&self) -> BTreeSet<Plugin> {
+        let mut plugins = BTreeSet::new();
+        for analyzer in self.analyzers().values() {
+            plugins.extend(analyzer.tokenizer.tokenizer_type.plugin());
+            for cf in &analyzer.character_filters {
+                plugins.extend(cf.character_filter_type.plugin());
+            }
+            for tf in &analyzer.token_filters {
+                plugins.extend(tf.filter_type.plugin());
+            }
+        }
+        for normalizer in self.normalizers().values() {
+            plugins.extend(normalizer.tokenizer.tokenizer_type.plugin());
+            for cf in &normalizer.character_filters {
+                plugins.extend(cf.character_filter_type.plugin());
+            }
+            for tf in &normalizer.token_filters {
+                plugins.extend(tf.filter_type.plugin());
+            }
+        }
+        plugins
+    }
+
+    pub fn check_plugins(&self) -> Result<(), UndeclaredPlugins> {
+        let undeclared: Vec<Plugin> = self
+            .required_plugins()
+            .into_iter()
+            .filter(|plugin| !self.plugins.contains(plugin))
+            .collect();
+        if undeclared.is_empty() {
+            Ok(())
+        } else {
+            Err(UndeclaredPlugins(undeclared))
+        }
+    }
+
+    /// Fails if analyzers or normalizers give different components the same
+    /// name, which elasticsearch would keep only one of. Serializing the
+    /// mapping checks this too.
+    pub fn check_analysis(&self) -> Result<(), BuildError> {
+        let mut analysis = AnalysisDTO::default();
+        process_fields(&self.fields, &mut analysis);
+        match analysis.conflicts.into_iter().next() {
+            Some(name) => Err(BuildError::NameConflict(name)),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if a field is named more than once, which a mapping can't
+    /// express. Serializing the mapping checks this too.
+    pub fn check_fields(&self) -> Result<(), ConflictingFields> {
+        let mut conflicts = Vec::new();
+        conflicting_fields(&self.fields, "", &mut conflicts);
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(ConflictingFields(conflicts))
+        }
+    }
+}
+
+/// Fields a mapping names more than once, by full path. Variants of a derived
+/// enum that wrap types mapping a shared field differently give both mappings.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ConflictingFields(pub Vec<String>);
+
+impl fmt::Display for ConflictingFields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the mapping maps these fields more than once: {}",
+            self.0.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ConflictingFields {}
+
+fn conflicting_fields(fields: &[Field], prefix: &str, conflicts: &mut Vec<String>) {
+    let mut names = BTreeSet::new();
+    for field in fields {
+        let path = format!("{}{}", prefix, field.name);
+        if !names.insert(field.name.as_str()) && !conflicts.contains(&path) {
+            conflicts.push(path.clone());
+        }
+        conflicting_fields(&field.fields, &format!("{}.", path), conflicts);
+        match &field.field_type {
+            FieldType::Nested { properties } => {
+                conflicting_fields(properties, &format!("{}.", path), conflicts)
+            }
+            FieldType::Object { templates } => conflicting_fields(templates, prefix, conflicts),
+            _ => (),
+        }
+    }
+}
+
+fn visit_fields<'a, F: FnMut(&'a Field)>(fields: &'a [Field], visit: &mut F) {
+    for field in fields {
+        visit(field);
+        visit_fields(&field.fields, visit);
+        match &field.field_type {
+            FieldType::Nested { properties } => visit_fields(properties, visit),
+            FieldType::Object { templates } => visit_fields(templates, visit),
+            _ => (),
+        }
+    }
+}
+
+// the templates of every object, with paths made absolute
+fn dynamic_templates(fields: &[Field], prefix: &str, templates: &mut Vec<Field>) {
+    for field in fields {
+        match &field.field_type {
+            FieldType::Nested { properties } => {
+                dynamic_templates(properties, &format!("{}{}.", prefix, field.name), templates)
+            }
+            // the templates of maps within maps go first, since the paths of
+            // the outer map's templates match their keys as well
+            FieldType::Object { templates: t } => {
+                dynamic_templates(t, prefix, templates);
+                templates.extend(t.iter().map(|template| Field {
+                    name: format!("{}{}", prefix, template.name),
+                    ..template.clone()
+                }))
+            }
+            _ => (),
+        }
+    }
+}
+
 pub trait Indexable {
     fn index_mapping() -> IndexMapping;
 }
 
+#[derive(Serialize)]
+struct FieldDTO<'a> {
+    #[serde(flatten)]
+    field_type: &'a FieldType,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_multi_fields"
+    )]
+    fields: Option<&'a Vec<Field>>,
+    #[serde(skip_serializing_if = "is_true")]
+    index: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    copy_to: &'a Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    meta: &'a BTreeMap<String, String>,
+}
+
+fn serialize_multi_fields<S>(fields: &Option<&Vec<Field>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serialize_fields(fields.map_or(&[], |f| f.as_slice()), serializer)
+}
+
+fn is_true(b: &bool) -> bool {
+    *b
+}
+
 #[derive(Serialize)]
 struct IndexMappingDTO {
+    #[serde(skip_serializing_if = "IndexSettings::is_empty")]
+    settings: IndexSettings,
     analysis: AnalysisDTO,
     mappings: MappingsDTO,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    aliases: BTreeMap<String, AliasDTO>,
 }
 
+// aliases are objects of options, none of which are used yet
+#[derive(Serialize)]
+struct AliasDTO {}
+
 impl From<IndexMapping> for IndexMappingDTO {
     fn from(mapping: IndexMapping) -> Self {
-        let mut analysis = AnalysisDTO {
-            char_filter: HashMap::new(),
-            tokenizer: HashMap::new(),
-            filter: HashMap::new(),
-            normalizer: HashMap::new(),
-            analyzer: HashMap::new(),
-        };
+        let mut analysis = AnalysisDTO::default();
 
         process_fields(&mapping.fields, &mut analysis);
+        let mut dynamic_templates = Vec::new();
+        self::dynamic_templates(&mapping.fields, "", &mut dynamic_templates);
 
         IndexMappingDTO {
-            analysis: analysis,
+            settings: mapping.settings,
+            analysis,
             mappings: MappingsDTO {
+                dynamic: mapping.dynamic,
                 properties: mapping.fields,
+                dynamic_templates,
             },
+            aliases: mapping
+                .aliases
+                .into_iter()
+                .map(|alias| (alias, AliasDTO {}))
+                .collect(),
+        }
+    }
+}
+
+// elasticsearch has a namespace for each kind of analysis component, so
+// analyzers may only share a component's name if they share its definition
+fn define<T: PartialEq + Clone>(
+    definitions: &mut HashMap<String, T>,
+    name: &str,
+    definition: &T,
+    conflicts: &mut Vec<String>,
+) {
+    match definitions.get(name) {
+        Some(existing) if existing != definition => {
+            if !conflicts.iter().any(|c| c == name) {
+                conflicts.push(name.to_string());
+            }
+        }
+        Some(_) => (),
+        None => {
+            definitions.insert(name.to_string(), definition.clone());
         }
     }
 }
@@ -112,32 +724,45 @@ impl From<IndexMapping> for IndexMappingDTO {
 fn process_fields(fields: &[Field], analysis: &mut AnalysisDTO) {
     for field in fields {
         process_fields(&field.fields, analysis);
+        let AnalysisDTO {
+            char_filter,
+            tokenizer,
+            filter,
+            normalizer,
+            analyzer,
+            conflicts,
+        } = analysis;
         match &field.field_type {
-            FieldType::Keyword { normalizer: n } => {
-                analysis.normalizer.insert(n.name.clone(), n.clone());
-                analysis
-                    .tokenizer
-                    .insert(n.tokenizer.name.clone(), n.tokenizer.tokenizer_type.clone());
+            FieldType::Nested { properties } => process_fields(properties, analysis),
+            FieldType::Object { templates } => process_fields(templates, analysis),
+            FieldType::Keyword { normalizer: n, .. } => {
+                define(normalizer, &n.name, n, conflicts);
+                let t = &n.tokenizer;
+                define(tokenizer, &t.name, &t.tokenizer_type, conflicts);
                 for cf in &n.character_filters {
-                    analysis
-                        .char_filter
-                        .insert(cf.name.clone(), cf.character_filter_type.clone());
+                    define(char_filter, &cf.name, &cf.character_filter_type, conflicts);
                 }
-            }
-            FieldType::Text { analyzer: a } => {
-                analysis.analyzer.insert(a.name.clone(), a.clone());
-                analysis
-                    .tokenizer
-                    .insert(a.tokenizer.name.clone(), a.tokenizer.tokenizer_type.clone());
-                for cf in &a.character_filters {
-                    analysis
-                        .char_filter
-                        .insert(cf.name.clone(), cf.character_filter_type.clone());
+                for tf in &n.token_filters {
+                    define(filter, &tf.name, &tf.filter_type, conflicts);
                 }
-                for tf in &a.token_filters {
-                    analysis
-                        .filter
-                        .insert(tf.name.clone(), tf.filter_type.clone());
+            }
+            FieldType::Text {
+                analyzer: a,
+                search_analyzer,
+            } => {
+                let defined = std::iter::once(a)
+                    .chain(search_analyzer)
+                    .filter(|a| !a.is_built_in());
+                for a in defined {
+                    define(analyzer, &a.name, a, conflicts);
+                    let t = &a.tokenizer;
+                    define(tokenizer, &t.name, &t.tokenizer_type, conflicts);
+                    for cf in &a.character_filters {
+                        define(char_filter, &cf.name, &cf.character_filter_type, conflicts);
+                    }
+                    for tf in &a.token_filters {
+                        define(filter, &tf.name, &tf.filter_type, conflicts);
+                    }
                 }
             }
             _ => (),
@@ -145,19 +770,52 @@ fn process_fields(fields: &[Field], analysis: &mut AnalysisDTO) {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 struct AnalysisDTO {
     char_filter: HashMap<String, CharacterFilterType>,
     tokenizer: HashMap<String, TokenizerType>,
     filter: HashMap<String, TokenFilterType>,
     normalizer: HashMap<String, Normalizer>,
     analyzer: HashMap<String, Analyzer>,
+    // names given to different definitions, which can't be serialized
+    #[serde(skip)]
+    conflicts: Vec<String>,
 }
 
 #[derive(Serialize)]
 struct MappingsDTO {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dynamic: Option<Dynamic>,
     #[serde(serialize_with = "serialize_fields")]
     properties: Vec<Field>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_templates"
+    )]
+    dynamic_templates: Vec<Field>,
+}
+
+fn serialize_templates<S>(templates: &[Field], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(templates.iter().map(|template| {
+        let mut entry = HashMap::new();
+        entry.insert(
+            &template.name,
+            DynamicTemplateDTO {
+                path_match: &template.name,
+                mapping: template,
+            },
+        );
+        entry
+    }))
+}
+
+#[derive(Serialize)]
+struct DynamicTemplateDTO<'a> {
+    path_match: &'a str,
+    mapping: &'a Field,
 }
 
 #[cfg(test)]
@@ -185,6 +843,7 @@ mod tests {
                     tokenize_on_chars: vec![CharacterGroups::Whitespace],
                 },
             },
+            token_filters: vec![],
         }
     }
 
@@ -196,7 +855,8 @@ mod tests {
     fn keyword_serialization() {
         assert_eq!(
             to_value(FieldType::Keyword {
-                normalizer: normalizer()
+                normalizer: normalizer(),
+                ignore_above: None,
             })
             .unwrap(),
             json!({
@@ -217,22 +877,40 @@ mod tests {
                         name: "int".to_string(),
                         field_type: FieldType::Integer,
                         fields: vec![],
+                        index: true,
+                        copy_to: vec![],
+                        meta: BTreeMap::new(),
                     }],
+                    index: true,
+                    copy_to: vec![],
+                    meta: BTreeMap::new(),
                 },
                 Field {
                     name: "keyword".to_string(),
                     field_type: FieldType::Keyword {
                         normalizer: normalizer(),
+                        ignore_above: None,
                     },
                     fields: vec![Field {
                         name: "text".to_string(),
                         field_type: FieldType::Text {
                             analyzer: analyzer(),
+                            search_analyzer: None,
                         },
                         fields: vec![],
+                        index: true,
+                        copy_to: vec![],
+                        meta: BTreeMap::new(),
                     }],
+                    index: true,
+                    copy_to: vec![],
+                    meta: BTreeMap::new(),
                 },
             ],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
         };
         let expected = json!({
             "analysis": {
@@ -298,4 +976,256 @@ mod tests {
         });
         assert_eq!(to_value(&index).unwrap(), expected)
     }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn plugins_must_be_declared() {
+        use crate::dsl::{
+            plugins::{IcuNormalization, Plugin, UndeclaredPlugins},
+            token_processing::{TokenFilter, TokenFilterType},
+        };
+        let analyzer = Analyzer::from_normalizer(
+            &normalizer(),
+            "folding".to_string(),
+            vec![TokenFilter {
+                name: "nfkc".to_string(),
+                filter_type: TokenFilterType::IcuNormalizer {
+                    name: IcuNormalization::Nfkc,
+                },
+            }],
+        );
+        let mut index = IndexMapping {
+            fields: vec![Field {
+                name: "text".to_string(),
+                field_type: FieldType::Text {
+                    analyzer,
+                    search_analyzer: None,
+                },
+                fields: vec![],
+                index: true,
+                copy_to: vec![],
+                meta: BTreeMap::new(),
+            }],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        assert_eq!(
+            index.check_plugins(),
+            Err(UndeclaredPlugins(vec![Plugin::Icu]))
+        );
+        assert!(to_value(&index).is_err());
+        index.plugins.push(Plugin::Icu);
+        assert!(to_value(&index).is_ok());
+    }
+
+    #[test]
+    fn built_in_analyzers_are_only_named() {
+        let index = IndexMapping {
+            fields: vec![Field::new(
+                "title",
+                FieldType::Text {
+                    analyzer: Analyzer::standard(),
+                    search_analyzer: None,
+                },
+            )],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        let value = to_value(&index).unwrap();
+        assert_eq!(value["mappings"]["properties"]["title"]["analyzer"], "standard");
+        assert_eq!(value["analysis"]["analyzer"], json!({}));
+        assert_eq!(value["analysis"]["tokenizer"], json!({}));
+    }
+
+    #[test]
+    fn fields_are_named_once() {
+        let mut index = IndexMapping {
+            fields: vec![
+                Field::new("id", FieldType::Long),
+                Field::new("owner", FieldType::Nested {
+                    properties: vec![
+                        Field::new("id", FieldType::Long),
+                        Field::new("id", FieldType::Integer),
+                    ],
+                }),
+            ],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        assert_eq!(
+            index.check_fields(),
+            Err(ConflictingFields(vec!["owner.id".to_string()]))
+        );
+        assert!(to_value(&index).is_err());
+        if let FieldType::Nested { properties } = &mut index.fields[1].field_type {
+            properties.pop();
+        }
+        assert!(to_value(&index).is_ok());
+    }
+
+    #[test]
+    fn field_builders() {
+        let field: Field = Field::text("title")
+            .analyzer(analyzer())
+            .search_analyzer(analyzer())
+            .subfield(Field::keyword("raw"))
+            .subfield(Field::keyword("raw").normalizer(normalizer()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            field,
+            Field {
+                name: "title".to_string(),
+                field_type: FieldType::Text {
+                    analyzer: analyzer(),
+                    search_analyzer: None,
+                },
+                fields: vec![Field::new(
+                    "raw",
+                    FieldType::Keyword {
+                        normalizer: normalizer(),
+                        ignore_above: None,
+                    }
+                )],
+                index: true,
+                copy_to: vec![],
+                meta: BTreeMap::new(),
+            }
+        );
+        assert_eq!(
+            Field::new("count", FieldType::Long).subfield(Field::text("text").build().unwrap()),
+            Field {
+                name: "count".to_string(),
+                field_type: FieldType::Long,
+                fields: vec![Field::text("text").build().unwrap()],
+                index: true,
+                copy_to: vec![],
+                meta: BTreeMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn field_builders_validate() {
+        assert_eq!(Field::keyword("").build(), Err(BuildError::EmptyName));
+        assert_eq!(
+            Field::text("title").subfield(Field::keyword("")).build(),
+            Err(BuildError::EmptyName)
+        );
+        assert!(matches!(
+            Field::text("title").subfield(Field::keyword("a.b")).build(),
+            Err(BuildError::InvalidField { field, .. }) if field == "a.b"
+        ));
+        assert!(matches!(
+            Field::keyword("sku")
+                .subfield(Field::new("parts", FieldType::Nested { properties: vec![] }))
+                .build(),
+            Err(BuildError::InvalidField { field, .. }) if field == "parts"
+        ));
+    }
+
+    #[test]
+    fn analyzers_share_components_by_definition() {
+        use crate::dsl::token_processing::{TokenFilter, TokenFilterType};
+        let with_filter = |name: &str, filter_type: TokenFilterType| {
+            Analyzer::builder(name)
+                .filter(TokenFilter::new("shared", filter_type))
+                .build()
+                .unwrap()
+        };
+        let text = |name: &str, analyzer: Analyzer| {
+            Field::text(name).analyzer(analyzer).build().unwrap()
+        };
+        let mut index = IndexMapping {
+            fields: vec![
+                text("title", with_filter("a", TokenFilterType::Lowercase)),
+                text("body", with_filter("b", TokenFilterType::Lowercase)),
+            ],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        assert_eq!(index.check_analysis(), Ok(()));
+        index.fields.push(text("notes", with_filter("c", TokenFilterType::FlattenGraph)));
+        assert_eq!(
+            index.check_analysis(),
+            Err(BuildError::NameConflict("shared".to_string()))
+        );
+        assert!(to_value(&index).is_err());
+    }
+
+    #[test]
+    fn field_parameters() {
+        let mut comment = Field::new(
+            "comment",
+            FieldType::Nested {
+                properties: vec![Field::text("body").analyzer(analyzer()).build().unwrap()],
+            },
+        );
+        comment.copy_to = vec!["all".to_string()];
+        let mut secret = Field::new("secret", FieldType::Long);
+        secret.index = false;
+        let price = Field::new(
+            "price",
+            FieldType::ScaledFloat {
+                scaling_factor: 100.0,
+            },
+        );
+        let value = to_value(&IndexMapping {
+            fields: vec![comment, secret, price],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        })
+        .unwrap();
+        assert_eq!(
+            value["mappings"]["properties"],
+            json!({
+                "comment": {
+                    "type": "nested",
+                    "properties": {
+                        "body": {"type": "text", "analyzer": "my_analyzer", "fields": {}}
+                    },
+                    "copy_to": ["all"]
+                },
+                "secret": {"type": "long", "fields": {}, "index": false},
+                "price": {"type": "scaled_float", "scaling_factor": 100.0, "fields": {}}
+            })
+        );
+        assert!(value["analysis"]["analyzer"]["my_analyzer"].is_object());
+    }
+
+    #[test]
+    fn index_settings() {
+        let mut mapping = IndexMapping {
+            fields: vec![Field::new("count", FieldType::Long)],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        let value = to_value(&mapping).unwrap();
+        assert!(value.get("settings").is_none());
+        assert!(value.get("aliases").is_none());
+        assert!(value["mappings"].get("dynamic").is_none());
+
+        mapping.settings = IndexSettings {
+            number_of_shards: Some(3),
+            number_of_replicas: None,
+        };
+        mapping.aliases = vec!["live".to_string()];
+        mapping.dynamic = Some(Dynamic::Strict);
+        let value = to_value(&mapping).unwrap();
+        assert_eq!(value["settings"], json!({"number_of_shards": 3}));
+        assert_eq!(value["aliases"], json!({"live": {}}));
+        assert_eq!(value["mappings"]["dynamic"], json!("strict"));
+    }
 }
@@ -0,0 +1,282 @@
+//! Mappings for the types of other crates, each behind the feature of the
+//! same name, with `decimal` for `rust_decimal`. Dates are given the format
+//! their serde output is written in. For `time` that is the output of its
+//! `serde-human-readable` feature, which sedk leaves to the application to
+//! enable; without it `time` writes dates as arrays of numbers.
+use super::field::{Field, FieldType};
+use crate::Normalizer;
+
+/// The format of `chrono`'s `DateTime` and `NaiveDateTime`, which are
+/// written as RFC 3339 with as many fractional digits as they need.
+pub const CHRONO_DATE_TIME_FORMAT: &str = "strict_date_optional_time";
+
+/// The format of `chrono::NaiveDate` and `time::Date`.
+pub const DATE_FORMAT: &str = "strict_date";
+
+/// The format of `time::OffsetDateTime`, as `2021-03-01 12:00:00.5 +01:00:00`.
+/// Java patterns take fractions of a fixed length, so each is tried in turn.
+pub const TIME_OFFSET_DATE_TIME_FORMAT: &str = "uuuu-MM-dd HH:mm:ss[.SSSSSSSSS][.SSSSSSSS]\
+[.SSSSSSS][.SSSSSS][.SSSSS][.SSSS][.SSS][.SS][.S] xxxxx";
+
+/// The format of `time::PrimitiveDateTime`, as `2021-03-01 12:00:00.5`.
+pub const TIME_PRIMITIVE_DATE_TIME_FORMAT: &str = "uuuu-MM-dd HH:mm:ss[.SSSSSSSSS][.SSSSSSSS]\
+[.SSSSSSS][.SSSSSS][.SSSSS][.SSSS][.SSS][.SS][.S]";
+
+fn date(name: Option<&str>, format: &str) -> Vec<Field> {
+    let field_type = FieldType::Date {
+        format: Some(format.to_string()),
+    };
+    name.map(|name| Field::new(name, field_type))
+        .into_iter()
+        .collect()
+}
+
+fn keyword(name: Option<&str>) -> Vec<Field> {
+    let field_type = FieldType::Keyword {
+        normalizer: Normalizer::default(),
+        ignore_above: None,
+    };
+    name.map(|name| Field::new(name, field_type))
+        .into_iter()
+        .collect()
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_types {
+    use super::*;
+    use crate::dsl::paths::{kind::Date, FieldPath, FieldPaths};
+    use crate::{HasOwnField, IntoFields};
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone};
+
+    impl<Tz: TimeZone> IntoFields for DateTime<Tz> {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            date(name, CHRONO_DATE_TIME_FORMAT)
+        }
+    }
+
+    impl<Tz: TimeZone> HasOwnField for DateTime<Tz> {}
+
+    impl<Tz: TimeZone> FieldPaths for DateTime<Tz> {
+        type Paths = FieldPath<Date>;
+    }
+
+    impl IntoFields for NaiveDateTime {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            date(name, CHRONO_DATE_TIME_FORMAT)
+        }
+    }
+
+    impl HasOwnField for NaiveDateTime {}
+
+    impl FieldPaths for NaiveDateTime {
+        type Paths = FieldPath<Date>;
+    }
+
+    impl IntoFields for NaiveDate {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            date(name, DATE_FORMAT)
+        }
+    }
+
+    impl HasOwnField for NaiveDate {}
+
+    impl FieldPaths for NaiveDate {
+        type Paths = FieldPath<Date>;
+    }
+}
+
+// these need time's serde-human-readable feature to hold, see the module docs
+#[cfg(feature = "time")]
+mod time_types {
+    use super::*;
+    use crate::dsl::paths::{kind::Date, FieldPath, FieldPaths};
+    use crate::{HasOwnField, IntoFields};
+    use time::{Date as TimeDate, OffsetDateTime, PrimitiveDateTime};
+
+    impl IntoFields for OffsetDateTime {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            date(name, TIME_OFFSET_DATE_TIME_FORMAT)
+        }
+    }
+
+    impl HasOwnField for OffsetDateTime {}
+
+    impl FieldPaths for OffsetDateTime {
+        type Paths = FieldPath<Date>;
+    }
+
+    impl IntoFields for PrimitiveDateTime {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            date(name, TIME_PRIMITIVE_DATE_TIME_FORMAT)
+        }
+    }
+
+    impl HasOwnField for PrimitiveDateTime {}
+
+    impl FieldPaths for PrimitiveDateTime {
+        type Paths = FieldPath<Date>;
+    }
+
+    impl IntoFields for TimeDate {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            date(name, DATE_FORMAT)
+        }
+    }
+
+    impl HasOwnField for TimeDate {}
+
+    impl FieldPaths for TimeDate {
+        type Paths = FieldPath<Date>;
+    }
+}
+
+// uuids are matched exactly, and make good ids
+#[cfg(feature = "uuid")]
+mod uuid_types {
+    use super::*;
+    use crate::dsl::paths::{kind::Keyword, FieldPath, FieldPaths};
+    use crate::{AsId, HasOwnField, IntoFields};
+    use std::borrow::Cow;
+    use uuid::Uuid;
+
+    impl IntoFields for Uuid {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            keyword(name)
+        }
+    }
+
+    impl HasOwnField for Uuid {}
+
+    impl FieldPaths for Uuid {
+        type Paths = FieldPath<Keyword>;
+    }
+
+    impl AsId for Uuid {
+        fn as_id(&self) -> Cow<'_, str> {
+            Cow::Owned(self.hyphenated().to_string())
+        }
+    }
+}
+
+// decimals are serialized as strings to keep every digit, which a keyword
+// keeps too. `#[sedk(type = "scaled_float", scaling_factor = 100)]` maps them
+// as numbers instead, for range queries and aggregations.
+#[cfg(feature = "decimal")]
+mod decimal_types {
+    use super::*;
+    use crate::dsl::paths::{kind::Keyword, FieldPath, FieldPaths};
+    use crate::{AsId, HasOwnField, IntoFields};
+    use rust_decimal::Decimal;
+    use std::borrow::Cow;
+
+    impl IntoFields for Decimal {
+        fn into_fields(name: Option<&str>) -> Vec<Field> {
+            keyword(name)
+        }
+    }
+
+    impl HasOwnField for Decimal {}
+
+    impl FieldPaths for Decimal {
+        type Paths = FieldPath<Keyword>;
+    }
+
+    impl AsId for Decimal {
+        fn as_id(&self) -> Cow<'_, str> {
+            Cow::Owned(self.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::date_format::DateFormat;
+    use crate::HasOwnField;
+    use serde::Serialize;
+    use serde_json::Value;
+
+    // the field `T` maps to, and whether it accepts `value` as serde writes it
+    fn accepts<T: HasOwnField + Serialize>(value: T) -> (FieldType, bool) {
+        let field = T::into_fields(Some("value")).remove(0);
+        let json = serde_json::to_value(&value).unwrap();
+        let written = match &json {
+            Value::String(s) => s.clone(),
+            _ => panic!("{} isn't a string", json),
+        };
+        let accepted = match &field.field_type {
+            FieldType::Date { format } => {
+                let format = DateFormat::parse(format.as_deref().unwrap()).unwrap();
+                format.accepts(&written)
+            }
+            FieldType::Keyword { .. } => true,
+            FieldType::Ip => written.parse::<std::net::IpAddr>().is_ok(),
+            other => panic!("unexpected {:?}", other),
+        };
+        (field.field_type, accepted)
+    }
+
+    #[test]
+    fn ip_addresses() {
+        let (field_type, accepted) = accepts("2001:db8::1".parse::<std::net::IpAddr>().unwrap());
+        assert_eq!(field_type, FieldType::Ip);
+        assert!(accepted);
+        assert!(accepts(std::net::Ipv4Addr::LOCALHOST).1);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_dates() {
+        use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+        let utc = DateTime::<Utc>::from_timestamp(1_614_600_000, 123_456_789).unwrap();
+        let offset = utc.with_timezone(&FixedOffset::east_opt(5 * 3600 + 1800).unwrap());
+        let (field_type, accepted) = accepts(utc);
+        assert_eq!(
+            field_type,
+            FieldType::Date {
+                format: Some("strict_date_optional_time".to_string())
+            }
+        );
+        assert!(accepted);
+        assert!(accepts(offset).1);
+        assert!(accepts(utc.naive_utc()).1);
+        assert!(accepts(DateTime::<Utc>::from_timestamp(1_614_600_000, 0).unwrap()).1);
+        assert!(accepts(NaiveDate::from_ymd_opt(2021, 3, 1).unwrap()).1);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_dates() {
+        use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+        let instant = OffsetDateTime::from_unix_timestamp_nanos(1_614_600_000_500_000_000).unwrap();
+        let offset = instant.to_offset(UtcOffset::from_hms(-3, -30, 0).unwrap());
+        for value in &[instant, offset, OffsetDateTime::UNIX_EPOCH] {
+            assert!(accepts(*value).1, "{}", value);
+        }
+        assert!(accepts(PrimitiveDateTime::new(instant.date(), instant.time())).1);
+        assert!(accepts(instant.date()).1);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuids() {
+        use crate::AsId;
+        let uuid = uuid::Uuid::from_u128(0x936d_a01f_9abd_4d9d_80c7_02af_85c8_22a8);
+        let (field_type, accepted) = accepts(uuid);
+        assert!(matches!(field_type, FieldType::Keyword { .. }) && accepted);
+        assert_eq!(
+            uuid.as_id(),
+            serde_json::to_value(uuid).unwrap().as_str().unwrap()
+        );
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimals() {
+        use crate::AsId;
+        let price = rust_decimal::Decimal::new(1999, 2);
+        let (field_type, accepted) = accepts(price);
+        assert!(matches!(field_type, FieldType::Keyword { .. }) && accepted);
+        assert_eq!(price.as_id(), "19.99");
+    }
+}
@@ -1,6 +1,10 @@
 pub mod analysis;
 pub mod character_filters;
+pub mod date_format;
 pub mod field;
+pub mod integrations;
+pub mod paths;
+pub mod plugins;
 pub mod primitives;
 pub mod token_processing;
 pub mod tokenizers;
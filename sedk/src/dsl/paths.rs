@@ -0,0 +1,298 @@
+//! Typed paths to the fields of a mapping, so that queries name fields that
+//! exist and have the type they expect. `#[derive(IntoFields)]` generates the
+//! paths of a struct, reached from `Struct::fields()`:
+//!
+//! ```ignore
+//! let path: FieldPath<Keyword> = Parent::fields().child().string().keyword();
+//! assert_eq!(path.as_str(), "child.string.keyword");
+//! ```
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Markers for the type a path's field is mapped to.
+pub mod kind {
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Text;
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Keyword;
+    /// Any of the integer and floating point types.
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Numeric;
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Date;
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Boolean;
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Ip;
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct DenseVector;
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Binary;
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Flattened;
+}
+
+use kind::{Boolean, Ip, Keyword, Numeric, Text};
+
+/// A path from the root of a mapping.
+pub trait PathNode {
+    fn at(path: String) -> Self;
+    fn path(&self) -> &str;
+}
+
+/// Types whose mapping has typed paths, which is every type implementing
+/// `IntoFields` that sedk or the derive knows about. Fields of other types
+/// can opt out with `#[sedk(paths = false)]`, leaving them an `ObjectPath`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no typed paths",
+    note = "derive `IntoFields` for `{Self}`, or mark fields holding a type that implements it by hand with `#[sedk(paths = false)]`"
+)]
+pub trait FieldPaths {
+    type Paths: PathNode;
+}
+
+/// The paths of a field once an analyzer or normalizer changes it to text or
+/// keyword, keeping its subfields.
+pub trait Retyped {
+    type Text: PathNode;
+    type Keyword: PathNode;
+}
+
+/// The dotted path of a field whose type is `K`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct FieldPath<K> {
+    path: String,
+    kind: PhantomData<K>,
+}
+
+impl<K> FieldPath<K> {
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// A multi-field of this field, typed by the caller as only the mapping
+    /// knows what it is.
+    pub fn subfield<S>(&self, name: &str) -> FieldPath<S> {
+        FieldPath::at(child(&self.path, name))
+    }
+}
+
+impl<K> PathNode for FieldPath<K> {
+    fn at(path: String) -> Self {
+        FieldPath {
+            path,
+            kind: PhantomData,
+        }
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl<K> Retyped for FieldPath<K> {
+    type Text = FieldPath<Text>;
+    type Keyword = FieldPath<Keyword>;
+}
+
+impl<K> fmt::Display for FieldPath<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+impl<K> AsRef<str> for FieldPath<K> {
+    fn as_ref(&self) -> &str {
+        &self.path
+    }
+}
+
+/// The path of a string, which is mapped with a `keyword` multi-field.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct StringPath<K = Text> {
+    path: String,
+    kind: PhantomData<K>,
+}
+
+impl<K> StringPath<K> {
+    /// The field itself.
+    pub fn field(&self) -> FieldPath<K> {
+        FieldPath::at(self.path.clone())
+    }
+
+    pub fn keyword(&self) -> FieldPath<Keyword> {
+        FieldPath::at(child(&self.path, "keyword"))
+    }
+}
+
+impl<K> PathNode for StringPath<K> {
+    fn at(path: String) -> Self {
+        StringPath {
+            path,
+            kind: PhantomData,
+        }
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl<K> Retyped for StringPath<K> {
+    type Text = StringPath<Text>;
+    type Keyword = StringPath<Keyword>;
+}
+
+impl<K> From<StringPath<K>> for FieldPath<K> {
+    fn from(path: StringPath<K>) -> Self {
+        path.field()
+    }
+}
+
+/// The path of a map, whose values are found under their keys.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MapPath<V> {
+    path: String,
+    values: PhantomData<fn() -> V>,
+}
+
+impl<V: FieldPaths> MapPath<V> {
+    pub fn key(&self, key: &str) -> V::Paths {
+        V::Paths::at(child(&self.path, key))
+    }
+}
+
+impl<V> PathNode for MapPath<V> {
+    fn at(path: String) -> Self {
+        MapPath {
+            path,
+            values: PhantomData,
+        }
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// The path of an object whose fields aren't known until runtime, such as
+/// the union of an enum's variants.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ObjectPath {
+    path: String,
+}
+
+impl ObjectPath {
+    pub fn field<K>(&self, name: &str) -> FieldPath<K> {
+        FieldPath::at(child(&self.path, name))
+    }
+
+    pub fn object(&self, name: &str) -> ObjectPath {
+        ObjectPath::at(child(&self.path, name))
+    }
+}
+
+impl PathNode for ObjectPath {
+    fn at(path: String) -> Self {
+        ObjectPath { path }
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// The path of `name` inside `parent`, the root having an empty path.
+pub fn child(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+macro_rules! paths {
+    ($paths:ty: $($t:ty),*) => {
+        $(
+            impl FieldPaths for $t {
+                type Paths = $paths;
+            }
+        )*
+    };
+}
+
+paths!(StringPath: String, &str);
+paths!(FieldPath<Keyword>: char);
+paths!(FieldPath<Boolean>: bool);
+paths!(FieldPath<Ip>: IpAddr, Ipv4Addr, Ipv6Addr);
+paths!(FieldPath<Numeric>: i8, i16, i32, i64, u8, u16, u32, u64, usize, f32, f64);
+
+// containers have the paths of their values, as they have their mappings
+macro_rules! from_inner {
+    ($($t:ty),*) => {
+        $(
+            impl<T: FieldPaths> FieldPaths for $t {
+                type Paths = T::Paths;
+            }
+        )*
+    };
+}
+
+from_inner!(
+    Option<T>,
+    Vec<T>,
+    VecDeque<T>,
+    BTreeSet<T>,
+    Box<T>,
+    Rc<T>,
+    Arc<T>
+);
+
+impl<T: FieldPaths, S> FieldPaths for HashSet<T, S> {
+    type Paths = T::Paths;
+}
+
+impl<T: FieldPaths, const N: usize> FieldPaths for [T; N] {
+    type Paths = T::Paths;
+}
+
+impl<V: FieldPaths, S> FieldPaths for HashMap<String, V, S> {
+    type Paths = MapPath<V>;
+}
+
+impl<V: FieldPaths> FieldPaths for BTreeMap<String, V> {
+    type Paths = MapPath<V>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths<T: FieldPaths>(path: &str) -> T::Paths {
+        T::Paths::at(path.to_string())
+    }
+
+    #[test]
+    fn strings_have_keyword_subfields() {
+        let title = paths::<Option<String>>("title");
+        assert_eq!(title.field().as_str(), "title");
+        assert_eq!(title.keyword().as_str(), "title.keyword");
+        let field: FieldPath<Text> = title.into();
+        assert_eq!(field.to_string(), "title");
+        let count: FieldPath<Numeric> = paths::<Vec<u32>>("count");
+        assert_eq!(count.subfield::<Keyword>("raw").as_str(), "count.raw");
+    }
+
+    #[test]
+    fn maps_and_objects() {
+        let prices = paths::<BTreeMap<String, f64>>("prices");
+        let eur: FieldPath<Numeric> = prices.key("eur");
+        assert_eq!(eur.as_str(), "prices.eur");
+        let object = ObjectPath::at(String::new());
+        assert_eq!(object.object("a").field::<Text>("b").as_str(), "a.b");
+    }
+}
@@ -0,0 +1,125 @@
+//! Analysis plugins. Components from a plugin are only available with the
+//! cargo feature of the same name, and a mapping using them only serializes
+//! once the plugin is declared among the cluster's plugins.
+#[cfg(any(
+    feature = "icu",
+    feature = "kuromoji",
+    feature = "nori",
+    feature = "phonetic"
+))]
+use serde::Serialize;
+use std::fmt;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub enum Plugin {
+    Icu,
+    Kuromoji,
+    Nori,
+    Smartcn,
+    Phonetic,
+}
+
+impl Plugin {
+    /// The name the plugin is installed under.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Plugin::Icu => "analysis-icu",
+            Plugin::Kuromoji => "analysis-kuromoji",
+            Plugin::Nori => "analysis-nori",
+            Plugin::Smartcn => "analysis-smartcn",
+            Plugin::Phonetic => "analysis-phonetic",
+        }
+    }
+}
+
+impl fmt::Display for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Plugins a mapping uses that the cluster was not declared to have.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct UndeclaredPlugins(pub Vec<Plugin>);
+
+impl fmt::Display for UndeclaredPlugins {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = self.0.iter().map(Plugin::name).collect();
+        write!(
+            f,
+            "the mapping uses components from undeclared plugins: {}",
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UndeclaredPlugins {}
+
+#[cfg(feature = "icu")]
+#[derive(PartialEq, Eq, Hash, Clone, Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum IcuNormalization {
+    Nfc,
+    Nfkc,
+    NfkcCf,
+}
+
+#[cfg(feature = "icu")]
+#[derive(PartialEq, Eq, Hash, Clone, Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CollationStrength {
+    Primary,
+    Secondary,
+    Tertiary,
+    Quaternary,
+    Identical,
+}
+
+#[cfg(feature = "kuromoji")]
+#[derive(PartialEq, Eq, Hash, Clone, Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum KuromojiMode {
+    Normal,
+    Search,
+    Extended,
+}
+
+#[cfg(feature = "nori")]
+#[derive(PartialEq, Eq, Hash, Clone, Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DecompoundMode {
+    None,
+    Discard,
+    Mixed,
+}
+
+#[cfg(feature = "phonetic")]
+#[derive(PartialEq, Eq, Hash, Clone, Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneticEncoder {
+    Metaphone,
+    DoubleMetaphone,
+    Soundex,
+    RefinedSoundex,
+    Caverphone1,
+    Caverphone2,
+    Cologne,
+    Nysiis,
+    Koelnerphonetik,
+    Haasephonetik,
+    BeiderMorse,
+    DaitchMokotoff,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undeclared() {
+        assert_eq!(
+            UndeclaredPlugins(vec![Plugin::Icu, Plugin::Nori]).to_string(),
+            "the mapping uses components from undeclared plugins: analysis-icu, analysis-nori"
+        );
+    }
+}
@@ -2,11 +2,33 @@ use super::{
     analysis::{Analyzer, Normalizer},
     field::{Field, FieldType},
 };
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::rc::Rc;
+use std::sync::Arc;
 
 pub trait IntoFields {
     fn into_fields(name: Option<&str>) -> Vec<Field>;
 }
 
+/// Types that map to a field of the name they're given, rather than only to
+/// fields under it as structs do. `#[sedk(analyzer, normalizer, subfield,
+/// meta)]` change that field, so need their type to have one.
+pub trait HasOwnField: IntoFields {}
+
+#[doc(hidden)]
+pub fn has_own_field<T: HasOwnField>() {}
+
+macro_rules! own_field {
+    ($($t:ty),*) => {
+        $(impl HasOwnField for $t {})*
+    };
+}
+own_field!(
+    String, i64, i32, i16, u64, f64, f32, bool, i8, u8, u16, u32, usize, char, IpAddr, Ipv4Addr,
+    Ipv6Addr, &str
+);
+
 impl IntoFields for String {
     fn into_fields(name: Option<&str>) -> Vec<Field> {
         match name {
@@ -14,15 +36,23 @@ impl IntoFields for String {
             Some(s) => vec![Field {
                 name: s.to_owned(),
                 fields: vec![Field {
-                    name: s.to_owned() + ".keyword",
+                    name: "keyword".to_owned(),
                     fields: Vec::new(),
                     field_type: FieldType::Keyword {
                         normalizer: Normalizer::default(),
+                        ignore_above: None,
                     },
+                    index: true,
+                    copy_to: Vec::new(),
+                    meta: BTreeMap::new(),
                 }],
                 field_type: FieldType::Text {
                     analyzer: Analyzer::default(),
+                    search_analyzer: None,
                 },
+                index: true,
+                copy_to: Vec::new(),
+                meta: BTreeMap::new(),
             }],
         }
     }
@@ -38,6 +68,9 @@ macro_rules! from_type {
                     name: s.to_owned(),
                     fields: Vec::new(),
                     field_type: $t,
+                    index: true,
+                    copy_to: Vec::new(),
+                    meta: BTreeMap::new(),
                 }],
             }
         }
@@ -64,3 +97,195 @@ impl IntoFields for f32 {
 impl IntoFields for bool {
     from_type!(FieldType::Boolean);
 }
+impl IntoFields for i8 {
+    from_type!(FieldType::Byte);
+}
+// unsigned types take the next signed type up, which holds all their values
+impl IntoFields for u8 {
+    from_type!(FieldType::Short);
+}
+impl IntoFields for u16 {
+    from_type!(FieldType::Integer);
+}
+impl IntoFields for u32 {
+    from_type!(FieldType::Long);
+}
+impl IntoFields for usize {
+    from_type!(FieldType::UnsignedLong);
+}
+impl IntoFields for char {
+    from_type!(FieldType::Keyword {
+        normalizer: Normalizer::default(),
+        ignore_above: None,
+    });
+}
+
+impl IntoFields for IpAddr {
+    from_type!(FieldType::Ip);
+}
+impl IntoFields for Ipv4Addr {
+    from_type!(FieldType::Ip);
+}
+impl IntoFields for Ipv6Addr {
+    from_type!(FieldType::Ip);
+}
+
+impl IntoFields for &str {
+    fn into_fields(name: Option<&str>) -> Vec<Field> {
+        String::into_fields(name)
+    }
+}
+
+// elasticsearch has no array type, and every field may be missing, so
+// optional values, collections and pointers map like the values they hold
+macro_rules! from_inner {
+    ($($t:ty),*) => {
+        $(
+            impl<T: IntoFields> IntoFields for $t {
+                fn into_fields(name: Option<&str>) -> Vec<Field> {
+                    T::into_fields(name)
+                }
+            }
+        )*
+    };
+}
+from_inner!(
+    Option<T>,
+    Vec<T>,
+    VecDeque<T>,
+    BTreeSet<T>,
+    Box<T>,
+    Rc<T>,
+    Arc<T>
+);
+
+impl<T: IntoFields, S> IntoFields for HashSet<T, S> {
+    fn into_fields(name: Option<&str>) -> Vec<Field> {
+        T::into_fields(name)
+    }
+}
+
+impl<T: IntoFields, const N: usize> IntoFields for [T; N] {
+    fn into_fields(name: Option<&str>) -> Vec<Field> {
+        T::into_fields(name)
+    }
+}
+
+macro_rules! own_field_of_inner {
+    ($($t:ty),*) => {
+        $(impl<T: HasOwnField> HasOwnField for $t {})*
+    };
+}
+own_field_of_inner!(
+    Option<T>,
+    Vec<T>,
+    VecDeque<T>,
+    BTreeSet<T>,
+    Box<T>,
+    Rc<T>,
+    Arc<T>
+);
+
+impl<T: HasOwnField, S> HasOwnField for HashSet<T, S> {}
+
+impl<T: HasOwnField, const N: usize> HasOwnField for [T; N] {}
+
+// maps with arbitrary keys become objects whose values are mapped like `V`
+// wherever they appear. `#[sedk(type = "flattened")]` maps them as a single
+// field instead.
+fn map_fields<V: IntoFields>(name: Option<&str>) -> Vec<Field> {
+    match name {
+        None => Vec::new(),
+        Some(s) => vec![Field::new(
+            s,
+            FieldType::Object {
+                templates: V::into_fields(Some(&(s.to_owned() + ".*"))),
+            },
+        )],
+    }
+}
+
+impl<V: IntoFields, S> IntoFields for HashMap<String, V, S> {
+    fn into_fields(name: Option<&str>) -> Vec<Field> {
+        map_fields::<V>(name)
+    }
+}
+
+impl<V: IntoFields> IntoFields for BTreeMap<String, V> {
+    fn into_fields(name: Option<&str>) -> Vec<Field> {
+        map_fields::<V>(name)
+    }
+}
+
+// the object holding the keys
+impl<V: IntoFields, S> HasOwnField for HashMap<String, V, S> {}
+
+impl<V: IntoFields> HasOwnField for BTreeMap<String, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::field::{IndexMapping, IndexSettings};
+    use serde_json::{json, to_value};
+
+    #[test]
+    fn containers_map_like_their_values() {
+        assert_eq!(
+            Option::<Vec<Arc<u32>>>::into_fields(Some("ids")),
+            vec![Field::new("ids", FieldType::Long)]
+        );
+        assert_eq!(
+            <[&str; 3]>::into_fields(Some("tags")),
+            String::into_fields(Some("tags"))
+        );
+        assert_eq!(
+            HashSet::<char>::into_fields(Some("grade")),
+            vec![Field::new(
+                "grade",
+                FieldType::Keyword {
+                    normalizer: Normalizer::default(),
+                    ignore_above: None,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn maps_use_dynamic_templates() {
+        let mapping = IndexMapping {
+            fields: HashMap::<String, Box<i8>>::into_fields(Some("scores")),
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        assert_eq!(
+            to_value(&mapping).unwrap()["mappings"],
+            json!({
+                "properties": {"scores": {"type": "object", "dynamic": true}},
+                "dynamic_templates": [
+                    {"scores.*": {"path_match": "scores.*", "mapping": {"type": "byte", "fields": {}}}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn maps_of_maps_put_inner_templates_first() {
+        let mapping = IndexMapping {
+            fields: HashMap::<String, BTreeMap<String, bool>>::into_fields(Some("flags")),
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        let templates = &to_value(&mapping).unwrap()["mappings"]["dynamic_templates"];
+        assert_eq!(
+            templates,
+            &json!([
+                {"flags.*.*": {"path_match": "flags.*.*", "mapping": {"type": "boolean", "fields": {}}}},
+                {"flags.*": {"path_match": "flags.*", "mapping": {"type": "object", "dynamic": true}}}
+            ])
+        );
+    }
+}
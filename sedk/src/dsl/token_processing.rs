@@ -1,3 +1,8 @@
+#[cfg(feature = "phonetic")]
+use super::plugins::PhoneticEncoder;
+use super::plugins::Plugin;
+#[cfg(feature = "icu")]
+use super::plugins::{CollationStrength, IcuNormalization};
 use serde::ser::Serializer;
 use serde::Serialize;
 
@@ -7,10 +12,42 @@ pub struct TokenFilter {
     pub filter_type: TokenFilterType,
 }
 
+impl TokenFilter {
+    pub fn new(name: &str, filter_type: TokenFilterType) -> TokenFilter {
+        TokenFilter {
+            name: name.to_string(),
+            filter_type,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Serialize, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TokenFilterType {
     Lowercase,
+    /// Folds letters outside the basic latin block to their ascii equivalent.
+    Asciifolding {
+        preserve_original: bool,
+    },
+    EdgeNgram {
+        min_gram: u8,
+        max_gram: u8,
+        preserve_original: bool,
+    },
+    Stop {
+        stopwords: Stopwords,
+        ignore_case: bool,
+    },
+    /// Splits tokens at punctuation, case changes and letter-number
+    /// transitions, joining the parts back together if asked.
+    WordDelimiterGraph {
+        generate_word_parts: bool,
+        generate_number_parts: bool,
+        catenate_all: bool,
+        split_on_case_change: bool,
+        split_on_numerics: bool,
+        preserve_original: bool,
+    },
     Stemmer {
         language: StemmerLanguage,
     },
@@ -29,6 +66,118 @@ pub enum TokenFilterType {
         token_separator: String,
         filler_token: String,
     },
+    #[cfg(feature = "icu")]
+    IcuFolding {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        unicode_set_filter: Option<String>,
+    },
+    #[cfg(feature = "icu")]
+    IcuNormalizer {
+        name: IcuNormalization,
+    },
+    #[cfg(feature = "icu")]
+    IcuCollation {
+        language: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        country: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        strength: Option<CollationStrength>,
+    },
+    #[cfg(feature = "kuromoji")]
+    KuromojiBaseform,
+    #[cfg(feature = "kuromoji")]
+    KuromojiPartOfSpeech {
+        stoptags: Vec<String>,
+    },
+    #[cfg(feature = "kuromoji")]
+    KuromojiReadingform {
+        use_romaji: bool,
+    },
+    #[cfg(feature = "kuromoji")]
+    KuromojiStemmer {
+        minimum_length: u8,
+    },
+    #[cfg(feature = "kuromoji")]
+    KuromojiNumber,
+    #[cfg(feature = "nori")]
+    NoriPartOfSpeech {
+        stoptags: Vec<String>,
+    },
+    #[cfg(feature = "nori")]
+    NoriReadingform,
+    #[cfg(feature = "nori")]
+    NoriNumber,
+    #[cfg(feature = "smartcn")]
+    SmartcnStop,
+    #[cfg(feature = "phonetic")]
+    Phonetic {
+        encoder: PhoneticEncoder,
+        replace: bool,
+    },
+}
+
+impl TokenFilterType {
+    /// Why elasticsearch would refuse these settings, if it would.
+    pub fn problem(&self) -> Option<String> {
+        match self {
+            TokenFilterType::Shingle {
+                min_shingle_size,
+                max_shingle_size,
+                ..
+            } if *min_shingle_size < 2 || min_shingle_size > max_shingle_size => Some(format!(
+                "shingle sizes must satisfy 2 <= min ({}) <= max ({})",
+                min_shingle_size, max_shingle_size
+            )),
+            TokenFilterType::EdgeNgram {
+                min_gram, max_gram, ..
+            } if *min_gram < 1 || min_gram > max_gram => Some(format!(
+                "gram sizes must satisfy 1 <= min ({}) <= max ({})",
+                min_gram, max_gram
+            )),
+            TokenFilterType::SynonymGraph { synonyms, .. }
+                if synonyms.iter().any(|s| s.from.is_empty()) =>
+            {
+                Some("a synonym rule has nothing to match".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the filter works a character at a time, which is all a
+    /// normalizer may do.
+    pub fn is_normalizing(&self) -> bool {
+        match self {
+            TokenFilterType::Lowercase | TokenFilterType::Asciifolding { .. } => true,
+            #[cfg(feature = "icu")]
+            TokenFilterType::IcuFolding { .. } | TokenFilterType::IcuNormalizer { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// The plugin that provides this filter, if it isn't built in.
+    pub fn plugin(&self) -> Option<Plugin> {
+        match self {
+            #[cfg(feature = "icu")]
+            TokenFilterType::IcuFolding { .. }
+            | TokenFilterType::IcuNormalizer { .. }
+            | TokenFilterType::IcuCollation { .. } => Some(Plugin::Icu),
+            #[cfg(feature = "kuromoji")]
+            TokenFilterType::KuromojiBaseform
+            | TokenFilterType::KuromojiPartOfSpeech { .. }
+            | TokenFilterType::KuromojiReadingform { .. }
+            | TokenFilterType::KuromojiStemmer { .. }
+            | TokenFilterType::KuromojiNumber => Some(Plugin::Kuromoji),
+            #[cfg(feature = "nori")]
+            TokenFilterType::NoriPartOfSpeech { .. }
+            | TokenFilterType::NoriReadingform
+            | TokenFilterType::NoriNumber => Some(Plugin::Nori),
+            #[cfg(feature = "smartcn")]
+            TokenFilterType::SmartcnStop => Some(Plugin::Smartcn),
+            #[cfg(feature = "phonetic")]
+            TokenFilterType::Phonetic { .. } => Some(Plugin::Phonetic),
+            _ => None,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Serialize, Debug)]
@@ -79,7 +228,7 @@ pub enum StemmerLanguage {
     MinimalNorwegian,
     LightNynorsk,
     MinimalNynorsk,
-    Portueguese,
+    Portuguese,
     LightPortuguese,
     MinimalPortuguese,
     PortugueseRslp,
@@ -93,10 +242,46 @@ pub enum StemmerLanguage {
     Turkish,
 }
 
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Stopwords {
+    /// One of elasticsearch's predefined lists, by language: `english` for
+    /// `_english_`.
+    Predefined(String),
+    List(Vec<String>),
+}
+
+impl Serialize for Stopwords {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Stopwords::Predefined(language) => format!("_{}_", language).serialize(serializer),
+            Stopwords::List(words) => words.serialize(serializer),
+        }
+    }
+}
+
+/// A synonym rule in the solr format. Each entry is a phrase which may contain
+/// several whitespace separated words. A rule with an empty `to` is a list of
+/// equivalent phrases, whose expansion is controlled by the filter's `expand`.
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct Synonym {
-    from: Vec<String>,
-    to: Vec<String>,
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+}
+
+impl Synonym {
+    pub fn explicit(from: &[&str], to: &[&str]) -> Synonym {
+        Synonym {
+            from: from.iter().map(|s| s.to_string()).collect(),
+            to: to.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn equivalent(phrases: &[&str]) -> Synonym {
+        Synonym::explicit(phrases, &[])
+    }
 }
 
 impl Serialize for Synonym {
@@ -104,7 +289,11 @@ impl Serialize for Synonym {
     where
         S: Serializer,
     {
-        format!("{} => {}", self.from.join(","), self.to.join(",")).serialize(serializer)
+        if self.to.is_empty() {
+            self.from.join(",").serialize(serializer)
+        } else {
+            format!("{} => {}", self.from.join(","), self.to.join(",")).serialize(serializer)
+        }
     }
 }
 
@@ -133,25 +322,35 @@ mod tests {
         assert_eq!(to_value(&tokenizer).unwrap(), expected)
     }
 
+    #[test]
+    fn portuguese_stemmer() {
+        let tokenizer = TokenFilterType::Stemmer {
+            language: StemmerLanguage::Portuguese,
+        };
+        let expected = json!({
+            "type": "stemmer",
+            "language": "portuguese"
+        });
+        assert_eq!(to_value(&tokenizer).unwrap(), expected)
+    }
+
     #[test]
     fn synonym_graph() {
         let tokenizer = TokenFilterType::SynonymGraph {
             expand: false,
             lenient: true,
-            synonyms: vec![Synonym {
-                from: vec!["short", "small", "little"]
-                    .iter()
-                    .map(|s| s.to_string())
-                    .collect(),
-                to: vec!["small"].iter().map(|s| s.to_string()).collect(),
-            }],
+            synonyms: vec![
+                Synonym::explicit(&["short", "small", "little"], &["small"]),
+                Synonym::equivalent(&["ny", "new york"]),
+            ],
         };
         let expected = json!({
             "type": "synonym_graph",
             "expand": false,
             "lenient": true,
             "synonyms": [
-                "short,small,little => small"
+                "short,small,little => small",
+                "ny,new york"
             ]
         });
         assert_eq!(to_value(&tokenizer).unwrap(), expected)
@@ -178,4 +377,71 @@ mod tests {
         });
         assert_eq!(to_value(&tokenizer).unwrap(), expected)
     }
+
+    #[test]
+    fn stop() {
+        let filter = TokenFilterType::Stop {
+            stopwords: Stopwords::Predefined("english".to_string()),
+            ignore_case: true,
+        };
+        let expected = json!({
+            "type": "stop",
+            "stopwords": "_english_",
+            "ignore_case": true
+        });
+        assert_eq!(to_value(&filter).unwrap(), expected);
+        assert_eq!(
+            to_value(Stopwords::List(vec!["a".to_string(), "the".to_string()])).unwrap(),
+            json!(["a", "the"])
+        );
+    }
+
+    #[test]
+    fn edge_ngram() {
+        let filter = TokenFilterType::EdgeNgram {
+            min_gram: 1,
+            max_gram: 10,
+            preserve_original: false,
+        };
+        let expected = json!({
+            "type": "edge_ngram",
+            "min_gram": 1,
+            "max_gram": 10,
+            "preserve_original": false
+        });
+        assert_eq!(to_value(&filter).unwrap(), expected)
+    }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn icu_collation() {
+        let filter = TokenFilterType::IcuCollation {
+            language: "de".to_string(),
+            country: None,
+            strength: Some(CollationStrength::Primary),
+        };
+        let expected = json!({
+            "type": "icu_collation",
+            "language": "de",
+            "strength": "primary"
+        });
+        assert_eq!(to_value(&filter).unwrap(), expected);
+        assert_eq!(filter.plugin(), Some(Plugin::Icu));
+        assert_eq!(TokenFilterType::Lowercase.plugin(), None);
+    }
+
+    #[cfg(feature = "phonetic")]
+    #[test]
+    fn phonetic() {
+        let filter = TokenFilterType::Phonetic {
+            encoder: PhoneticEncoder::DoubleMetaphone,
+            replace: false,
+        };
+        let expected = json!({
+            "type": "phonetic",
+            "encoder": "double_metaphone",
+            "replace": false
+        });
+        assert_eq!(to_value(&filter).unwrap(), expected)
+    }
 }
@@ -1,3 +1,8 @@
+#[cfg(feature = "nori")]
+use super::plugins::DecompoundMode;
+#[cfg(feature = "kuromoji")]
+use super::plugins::KuromojiMode;
+use super::plugins::Plugin;
 use serde::ser::Serializer;
 use serde::Serialize;
 
@@ -7,7 +12,16 @@ pub struct Tokenizer {
     pub tokenizer_type: TokenizerType,
 }
 
-const DEFAULT_TOKENIZER_NAME: &'static str = "sedk_default_tokenizer";
+impl Tokenizer {
+    pub fn new(name: &str, tokenizer_type: TokenizerType) -> Tokenizer {
+        Tokenizer {
+            name: name.to_string(),
+            tokenizer_type,
+        }
+    }
+}
+
+const DEFAULT_TOKENIZER_NAME: &str = "sedk_default_tokenizer";
 impl Default for Tokenizer {
     fn default() -> Self {
         Tokenizer {
@@ -26,6 +40,53 @@ pub enum TokenizerType {
     CharacterGroup {
         tokenize_on_chars: Vec<CharacterGroups>,
     },
+    #[serde(rename = "standard")]
+    Standard,
+    /// Emits the whole text as a single token.
+    #[serde(rename = "keyword")]
+    Keyword,
+    /// Emits every prefix of a path that ends at a delimiter, or every suffix
+    /// when reversed.
+    #[serde(rename = "path_hierarchy")]
+    PathHierarchy { delimiter: char, reverse: bool },
+    #[cfg(feature = "icu")]
+    #[serde(rename = "icu_tokenizer")]
+    Icu,
+    #[cfg(feature = "kuromoji")]
+    #[serde(rename = "kuromoji_tokenizer")]
+    Kuromoji {
+        mode: KuromojiMode,
+        discard_punctuation: bool,
+    },
+    #[cfg(feature = "nori")]
+    #[serde(rename = "nori_tokenizer")]
+    Nori {
+        decompound_mode: DecompoundMode,
+        discard_punctuation: bool,
+    },
+    #[cfg(feature = "smartcn")]
+    #[serde(rename = "smartcn_tokenizer")]
+    Smartcn,
+}
+
+impl TokenizerType {
+    /// The plugin that provides this tokenizer, if it isn't built in.
+    pub fn plugin(&self) -> Option<Plugin> {
+        match self {
+            TokenizerType::CharacterGroup { .. }
+            | TokenizerType::Standard
+            | TokenizerType::Keyword
+            | TokenizerType::PathHierarchy { .. } => None,
+            #[cfg(feature = "icu")]
+            TokenizerType::Icu => Some(Plugin::Icu),
+            #[cfg(feature = "kuromoji")]
+            TokenizerType::Kuromoji { .. } => Some(Plugin::Kuromoji),
+            #[cfg(feature = "nori")]
+            TokenizerType::Nori { .. } => Some(Plugin::Nori),
+            #[cfg(feature = "smartcn")]
+            TokenizerType::Smartcn => Some(Plugin::Smartcn),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
@@ -80,4 +141,53 @@ mod tests {
         });
         assert_eq!(to_value(&tokenizer).unwrap(), expected)
     }
+
+    #[test]
+    fn path_hierarchy() {
+        let tokenizer = TokenizerType::PathHierarchy {
+            delimiter: '.',
+            reverse: true,
+        };
+        let expected = json!({
+            "type": "path_hierarchy",
+            "delimiter": ".",
+            "reverse": true
+        });
+        assert_eq!(to_value(&tokenizer).unwrap(), expected);
+        assert_eq!(
+            to_value(TokenizerType::Standard).unwrap(),
+            json!({"type": "standard"})
+        );
+    }
+
+    #[cfg(feature = "kuromoji")]
+    #[test]
+    fn kuromoji() {
+        let tokenizer = TokenizerType::Kuromoji {
+            mode: KuromojiMode::Search,
+            discard_punctuation: true,
+        };
+        let expected = json!({
+            "type": "kuromoji_tokenizer",
+            "mode": "search",
+            "discard_punctuation": true
+        });
+        assert_eq!(to_value(&tokenizer).unwrap(), expected);
+        assert_eq!(tokenizer.plugin(), Some(Plugin::Kuromoji));
+    }
+
+    #[cfg(feature = "nori")]
+    #[test]
+    fn nori() {
+        let tokenizer = TokenizerType::Nori {
+            decompound_mode: DecompoundMode::Mixed,
+            discard_punctuation: false,
+        };
+        let expected = json!({
+            "type": "nori_tokenizer",
+            "decompound_mode": "mixed",
+            "discard_punctuation": false
+        });
+        assert_eq!(to_value(&tokenizer).unwrap(), expected)
+    }
 }
@@ -1,7 +1,22 @@
 #![allow(dead_code)]
-mod dsl;
+pub mod analyze;
+pub mod check;
+pub mod deploy;
+pub mod dictionary;
+pub mod document;
+pub mod dsl;
+pub mod recipes;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transport;
+pub mod verify;
+pub use check::Violation;
+pub use dictionary::data_dictionary;
+pub use document::{AsId, Document};
 pub use dsl::{
-    primitives::IntoFields,
-    field::{Field, FieldType, Indexable, IndexMapping},
+    primitives::{HasOwnField, IntoFields},
+    field::{ConflictingFields, Dynamic, Field, FieldType, Indexable, IndexMapping, IndexSettings},
     analysis::{Analyzer, Normalizer}
-};
\ No newline at end of file
+};
+#[cfg(feature = "derive")]
+pub use sedk_derive::{Document, IntoFields, Indexable};
@@ -0,0 +1,395 @@
+//! Analyzers for common search patterns. Each recipe returns the analyzer to
+//! index a field with along with the analyzer its queries should use, and
+//! names every component after the recipe so that several recipes can share
+//! an index.
+use crate::dsl::{
+    analysis::{Analyzer, BuildError, Normalizer},
+    field::FieldType,
+    token_processing::{StemmerLanguage, Stopwords, TokenFilter, TokenFilterType},
+    tokenizers::{CharacterGroups, Tokenizer, TokenizerType},
+};
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Recipe {
+    pub index: Analyzer,
+    pub search: Analyzer,
+}
+
+impl Recipe {
+    /// A text field analyzed with this recipe.
+    pub fn field_type(&self) -> FieldType {
+        FieldType::Text {
+            analyzer: self.index.clone(),
+            search_analyzer: if self.search == self.index {
+                None
+            } else {
+                Some(self.search.clone())
+            },
+        }
+    }
+
+    fn same(analyzer: Analyzer) -> Recipe {
+        Recipe {
+            search: analyzer.clone(),
+            index: analyzer,
+        }
+    }
+}
+
+fn tokenizer(recipe: &str, name: &str, tokenizer_type: TokenizerType) -> Tokenizer {
+    Tokenizer {
+        name: format!("{}_{}", recipe, name),
+        tokenizer_type,
+    }
+}
+
+fn filter(recipe: &str, name: &str, filter_type: TokenFilterType) -> TokenFilter {
+    TokenFilter {
+        name: format!("{}_{}", recipe, name),
+        filter_type,
+    }
+}
+
+// a filter elasticsearch would accept the settings of
+fn checked(filter: TokenFilter) -> Result<TokenFilter, BuildError> {
+    match filter.filter_type.problem() {
+        Some(reason) => Err(BuildError::InvalidSettings {
+            component: filter.name,
+            reason,
+        }),
+        None => Ok(filter),
+    }
+}
+
+fn analyzer(name: String, tokenizer: Tokenizer, token_filters: Vec<TokenFilter>) -> Analyzer {
+    Analyzer {
+        name,
+        character_filters: vec![],
+        tokenizer,
+        token_filters,
+    }
+}
+
+fn search_name(recipe: &str) -> String {
+    format!("{}_search", recipe)
+}
+
+/// Search as you type. Words are indexed as every prefix between `min_gram`
+/// and `max_gram` characters long, while queries are left whole so that a
+/// partial word matches the prefixes of longer ones. Fails unless
+/// `1 <= min_gram <= max_gram`.
+pub fn autocomplete(name: &str, min_gram: u8, max_gram: u8) -> Result<Recipe, BuildError> {
+    let standard = tokenizer(name, "standard", TokenizerType::Standard);
+    let lowercase = filter(name, "lowercase", TokenFilterType::Lowercase);
+    let folding = filter(
+        name,
+        "asciifolding",
+        TokenFilterType::Asciifolding {
+            preserve_original: false,
+        },
+    );
+    let prefixes = checked(filter(
+        name,
+        "edge_ngram",
+        TokenFilterType::EdgeNgram {
+            min_gram,
+            max_gram,
+            preserve_original: false,
+        },
+    ))?;
+    Ok(Recipe {
+        index: analyzer(
+            name.to_string(),
+            standard.clone(),
+            vec![lowercase.clone(), folding.clone(), prefixes],
+        ),
+        search: analyzer(search_name(name), standard, vec![lowercase, folding]),
+    })
+}
+
+/// Full text in one language: lowercased, without `stopwords` and stemmed.
+pub fn language(name: &str, stopwords: Stopwords, stemmer: StemmerLanguage) -> Recipe {
+    Recipe::same(analyzer(
+        name.to_string(),
+        tokenizer(name, "standard", TokenizerType::Standard),
+        vec![
+            filter(name, "lowercase", TokenFilterType::Lowercase),
+            filter(
+                name,
+                "stop",
+                TokenFilterType::Stop {
+                    stopwords,
+                    ignore_case: false,
+                },
+            ),
+            filter(
+                name,
+                "stemmer",
+                TokenFilterType::Stemmer { language: stemmer },
+            ),
+        ],
+    ))
+}
+
+/// Exact matching on keywords, ignoring case and accents. A normalizer
+/// applies to queries as well, so there's no separate search side.
+pub fn exact_match(name: &str) -> Normalizer {
+    Normalizer {
+        name: name.to_string(),
+        character_filters: vec![],
+        tokenizer: Tokenizer::default(),
+        token_filters: vec![
+            filter(name, "lowercase", TokenFilterType::Lowercase),
+            filter(
+                name,
+                "asciifolding",
+                TokenFilterType::Asciifolding {
+                    preserve_original: false,
+                },
+            ),
+        ],
+    }
+}
+
+/// Product codes and other identifiers, where `SD-500`, `sd500` and
+/// `SD 500` should all match. Identifiers are indexed whole, joined up and in
+/// parts, and queries are split into parts that match as a phrase.
+pub fn identifier(name: &str) -> Recipe {
+    let whitespace = tokenizer(
+        name,
+        "whitespace",
+        TokenizerType::CharacterGroup {
+            tokenize_on_chars: vec![CharacterGroups::Whitespace],
+        },
+    );
+    let lowercase = filter(name, "lowercase", TokenFilterType::Lowercase);
+    let split = |catenate: bool| TokenFilterType::WordDelimiterGraph {
+        generate_word_parts: true,
+        generate_number_parts: true,
+        catenate_all: catenate,
+        split_on_case_change: true,
+        split_on_numerics: true,
+        preserve_original: catenate,
+    };
+    Recipe {
+        // graph filters have to be flattened before indexing
+        index: analyzer(
+            name.to_string(),
+            whitespace.clone(),
+            vec![
+                filter(name, "word_delimiter", split(true)),
+                lowercase.clone(),
+                filter(name, "flatten_graph", TokenFilterType::FlattenGraph),
+            ],
+        ),
+        search: analyzer(
+            search_name(name),
+            whitespace,
+            vec![
+                filter(name, "word_delimiter_search", split(false)),
+                lowercase,
+            ],
+        ),
+    }
+}
+
+/// Paths split on `delimiter`. A path is indexed along with each of its
+/// ancestors, and a query for a path matches everything beneath it.
+pub fn path(name: &str, delimiter: char) -> Recipe {
+    Recipe {
+        index: analyzer(
+            name.to_string(),
+            tokenizer(
+                name,
+                "path_hierarchy",
+                TokenizerType::PathHierarchy {
+                    delimiter,
+                    reverse: false,
+                },
+            ),
+            vec![],
+        ),
+        search: analyzer(
+            search_name(name),
+            tokenizer(name, "keyword", TokenizerType::Keyword),
+            vec![],
+        ),
+    }
+}
+
+/// Phrases of up to `max_shingle_size` words indexed as single terms, which
+/// makes phrase-like matching cheap. Queries are shingled too, but only fall
+/// back to single words when they have no phrases. Fails unless
+/// `max_shingle_size` is at least 2.
+pub fn shingles(name: &str, max_shingle_size: u8) -> Result<Recipe, BuildError> {
+    let standard = tokenizer(name, "standard", TokenizerType::Standard);
+    let lowercase = filter(name, "lowercase", TokenFilterType::Lowercase);
+    let shingle = |output_unigrams: bool| TokenFilterType::Shingle {
+        max_shingle_size,
+        min_shingle_size: 2,
+        output_unigrams,
+        output_unigrams_if_no_shingles: !output_unigrams,
+        token_separator: " ".to_string(),
+        filler_token: "_".to_string(),
+    };
+    Ok(Recipe {
+        index: analyzer(
+            name.to_string(),
+            standard.clone(),
+            vec![
+                lowercase.clone(),
+                checked(filter(name, "shingle", shingle(true)))?,
+            ],
+        ),
+        search: analyzer(
+            search_name(name),
+            standard,
+            vec![
+                lowercase,
+                checked(filter(name, "shingle_search", shingle(false)))?,
+            ],
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::field::{Field, IndexMapping, IndexSettings};
+    use serde_json::{json, to_value};
+    use std::collections::BTreeMap;
+
+    fn terms(analyzer: &Analyzer, text: &str) -> Vec<String> {
+        analyzer
+            .analyze(text)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.term)
+            .collect()
+    }
+
+    #[test]
+    fn autocomplete_prefixes() {
+        let recipe = autocomplete("suggest", 1, 4).unwrap();
+        assert_eq!(terms(&recipe.index, "Café"), vec!["c", "ca", "caf", "cafe"]);
+        assert_eq!(terms(&recipe.search, "Café"), vec!["cafe"]);
+    }
+
+    #[test]
+    fn sizes_are_checked() {
+        assert!(matches!(
+            autocomplete("suggest", 5, 2),
+            Err(BuildError::InvalidSettings { component, .. }) if component == "suggest_edge_ngram"
+        ));
+        assert!(matches!(
+            shingles("phrases", 1),
+            Err(BuildError::InvalidSettings { component, .. }) if component == "phrases_shingle"
+        ));
+    }
+
+    #[test]
+    fn language_stems() {
+        let recipe = language(
+            "english",
+            Stopwords::Predefined("english".to_string()),
+            StemmerLanguage::English,
+        );
+        assert_eq!(recipe.index, recipe.search);
+        assert_eq!(
+            terms(&recipe.index, "The Running of the dogs"),
+            vec!["run", "dog"]
+        );
+    }
+
+    #[test]
+    fn exact_match_folds() {
+        assert_eq!(
+            exact_match("exact").normalize("Crème BRÛLÉE").unwrap(),
+            "creme brulee"
+        );
+    }
+
+    #[test]
+    fn identifier_parts() {
+        let recipe = identifier("sku");
+        assert_eq!(
+            terms(&recipe.index, "SD-500"),
+            vec!["sd-500", "sd500", "sd", "500"]
+        );
+        assert_eq!(terms(&recipe.search, "sd500"), vec!["sd", "500"]);
+    }
+
+    #[test]
+    fn path_ancestors() {
+        let recipe = path("category", '/');
+        assert_eq!(
+            terms(&recipe.index, "books/fiction/crime"),
+            vec!["books", "books/fiction", "books/fiction/crime"]
+        );
+        assert_eq!(
+            terms(&recipe.search, "books/fiction"),
+            vec!["books/fiction"]
+        );
+    }
+
+    #[test]
+    fn shingled_phrases() {
+        let recipe = shingles("phrases", 2).unwrap();
+        assert_eq!(
+            terms(&recipe.index, "New York city"),
+            vec!["new", "new york", "york", "york city", "city"]
+        );
+        assert_eq!(
+            terms(&recipe.search, "New York city"),
+            vec!["new york", "york city"]
+        );
+        assert_eq!(terms(&recipe.search, "York"), vec!["york"]);
+    }
+
+    #[test]
+    fn recipes_share_an_index() {
+        let mapping = IndexMapping {
+            fields: vec![
+                Field {
+                    name: "title".to_string(),
+                    field_type: autocomplete("suggest", 2, 10).unwrap().field_type(),
+                    fields: vec![],
+                    index: true,
+                    copy_to: vec![],
+                    meta: BTreeMap::new(),
+                },
+                Field {
+                    name: "body".to_string(),
+                    field_type: shingles("phrases", 3).unwrap().field_type(),
+                    fields: vec![],
+                    index: true,
+                    copy_to: vec![],
+                    meta: BTreeMap::new(),
+                },
+            ],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        let value = to_value(&mapping).unwrap();
+        assert_eq!(
+            value["mappings"]["properties"]["title"],
+            json!({
+                "type": "text",
+                "analyzer": "suggest",
+                "search_analyzer": "suggest_search",
+                "fields": {}
+            })
+        );
+        assert_eq!(
+            value["analysis"]["analyzer"]["suggest_search"],
+            json!({
+                "char_filter": [],
+                "tokenizer": "suggest_standard",
+                "filter": ["suggest_lowercase", "suggest_asciifolding"]
+            })
+        );
+        assert_eq!(value["analysis"]["tokenizer"].as_object().unwrap().len(), 2);
+    }
+}
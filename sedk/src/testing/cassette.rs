@@ -0,0 +1,412 @@
+//! Recording a cluster's answers once and replaying them offline, for what
+//! only a real cluster can say, such as search scores and `_analyze` output.
+//!
+//! A cassette is a json file of exchanges:
+//!
+//! ```json
+//! [
+//!   {
+//!     "method": "POST",
+//!     "path": "/products/_search",
+//!     "body": {"query": {"match": {"title": "desk"}}},
+//!     "status": 200,
+//!     "response": {"took": 3, "timed_out": false, "hits": {"hits": []}}
+//!   }
+//! ]
+//! ```
+use crate::transport::{AsyncTransport, Body, Request, Response, Transport, TransportError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The environment variable that makes [`cassette`] record rather than
+/// replay.
+pub const RECORD_VAR: &str = "SEDK_RECORD";
+
+/// A request and the cluster's response to it. Requests are kept normalised,
+/// with their query parameters sorted into the path and their body as json,
+/// an ndjson body being the array of its lines.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub body: Value,
+    pub status: u16,
+    #[serde(default)]
+    pub response: Value,
+}
+
+impl Exchange {
+    fn new(request: &Request, response: &Response) -> Exchange {
+        let (method, path, body) = normalise(request);
+        Exchange {
+            method,
+            path,
+            body,
+            status: response.status,
+            response: response.body.clone(),
+        }
+    }
+
+    /// Whether this exchange answers `request`. Bodies are compared as json,
+    /// so the order of keys and whitespace don't matter.
+    pub fn answers(&self, request: &Request) -> bool {
+        let (method, path, body) = normalise(request);
+        self.method == method && self.path == path && self.body == body
+    }
+}
+
+fn normalise(request: &Request) -> (String, String, Value) {
+    let mut sorted = request.clone();
+    sorted.query.sort();
+    let body = match &request.body {
+        Some(Body::Json(body)) => body.clone(),
+        Some(Body::NdJson(lines)) => Value::Array(lines.clone()),
+        None => Value::Null,
+    };
+    (
+        request.method.as_str().to_string(),
+        sorted.path_and_query(),
+        body,
+    )
+}
+
+#[derive(Debug)]
+pub enum CassetteError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for CassetteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CassetteError::Io(path, e) => write!(f, "could not access {}: {}", path.display(), e),
+            CassetteError::Parse(path, e) => {
+                write!(f, "could not parse {}: {}", path.display(), e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CassetteError {}
+
+/// Reads the exchanges of a cassette.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Exchange>, CassetteError> {
+    let path = path.as_ref();
+    let source =
+        std::fs::read_to_string(path).map_err(|e| CassetteError::Io(path.to_owned(), e))?;
+    serde_json::from_str(&source).map_err(|e| CassetteError::Parse(path.to_owned(), e))
+}
+
+fn save(path: &Path, exchanges: &[Exchange]) -> Result<(), CassetteError> {
+    let io = |e| CassetteError::Io(path.to_owned(), e);
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(io)?;
+    }
+    let json = serde_json::to_string_pretty(exchanges)
+        .map_err(|e| CassetteError::Parse(path.to_owned(), e))?;
+    std::fs::write(path, json + "\n").map_err(io)
+}
+
+/// Passes requests on to a cluster and records each exchange, writing the
+/// cassette when dropped or saved. Failures to reach the cluster aren't
+/// recorded.
+pub struct RecordingTransport<T> {
+    inner: T,
+    path: PathBuf,
+    exchanges: Mutex<Vec<Exchange>>,
+}
+
+impl<T> RecordingTransport<T> {
+    /// Records to a new cassette at `path`, replacing any there.
+    pub fn new<P: AsRef<Path>>(inner: T, path: P) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner,
+            path: path.as_ref().to_owned(),
+            exchanges: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The exchanges recorded so far.
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        self.recorded().clone()
+    }
+
+    /// Writes the cassette.
+    pub fn save(&self) -> Result<(), CassetteError> {
+        save(&self.path, &self.recorded())
+    }
+
+    fn recorded(&self) -> std::sync::MutexGuard<'_, Vec<Exchange>> {
+        self.exchanges.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record(&self, request: &Request, response: &Response) {
+        self.recorded().push(Exchange::new(request, response));
+    }
+}
+
+impl<T> Drop for RecordingTransport<T> {
+    // a cassette that can't be written fails the test recording it, unless
+    // it's failing already
+    fn drop(&mut self) {
+        if let Err(e) = self.save() {
+            if !std::thread::panicking() {
+                panic!("{}", e);
+            }
+        }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        let response = self.inner.send(request)?;
+        self.record(request, &response);
+        Ok(response)
+    }
+}
+
+impl<T: AsyncTransport + Sync> AsyncTransport for RecordingTransport<T> {
+    fn send(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response, TransportError>> + Send {
+        let exchange = self.inner.send(request);
+        let request = request.clone();
+        async move {
+            let response = exchange.await?;
+            self.record(&request, &response);
+            Ok(response)
+        }
+    }
+}
+
+/// Answers requests from a cassette without a cluster. A request made more
+/// than once gets the responses recorded for it in turn, and the last of
+/// them once they run out. Requests the cassette has no answer for fail.
+pub struct ReplayTransport {
+    exchanges: Vec<Exchange>,
+    replayed: Mutex<Vec<bool>>,
+}
+
+impl ReplayTransport {
+    pub fn new(exchanges: Vec<Exchange>) -> ReplayTransport {
+        let replayed = Mutex::new(vec![false; exchanges.len()]);
+        ReplayTransport {
+            exchanges,
+            replayed,
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ReplayTransport, CassetteError> {
+        load(path).map(ReplayTransport::new)
+    }
+
+    fn replay(&self, request: &Request) -> Result<Response, TransportError> {
+        let mut replayed = self.replayed.lock().unwrap_or_else(|e| e.into_inner());
+        let answers: Vec<usize> = (0..self.exchanges.len())
+            .filter(|&i| self.exchanges[i].answers(request))
+            .collect();
+        let chosen = answers
+            .iter()
+            .find(|&&i| !replayed[i])
+            .or_else(|| answers.last())
+            .copied();
+        match chosen {
+            Some(i) => {
+                replayed[i] = true;
+                Ok(Response {
+                    status: self.exchanges[i].status,
+                    body: self.exchanges[i].response.clone(),
+                })
+            }
+            None => {
+                let (method, path, body) = normalise(request);
+                Err(TransportError::Connection(format!(
+                    "no recorded response for {} {} {}",
+                    method, path, body
+                )))
+            }
+        }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        self.replay(request)
+    }
+}
+
+impl AsyncTransport for ReplayTransport {
+    fn send(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response, TransportError>> + Send {
+        std::future::ready(self.replay(request))
+    }
+}
+
+/// Either of the cassette transports, as chosen by [`cassette`].
+pub enum CassetteTransport<T> {
+    Recording(RecordingTransport<T>),
+    Replaying(ReplayTransport),
+}
+
+impl<T: Transport> Transport for CassetteTransport<T> {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        match self {
+            CassetteTransport::Recording(transport) => Transport::send(transport, request),
+            CassetteTransport::Replaying(transport) => Transport::send(transport, request),
+        }
+    }
+}
+
+impl<T: AsyncTransport + Sync> AsyncTransport for CassetteTransport<T> {
+    fn send(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response, TransportError>> + Send {
+        let (recording, replayed) = match self {
+            CassetteTransport::Recording(transport) => {
+                (Some(AsyncTransport::send(transport, request)), None)
+            }
+            CassetteTransport::Replaying(transport) => (None, Some(transport.replay(request))),
+        };
+        async move {
+            match (recording, replayed) {
+                (Some(recording), _) => recording.await,
+                (None, Some(replayed)) => replayed,
+                (None, None) => unreachable!("one of the transports answers"),
+            }
+        }
+    }
+}
+
+/// Replays the cassette at `path`, or when the `SEDK_RECORD` environment
+/// variable is set, records a new one over the transport `connect` gives.
+/// Suites can then be run against a cluster once to capture their fixtures,
+/// and offline from then on.
+///
+/// ```no_run
+/// # #[cfg(feature = "blocking")] {
+/// use sedk::testing::cassette;
+/// use sedk::transport::{Client, HttpTransport};
+///
+/// let transport = cassette("tests/cassettes/search.json", || {
+///     HttpTransport::new("http://localhost:9200")
+/// })?;
+/// let response = Client::new(transport).search("products", serde_json::json!({}))?;
+/// # }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn cassette<T, P: AsRef<Path>>(
+    path: P,
+    connect: impl FnOnce() -> T,
+) -> Result<CassetteTransport<T>, CassetteError> {
+    if std::env::var_os(RECORD_VAR).is_some() {
+        Ok(CassetteTransport::Recording(RecordingTransport::new(
+            connect(),
+            path,
+        )))
+    } else {
+        ReplayTransport::from_file(path).map(CassetteTransport::Replaying)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeCluster;
+    use crate::transport::{Client, Method};
+    use serde_json::json;
+
+    fn scratch(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sedk-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn recordings_replay_offline() {
+        let path = scratch("recordings_replay_offline.json");
+        {
+            let client = Client::new(RecordingTransport::new(FakeCluster::new(), &path));
+            let transport = client.transport();
+            transport
+                .send(&Request::new(Method::Put, "/products").with_json(json!({})))
+                .unwrap();
+            transport
+                .send(&Request::new(Method::Post, "/_bulk").with_ndjson(vec![
+                    json!({"index": {"_index": "products", "_id": "a1"}}),
+                    json!({"title": "Oak desk"}),
+                ]))
+                .unwrap();
+            assert_eq!(client.count("products").unwrap(), 1);
+            assert!(client.count("missing").is_err());
+        }
+        let exchanges = load(&path).unwrap();
+        assert_eq!(exchanges.len(), 4);
+        assert_eq!(exchanges[1].body[1], json!({"title": "Oak desk"}));
+
+        let client = Client::new(ReplayTransport::from_file(&path).unwrap());
+        assert_eq!(client.count("products").unwrap(), 1);
+        let error = client.count("missing").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            Some(&crate::transport::ErrorKind::IndexNotFound)
+        );
+        let error = client.count("unrecorded").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "connection failed: no recorded response for GET /unrecorded/_count null"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn requests_match_when_normalised() {
+        let recorded = Exchange {
+            method: "GET".to_string(),
+            path: "/products/_search?from=10&size=5".to_string(),
+            body: json!({"query": {"match_all": {}}, "size": 5}),
+            status: 200,
+            response: Value::Null,
+        };
+        let request = Request::new(Method::Get, "/products/_search")
+            .with_query("size", "5")
+            .with_query("from", "10");
+        let body = serde_json::from_str(r#"{ "size": 5, "query": { "match_all": {} } }"#).unwrap();
+        assert!(recorded.answers(&request.clone().with_json(body)));
+        assert!(!recorded.answers(&request.with_json(json!({"size": 5}))));
+    }
+
+    #[test]
+    fn repeated_requests_replay_in_turn() {
+        let exchange = |count: u64| Exchange {
+            method: "GET".to_string(),
+            path: "/products/_count".to_string(),
+            body: Value::Null,
+            status: 200,
+            response: json!({ "count": count }),
+        };
+        let client = Client::new(ReplayTransport::new(vec![exchange(0), exchange(3)]));
+        let counts: Vec<u64> = (0..3).map(|_| client.count("products").unwrap()).collect();
+        assert_eq!(counts, vec![0, 3, 3]);
+    }
+
+    #[tokio::test]
+    async fn fixtures_replay_as_cassettes() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cluster.json");
+        let client = crate::transport::AsyncClient::new(ReplayTransport::from_file(path).unwrap());
+        let response = client
+            .search("listings", json!({"query": {"match": {"title": "desk"}}}))
+            .await
+            .unwrap();
+        assert_eq!(response.hits[0].id, "a-1");
+    }
+}
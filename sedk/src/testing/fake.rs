@@ -0,0 +1,1652 @@
+//! An elasticsearch cluster held in memory, for testing code that manages
+//! indices without running one.
+use crate::transport::{Body, Method, Request, Response, Transport, TransportError};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+/// A [`Transport`] answering like a single node cluster whose state is kept
+/// in memory, so index and alias management can be tested deterministically.
+///
+/// It keeps the mappings, settings, aliases and documents of its indices, and
+/// refuses what elasticsearch refuses: invalid index names, aliases named
+/// like indices or with more than one write index, mapping updates that
+/// change existing fields, and documents that don't fit a strict mapping.
+/// Alias actions are applied all together or not at all. Writes are visible
+/// at once, so there's no need to refresh.
+///
+/// Searching and scripts aren't emulated, and counts ignore any query.
+///
+/// ```
+/// use sedk::testing::FakeCluster;
+/// use sedk::transport::api::AliasAction;
+/// use sedk::transport::{Client, ErrorKind};
+/// # use sedk::dsl::field::{Field, IndexMapping, IndexSettings};
+/// # let mapping = IndexMapping {
+/// #     fields: vec![Field::keyword("sku").build().unwrap()],
+/// #     plugins: vec![],
+/// #     settings: IndexSettings::default(),
+/// #     aliases: vec![],
+/// #     dynamic: None,
+/// # };
+///
+/// let client = Client::new(FakeCluster::new());
+/// client.create_index("products-1", &mapping)?;
+/// client.update_aliases(vec![AliasAction::Add {
+///     index: "products-1".to_string(),
+///     alias: "products".to_string(),
+///     is_write_index: None,
+/// }])?;
+/// let error = client.create_index("products", &mapping).unwrap_err();
+/// assert_eq!(error.kind(), Some(&ErrorKind::Other("invalid_index_name_exception".to_string())));
+/// # Ok::<(), sedk::transport::ApiError>(())
+/// ```
+#[derive(Default)]
+pub struct FakeCluster {
+    state: Mutex<Cluster>,
+}
+
+impl FakeCluster {
+    pub fn new() -> FakeCluster {
+        FakeCluster::default()
+    }
+
+    /// The names of the indices in the cluster.
+    pub fn indices(&self) -> Vec<String> {
+        self.cluster().indices.keys().cloned().collect()
+    }
+
+    fn cluster(&self) -> std::sync::MutexGuard<'_, Cluster> {
+        // a panicking test can't leave the state half changed, since changes
+        // are made to a copy that replaces it
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for FakeCluster {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        let mut cluster = self.cluster();
+        let mut changed = cluster.clone();
+        let response = match changed.handle(request) {
+            Ok(body) => {
+                *cluster = changed;
+                Response { status: 200, body }
+            }
+            Err(failure) => failure.into_response(),
+        };
+        Ok(response)
+    }
+}
+
+#[derive(Clone, Default)]
+struct Cluster {
+    indices: BTreeMap<String, Index>,
+    // for documents written without an id
+    next_id: u64,
+    /// The results of the tasks started, by task id.
+    tasks: BTreeMap<String, Value>,
+}
+
+#[derive(Clone)]
+struct Index {
+    mappings: Map<String, Value>,
+    settings: Map<String, Value>,
+    /// The options of each alias, such as `is_write_index`.
+    aliases: BTreeMap<String, Map<String, Value>>,
+    documents: BTreeMap<String, Stored>,
+}
+
+#[derive(Clone)]
+struct Stored {
+    source: Value,
+    version: u64,
+}
+
+/// An error response.
+struct Failure {
+    status: u16,
+    kind: &'static str,
+    reason: String,
+    index: Option<String>,
+}
+
+impl Failure {
+    fn new(status: u16, kind: &'static str, reason: String) -> Failure {
+        Failure {
+            status,
+            kind,
+            reason,
+            index: None,
+        }
+    }
+
+    fn on(mut self, index: &str) -> Failure {
+        self.index = Some(index.to_string());
+        self
+    }
+
+    fn index_not_found(index: &str) -> Failure {
+        Failure::new(
+            404,
+            "index_not_found_exception",
+            format!("no such index [{}]", index),
+        )
+        .on(index)
+    }
+
+    fn illegal_argument(reason: String) -> Failure {
+        Failure::new(400, "illegal_argument_exception", reason)
+    }
+
+    fn to_value(&self) -> Value {
+        let mut error = json!({ "type": self.kind, "reason": self.reason });
+        if let Some(index) = &self.index {
+            error["index"] = json!(index);
+        }
+        error
+    }
+
+    fn into_response(self) -> Response {
+        let mut error = self.to_value();
+        error["root_cause"] = json!([self.to_value()]);
+        Response {
+            status: self.status,
+            body: json!({ "error": error, "status": self.status }),
+        }
+    }
+}
+
+fn acknowledged() -> Value {
+    json!({ "acknowledged": true })
+}
+
+impl Cluster {
+    fn handle(&mut self, request: &Request) -> Result<Value, Failure> {
+        let segments: Vec<String> = request
+            .path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(decode)
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let body = match &request.body {
+            Some(Body::Json(body)) => body.clone(),
+            Some(Body::NdJson(lines)) => Value::Array(lines.clone()),
+            None => Value::Null,
+        };
+        match (request.method, segments.as_slice()) {
+            (Method::Post, ["_aliases"]) => self.update_aliases(&body),
+            (Method::Post, ["_bulk"]) | (Method::Put, ["_bulk"]) => self.bulk(&body),
+            (Method::Get, ["_alias"]) => self.get_aliases("_all"),
+            (Method::Get, ["_alias", alias]) => self.get_alias(alias),
+            (Method::Post, ["_reindex"]) => {
+                let wait = !request
+                    .query
+                    .iter()
+                    .any(|(key, value)| key == "wait_for_completion" && value == "false");
+                self.reindex(&body, wait)
+            }
+            (Method::Get, ["_tasks", task]) => match self.tasks.get(*task) {
+                Some(result) => Ok(result.clone()),
+                None => Err(Failure::new(
+                    404,
+                    "resource_not_found_exception",
+                    format!(
+                        "task [{}] isn't running and hasn't stored its results",
+                        task
+                    ),
+                )),
+            },
+            (Method::Get, [target, "_alias"]) => self.get_aliases(target),
+            (Method::Get, [target, "_mapping"]) => {
+                self.by_index(target, |index| json!({ "mappings": index.mappings }))
+            }
+            (Method::Put, [target, "_mapping"]) | (Method::Post, [target, "_mapping"]) => {
+                self.put_mapping(target, &body)
+            }
+            (Method::Get, [target, "_settings"]) => self.by_index(
+                target,
+                |index| json!({ "settings": { "index": index.settings } }),
+            ),
+            (Method::Get, [target, "_count"]) | (Method::Post, [target, "_count"]) => {
+                self.count(target)
+            }
+            (Method::Post, [target, "_refresh"]) | (Method::Get, [target, "_refresh"]) => {
+                let shards = self.resolve(target)?.len();
+                Ok(json!({ "_shards": shards_ok(shards) }))
+            }
+            (Method::Get, [index, "_doc", id]) => self.get_document(index, id),
+            (Method::Put, [index]) => self.create_index(index, &body),
+            (Method::Get, [target]) => self.by_index(target, |index| {
+                json!({
+                    "aliases": index.aliases,
+                    "mappings": index.mappings,
+                    "settings": { "index": index.settings },
+                })
+            }),
+            (Method::Head, [target]) => self.resolve(target).map(|_| Value::Null),
+            (Method::Delete, [target]) => self.delete_index(target),
+            _ => Err(Failure::new(
+                400,
+                "illegal_argument_exception",
+                format!(
+                    "no handler found for uri [{}] and method [{}]",
+                    request.path,
+                    request.method.as_str()
+                ),
+            )),
+        }
+    }
+
+    /// The indices an expression names: a comma separated list of indices,
+    /// aliases and patterns.
+    fn resolve(&self, expression: &str) -> Result<Vec<String>, Failure> {
+        let mut names = BTreeSet::new();
+        for part in expression.split(',') {
+            if part == "_all" || part.contains('*') {
+                let pattern = if part == "_all" { "*" } else { part };
+                for (name, index) in &self.indices {
+                    let aliased = index.aliases.keys().any(|alias| matches(pattern, alias));
+                    if matches(pattern, name) || aliased {
+                        names.insert(name.clone());
+                    }
+                }
+            } else if self.indices.contains_key(part) {
+                names.insert(part.to_string());
+            } else {
+                let aliased = self.aliased(part);
+                if aliased.is_empty() {
+                    return Err(Failure::index_not_found(part));
+                }
+                names.extend(aliased);
+            }
+        }
+        Ok(names.into_iter().collect())
+    }
+
+    fn aliased(&self, alias: &str) -> Vec<String> {
+        self.indices
+            .iter()
+            .filter(|(_, index)| index.aliases.contains_key(alias))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    fn by_index(&self, target: &str, view: impl Fn(&Index) -> Value) -> Result<Value, Failure> {
+        let mut indices = Map::new();
+        for name in self.resolve(target)? {
+            indices.insert(name.clone(), view(&self.indices[&name]));
+        }
+        Ok(Value::Object(indices))
+    }
+
+    fn create_index(&mut self, name: &str, body: &Value) -> Result<Value, Failure> {
+        self.new_index(name, body)?;
+        Ok(json!({ "acknowledged": true, "shards_acknowledged": true, "index": name }))
+    }
+
+    fn new_index(&mut self, name: &str, body: &Value) -> Result<(), Failure> {
+        if let Err(problem) = valid_name(name) {
+            return Err(invalid_index_name(name, &problem));
+        }
+        if self.indices.contains_key(name) {
+            return Err(Failure::new(
+                400,
+                "resource_already_exists_exception",
+                format!("index [{}] already exists", name),
+            )
+            .on(name));
+        }
+        if !self.aliased(name).is_empty() {
+            return Err(invalid_index_name(name, "already exists as alias"));
+        }
+        let mut settings = Map::new();
+        settings.insert("number_of_shards".to_string(), json!("1"));
+        settings.insert("number_of_replicas".to_string(), json!("1"));
+        if let Some(given) = body["settings"].as_object() {
+            flatten_settings(given, &mut settings);
+        }
+        settings.insert("provided_name".to_string(), json!(name));
+        let aliases = match &body["aliases"] {
+            Value::Object(aliases) => aliases
+                .iter()
+                .map(|(alias, options)| {
+                    let options = options.as_object().cloned().unwrap_or_default();
+                    (alias.clone(), options)
+                })
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+        let mut mappings = Map::new();
+        if let Some(given) = body["mappings"].as_object() {
+            merge_mappings(&mut mappings, given)
+                .map_err(|reason| Failure::new(400, "mapper_parsing_exception", reason).on(name))?;
+        }
+        self.indices.insert(
+            name.to_string(),
+            Index {
+                mappings,
+                settings,
+                aliases,
+                documents: BTreeMap::new(),
+            },
+        );
+        self.check_aliases()
+    }
+
+    fn delete_index(&mut self, target: &str) -> Result<Value, Failure> {
+        for part in target.split(',') {
+            if !self.indices.contains_key(part) && !self.aliased(part).is_empty() {
+                return Err(Failure::illegal_argument(format!(
+                    "The provided expression [{}] matches an alias, specify the corresponding \
+                     concrete indices instead.",
+                    part
+                )));
+            }
+        }
+        for name in self.resolve(target)? {
+            self.indices.remove(&name);
+        }
+        Ok(acknowledged())
+    }
+
+    fn put_mapping(&mut self, target: &str, body: &Value) -> Result<Value, Failure> {
+        let update = match body {
+            Value::Object(update) => update,
+            _ => {
+                return Err(Failure::new(
+                    400,
+                    "action_request_validation_exception",
+                    "Validation Failed: 1: mapping source is empty;".to_string(),
+                ))
+            }
+        };
+        for name in self.resolve(target)? {
+            let index = self.indices.get_mut(&name).expect("resolved indices exist");
+            merge_mappings(&mut index.mappings, update).map_err(Failure::illegal_argument)?;
+        }
+        Ok(acknowledged())
+    }
+
+    fn get_aliases(&self, target: &str) -> Result<Value, Failure> {
+        self.by_index(target, |index| json!({ "aliases": index.aliases }))
+    }
+
+    fn get_alias(&self, alias: &str) -> Result<Value, Failure> {
+        let mut indices = Map::new();
+        for (name, index) in &self.indices {
+            let aliases: Map<String, Value> = index
+                .aliases
+                .iter()
+                .filter(|(name, _)| matches(alias, name))
+                .map(|(name, options)| (name.clone(), Value::Object(options.clone())))
+                .collect();
+            if !aliases.is_empty() {
+                indices.insert(name.clone(), json!({ "aliases": aliases }));
+            }
+        }
+        if indices.is_empty() {
+            return Err(Failure::new(
+                404,
+                "aliases_not_found_exception",
+                format!("alias [{}] missing", alias),
+            ));
+        }
+        Ok(Value::Object(indices))
+    }
+
+    fn update_aliases(&mut self, body: &Value) -> Result<Value, Failure> {
+        let actions = match body["actions"].as_array() {
+            Some(actions) if !actions.is_empty() => actions,
+            _ => {
+                return Err(Failure::new(
+                    400,
+                    "action_request_validation_exception",
+                    "Validation Failed: 1: no actions specified;".to_string(),
+                ))
+            }
+        };
+        for action in actions {
+            let (name, options) = match action.as_object().and_then(|a| a.iter().next()) {
+                Some(action) => action,
+                None => {
+                    return Err(Failure::illegal_argument(format!(
+                        "empty action {}",
+                        action
+                    )))
+                }
+            };
+            let indices = self.action_indices(options)?;
+            if name == "remove_index" {
+                for index in indices {
+                    if self.indices.remove(&index).is_none() {
+                        return Err(Failure::index_not_found(&index));
+                    }
+                }
+                continue;
+            }
+            let aliases = names(options, "alias", "aliases");
+            if aliases.is_empty() {
+                return Err(Failure::illegal_argument(format!(
+                    "[{}] requires an [alias] to be set",
+                    name
+                )));
+            }
+            for index in &indices {
+                let index_aliases = &mut self.indices.get_mut(index).expect("checked").aliases;
+                for alias in &aliases {
+                    match name.as_str() {
+                        "add" => {
+                            let mut settings = options.as_object().cloned().unwrap_or_default();
+                            for key in &["index", "indices", "alias", "aliases"] {
+                                settings.remove(*key);
+                            }
+                            index_aliases.insert(alias.clone(), settings);
+                        }
+                        "remove" => {
+                            if index_aliases.remove(alias).is_none() {
+                                return Err(Failure::new(
+                                    404,
+                                    "aliases_not_found_exception",
+                                    format!("aliases [{}] missing", alias),
+                                ));
+                            }
+                        }
+                        other => {
+                            return Err(Failure::illegal_argument(format!(
+                                "Unknown alias action [{}]",
+                                other
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+        self.check_aliases()?;
+        Ok(acknowledged())
+    }
+
+    // the concrete indices an alias action names; aliases aren't taken
+    fn action_indices(&self, options: &Value) -> Result<Vec<String>, Failure> {
+        let mut indices = Vec::new();
+        for name in names(options, "index", "indices") {
+            if name.contains('*') {
+                let matching = self.indices.keys().filter(|index| matches(&name, index));
+                indices.extend(matching.cloned());
+            } else if self.indices.contains_key(&name) {
+                indices.push(name);
+            } else {
+                return Err(Failure::index_not_found(&name));
+            }
+        }
+        if indices.is_empty() {
+            return Err(Failure::illegal_argument(
+                "One of [index] or [indices] is required".to_string(),
+            ));
+        }
+        Ok(indices)
+    }
+
+    /// Whether the aliases as they stand are allowed, checked after every
+    /// change to them.
+    fn check_aliases(&self) -> Result<(), Failure> {
+        let mut write_indices: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (name, index) in &self.indices {
+            for (alias, options) in &index.aliases {
+                if let Err(problem) = valid_name(alias) {
+                    return Err(invalid_alias_name(alias, &problem));
+                }
+                if self.indices.contains_key(alias) {
+                    return Err(invalid_alias_name(
+                        alias,
+                        "an index or data stream exists with the same name as the alias",
+                    ));
+                }
+                if options.get("is_write_index") == Some(&Value::Bool(true)) {
+                    write_indices.entry(alias).or_default().push(name);
+                }
+            }
+        }
+        match write_indices
+            .into_iter()
+            .find(|(_, indices)| indices.len() > 1)
+        {
+            Some((alias, indices)) => Err(Failure::new(
+                400,
+                "illegal_state_exception",
+                format!(
+                    "alias [{}] has more than one write index [{}]",
+                    alias,
+                    indices.join(",")
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The index writes to `target` go to, creating it if there's no index
+    /// or alias of that name.
+    fn write_index(&mut self, target: &str) -> Result<String, Failure> {
+        if self.indices.contains_key(target) {
+            return Ok(target.to_string());
+        }
+        let aliased = self.aliased(target);
+        if aliased.is_empty() {
+            self.new_index(target, &Value::Null)?;
+            return Ok(target.to_string());
+        }
+        let is_write_index = |name: &String| {
+            self.indices[name].aliases[target]
+                .get("is_write_index")
+                .and_then(Value::as_bool)
+        };
+        let chosen = match aliased.as_slice() {
+            [only] if is_write_index(only) != Some(false) => Some(only),
+            _ => aliased
+                .iter()
+                .find(|name| is_write_index(name) == Some(true)),
+        };
+        chosen.cloned().ok_or_else(|| {
+            Failure::illegal_argument(format!(
+                "no write index is defined for alias [{}]. The write index may be explicitly \
+                 disabled using is_write_index=false or the alias points to multiple indices \
+                 without one being designated as a write index",
+                target
+            ))
+        })
+    }
+
+    fn bulk(&mut self, body: &Value) -> Result<Value, Failure> {
+        let mut lines = body
+            .as_array()
+            .map(|lines| lines.iter())
+            .into_iter()
+            .flatten();
+        let mut items = Vec::new();
+        let mut errors = false;
+        while let Some(line) = lines.next() {
+            let (action, metadata) = match line.as_object().and_then(|l| l.iter().next()) {
+                Some((action, metadata)) if BULK_ACTIONS.contains(&action.as_str()) => {
+                    (action.as_str(), metadata)
+                }
+                _ => {
+                    return Err(Failure::illegal_argument(format!(
+                        "Malformed action/metadata line, expected one of {:?} but found {}",
+                        BULK_ACTIONS, line
+                    )))
+                }
+            };
+            let source = match action {
+                "delete" => None,
+                _ => lines.next(),
+            };
+            let target = metadata["_index"].as_str().unwrap_or_default();
+            let id = match metadata["_id"].as_str() {
+                Some(id) => id.to_string(),
+                None if action == "index" || action == "create" => {
+                    self.next_id += 1;
+                    format!("generated-{}", self.next_id)
+                }
+                None => {
+                    return Err(Failure::new(
+                        400,
+                        "action_request_validation_exception",
+                        "Validation Failed: 1: id is missing;".to_string(),
+                    ))
+                }
+            };
+            let mut item = json!({ "_index": target, "_id": id });
+            match self.write(action, target, &id, metadata, source) {
+                Ok((index, version, result, status)) => {
+                    item["_index"] = json!(index);
+                    item["_version"] = json!(version);
+                    item["result"] = json!(result);
+                    item["status"] = json!(status);
+                }
+                Err(failure) => {
+                    errors = true;
+                    if let Some(index) = &failure.index {
+                        item["_index"] = json!(index);
+                    }
+                    item["status"] = json!(failure.status);
+                    item["error"] = failure.to_value();
+                }
+            }
+            items.push(json!({ action: item }));
+        }
+        Ok(json!({ "took": 0, "errors": errors, "items": items }))
+    }
+
+    /// One write of a bulk request, giving the index written to, the
+    /// document's new version, the result and the status.
+    fn write(
+        &mut self,
+        action: &str,
+        target: &str,
+        id: &str,
+        metadata: &Value,
+        source: Option<&Value>,
+    ) -> Result<(String, u64, &'static str, u16), Failure> {
+        let name = self.write_index(target)?;
+        let index = self.indices.get_mut(&name).expect("write indices exist");
+        let current = index.documents.get(id).map(|stored| stored.version);
+        let conflict = |reason: String| {
+            Failure::new(409, "version_conflict_engine_exception", reason).on(&name)
+        };
+        let version = match (
+            metadata["version"].as_u64(),
+            metadata["version_type"].as_str(),
+        ) {
+            (Some(given), Some(version_type)) if version_type.starts_with("external") => {
+                let newer = match (current, version_type) {
+                    (Some(current), "external_gte") => given >= current,
+                    (Some(current), _) => given > current,
+                    (None, _) => true,
+                };
+                if !newer {
+                    return Err(conflict(format!(
+                        "[{}]: version conflict, current version [{}] is higher or equal to the \
+                         one provided [{}]",
+                        id,
+                        current.unwrap_or_default(),
+                        given
+                    )));
+                }
+                given
+            }
+            _ => current.unwrap_or_default() + 1,
+        };
+        let source = match (action, source, current) {
+            ("delete", _, None) => return Ok((name, 1, "not_found", 404)),
+            ("delete", _, Some(_)) => {
+                index.documents.remove(id);
+                return Ok((name, version, "deleted", 200));
+            }
+            ("create", _, Some(current)) => {
+                return Err(conflict(format!(
+                    "[{}]: version conflict, document already exists (current version [{}])",
+                    id, current
+                )))
+            }
+            ("update", _, None) => {
+                return Err(Failure::new(
+                    404,
+                    "document_missing_exception",
+                    format!("[_doc][{}]: document missing", id),
+                )
+                .on(&name))
+            }
+            ("update", Some(update), Some(_)) => {
+                let mut merged = index.documents[id].source.clone();
+                merge_objects(&mut merged, &update["doc"]);
+                merged
+            }
+            (_, Some(source), _) => source.clone(),
+            (_, None, _) => {
+                return Err(Failure::illegal_argument(
+                    "The bulk request must be terminated by a newline [\\n]".to_string(),
+                ))
+            }
+        };
+        let mut mappings = index.mappings.clone();
+        map_document(&mut mappings, &source, id).map_err(|failure| failure.on(&name))?;
+        index.mappings = mappings;
+        index
+            .documents
+            .insert(id.to_string(), Stored { source, version });
+        let (result, status) = match current {
+            Some(_) => ("updated", 200),
+            None => ("created", 201),
+        };
+        Ok((name, version, result, status))
+    }
+
+    /// Copies documents between indices as a reindex does, which when not
+    /// waited for is a task that has already completed.
+    fn reindex(&mut self, body: &Value, wait: bool) -> Result<Value, Failure> {
+        if !body["script"].is_null() {
+            return Err(Failure::illegal_argument(
+                "scripts aren't run by the fake cluster".to_string(),
+            ));
+        }
+        let (source, dest) =
+            match (
+                body["source"]["index"].as_str(),
+                body["dest"]["index"].as_str(),
+            ) {
+                (Some(source), Some(dest)) => (source, dest),
+                _ => return Err(Failure::new(
+                    400,
+                    "action_request_validation_exception",
+                    "Validation Failed: 1: use _all if you really want to copy from all existing \
+                     indexes;2: index must be specified;"
+                        .to_string(),
+                )),
+            };
+        let mut documents = Vec::new();
+        for name in self.resolve(source)? {
+            for (id, stored) in &self.indices[&name].documents {
+                documents.push((id.clone(), stored.source.clone()));
+            }
+        }
+        let (mut created, mut updated, mut failures) = (0, 0, Vec::new());
+        for (id, source) in &documents {
+            match self.write("index", dest, id, &Value::Null, Some(source)) {
+                Ok((_, _, "created", _)) => created += 1,
+                Ok(_) => updated += 1,
+                Err(failure) => failures.push(json!({
+                    "index": dest,
+                    "id": id,
+                    "cause": failure.to_value(),
+                    "status": failure.status,
+                })),
+            }
+        }
+        let response = json!({
+            "took": 0,
+            "timed_out": false,
+            "total": documents.len(),
+            "created": created,
+            "updated": updated,
+            "deleted": 0,
+            "failures": failures,
+        });
+        if wait {
+            return Ok(response);
+        }
+        let task = format!("fake:{}", self.tasks.len() + 1);
+        self.tasks.insert(
+            task.clone(),
+            json!({
+                "completed": true,
+                "task": { "node": "fake", "action": "indices:data/write/reindex" },
+                "response": response,
+            }),
+        );
+        Ok(json!({ "task": task }))
+    }
+
+    fn count(&self, target: &str) -> Result<Value, Failure> {
+        let indices = self.resolve(target)?;
+        let count: usize = indices
+            .iter()
+            .map(|name| self.indices[name].documents.len())
+            .sum();
+        Ok(json!({ "count": count, "_shards": shards_ok(indices.len()) }))
+    }
+
+    fn get_document(&self, index: &str, id: &str) -> Result<Value, Failure> {
+        let name = match self.resolve(index)?.as_slice() {
+            [name] => name.clone(),
+            _ => {
+                return Err(Failure::illegal_argument(format!(
+                    "alias [{}] has more than one index associated with it, can't execute a \
+                     single index op",
+                    index
+                )))
+            }
+        };
+        match self.indices[&name].documents.get(id) {
+            Some(stored) => Ok(json!({
+                "_index": name,
+                "_id": id,
+                "_version": stored.version,
+                "found": true,
+                "_source": stored.source,
+            })),
+            None => Err(Failure::new(
+                404,
+                "document_missing_exception",
+                format!("[{}]: document missing", id),
+            )
+            .on(&name)),
+        }
+    }
+}
+
+const BULK_ACTIONS: [&str; 4] = ["index", "create", "update", "delete"];
+
+fn shards_ok(total: usize) -> Value {
+    json!({ "total": total, "successful": total, "skipped": 0, "failed": 0 })
+}
+
+fn invalid_index_name(name: &str, problem: &str) -> Failure {
+    Failure::new(
+        400,
+        "invalid_index_name_exception",
+        format!("Invalid index name [{}], {}", name, problem),
+    )
+    .on(name)
+}
+
+fn invalid_alias_name(alias: &str, problem: &str) -> Failure {
+    Failure::new(
+        400,
+        "invalid_alias_name_exception",
+        format!("Invalid alias name [{}]: {}", alias, problem),
+    )
+}
+
+/// Why elasticsearch would refuse `name` as the name of an index.
+fn valid_name(name: &str) -> Result<(), String> {
+    const FORBIDDEN: [char; 11] = ['\\', '/', '*', '?', '"', '<', '>', '|', ' ', ',', '#'];
+    if name.is_empty() {
+        return Err("must not be empty".to_string());
+    }
+    if name.chars().any(|c| c.is_uppercase()) {
+        return Err("must be lowercase".to_string());
+    }
+    if let Some(c) = name.chars().find(|c| FORBIDDEN.contains(c) || *c == ':') {
+        return Err(format!("must not contain the following characters [{}]", c));
+    }
+    if name.starts_with(['_', '-', '+']) {
+        return Err("must not start with '_', '-', or '+'".to_string());
+    }
+    if name == "." || name == ".." {
+        return Err("must not be '.' or '..'".to_string());
+    }
+    if name.len() > 255 {
+        return Err("index name is too long".to_string());
+    }
+    Ok(())
+}
+
+// the values of a key that may be given once or as a list
+fn names(options: &Value, one: &str, many: &str) -> Vec<String> {
+    let mut names: Vec<String> = options[one]
+        .as_str()
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    if let Some(list) = options[many].as_array() {
+        names.extend(list.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    names
+}
+
+/// Whether `name` matches a pattern in which `*` stands for any characters.
+fn matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            name.starts_with(prefix)
+                && (0..=name.len() - prefix.len())
+                    .filter(|&skip| name.is_char_boundary(prefix.len() + skip))
+                    .any(|skip| matches(rest, &name[prefix.len() + skip..]))
+        }
+    }
+}
+
+fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Index settings as elasticsearch reports them: without their `index.`
+/// prefix and with scalars as strings.
+fn flatten_settings(given: &Map<String, Value>, settings: &mut Map<String, Value>) {
+    for (key, value) in given {
+        let key = key.strip_prefix("index.").unwrap_or(key);
+        match value {
+            Value::Object(nested) if key == "index" => flatten_settings(nested, settings),
+            Value::Bool(_) | Value::Number(_) => {
+                settings.insert(key.to_string(), json!(value.to_string()));
+            }
+            _ => {
+                settings.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+}
+
+fn merge_objects(target: &mut Value, update: &Value) {
+    match (target, update) {
+        (Value::Object(target), Value::Object(update)) => {
+            for (key, value) in update {
+                match target.get_mut(key) {
+                    Some(existing) if existing.is_object() => merge_objects(existing, value),
+                    _ => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, update) => *target = update.clone(),
+    }
+}
+
+// field parameters an existing field's mapping can change
+const UPDATABLE: [&str; 7] = [
+    "ignore_above",
+    "meta",
+    "search_analyzer",
+    "search_quote_analyzer",
+    "copy_to",
+    "dynamic",
+    "ignore_malformed",
+];
+
+/// Adds `update` to the mappings of an index, failing as elasticsearch does
+/// when it would change how existing fields are indexed.
+fn merge_mappings(
+    mappings: &mut Map<String, Value>,
+    update: &Map<String, Value>,
+) -> Result<(), String> {
+    for (key, value) in update {
+        match (key.as_str(), value) {
+            ("properties", Value::Object(properties)) => {
+                let existing = properties_of(mappings);
+                merge_properties(existing, properties, "")?;
+            }
+            _ => {
+                mappings.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn properties_of(mapping: &mut Map<String, Value>) -> &mut Map<String, Value> {
+    let properties = mapping
+        .entry("properties")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !properties.is_object() {
+        *properties = Value::Object(Map::new());
+    }
+    properties.as_object_mut().expect("made an object")
+}
+
+fn merge_properties(
+    existing: &mut Map<String, Value>,
+    update: &Map<String, Value>,
+    prefix: &str,
+) -> Result<(), String> {
+    for (name, field) in update {
+        // dotted names stand for objects, as they do in documents
+        let (name, field) = match name.split_once('.') {
+            Some((name, rest)) => (name, json!({ "properties": { rest: field } })),
+            None => (name.as_str(), field.clone()),
+        };
+        let path = format!("{}{}", prefix, name);
+        match (existing.get_mut(name), &field) {
+            (Some(Value::Object(current)), Value::Object(field)) => {
+                merge_field(current, field, &path)?
+            }
+            _ => {
+                let mut field = field;
+                if let Some(Value::Object(properties)) = field.get_mut("properties") {
+                    let mut expanded = Map::new();
+                    merge_properties(&mut expanded, properties, &format!("{}.", path))?;
+                    *properties = expanded;
+                }
+                existing.insert(name.to_string(), field);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn merge_field(
+    current: &mut Map<String, Value>,
+    update: &Map<String, Value>,
+    path: &str,
+) -> Result<(), String> {
+    let (from, to) = (field_type(current), field_type(update));
+    if from != to {
+        return Err(format!(
+            "mapper [{}] cannot be changed from type [{}] to [{}]",
+            path, from, to
+        ));
+    }
+    let keys: BTreeSet<String> = current.keys().chain(update.keys()).cloned().collect();
+    for key in keys {
+        match key.as_str() {
+            "type" => (),
+            "properties" | "fields" => {
+                if let Some(Value::Object(fields)) = update.get(&key) {
+                    let existing = current
+                        .entry(key.as_str())
+                        .or_insert_with(|| Value::Object(Map::new()));
+                    if let Value::Object(existing) = existing {
+                        merge_properties(existing, fields, &format!("{}.", path))?;
+                    }
+                }
+            }
+            updatable if UPDATABLE.contains(&updatable) => {
+                if let Some(value) = update.get(updatable) {
+                    current.insert(key.clone(), value.clone());
+                }
+            }
+            parameter => {
+                let (before, after) = (current.get(parameter), update.get(parameter));
+                if before != after {
+                    return Err(format!(
+                        "Mapper for [{}] conflicts with existing mapper:\n\tCannot update \
+                         parameter [{}] from [{}] to [{}]",
+                        path,
+                        parameter,
+                        describe(before),
+                        describe(after)
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn field_type(field: &Map<String, Value>) -> &str {
+    field
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or("object")
+}
+
+fn describe(value: Option<&Value>) -> String {
+    match value {
+        None => "default".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Checks `source` against the mappings of its index, adding the fields that
+/// dynamic mapping adds.
+fn map_document(
+    mappings: &mut Map<String, Value>,
+    source: &Value,
+    id: &str,
+) -> Result<(), Failure> {
+    let object = match source {
+        Value::Object(object) => object,
+        _ => {
+            return Err(Failure::new(
+                400,
+                "mapper_parsing_exception",
+                "failed to parse, document is empty".to_string(),
+            ))
+        }
+    };
+    let dynamic = dynamic_of(mappings, "true");
+    let templates = templates_of(mappings);
+    map_object(
+        properties_of(mappings),
+        object,
+        &dynamic,
+        &templates,
+        "",
+        id,
+    )
+}
+
+// the path_match and mapping of each dynamic template, in order
+fn templates_of(mappings: &Map<String, Value>) -> Vec<(String, Value)> {
+    let templates = match mappings.get("dynamic_templates") {
+        Some(Value::Array(templates)) => templates,
+        _ => return Vec::new(),
+    };
+    templates
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|template| template.values())
+        .filter_map(|template| {
+            let path_match = template.get("path_match")?.as_str()?;
+            Some((path_match.to_string(), template.get("mapping")?.clone()))
+        })
+        .collect()
+}
+
+fn dynamic_of(mapping: &Map<String, Value>, inherited: &str) -> String {
+    match mapping.get("dynamic") {
+        Some(Value::String(dynamic)) => dynamic.clone(),
+        Some(Value::Bool(dynamic)) => dynamic.to_string(),
+        _ => inherited.to_string(),
+    }
+}
+
+fn map_object(
+    properties: &mut Map<String, Value>,
+    object: &Map<String, Value>,
+    dynamic: &str,
+    templates: &[(String, Value)],
+    prefix: &str,
+    id: &str,
+) -> Result<(), Failure> {
+    for (key, value) in object {
+        // dotted names stand for objects
+        let (name, value) = match key.split_once('.') {
+            Some((name, rest)) => (name, json!({ rest: value })),
+            None => (key.as_str(), value.clone()),
+        };
+        let path = format!("{}{}", prefix, name);
+        if !properties.contains_key(name) {
+            let parent = prefix.strip_suffix('.').unwrap_or("_doc");
+            match dynamic {
+                "strict" => {
+                    return Err(Failure::new(
+                        400,
+                        "strict_dynamic_mapping_exception",
+                        format!(
+                            "mapping set to strict, dynamic introduction of [{}] within [{}] is \
+                             not allowed",
+                            name, parent
+                        ),
+                    ))
+                }
+                // the first template whose path matches maps the field, and
+                // dynamic mapping guesses at it otherwise
+                "true" => match templates
+                    .iter()
+                    .find(|(path_match, _)| matches(path_match, &path))
+                    .map(|(_, mapping)| mapping.clone())
+                    .or_else(|| dynamic_mapping(&value))
+                {
+                    Some(mapping) => {
+                        properties.insert(name.to_string(), mapping);
+                    }
+                    None => continue,
+                },
+                _ => continue,
+            }
+        }
+        let field = properties[name]
+            .as_object_mut()
+            .expect("fields are objects");
+        let values = match &value {
+            Value::Array(values) => values.clone(),
+            value => vec![value.clone()],
+        };
+        for value in values.iter().filter(|value| !value.is_null()) {
+            map_value(field, value, dynamic, templates, &path, id)?;
+        }
+    }
+    Ok(())
+}
+
+fn map_value(
+    field: &mut Map<String, Value>,
+    value: &Value,
+    dynamic: &str,
+    templates: &[(String, Value)],
+    path: &str,
+    id: &str,
+) -> Result<(), Failure> {
+    let field_type = field_type(field).to_string();
+    match (field_type.as_str(), value) {
+        ("object", Value::Object(object)) | ("nested", Value::Object(object)) => {
+            let dynamic = dynamic_of(field, dynamic);
+            map_object(
+                properties_of(field),
+                object,
+                &dynamic,
+                templates,
+                &format!("{}.", path),
+                id,
+            )
+        }
+        ("object", _) | ("nested", _) => Err(Failure::new(
+            400,
+            "mapper_parsing_exception",
+            format!(
+                "object mapping for [{}] tried to parse field [{}] as object, but found a \
+                 concrete value",
+                path, path
+            ),
+        )),
+        (field_type, value) if !fits(field_type, value) => Err(Failure::new(
+            400,
+            "mapper_parsing_exception",
+            format!(
+                "failed to parse field [{}] of type [{}] in document with id '{}'. Preview of \
+                 field's value: '{}'",
+                path,
+                field_type,
+                id,
+                describe(Some(value))
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Whether a value can be indexed in a field of `field_type`. Only numbers,
+/// with the ranges of integer types, and booleans are checked, along with
+/// objects given to leaf fields.
+fn fits(field_type: &str, value: &Value) -> bool {
+    const NUMBERS: [&str; 10] = [
+        "long",
+        "integer",
+        "short",
+        "byte",
+        "double",
+        "float",
+        "half_float",
+        "scaled_float",
+        "unsigned_long",
+        "token_count",
+    ];
+    match value {
+        Value::Object(_) => {
+            matches!(field_type, "geo_point" | "geo_shape" | "flattened" | "join")
+                || field_type.ends_with("_range")
+        }
+        Value::String(s) if NUMBERS.contains(&field_type) => s
+            .trim()
+            .parse::<f64>()
+            .is_ok_and(|n| in_range(field_type, n)),
+        Value::String(s) if field_type == "boolean" => {
+            matches!(s.as_str(), "true" | "false" | "")
+        }
+        Value::Bool(_) => !NUMBERS.contains(&field_type),
+        Value::Number(n) => {
+            field_type != "boolean" && n.as_f64().is_none_or(|n| in_range(field_type, n))
+        }
+        _ => true,
+    }
+}
+
+// elasticsearch checks the range of integers before dropping any fraction
+fn in_range(field_type: &str, n: f64) -> bool {
+    let (min, max) = match field_type {
+        "byte" => (i8::MIN as f64, i8::MAX as f64),
+        "short" => (i16::MIN as f64, i16::MAX as f64),
+        "integer" => (i32::MIN as f64, i32::MAX as f64),
+        "long" => (i64::MIN as f64, i64::MAX as f64),
+        "unsigned_long" => (0.0, u64::MAX as f64),
+        _ => return true,
+    };
+    min <= n && n <= max
+}
+
+/// The mapping elasticsearch's dynamic mapping gives a new field, which for
+/// nulls and empty arrays is none yet. Dates aren't detected.
+fn dynamic_mapping(value: &Value) -> Option<Value> {
+    let mapping = match value {
+        Value::Null => return None,
+        Value::Array(values) => return values.iter().find_map(dynamic_mapping),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => json!({ "type": "float" }),
+        Value::Number(_) => json!({ "type": "long" }),
+        Value::String(_) => json!({
+            "type": "text",
+            "fields": { "keyword": { "type": "keyword", "ignore_above": 256 } }
+        }),
+        Value::Object(_) => json!({ "properties": {} }),
+    };
+    Some(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::api::{AliasAction, BulkOperation, Endpoint, UpdateAliases};
+    use crate::transport::{ApiError, Client, ErrorKind};
+    use crate::Document;
+    use std::borrow::Cow;
+
+    fn client() -> Client<FakeCluster> {
+        Client::new(FakeCluster::new())
+    }
+
+    fn create(client: &Client<FakeCluster>, index: &str, body: Value) -> Result<(), ApiError> {
+        let response = client
+            .transport()
+            .send(&Request::new(Method::Put, &format!("/{}", index)).with_json(body))?;
+        crate::transport::api::handle(&Acknowledged, response)
+    }
+
+    struct Acknowledged;
+
+    impl Endpoint for Acknowledged {
+        type Output = ();
+
+        fn request(&self) -> Result<Request, ApiError> {
+            unreachable!()
+        }
+
+        fn output(&self, _: Response) -> Result<(), ApiError> {
+            Ok(())
+        }
+    }
+
+    fn other(kind: &str) -> Option<ErrorKind> {
+        Some(ErrorKind::Other(kind.to_string()))
+    }
+
+    fn add(index: &str, alias: &str) -> AliasAction {
+        AliasAction::Add {
+            index: index.to_string(),
+            alias: alias.to_string(),
+            is_write_index: None,
+        }
+    }
+
+    fn remove(index: &str, alias: &str) -> AliasAction {
+        AliasAction::Remove {
+            index: index.to_string(),
+            alias: alias.to_string(),
+        }
+    }
+
+    #[test]
+    fn index_names_are_validated() {
+        let client = client();
+        for name in &["Products", "_products", "pro*ducts", "a:b", ".."] {
+            let error = create(&client, name, json!({})).unwrap_err();
+            assert_eq!(
+                error.kind().cloned(),
+                other("invalid_index_name_exception"),
+                "{}",
+                name
+            );
+        }
+        create(&client, "products", json!({})).unwrap();
+        let error = create(&client, "products", json!({})).unwrap_err();
+        assert_eq!(error.kind(), Some(&ErrorKind::ResourceAlreadyExists));
+        assert_eq!(client.transport().indices(), vec!["products"]);
+    }
+
+    #[test]
+    fn alias_actions_are_atomic() {
+        let client = client();
+        create(&client, "products-1", json!({})).unwrap();
+        create(&client, "products-2", json!({})).unwrap();
+        client
+            .update_aliases(vec![add("products-1", "products")])
+            .unwrap();
+
+        // the removal fails, so the addition before it is undone
+        let error = client
+            .update_aliases(vec![
+                add("products-2", "products"),
+                remove("products-2", "live"),
+            ])
+            .unwrap_err();
+        assert_eq!(error.kind().cloned(), other("aliases_not_found_exception"));
+        let aliases = client.get_aliases(Some("products")).unwrap();
+        assert_eq!(aliases.keys().collect::<Vec<_>>(), vec!["products-1"]);
+
+        client
+            .update_aliases(vec![
+                remove("products-1", "products"),
+                add("products-2", "products"),
+            ])
+            .unwrap();
+        let aliases = client.get_aliases(None).unwrap();
+        assert_eq!(aliases["products-1"], Vec::<String>::new());
+        assert_eq!(aliases["products-2"], vec!["products"]);
+    }
+
+    #[test]
+    fn aliases_are_unique() {
+        let client = client();
+        create(&client, "products-1", json!({})).unwrap();
+        create(&client, "products-2", json!({})).unwrap();
+        let error = client
+            .update_aliases(vec![add("products-1", "products-2")])
+            .unwrap_err();
+        assert_eq!(error.kind().cloned(), other("invalid_alias_name_exception"));
+
+        let write_index = |index: &str| json!({"add": {"index": index, "alias": "products", "is_write_index": true}});
+        let response = client
+            .transport()
+            .send(
+                &UpdateAliases { actions: vec![] }
+                    .request()
+                    .unwrap()
+                    .with_json(json!({
+                        "actions": [write_index("products-1"), write_index("products-2")]
+                    })),
+            )
+            .unwrap();
+        assert_eq!(response.status, 400);
+        assert_eq!(response.body["error"]["type"], "illegal_state_exception");
+        assert!(client.get_aliases(Some("products")).is_err());
+    }
+
+    #[test]
+    fn mapping_updates_keep_existing_fields() {
+        let client = client();
+        create(
+            &client,
+            "products",
+            json!({"mappings": {"properties": {
+                "title": {"type": "text", "analyzer": "english"},
+                "sku": {"type": "keyword", "ignore_above": 16}
+            }}}),
+        )
+        .unwrap();
+        let put = |properties: Value| {
+            let request = Request::new(Method::Put, "/products/_mapping")
+                .with_json(json!({ "properties": properties }));
+            crate::transport::api::handle(&Acknowledged, client.transport().send(&request).unwrap())
+        };
+        put(json!({"sku": {"type": "keyword", "ignore_above": 64}, "stars": {"type": "byte"}}))
+            .unwrap();
+        let error = put(json!({"sku": {"type": "long"}})).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "elasticsearch answered 400: illegal_argument_exception: mapper [sku] cannot be \
+             changed from type [keyword] to [long]"
+        );
+        let error = put(json!({"title": {"type": "text"}})).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Cannot update parameter [analyzer] from [english] to [default]"));
+
+        let mapping = &client.get_mapping("products").unwrap()["products"];
+        assert_eq!(mapping["properties"]["sku"]["ignore_above"], 64);
+        assert_eq!(mapping["properties"]["stars"]["type"], "byte");
+    }
+
+    #[derive(serde::Serialize)]
+    struct Listing {
+        sku: &'static str,
+        stars: Value,
+    }
+
+    impl Document for Listing {
+        fn id(&self) -> Cow<'_, str> {
+            Cow::Borrowed(self.sku)
+        }
+    }
+
+    #[test]
+    fn bulk_writes_follow_the_mapping_and_aliases() {
+        let client = client();
+        create(
+            &client,
+            "listings-1",
+            json!({
+                "mappings": {"dynamic": "strict", "properties": {
+                    "sku": {"type": "keyword"},
+                    "stars": {"type": "byte"}
+                }},
+                "aliases": {"listings": {}}
+            }),
+        )
+        .unwrap();
+        let listing = |sku, stars| Listing { sku, stars };
+        let response = client
+            .bulk(vec![
+                BulkOperation::index("listings", &listing("a1", json!(4))).unwrap(),
+                BulkOperation::create("listings", &listing("a1", json!(5))).unwrap(),
+                BulkOperation::index("listings", &listing("b2", json!("many"))).unwrap(),
+                BulkOperation::index("listings", &listing("c3", json!(2))).unwrap(),
+                BulkOperation::delete("listings", &listing("c3", json!(2))),
+            ])
+            .unwrap();
+        let statuses: Vec<u16> = response.items.iter().map(|item| item.status).collect();
+        assert_eq!(statuses, vec![201, 409, 400, 201, 200]);
+        assert_eq!(
+            response.items[2].error.as_ref().unwrap().kind,
+            ErrorKind::MapperParsing
+        );
+        assert!(response.items.iter().all(|item| item.index == "listings-1"));
+        assert_eq!(client.count("listings").unwrap(), 1);
+
+        let strict = client
+            .transport()
+            .send(&Request::new(Method::Post, "/_bulk").with_ndjson(vec![
+                json!({"index": {"_index": "listings", "_id": "d4"}}),
+                json!({"sku": "d4", "colour": "red"}),
+            ]));
+        let item = &strict.unwrap().body["items"][0]["index"];
+        assert_eq!(item["error"]["type"], "strict_dynamic_mapping_exception");
+
+        // an alias over two indices needs a write index
+        create(&client, "listings-2", json!({"aliases": {"listings": {}}})).unwrap();
+        let response = client
+            .bulk(vec![BulkOperation::index(
+                "listings",
+                &listing("e5", json!(1)),
+            )
+            .unwrap()])
+            .unwrap();
+        assert_eq!(
+            response.items[0].error.as_ref().unwrap().kind,
+            ErrorKind::IllegalArgument
+        );
+        assert_eq!(client.count("listings-*").unwrap(), 1);
+    }
+
+    #[test]
+    fn dynamic_mapping_adds_fields() {
+        let client = client();
+        let response = client
+            .bulk(vec![BulkOperation::index(
+                "logs",
+                &Listing {
+                    sku: "a1",
+                    stars: json!(1.5),
+                },
+            )
+            .unwrap()])
+            .unwrap();
+        assert_eq!(response.items[0].status, 201);
+        let mapping = &client.get_mapping("logs").unwrap()["logs"];
+        assert_eq!(mapping["properties"]["stars"]["type"], "float");
+        assert_eq!(
+            mapping["properties"]["sku"]["fields"]["keyword"]["type"],
+            "keyword"
+        );
+        assert_eq!(
+            client.get_settings("logs").unwrap()["logs"]["index"]["number_of_shards"],
+            "1"
+        );
+    }
+
+    #[test]
+    fn maps_take_new_keys_in_strict_mappings() {
+        use crate::dsl::field::{Dynamic, IndexMapping, IndexSettings};
+        use crate::IntoFields;
+        use std::collections::HashMap;
+
+        let client = client();
+        let mut fields = String::into_fields(Some("sku"));
+        fields.extend(HashMap::<String, i8>::into_fields(Some("stars")));
+        let mapping = IndexMapping {
+            fields,
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: Some(Dynamic::Strict),
+        };
+        client.create_index("listings", &mapping).unwrap();
+        let listing = Listing {
+            sku: "a1",
+            stars: json!({"food": 4, "service": 2}),
+        };
+        let response = client
+            .bulk(vec![BulkOperation::index("listings", &listing).unwrap()])
+            .unwrap();
+        assert_eq!(response.items[0].status, 201);
+        assert_eq!(client.count("listings").unwrap(), 1);
+    }
+
+    #[test]
+    fn templates_map_new_fields() {
+        use crate::dsl::field::{IndexMapping, IndexSettings};
+        use crate::IntoFields;
+        use std::collections::HashMap;
+
+        let client = client();
+        let mut fields = String::into_fields(Some("sku"));
+        fields.extend(HashMap::<String, i8>::into_fields(Some("stars")));
+        let mapping = IndexMapping {
+            fields,
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        };
+        client.create_index("listings", &mapping).unwrap();
+        let listing = |sku, stars| Listing { sku, stars };
+        let response = client
+            .bulk(vec![
+                BulkOperation::index("listings", &listing("a1", json!({"food": 4}))).unwrap(),
+                BulkOperation::index("listings", &listing("b2", json!({"view": 500}))).unwrap(),
+                BulkOperation::index("listings", &listing("c3", json!({"food": "-200"}))).unwrap(),
+            ])
+            .unwrap();
+        let statuses: Vec<u16> = response.items.iter().map(|item| item.status).collect();
+        assert_eq!(statuses, vec![201, 400, 400]);
+        assert_eq!(
+            response.items[1].error.as_ref().unwrap().kind,
+            ErrorKind::MapperParsing
+        );
+        let mapping = &client.get_mapping("listings").unwrap()["listings"];
+        assert_eq!(
+            mapping["properties"]["stars"]["properties"]["food"]["type"],
+            "byte"
+        );
+        assert!(mapping["properties"]["stars"]["properties"]
+            .get("view")
+            .is_none());
+    }
+
+    #[test]
+    fn dotted_names_in_mappings_are_objects() {
+        let client = client();
+        create(
+            &client,
+            "shops",
+            json!({"mappings": {"dynamic": "strict", "properties": {
+                "address.city": {"type": "keyword"},
+                "address.geo.zone": {"type": "byte"},
+                "address": {"properties": {"zip": {"type": "keyword"}}}
+            }}}),
+        )
+        .unwrap();
+        let properties = &client.get_mapping("shops").unwrap()["shops"]["properties"];
+        assert_eq!(
+            properties["address"]["properties"]["geo"]["properties"]["zone"]["type"],
+            "byte"
+        );
+        let written = client
+            .transport()
+            .send(&Request::new(Method::Post, "/_bulk").with_ndjson(vec![
+                json!({"index": {"_index": "shops", "_id": "1"}}),
+                json!({"address": {"city": "Oslo", "zip": "0150", "geo": {"zone": 3}}}),
+            ]));
+        assert_eq!(written.unwrap().body["errors"], false);
+    }
+}
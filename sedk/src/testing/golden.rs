@@ -0,0 +1,453 @@
+//! Golden tests for analysis. A fixture file lists texts and the tokens an
+//! analyzer or normalizer of an index mapping should produce for them:
+//!
+//! ```toml
+//! [[case]]
+//! analyzer = "title"
+//! text = "NY city"
+//! tokens = ["ny", "new", "york", "city"]
+//!
+//! [[case]]
+//! analyzer = "title"
+//! text = "Running"
+//! tokens = [{ token = "run", position = 0, start_offset = 0, end_offset = 7 }]
+//! ```
+//!
+//! Tokens are either a bare term or a table that also pins any of
+//! `position`, `position_length`, `start_offset`, `end_offset` and `type`.
+//! Attributes left out are not compared. Every case runs against local
+//! emulation, and failures are reported as token-level diffs.
+use crate::analyze::{align, describe, Aligned, AnalysisError, Token};
+use crate::dsl::field::IndexMapping;
+use crate::verify::InlineAnalysis;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Golden {
+    #[serde(rename = "case", default)]
+    pub cases: Vec<GoldenCase>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoldenCase {
+    /// The name of an analyzer, or of a normalizer, used in the mapping.
+    pub analyzer: String,
+    pub text: String,
+    pub tokens: Vec<ExpectedToken>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize)]
+#[serde(from = "ExpectedTokenDef")]
+pub struct ExpectedToken {
+    pub term: String,
+    pub position: Option<usize>,
+    pub position_length: Option<usize>,
+    pub start_offset: Option<usize>,
+    pub end_offset: Option<usize>,
+    pub token_type: Option<String>,
+}
+
+// a misspelt key in a detailed token is an error rather than an attribute
+// left unchecked
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum ExpectedTokenDef {
+    Term(String),
+    Detailed {
+        token: String,
+        position: Option<usize>,
+        position_length: Option<usize>,
+        start_offset: Option<usize>,
+        end_offset: Option<usize>,
+        #[serde(rename = "type")]
+        token_type: Option<String>,
+    },
+}
+
+impl From<ExpectedTokenDef> for ExpectedToken {
+    fn from(def: ExpectedTokenDef) -> Self {
+        match def {
+            ExpectedTokenDef::Term(term) => ExpectedToken::term(&term),
+            ExpectedTokenDef::Detailed {
+                token,
+                position,
+                position_length,
+                start_offset,
+                end_offset,
+                token_type,
+            } => ExpectedToken {
+                term: token,
+                position,
+                position_length,
+                start_offset,
+                end_offset,
+                token_type,
+            },
+        }
+    }
+}
+
+impl ExpectedToken {
+    pub fn term(term: &str) -> ExpectedToken {
+        ExpectedToken {
+            term: term.to_string(),
+            position: None,
+            position_length: None,
+            start_offset: None,
+            end_offset: None,
+            token_type: None,
+        }
+    }
+
+    /// Whether `token` agrees with every attribute this expectation pins.
+    pub fn matches(&self, token: &Token) -> bool {
+        fn agrees<T: PartialEq>(expected: &Option<T>, actual: &T) -> bool {
+            expected.as_ref().is_none_or(|e| e == actual)
+        }
+        self.term == token.term
+            && agrees(&self.position, &token.position)
+            && agrees(&self.position_length, &token.position_length)
+            && agrees(&self.start_offset, &token.start_offset)
+            && agrees(&self.end_offset, &token.end_offset)
+            && agrees(&self.token_type, &token.token_type)
+    }
+}
+
+impl fmt::Display for ExpectedToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.term)?;
+        if let Some(position) = self.position {
+            write!(f, " @{}", position)?;
+        }
+        if let Some(length) = self.position_length {
+            write!(f, " +{}", length)?;
+        }
+        match (self.start_offset, self.end_offset) {
+            (None, None) => (),
+            (start, end) => {
+                let bound = |o: Option<usize>| o.map_or("?".to_string(), |o| o.to_string());
+                write!(f, " [{}..{}]", bound(start), bound(end))?
+            }
+        }
+        if let Some(token_type) = &self.token_type {
+            write!(f, " {}", token_type)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoldenError::Io(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+            GoldenError::Parse(path, e) => write!(f, "could not parse {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+impl Golden {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Golden, GoldenError> {
+        let path = path.as_ref();
+        let source =
+            std::fs::read_to_string(path).map_err(|e| GoldenError::Io(path.to_owned(), e))?;
+        Golden::parse(&source).map_err(|e| GoldenError::Parse(path.to_owned(), e))
+    }
+
+    pub fn parse(source: &str) -> Result<Golden, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    /// Runs every case against the analyzers and normalizers of `mapping`.
+    pub fn run(&self, mapping: &IndexMapping) -> GoldenReport {
+        let analyzers = mapping.analyzers();
+        let normalizers = mapping.normalizers();
+        let mut report = GoldenReport {
+            passed: 0,
+            failures: Vec::new(),
+        };
+        for case in &self.cases {
+            let name = case.analyzer.as_str();
+            let analyzed = match (analyzers.get(name), normalizers.get(name)) {
+                (Some(analyzer), _) => analyzer.analyze_locally(&case.text),
+                (None, Some(normalizer)) => normalizer.analyze_locally(&case.text),
+                (None, None) => {
+                    report.fail(case, Outcome::UnknownAnalyzer);
+                    continue;
+                }
+            };
+            match analyzed {
+                Ok(tokens) => {
+                    let changes = GoldenChange::diff(&case.tokens, &tokens);
+                    if changes.iter().all(|c| matches!(c, GoldenChange::Same(_))) {
+                        report.passed += 1;
+                    } else {
+                        report.fail(case, Outcome::Mismatch(changes));
+                    }
+                }
+                Err(e) => report.fail(case, Outcome::Analysis(e)),
+            }
+        }
+        report
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum GoldenChange {
+    Same(Token),
+    /// Expected but not produced.
+    Missing(ExpectedToken),
+    /// Produced but not expected.
+    Unexpected(Token),
+}
+
+impl GoldenChange {
+    fn diff(expected: &[ExpectedToken], actual: &[Token]) -> Vec<GoldenChange> {
+        align(expected, actual, ExpectedToken::matches)
+            .into_iter()
+            .map(|aligned| match aligned {
+                Aligned::Both(_, j) => GoldenChange::Same(actual[j].clone()),
+                Aligned::Expected(i) => GoldenChange::Missing(expected[i].clone()),
+                Aligned::Actual(j) => GoldenChange::Unexpected(actual[j].clone()),
+            })
+            .collect()
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Outcome {
+    /// No analyzer or normalizer in the mapping has the case's name.
+    UnknownAnalyzer,
+    Analysis(AnalysisError),
+    Mismatch(Vec<GoldenChange>),
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GoldenFailure {
+    pub case: GoldenCase,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for GoldenFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} on {:?}: ", self.case.analyzer, self.case.text)?;
+        match &self.outcome {
+            Outcome::UnknownAnalyzer => writeln!(f, "not an analyzer or normalizer of the mapping"),
+            Outcome::Analysis(e) => writeln!(f, "{}", e),
+            Outcome::Mismatch(changes) => {
+                writeln!(f, "tokens differ")?;
+                for change in changes {
+                    match change {
+                        GoldenChange::Same(token) => writeln!(f, "    {}", describe(token))?,
+                        GoldenChange::Missing(expected) => writeln!(f, "  - {}", expected)?,
+                        GoldenChange::Unexpected(token) => writeln!(f, "  + {}", describe(token))?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GoldenReport {
+    pub passed: usize,
+    pub failures: Vec<GoldenFailure>,
+}
+
+impl GoldenReport {
+    fn fail(&mut self, case: &GoldenCase, outcome: Outcome) {
+        self.failures.push(GoldenFailure {
+            case: case.clone(),
+            outcome,
+        });
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for GoldenReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} golden cases failed",
+            self.failures.len(),
+            self.passed + self.failures.len()
+        )?;
+        for failure in &self.failures {
+            writeln!(f)?;
+            failure.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Loads the fixture at `path` and panics with a readable report unless
+/// every case passes against `mapping`.
+pub fn assert_golden<P: AsRef<Path>>(mapping: &IndexMapping, path: P) {
+    let golden = Golden::load(path).unwrap_or_else(|e| panic!("{}", e));
+    let report = golden.run(mapping);
+    assert!(report.is_success(), "{}", report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::{
+        analysis::{Analyzer, Normalizer},
+        field::{Field, FieldType, IndexSettings},
+        token_processing::{TokenFilter, TokenFilterType},
+    };
+    use std::collections::BTreeMap;
+
+    fn mapping() -> IndexMapping {
+        let analyzer = Analyzer {
+            name: "lower".to_string(),
+            token_filters: vec![TokenFilter {
+                name: "lowercase".to_string(),
+                filter_type: TokenFilterType::Lowercase,
+            }],
+            ..Analyzer::default()
+        };
+        IndexMapping {
+            fields: vec![Field {
+                name: "title".to_string(),
+                field_type: FieldType::Keyword {
+                    normalizer: Normalizer::default(),
+                    ignore_above: None,
+                },
+                fields: vec![Field {
+                    name: "text".to_string(),
+                    field_type: FieldType::Text {
+                        analyzer,
+                        search_analyzer: None,
+                    },
+                    fields: vec![],
+                    index: true,
+                    copy_to: vec![],
+                    meta: BTreeMap::new(),
+                }],
+                index: true,
+                copy_to: vec![],
+                meta: BTreeMap::new(),
+            }],
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: None,
+        }
+    }
+
+    #[test]
+    fn bare_and_detailed_tokens() {
+        let golden = Golden::parse(
+            r#"
+            [[case]]
+            analyzer = "lower"
+            text = "Hello World"
+            tokens = ["hello", { token = "world", position = 1, start_offset = 6, type = "word" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            golden.cases[0].tokens,
+            vec![
+                ExpectedToken::term("hello"),
+                ExpectedToken {
+                    position: Some(1),
+                    start_offset: Some(6),
+                    token_type: Some("word".to_string()),
+                    ..ExpectedToken::term("world")
+                }
+            ]
+        );
+        let report = golden.run(&mapping());
+        assert!(report.is_success(), "{}", report);
+    }
+
+    #[test]
+    fn normalizers_by_name() {
+        let golden = Golden::parse(
+            r#"
+            [[case]]
+            analyzer = "sedk_default_normalizer"
+            text = "As Is"
+            tokens = ["As Is"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(golden.run(&mapping()).passed, 1);
+    }
+
+    #[test]
+    fn readable_report() {
+        let golden = Golden::parse(
+            r#"
+            [[case]]
+            analyzer = "lower"
+            text = "the Quick fox"
+            tokens = ["the", { token = "quik", position = 1 }, "fox"]
+
+            [[case]]
+            analyzer = "missing"
+            text = "anything"
+            tokens = []
+            "#,
+        )
+        .unwrap();
+        let report = golden.run(&mapping());
+        assert_eq!(report.passed, 0);
+        assert_eq!(
+            report.to_string(),
+            r#"2 of 2 golden cases failed
+
+lower on "the Quick fox": tokens differ
+    "the" @0 [0..3] word
+  - "quik" @1
+  + "quick" @1 [4..9] word
+    "fox" @2 [10..13] word
+
+missing on "anything": not an analyzer or normalizer of the mapping
+"#
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(Golden::parse(
+            r#"
+            [[case]]
+            analyser = "lower"
+            text = "x"
+            tokens = []
+            "#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn unknown_token_keys_are_rejected() {
+        assert!(Golden::parse(
+            r#"
+            [[case]]
+            analyzer = "lower"
+            text = "x"
+            tokens = [{ token = "x", postion = 0 }]
+            "#
+        )
+        .is_err());
+    }
+}
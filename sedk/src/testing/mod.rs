@@ -0,0 +1,18 @@
+//! Helpers for testing code built on sedk.
+mod cassette;
+mod fake;
+mod golden;
+mod synthetic;
+
+pub use cassette::{
+    cassette, load, CassetteError, CassetteTransport, Exchange, RecordingTransport,
+    ReplayTransport, RECORD_VAR,
+};
+pub use fake::FakeCluster;
+pub use golden::{
+    assert_golden, ExpectedToken, Golden, GoldenCase, GoldenChange, GoldenError, GoldenFailure,
+    GoldenReport, Outcome,
+};
+#[cfg(feature = "proptest")]
+pub use synthetic::documents;
+pub use synthetic::Synthetic;
@@ -0,0 +1,508 @@
+//! Random documents that fit a mapping, for load tests and for fuzzing code
+//! that indexes or queries them:
+//!
+//! ```
+//! use sedk::testing::Synthetic;
+//! use sedk::{Dynamic, Field, FieldType, IndexMapping, IndexSettings};
+//!
+//! let mapping = IndexMapping {
+//!     fields: vec![Field::keyword("colour").build().unwrap(), Field::new("size", FieldType::Byte)],
+//!     plugins: vec![],
+//!     settings: IndexSettings::default(),
+//!     aliases: vec![],
+//!     dynamic: Some(Dynamic::Strict),
+//! };
+//! let synthetic = Synthetic::new(&mapping).vocabulary(vec!["red", "green", "blue"]);
+//! for document in synthetic.documents(7).take(100) {
+//!     assert!(mapping.check_document(&document).is_empty());
+//! }
+//! ```
+//!
+//! Fields are left out or given several values at random, numbers stay in the
+//! range of their type, dates are written in the field's format, and keywords
+//! and text are made of words from the vocabulary. With the `proptest`
+//! feature, `Synthetic::strategy` and `documents` give the same documents as
+//! proptest strategies, which shrink towards documents with fewer and smaller
+//! values.
+use crate::dsl::date_format::DateFormat;
+use crate::dsl::field::{Field, FieldType, IndexMapping, Indexable};
+#[cfg(feature = "proptest")]
+use proptest::strategy::{BoxedStrategy, Strategy};
+use serde_json::{Map, Value};
+
+const VOCABULARY: &[&str] = &[
+    "the", "quick", "brown", "fox", "jumps", "over", "lazy", "dog", "red", "green", "blue",
+    "small", "large", "new", "old", "fast", "slow", "light", "dark", "open",
+];
+
+// dates fall between 1970 and 2100
+const LAST_MILLIS: u64 = 4_102_444_800_000;
+
+/// Generates documents for a mapping.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Synthetic {
+    fields: Vec<Field>,
+    vocabulary: Vec<String>,
+    max_values: usize,
+    max_words: usize,
+}
+
+impl Synthetic {
+    pub fn new(mapping: &IndexMapping) -> Synthetic {
+        Synthetic {
+            fields: mapping.fields.clone(),
+            vocabulary: VOCABULARY.iter().map(|word| word.to_string()).collect(),
+            max_values: 3,
+            max_words: 8,
+        }
+    }
+
+    /// Generates documents for the mapping of `T`.
+    pub fn of<T: Indexable>() -> Synthetic {
+        Synthetic::new(&T::index_mapping())
+    }
+
+    /// The words keywords and text are made of, by default a few common
+    /// english words.
+    ///
+    /// # Panics
+    ///
+    /// When there are no words.
+    pub fn vocabulary<S: Into<String>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.vocabulary = words.into_iter().map(Into::into).collect();
+        assert!(!self.vocabulary.is_empty(), "the vocabulary has no words");
+        self
+    }
+
+    /// The most values a field is given as an array, 3 by default. Fields
+    /// are only given arrays when this is more than 1.
+    pub fn max_values(mut self, max: usize) -> Self {
+        self.max_values = max.max(1);
+        self
+    }
+
+    /// The most words in the value of a text field, 8 by default.
+    pub fn max_words(mut self, max: usize) -> Self {
+        self.max_words = max.max(1);
+        self
+    }
+
+    /// The document of `seed`, which is the same each time.
+    pub fn document(&self, seed: u64) -> Value {
+        self.generate(&mut Draws::Seeded(seed))
+    }
+
+    /// Documents without end, the same for each `seed`.
+    pub fn documents(&self, seed: u64) -> impl Iterator<Item = Value> + '_ {
+        let mut draws = Draws::Seeded(seed);
+        std::iter::repeat_with(move || self.generate(&mut draws))
+    }
+
+    /// A proptest strategy for the documents.
+    #[cfg(feature = "proptest")]
+    pub fn strategy(self) -> BoxedStrategy<Value> {
+        use proptest::prelude::any;
+        // enough for every field to be given a few values, so documents are
+        // only cut short while shrinking
+        let draws = 64 * count(&self.fields).max(1);
+        proptest::collection::vec(any::<u64>(), 0..=draws)
+            .prop_map(move |choices| self.generate(&mut Draws::Replayed(choices.iter())))
+            .boxed()
+    }
+
+    fn generate(&self, draws: &mut Draws) -> Value {
+        Value::Object(self.object(&self.fields, draws))
+    }
+
+    fn object(&self, fields: &[Field], draws: &mut Draws) -> Map<String, Value> {
+        let mut object = Map::new();
+        for field in fields {
+            // a quarter of fields are left out
+            if draws.below(4) != 0 {
+                insert(&mut object, &field.name, self.values(field, draws));
+            }
+        }
+        object
+    }
+
+    // a value of the field, or an array of them where arrays are allowed
+    fn values(&self, field: &Field, draws: &mut Draws) -> Value {
+        let single = match field.field_type {
+            FieldType::DenseVector { .. } | FieldType::Flattened | FieldType::Object { .. } => true,
+            _ => self.max_values == 1 || draws.below(2) == 0,
+        };
+        if single {
+            return self.value(field, draws);
+        }
+        let count = 1 + draws.below(self.max_values as u64);
+        Value::Array((0..count).map(|_| self.value(field, draws)).collect())
+    }
+
+    fn value(&self, field: &Field, draws: &mut Draws) -> Value {
+        match &field.field_type {
+            FieldType::Keyword { .. } => {
+                let word = self.word(draws).to_string();
+                Value::String(truncate(word, limit(field)))
+            }
+            FieldType::Text { .. } => {
+                let count = 1 + draws.below(self.max_words as u64);
+                let words: Vec<&str> = (0..count).map(|_| self.word(draws)).collect();
+                Value::String(truncate(words.join(" "), limit(field)))
+            }
+            FieldType::Binary => {
+                let bytes: Vec<u8> = (0..draws.below(9))
+                    .map(|_| draws.below(256) as u8)
+                    .collect();
+                Value::String(base64(&bytes))
+            }
+            FieldType::Boolean => Value::Bool(draws.below(2) == 1),
+            FieldType::Date { format } => {
+                let millis = draws.below(LAST_MILLIS) as i64;
+                let parsed = match format {
+                    Some(format) => DateFormat::parse(format),
+                    None => Ok(DateFormat::default()),
+                };
+                match parsed {
+                    Ok(parsed) => Value::String(parsed.write(millis, &mut || draws.below(2) == 1)),
+                    // dates can't be written in formats sedk can't read
+                    Err(_) => Value::Null,
+                }
+            }
+            FieldType::Ip => {
+                let octets = (draws.below(1 << 32) as u32).to_be_bytes();
+                Value::String(std::net::Ipv4Addr::from(octets).to_string())
+            }
+            FieldType::DenseVector { dims } => (0..*dims)
+                .map(|_| zigzag(draws.below(2001)) as f64 / 1000.0)
+                .collect(),
+            FieldType::Byte => zigzag(draws.below(1 << 8)).into(),
+            FieldType::Short => zigzag(draws.below(1 << 16)).into(),
+            FieldType::Integer => zigzag(draws.below(1 << 32)).into(),
+            FieldType::Long => zigzag(draws.next()).into(),
+            FieldType::UnsignedLong => draws.next().into(),
+            // sixteenths within the range of a half float
+            FieldType::Double
+            | FieldType::Float
+            | FieldType::HalfFloat
+            | FieldType::ScaledFloat { .. } => (zigzag(draws.below(1 << 20)) as f64 / 16.0).into(),
+            FieldType::Nested { properties } => Value::Object(self.object(properties, draws)),
+            FieldType::Flattened => {
+                let mut object = Map::new();
+                for _ in 0..draws.below(self.max_values as u64 + 1) {
+                    let key = self.word(draws).to_string();
+                    object.insert(key, Value::String(self.word(draws).to_string()));
+                }
+                Value::Object(object)
+            }
+            // the templates of a map either match its values, or, for maps
+            // of structs, the fields within them
+            FieldType::Object { templates } => {
+                let values = format!("{}.*", field.name);
+                let within = format!("{}.", values);
+                let fields: Vec<Field> = templates
+                    .iter()
+                    .filter_map(|template| {
+                        let name = template.name.strip_prefix(&within)?;
+                        Some(Field {
+                            name: name.to_string(),
+                            ..template.clone()
+                        })
+                    })
+                    .collect();
+                let mut object = Map::new();
+                for _ in 0..draws.below(self.max_values as u64 + 1) {
+                    let key = self.word(draws).to_string();
+                    let value = match templates.iter().find(|t| t.name == values) {
+                        Some(template) => self.values(template, draws),
+                        None => Value::Object(self.object(&fields, draws)),
+                    };
+                    object.insert(key, value);
+                }
+                Value::Object(object)
+            }
+        }
+    }
+
+    fn word(&self, draws: &mut Draws) -> &str {
+        &self.vocabulary[draws.below(self.vocabulary.len() as u64) as usize]
+    }
+}
+
+/// A proptest strategy for documents of `T`'s mapping.
+#[cfg(feature = "proptest")]
+pub fn documents<T: Indexable>() -> BoxedStrategy<Value> {
+    Synthetic::of::<T>().strategy()
+}
+
+// where values come from: a seeded stream, or the choices proptest shrinks,
+// which give the smallest values as they reach zero or run out
+enum Draws<'a> {
+    Seeded(u64),
+    Replayed(std::slice::Iter<'a, u64>),
+}
+
+impl Draws<'_> {
+    fn next(&mut self) -> u64 {
+        match self {
+            // splitmix64
+            Draws::Seeded(state) => {
+                *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = *state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^ (z >> 31)
+            }
+            Draws::Replayed(choices) => choices.next().copied().unwrap_or(0),
+        }
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+// 0, -1, 1, -2, 2 and so on, so that small draws give small numbers
+fn zigzag(n: u64) -> i64 {
+    (n >> 1) as i64 ^ -((n & 1) as i64)
+}
+
+#[cfg(feature = "proptest")]
+fn count(fields: &[Field]) -> usize {
+    fields
+        .iter()
+        .map(|field| match &field.field_type {
+            FieldType::Nested { properties } => 1 + count(properties),
+            FieldType::Object { templates } => 1 + count(templates),
+            _ => 1,
+        })
+        .sum()
+}
+
+// the shortest ignore_above of the field and its subfields, so that every
+// keyword indexes the value
+fn limit(field: &Field) -> Option<usize> {
+    std::iter::once(field)
+        .chain(&field.fields)
+        .filter_map(|field| match field.field_type {
+            FieldType::Keyword { ignore_above, .. } => ignore_above,
+            _ => None,
+        })
+        .min()
+        .map(|limit| limit as usize)
+}
+
+// elasticsearch counts characters as java does, in utf-16
+fn truncate(mut value: String, limit: Option<usize>) -> String {
+    if let Some(limit) = limit {
+        let mut length = 0;
+        let end = value
+            .char_indices()
+            .find(|(_, c)| {
+                length += c.len_utf16();
+                length > limit
+            })
+            .map_or(value.len(), |(i, _)| i);
+        value.truncate(end);
+    }
+    value
+}
+
+// values at dotted paths go in objects, as `a.b` in `{"a": {"b": ...}}`
+fn insert(object: &mut Map<String, Value>, path: &str, value: Value) {
+    match path.split_once('.') {
+        None => {
+            object.insert(path.to_string(), value);
+        }
+        Some((parent, rest)) => {
+            let parent = object
+                .entry(parent)
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(parent) = parent {
+                insert(parent, rest, value)
+            }
+        }
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::field::{Dynamic, IndexSettings};
+    use crate::IntoFields;
+    use std::collections::HashMap;
+
+    fn mapping() -> IndexMapping {
+        let mut fields = String::into_fields(Some("title"));
+        fields[0].fields[0] = Field::keyword("keyword").ignore_above(12).build().unwrap();
+        fields.extend(vec![
+            Field::keyword("address.city").build().unwrap(),
+            Field::new("address.zip", FieldType::Short),
+            Field::new("at", FieldType::Date { format: None }),
+            Field::new(
+                "day",
+                FieldType::Date {
+                    format: Some("dd/MM/uuuu[ HH:mm[:ss]][.SSS][.SS]||epoch_second".to_string()),
+                },
+            ),
+            Field::new("from", FieldType::Ip),
+            Field::new("stars", FieldType::Byte),
+            Field::new("weight", FieldType::HalfFloat),
+            Field::new("count", FieldType::UnsignedLong),
+            Field::new("hash", FieldType::Binary),
+            Field::new("open", FieldType::Boolean),
+            Field::new("labels", FieldType::Flattened),
+            Field::new("embedding", FieldType::DenseVector { dims: 4 }),
+            Field::new(
+                "reviews",
+                FieldType::Nested {
+                    properties: vec![
+                        Field::new("stars", FieldType::Long),
+                        Field::text("body").build().unwrap(),
+                    ],
+                },
+            ),
+        ]);
+        fields.extend(HashMap::<String, Vec<i32>>::into_fields(Some("stock")));
+        fields.extend(HashMap::<String, HashMap<String, bool>>::into_fields(Some(
+            "flags",
+        )));
+        // a map of structs, as the derives map them
+        fields.push(Field::new(
+            "owners",
+            FieldType::Object {
+                templates: vec![
+                    Field::keyword("owners.*.name").build().unwrap(),
+                    Field::new("owners.*.share", FieldType::Byte),
+                ],
+            },
+        ));
+        IndexMapping {
+            fields,
+            plugins: vec![],
+            settings: IndexSettings::default(),
+            aliases: vec![],
+            dynamic: Some(Dynamic::Strict),
+        }
+    }
+
+    #[test]
+    fn documents_fit_the_mapping() {
+        use crate::testing::FakeCluster;
+        use crate::transport::api::{BulkAction, BulkOperation};
+        use crate::transport::Client;
+
+        let mapping = mapping();
+        let synthetic = Synthetic::new(&mapping);
+        let mut operations = Vec::new();
+        for (i, document) in synthetic.documents(1).take(500).enumerate() {
+            let violations = mapping.check_document(&document);
+            assert!(violations.is_empty(), "{}: {:?}", document, violations);
+            operations.push(BulkOperation {
+                action: BulkAction::Index,
+                index: "synthetic".to_string(),
+                id: Some(i.to_string()),
+                routing: None,
+                version: None,
+                source: Some(document),
+            });
+        }
+        // and elasticsearch would take them
+        let client = Client::new(FakeCluster::new());
+        client.create_index("synthetic", &mapping).unwrap();
+        let response = client.bulk(operations).unwrap();
+        let failures: Vec<_> = response.failures().collect();
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert_eq!(client.count("synthetic").unwrap(), 500);
+
+        assert_eq!(synthetic.document(3), synthetic.document(3));
+        assert_ne!(synthetic.document(3), synthetic.document(4));
+    }
+
+    #[test]
+    fn keywords_come_from_the_vocabulary() {
+        let mapping = IndexMapping {
+            fields: vec![Field::keyword("tags").build().unwrap()],
+            ..mapping()
+        };
+        let synthetic = Synthetic::new(&mapping)
+            .vocabulary(vec!["a", "b"])
+            .max_values(5);
+        let mut longest = 0;
+        for document in synthetic.documents(2).take(100) {
+            let tags = match &document["tags"] {
+                Value::Array(tags) => tags.clone(),
+                Value::Null => vec![],
+                tag => vec![tag.clone()],
+            };
+            longest = longest.max(tags.len());
+            assert!(
+                tags.iter().all(|tag| tag == "a" || tag == "b"),
+                "{}",
+                document
+            );
+        }
+        assert_eq!(longest, 5);
+    }
+
+    #[test]
+    fn zero_draws_give_empty_documents() {
+        let synthetic = Synthetic::new(&mapping());
+        assert_eq!(
+            synthetic.generate(&mut Draws::Replayed([].iter())),
+            serde_json::json!({})
+        );
+        assert_eq!(base64(b"sedk!"), "c2VkayE=");
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn strategies_shrink_failing_documents() {
+        use proptest::test_runner::{Config, TestError, TestRng, TestRunner};
+        let mapping = IndexMapping {
+            fields: vec![Field::new("stars", FieldType::Byte)],
+            ..mapping()
+        };
+        let config = Config {
+            failure_persistence: None,
+            ..Config::default()
+        };
+        let rng = TestRng::deterministic_rng(config.rng_algorithm);
+        let mut runner = TestRunner::new_with_rng(config, rng);
+        let result = runner.run(&Synthetic::new(&mapping).strategy(), |document| {
+            let stars = match &document["stars"] {
+                Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+            proptest::prop_assert!(stars.iter().all(|s| s.as_i64().unwrap_or(0) < 10));
+            Ok(())
+        });
+        // the failing value is left alone, without other values or fields
+        match result {
+            Err(TestError::Fail(_, document)) => {
+                let stars = document["stars"].as_i64().unwrap();
+                assert_eq!(document.as_object().unwrap().len(), 1);
+                assert!((10..64).contains(&stars), "{}", document);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,862 @@
+//! Typed calls to the cluster's apis. Each endpoint builds its request and
+//! reads the response to it, leaving the sending to a client, so the same
+//! endpoints serve blocking and async transports alike.
+use super::error::{ApiError, ElasticsearchError};
+use super::{encode, Method, Request, Response};
+use crate::analyze::Token;
+use crate::document::Document;
+use crate::dsl::field::IndexMapping;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+pub trait Endpoint {
+    type Output;
+
+    fn request(&self) -> Result<Request, ApiError>;
+
+    /// What a successful response says. Error statuses never get here.
+    fn output(&self, response: Response) -> Result<Self::Output, ApiError>;
+}
+
+/// The output of `response` to `endpoint`, or the error the cluster answered
+/// with.
+pub fn handle<E: Endpoint + ?Sized>(
+    endpoint: &E,
+    response: Response,
+) -> Result<E::Output, ApiError> {
+    if !response.is_success() {
+        return Err(ApiError::Elasticsearch {
+            status: response.status,
+            error: ElasticsearchError::from_response(response.status, &response.body),
+        });
+    }
+    endpoint.output(response)
+}
+
+fn parse<T: DeserializeOwned>(body: Value) -> Result<T, ApiError> {
+    serde_json::from_value(body).map_err(|e| ApiError::UnexpectedResponse(e.to_string()))
+}
+
+fn path(index: &str, api: &str) -> String {
+    format!("/{}{}", encode(index), api)
+}
+
+/// Creates an index with the settings, analysis, mappings and aliases of
+/// `mapping`.
+pub struct CreateIndex<'a> {
+    pub index: &'a str,
+    pub mapping: &'a IndexMapping,
+}
+
+impl Endpoint for CreateIndex<'_> {
+    type Output = ();
+
+    // the mapping serializes its analysis beside its settings, while index
+    // creation takes it within them
+    fn request(&self) -> Result<Request, ApiError> {
+        let mut body = match serde_json::to_value(self.mapping) {
+            Ok(Value::Object(body)) => body,
+            Ok(_) => Map::new(),
+            Err(e) => return Err(ApiError::InvalidRequest(e.to_string())),
+        };
+        if let Some(analysis) = body.remove("analysis") {
+            let settings = body
+                .entry("settings")
+                .or_insert_with(|| Value::Object(Map::new()));
+            settings["analysis"] = analysis;
+        }
+        Ok(Request::new(Method::Put, &path(self.index, "")).with_json(Value::Object(body)))
+    }
+
+    fn output(&self, _: Response) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
+
+/// Adds the fields of `mapping` to an existing index. Fields already mapped
+/// can't change type.
+pub struct PutMapping<'a> {
+    pub index: &'a str,
+    pub mapping: &'a IndexMapping,
+}
+
+impl Endpoint for PutMapping<'_> {
+    type Output = ();
+
+    fn request(&self) -> Result<Request, ApiError> {
+        let body = serde_json::to_value(self.mapping)
+            .map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+        Ok(Request::new(Method::Put, &path(self.index, "/_mapping"))
+            .with_json(body["mappings"].clone()))
+    }
+
+    fn output(&self, _: Response) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
+
+/// The mappings of the indices `index` matches, by the name of each index,
+/// since it may be an alias or a pattern.
+pub struct GetMapping<'a> {
+    pub index: &'a str,
+}
+
+impl Endpoint for GetMapping<'_> {
+    type Output = BTreeMap<String, Value>;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        Ok(Request::new(Method::Get, &path(self.index, "/_mapping")))
+    }
+
+    fn output(&self, response: Response) -> Result<Self::Output, ApiError> {
+        by_index(response, "mappings")
+    }
+}
+
+/// The settings of the indices `index` matches, by the name of each index.
+pub struct GetSettings<'a> {
+    pub index: &'a str,
+}
+
+impl Endpoint for GetSettings<'_> {
+    type Output = BTreeMap<String, Value>;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        Ok(Request::new(Method::Get, &path(self.index, "/_settings")))
+    }
+
+    fn output(&self, response: Response) -> Result<Self::Output, ApiError> {
+        by_index(response, "settings")
+    }
+}
+
+// responses keyed by index, each holding `key`
+fn by_index(response: Response, key: &str) -> Result<BTreeMap<String, Value>, ApiError> {
+    let indices: BTreeMap<String, Map<String, Value>> = parse(response.body)?;
+    Ok(indices
+        .into_iter()
+        .map(|(index, mut value)| (index, value.remove(key).unwrap_or(Value::Null)))
+        .collect())
+}
+
+/// The aliases of the indices `index` matches, or of every index, by the
+/// name of each index.
+pub struct GetAliases<'a> {
+    pub index: Option<&'a str>,
+}
+
+impl Endpoint for GetAliases<'_> {
+    type Output = BTreeMap<String, Vec<String>>;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        let path = match self.index {
+            Some(index) => path(index, "/_alias"),
+            None => "/_alias".to_string(),
+        };
+        Ok(Request::new(Method::Get, &path))
+    }
+
+    fn output(&self, response: Response) -> Result<Self::Output, ApiError> {
+        Ok(by_index(response, "aliases")?
+            .into_iter()
+            .map(|(index, aliases)| {
+                let names = match aliases {
+                    Value::Object(aliases) => aliases.into_iter().map(|(name, _)| name).collect(),
+                    _ => Vec::new(),
+                };
+                (index, names)
+            })
+            .collect())
+    }
+}
+
+/// The indices an alias points at, and whether each is the one writes to
+/// the alias go to. A missing alias is an error with a 404 status.
+pub struct GetAlias<'a> {
+    pub alias: &'a str,
+}
+
+impl Endpoint for GetAlias<'_> {
+    type Output = BTreeMap<String, Option<bool>>;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        Ok(Request::new(
+            Method::Get,
+            &format!("/_alias/{}", encode(self.alias)),
+        ))
+    }
+
+    fn output(&self, response: Response) -> Result<Self::Output, ApiError> {
+        Ok(by_index(response, "aliases")?
+            .into_iter()
+            .map(|(index, aliases)| {
+                let is_write_index = aliases[self.alias]["is_write_index"].as_bool();
+                (index, is_write_index)
+            })
+            .collect())
+    }
+}
+
+/// Whether an index or alias exists. It doesn't when the request fails with
+/// a 404 status.
+pub struct IndexExists<'a> {
+    pub index: &'a str,
+}
+
+impl Endpoint for IndexExists<'_> {
+    type Output = ();
+
+    fn request(&self) -> Result<Request, ApiError> {
+        Ok(Request::new(Method::Head, &path(self.index, "")))
+    }
+
+    fn output(&self, _: Response) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum AliasAction {
+    Add {
+        index: String,
+        alias: String,
+        /// Whether writes to the alias go to this index, which only needs
+        /// saying when the alias points at several.
+        is_write_index: Option<bool>,
+    },
+    Remove {
+        index: String,
+        alias: String,
+    },
+    /// Deletes an index, so that an alias can take its name in the same
+    /// update.
+    RemoveIndex {
+        index: String,
+    },
+}
+
+/// Changes aliases together, so that an alias can be moved from one index
+/// to another without a moment where it points at neither.
+pub struct UpdateAliases {
+    pub actions: Vec<AliasAction>,
+}
+
+impl Endpoint for UpdateAliases {
+    type Output = ();
+
+    fn request(&self) -> Result<Request, ApiError> {
+        let actions: Vec<Value> = self
+            .actions
+            .iter()
+            .map(|action| match action {
+                AliasAction::Add {
+                    index,
+                    alias,
+                    is_write_index,
+                } => {
+                    let mut add = json!({"index": index, "alias": alias});
+                    if let Some(is_write_index) = is_write_index {
+                        add["is_write_index"] = json!(is_write_index);
+                    }
+                    json!({ "add": add })
+                }
+                AliasAction::Remove { index, alias } => {
+                    json!({"remove": {"index": index, "alias": alias}})
+                }
+                AliasAction::RemoveIndex { index } => json!({"remove_index": {"index": index}}),
+            })
+            .collect();
+        Ok(Request::new(Method::Post, "/_aliases").with_json(json!({ "actions": actions })))
+    }
+
+    fn output(&self, _: Response) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkAction {
+    Index,
+    Create,
+    Update,
+    Delete,
+}
+
+/// One write of a bulk request.
+#[derive(PartialEq, Clone, Debug)]
+pub struct BulkOperation {
+    pub action: BulkAction,
+    pub index: String,
+    pub id: Option<String>,
+    pub routing: Option<String>,
+    /// An external version, which the write must be newer than.
+    pub version: Option<u64>,
+    /// The document, or for updates the partial document merged into the
+    /// stored one.
+    pub source: Option<Value>,
+}
+
+impl BulkOperation {
+    /// Indexes `document`, replacing any with the same id.
+    pub fn index<D: Document + Serialize>(
+        index: &str,
+        document: &D,
+    ) -> Result<BulkOperation, ApiError> {
+        BulkOperation::write(BulkAction::Index, index, document)
+    }
+
+    /// Indexes `document`, failing if there's one with the same id.
+    pub fn create<D: Document + Serialize>(
+        index: &str,
+        document: &D,
+    ) -> Result<BulkOperation, ApiError> {
+        BulkOperation::write(BulkAction::Create, index, document)
+    }
+
+    pub fn delete<D: Document>(index: &str, document: &D) -> BulkOperation {
+        BulkOperation {
+            action: BulkAction::Delete,
+            index: index.to_string(),
+            id: Some(document.id().into_owned()),
+            routing: document.routing().map(|routing| routing.into_owned()),
+            version: document.version(),
+            source: None,
+        }
+    }
+
+    fn write<D: Document + Serialize>(
+        action: BulkAction,
+        index: &str,
+        document: &D,
+    ) -> Result<BulkOperation, ApiError> {
+        let source =
+            serde_json::to_value(document).map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+        Ok(BulkOperation {
+            source: Some(source),
+            action,
+            ..BulkOperation::delete(index, document)
+        })
+    }
+
+    // the action line and, for all but deletes, the source line
+    fn lines(&self) -> Vec<Value> {
+        let mut metadata = json!({ "_index": self.index });
+        if let Some(id) = &self.id {
+            metadata["_id"] = json!(id);
+        }
+        if let Some(routing) = &self.routing {
+            metadata["routing"] = json!(routing);
+        }
+        if let Some(version) = self.version {
+            metadata["version"] = json!(version);
+            metadata["version_type"] = json!("external");
+        }
+        let mut lines = vec![json!({ action_name(self.action): metadata })];
+        match (&self.source, self.action) {
+            (Some(source), BulkAction::Update) => lines.push(json!({ "doc": source })),
+            (Some(source), _) => lines.push(source.clone()),
+            (None, _) => (),
+        }
+        lines
+    }
+}
+
+fn action_name(action: BulkAction) -> &'static str {
+    match action {
+        BulkAction::Index => "index",
+        BulkAction::Create => "create",
+        BulkAction::Update => "update",
+        BulkAction::Delete => "delete",
+    }
+}
+
+/// Writes many documents in one request. Each write succeeds or fails on its
+/// own, so the response has to be checked for failures.
+pub struct Bulk {
+    pub operations: Vec<BulkOperation>,
+}
+
+impl Endpoint for Bulk {
+    type Output = BulkResponse;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        let lines = self
+            .operations
+            .iter()
+            .flat_map(BulkOperation::lines)
+            .collect();
+        Ok(Request::new(Method::Post, "/_bulk").with_ndjson(lines))
+    }
+
+    fn output(&self, response: Response) -> Result<BulkResponse, ApiError> {
+        let raw: RawBulkResponse = parse(response.body)?;
+        let items = raw
+            .items
+            .into_iter()
+            .filter_map(|item| item.into_iter().next())
+            .map(|(action, item)| BulkItem {
+                action,
+                index: item.index,
+                id: item.id,
+                status: item.status,
+                error: item.error.as_ref().and_then(ElasticsearchError::from_value),
+            })
+            .collect();
+        Ok(BulkResponse {
+            took: raw.took,
+            errors: raw.errors,
+            items,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct RawBulkResponse {
+    took: u64,
+    errors: bool,
+    items: Vec<BTreeMap<BulkAction, RawBulkItem>>,
+}
+
+#[derive(Deserialize)]
+struct RawBulkItem {
+    #[serde(rename = "_index")]
+    index: String,
+    #[serde(rename = "_id", default)]
+    id: Option<String>,
+    status: u16,
+    error: Option<Value>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct BulkResponse {
+    /// Milliseconds the cluster spent on the request.
+    pub took: u64,
+    /// Whether any write failed.
+    pub errors: bool,
+    /// The result of each write, in the order they were sent.
+    pub items: Vec<BulkItem>,
+}
+
+impl BulkResponse {
+    pub fn failures(&self) -> impl Iterator<Item = &BulkItem> {
+        self.items.iter().filter(|item| item.error.is_some())
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct BulkItem {
+    pub action: BulkAction,
+    pub index: String,
+    pub id: Option<String>,
+    pub status: u16,
+    pub error: Option<ElasticsearchError>,
+}
+
+/// Searches the indices `index` matches with a search request body.
+pub struct Search<'a> {
+    pub index: &'a str,
+    pub body: Value,
+}
+
+impl Endpoint for Search<'_> {
+    type Output = SearchResponse;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        Ok(Request::new(Method::Post, &path(self.index, "/_search")).with_json(self.body.clone()))
+    }
+
+    fn output(&self, response: Response) -> Result<SearchResponse, ApiError> {
+        let raw: RawSearchResponse = parse(response.body)?;
+        Ok(SearchResponse {
+            took: raw.took,
+            timed_out: raw.timed_out,
+            total: raw.hits.total.map(|total| total.value),
+            hits: raw.hits.hits,
+            aggregations: raw.aggregations,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct RawSearchResponse {
+    took: u64,
+    timed_out: bool,
+    hits: RawHits,
+    aggregations: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct RawHits {
+    total: Option<RawTotal>,
+    hits: Vec<Hit>,
+}
+
+#[derive(Deserialize)]
+struct RawTotal {
+    value: u64,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SearchResponse {
+    /// Milliseconds the cluster spent on the search.
+    pub took: u64,
+    pub timed_out: bool,
+    /// How many documents matched, which is a lower bound when elasticsearch
+    /// stops counting.
+    pub total: Option<u64>,
+    pub hits: Vec<Hit>,
+    pub aggregations: Option<Value>,
+}
+
+impl SearchResponse {
+    /// The source of each hit read as a `T`.
+    pub fn sources<T: DeserializeOwned>(&self) -> Result<Vec<T>, ApiError> {
+        self.hits
+            .iter()
+            .map(|hit| parse(hit.source.clone()))
+            .collect()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize)]
+pub struct Hit {
+    #[serde(rename = "_index")]
+    pub index: String,
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(rename = "_score")]
+    pub score: Option<f64>,
+    #[serde(rename = "_source", default)]
+    pub source: Value,
+}
+
+/// A painless script, run on each document by a reindex.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Script {
+    pub source: String,
+    /// Values the script reads as `params`.
+    pub params: Map<String, Value>,
+}
+
+impl Script {
+    pub fn new(source: &str) -> Script {
+        Script {
+            source: source.to_string(),
+            params: Map::new(),
+        }
+    }
+
+    pub fn param(mut self, name: &str, value: Value) -> Script {
+        self.params.insert(name.to_string(), value);
+        self
+    }
+
+    fn to_value(&self) -> Value {
+        json!({ "source": self.source, "lang": "painless", "params": self.params })
+    }
+}
+
+/// Starts copying the documents of `source` into `dest`, as a task whose id
+/// is the output. `dest` is refreshed once the copy is done, so that its
+/// documents can be counted.
+pub struct Reindex<'a> {
+    pub source: &'a str,
+    pub dest: &'a str,
+    pub script: Option<&'a Script>,
+}
+
+impl Endpoint for Reindex<'_> {
+    type Output = String;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        let mut body = json!({
+            "source": { "index": self.source },
+            "dest": { "index": self.dest },
+        });
+        if let Some(script) = self.script {
+            body["script"] = script.to_value();
+        }
+        Ok(Request::new(Method::Post, "/_reindex")
+            .with_query("wait_for_completion", "false")
+            .with_query("refresh", "true")
+            .with_json(body))
+    }
+
+    fn output(&self, response: Response) -> Result<String, ApiError> {
+        match response.body["task"].as_str() {
+            Some(task) => Ok(task.to_string()),
+            None => Err(ApiError::UnexpectedResponse(format!(
+                "no task in {}",
+                response.body
+            ))),
+        }
+    }
+}
+
+/// The state of a task, such as a reindex started without waiting for it.
+pub struct GetTask<'a> {
+    pub task: &'a str,
+}
+
+impl Endpoint for GetTask<'_> {
+    type Output = Task;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        Ok(Request::new(
+            Method::Get,
+            &format!("/_tasks/{}", encode(self.task)),
+        ))
+    }
+
+    fn output(&self, response: Response) -> Result<Task, ApiError> {
+        let body = response.body;
+        Ok(Task {
+            completed: body["completed"].as_bool().unwrap_or_default(),
+            error: ElasticsearchError::from_value(&body["error"]),
+            response: body.get("response").cloned(),
+        })
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Task {
+    pub completed: bool,
+    /// Why the task failed as a whole.
+    pub error: Option<ElasticsearchError>,
+    /// What a completed task's api would have answered, such as a reindex's
+    /// counts and the documents that failed.
+    pub response: Option<Value>,
+}
+
+/// How many documents the indices `index` matches hold.
+pub struct Count<'a> {
+    pub index: &'a str,
+}
+
+impl Endpoint for Count<'_> {
+    type Output = u64;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        Ok(Request::new(Method::Get, &path(self.index, "/_count")))
+    }
+
+    fn output(&self, response: Response) -> Result<u64, ApiError> {
+        #[derive(Deserialize)]
+        struct Counted {
+            count: u64,
+        }
+        parse::<Counted>(response.body).map(|body| body.count)
+    }
+}
+
+/// The tokens an analyzer produces for a text. `body` names the analyzer, or
+/// a field whose analyzer is used, or describes one inline.
+pub struct Analyze<'a> {
+    /// The index whose analyzers and fields are named, when any are.
+    pub index: Option<&'a str>,
+    pub body: Value,
+}
+
+impl Endpoint for Analyze<'_> {
+    type Output = Vec<Token>;
+
+    fn request(&self) -> Result<Request, ApiError> {
+        let path = match self.index {
+            Some(index) => path(index, "/_analyze"),
+            None => "/_analyze".to_string(),
+        };
+        Ok(Request::new(Method::Post, &path).with_json(self.body.clone()))
+    }
+
+    fn output(&self, response: Response) -> Result<Vec<Token>, ApiError> {
+        #[derive(Deserialize)]
+        struct Tokens {
+            tokens: Vec<Token>,
+        }
+        parse::<Tokens>(response.body).map(|body| body.tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::field::{Field, IndexSettings};
+    use crate::transport::Body;
+    use crate::Analyzer;
+    use std::borrow::Cow;
+
+    #[test]
+    fn indices_are_created_with_analysis_in_their_settings() {
+        let mapping = IndexMapping {
+            fields: vec![Field::text("title").analyzer(Analyzer::default()).build().unwrap()],
+            plugins: vec![],
+            settings: IndexSettings {
+                number_of_shards: Some(2),
+                number_of_replicas: None,
+            },
+            aliases: vec!["live".to_string()],
+            dynamic: None,
+        };
+        let request = CreateIndex {
+            index: "products",
+            mapping: &mapping,
+        }
+        .request()
+        .unwrap();
+        assert_eq!(request.path, "/products");
+        let body = match request.body {
+            Some(Body::Json(body)) => body,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(body["settings"]["number_of_shards"], 2);
+        assert!(body["settings"]["analysis"]["analyzer"].is_object());
+        assert!(body.get("analysis").is_none());
+        assert_eq!(body["aliases"], json!({"live": {}}));
+        assert_eq!(body["mappings"]["properties"]["title"]["type"], "text");
+    }
+
+    struct Product {
+        sku: &'static str,
+        shop: Option<&'static str>,
+        version: Option<u64>,
+    }
+
+    impl Document for Product {
+        fn id(&self) -> Cow<'_, str> {
+            Cow::Borrowed(self.sku)
+        }
+
+        fn routing(&self) -> Option<Cow<'_, str>> {
+            self.shop.map(Cow::Borrowed)
+        }
+
+        fn version(&self) -> Option<u64> {
+            self.version
+        }
+    }
+
+    impl Serialize for Product {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            json!({ "sku": self.sku }).serialize(serializer)
+        }
+    }
+
+    #[test]
+    fn bulk_requests_carry_identity() {
+        let versioned = Product {
+            sku: "a1",
+            shop: Some("oslo"),
+            version: Some(3),
+        };
+        let plain = Product {
+            sku: "b2",
+            shop: None,
+            version: None,
+        };
+        let bulk = Bulk {
+            operations: vec![
+                BulkOperation::index("products", &versioned).unwrap(),
+                BulkOperation::delete("products", &plain),
+            ],
+        };
+        let request = bulk.request().unwrap();
+        assert_eq!(
+            request.body,
+            Some(Body::NdJson(vec![
+                json!({"index": {
+                    "_index": "products",
+                    "_id": "a1",
+                    "routing": "oslo",
+                    "version": 3,
+                    "version_type": "external"
+                }}),
+                json!({"sku": "a1"}),
+                json!({"delete": {"_index": "products", "_id": "b2"}}),
+            ]))
+        );
+    }
+
+    #[test]
+    fn bulk_responses_report_each_failure() {
+        let body = json!({
+            "took": 30,
+            "errors": true,
+            "items": [
+                {"index": {"_index": "products", "_id": "a1", "status": 201}},
+                {"create": {
+                    "_index": "products",
+                    "_id": "b2",
+                    "status": 409,
+                    "error": {
+                        "type": "version_conflict_engine_exception",
+                        "reason": "[b2]: version conflict, document already exists"
+                    }
+                }}
+            ]
+        });
+        let response = Bulk { operations: vec![] }
+            .output(Response { status: 200, body })
+            .unwrap();
+        let failures: Vec<&BulkItem> = response.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].action, BulkAction::Create);
+        assert_eq!(
+            failures[0].error.as_ref().unwrap().kind,
+            crate::transport::ErrorKind::VersionConflictEngine
+        );
+    }
+
+    #[test]
+    fn alias_updates_name_write_indices() {
+        let request = UpdateAliases {
+            actions: vec![
+                AliasAction::RemoveIndex {
+                    index: "products".to_string(),
+                },
+                AliasAction::Add {
+                    index: "products-2".to_string(),
+                    alias: "products".to_string(),
+                    is_write_index: Some(true),
+                },
+            ],
+        }
+        .request()
+        .unwrap();
+        assert_eq!(
+            request.body,
+            Some(Body::Json(json!({"actions": [
+                {"remove_index": {"index": "products"}},
+                {"add": {"index": "products-2", "alias": "products", "is_write_index": true}}
+            ]})))
+        );
+    }
+
+    #[test]
+    fn error_statuses_become_typed_errors() {
+        let body = json!({
+            "error": {"type": "index_not_found_exception", "reason": "no such index [nope]", "index": "nope"},
+            "status": 404
+        });
+        let error = handle(
+            &GetMapping { index: "nope" },
+            Response { status: 404, body },
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.kind(),
+            Some(&crate::transport::ErrorKind::IndexNotFound)
+        );
+    }
+
+    #[test]
+    fn mappings_by_index() {
+        let body = json!({"products-2": {"mappings": {"dynamic": "strict"}}});
+        let mappings = GetMapping { index: "products" }
+            .output(Response { status: 200, body })
+            .unwrap();
+        assert_eq!(mappings["products-2"], json!({"dynamic": "strict"}));
+    }
+}
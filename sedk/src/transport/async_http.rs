@@ -0,0 +1,65 @@
+use super::{body_from_text, AsyncTransport, Request, Response, TransportError};
+use std::future::Future;
+use std::time::Duration;
+
+/// An async transport over http, for a cluster reachable at `base_url`.
+/// https needs one of reqwest's tls features.
+pub struct AsyncHttpTransport {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl AsyncHttpTransport {
+    pub fn new(base_url: &str) -> AsyncHttpTransport {
+        AsyncHttpTransport::with_timeout(base_url, Duration::from_secs(30))
+    }
+
+    pub fn with_timeout(base_url: &str, timeout: Duration) -> AsyncHttpTransport {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("a client without tls or proxies can be built");
+        AsyncHttpTransport::with_client(base_url, client)
+    }
+
+    /// Sends requests with `client`, for its connection pool, proxies or
+    /// certificates.
+    pub fn with_client(base_url: &str, client: reqwest::Client) -> AsyncHttpTransport {
+        AsyncHttpTransport {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client,
+        }
+    }
+}
+
+impl AsyncTransport for AsyncHttpTransport {
+    fn send(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response, TransportError>> + Send {
+        let url = format!("{}{}", self.base_url, request.path_and_query());
+        let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes())
+            .expect("methods are valid");
+        let mut builder = self.client.request(method, url);
+        if let Some(body) = &request.body {
+            builder = builder
+                .header("content-type", body.content_type())
+                .body(body.to_bytes());
+        }
+        async move {
+            let response = builder
+                .send()
+                .await
+                .map_err(|e| TransportError::Connection(e.to_string()))?;
+            let status = response.status().as_u16();
+            let text = response
+                .text()
+                .await
+                .map_err(|e| TransportError::InvalidResponse(e.to_string()))?;
+            Ok(Response {
+                status,
+                body: body_from_text(text),
+            })
+        }
+    }
+}
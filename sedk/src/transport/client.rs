@@ -0,0 +1,202 @@
+use super::api::{
+    handle, AliasAction, Analyze, Bulk, BulkOperation, BulkResponse, Count, CreateIndex, Endpoint,
+    GetAlias, GetAliases, GetMapping, GetSettings, GetTask, IndexExists, PutMapping, Reindex,
+    Script, Search, SearchResponse, Task, UpdateAliases,
+};
+use super::error::ApiError;
+use super::{AsyncTransport, Transport};
+use crate::analyze::Token;
+use crate::dsl::field::IndexMapping;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Calls the cluster's apis over a blocking transport.
+pub struct Client<T> {
+    transport: T,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T) -> Client<T> {
+        Client { transport }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn send<E: Endpoint + ?Sized>(&self, endpoint: &E) -> Result<E::Output, ApiError> {
+        let response = self.transport.send(&endpoint.request()?)?;
+        handle(endpoint, response)
+    }
+
+    pub fn create_index(&self, index: &str, mapping: &IndexMapping) -> Result<(), ApiError> {
+        self.send(&CreateIndex { index, mapping })
+    }
+
+    pub fn put_mapping(&self, index: &str, mapping: &IndexMapping) -> Result<(), ApiError> {
+        self.send(&PutMapping { index, mapping })
+    }
+
+    pub fn get_mapping(&self, index: &str) -> Result<BTreeMap<String, Value>, ApiError> {
+        self.send(&GetMapping { index })
+    }
+
+    pub fn get_settings(&self, index: &str) -> Result<BTreeMap<String, Value>, ApiError> {
+        self.send(&GetSettings { index })
+    }
+
+    pub fn get_aliases(
+        &self,
+        index: Option<&str>,
+    ) -> Result<BTreeMap<String, Vec<String>>, ApiError> {
+        self.send(&GetAliases { index })
+    }
+
+    /// The indices `alias` points at, with whether each is its write index,
+    /// and none when there's no such alias.
+    pub fn get_alias(&self, alias: &str) -> Result<BTreeMap<String, Option<bool>>, ApiError> {
+        missing_as(self.send(&GetAlias { alias }), BTreeMap::new())
+    }
+
+    pub fn index_exists(&self, index: &str) -> Result<bool, ApiError> {
+        missing_as(self.send(&IndexExists { index }).map(|()| true), false)
+    }
+
+    pub fn update_aliases(&self, actions: Vec<AliasAction>) -> Result<(), ApiError> {
+        self.send(&UpdateAliases { actions })
+    }
+
+    /// Starts a reindex, giving the id of its task.
+    pub fn reindex(
+        &self,
+        source: &str,
+        dest: &str,
+        script: Option<&Script>,
+    ) -> Result<String, ApiError> {
+        self.send(&Reindex {
+            source,
+            dest,
+            script,
+        })
+    }
+
+    pub fn get_task(&self, task: &str) -> Result<Task, ApiError> {
+        self.send(&GetTask { task })
+    }
+
+    pub fn bulk(&self, operations: Vec<BulkOperation>) -> Result<BulkResponse, ApiError> {
+        self.send(&Bulk { operations })
+    }
+
+    pub fn search(&self, index: &str, body: Value) -> Result<SearchResponse, ApiError> {
+        self.send(&Search { index, body })
+    }
+
+    pub fn count(&self, index: &str) -> Result<u64, ApiError> {
+        self.send(&Count { index })
+    }
+
+    pub fn analyze(&self, index: Option<&str>, body: Value) -> Result<Vec<Token>, ApiError> {
+        self.send(&Analyze { index, body })
+    }
+}
+
+/// Calls the cluster's apis over an async transport.
+pub struct AsyncClient<T> {
+    transport: T,
+}
+
+impl<T: AsyncTransport> AsyncClient<T> {
+    pub fn new(transport: T) -> AsyncClient<T> {
+        AsyncClient { transport }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub async fn send<E: Endpoint + ?Sized>(&self, endpoint: &E) -> Result<E::Output, ApiError> {
+        let response = self.transport.send(&endpoint.request()?).await?;
+        handle(endpoint, response)
+    }
+
+    pub async fn create_index(&self, index: &str, mapping: &IndexMapping) -> Result<(), ApiError> {
+        self.send(&CreateIndex { index, mapping }).await
+    }
+
+    pub async fn put_mapping(&self, index: &str, mapping: &IndexMapping) -> Result<(), ApiError> {
+        self.send(&PutMapping { index, mapping }).await
+    }
+
+    pub async fn get_mapping(&self, index: &str) -> Result<BTreeMap<String, Value>, ApiError> {
+        self.send(&GetMapping { index }).await
+    }
+
+    pub async fn get_settings(&self, index: &str) -> Result<BTreeMap<String, Value>, ApiError> {
+        self.send(&GetSettings { index }).await
+    }
+
+    pub async fn get_aliases(
+        &self,
+        index: Option<&str>,
+    ) -> Result<BTreeMap<String, Vec<String>>, ApiError> {
+        self.send(&GetAliases { index }).await
+    }
+
+    pub async fn get_alias(&self, alias: &str) -> Result<BTreeMap<String, Option<bool>>, ApiError> {
+        missing_as(self.send(&GetAlias { alias }).await, BTreeMap::new())
+    }
+
+    pub async fn index_exists(&self, index: &str) -> Result<bool, ApiError> {
+        missing_as(
+            self.send(&IndexExists { index }).await.map(|()| true),
+            false,
+        )
+    }
+
+    pub async fn update_aliases(&self, actions: Vec<AliasAction>) -> Result<(), ApiError> {
+        self.send(&UpdateAliases { actions }).await
+    }
+
+    pub async fn reindex(
+        &self,
+        source: &str,
+        dest: &str,
+        script: Option<&Script>,
+    ) -> Result<String, ApiError> {
+        self.send(&Reindex {
+            source,
+            dest,
+            script,
+        })
+        .await
+    }
+
+    pub async fn get_task(&self, task: &str) -> Result<Task, ApiError> {
+        self.send(&GetTask { task }).await
+    }
+
+    pub async fn bulk(&self, operations: Vec<BulkOperation>) -> Result<BulkResponse, ApiError> {
+        self.send(&Bulk { operations }).await
+    }
+
+    pub async fn search(&self, index: &str, body: Value) -> Result<SearchResponse, ApiError> {
+        self.send(&Search { index, body }).await
+    }
+
+    pub async fn count(&self, index: &str) -> Result<u64, ApiError> {
+        self.send(&Count { index }).await
+    }
+
+    pub async fn analyze(&self, index: Option<&str>, body: Value) -> Result<Vec<Token>, ApiError> {
+        self.send(&Analyze { index, body }).await
+    }
+}
+
+// what a request for something that isn't there gives
+fn missing_as<O>(result: Result<O, ApiError>, missing: O) -> Result<O, ApiError> {
+    match result {
+        Err(ApiError::Elasticsearch { status: 404, .. }) => Ok(missing),
+        result => result,
+    }
+}
@@ -0,0 +1,238 @@
+//! The errors elasticsearch answers with, which name the exception and its
+//! reason along with the errors that caused it:
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "root_cause": [{"type": "mapper_parsing_exception", "reason": "failed to parse field [stars]"}],
+//!     "type": "mapper_parsing_exception",
+//!     "reason": "failed to parse field [stars]",
+//!     "caused_by": {"type": "number_format_exception", "reason": "For input string: \"many\""}
+//!   },
+//!   "status": 400
+//! }
+//! ```
+use super::TransportError;
+use serde_json::Value;
+use std::fmt;
+
+/// The exceptions sedk's callers are most likely to handle, by their
+/// elasticsearch `type`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ErrorKind {
+    /// A document or mapping that doesn't fit the mapping of the index.
+    MapperParsing,
+    /// A field that isn't mapped, in an index whose mapping is strict.
+    StrictDynamicMapping,
+    ResourceAlreadyExists,
+    IndexNotFound,
+    IllegalArgument,
+    /// A write whose version is older than the stored document's.
+    VersionConflictEngine,
+    DocumentMissing,
+    Parsing,
+    SearchPhaseExecution,
+    Other(String),
+}
+
+impl ErrorKind {
+    pub fn from_type(name: &str) -> ErrorKind {
+        match name {
+            "mapper_parsing_exception" => ErrorKind::MapperParsing,
+            "strict_dynamic_mapping_exception" => ErrorKind::StrictDynamicMapping,
+            "resource_already_exists_exception" => ErrorKind::ResourceAlreadyExists,
+            "index_not_found_exception" => ErrorKind::IndexNotFound,
+            "illegal_argument_exception" => ErrorKind::IllegalArgument,
+            "version_conflict_engine_exception" => ErrorKind::VersionConflictEngine,
+            "document_missing_exception" => ErrorKind::DocumentMissing,
+            "parsing_exception" => ErrorKind::Parsing,
+            "search_phase_execution_exception" => ErrorKind::SearchPhaseExecution,
+            other => ErrorKind::Other(other.to_string()),
+        }
+    }
+
+    /// The `type` elasticsearch gives the exception.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorKind::MapperParsing => "mapper_parsing_exception",
+            ErrorKind::StrictDynamicMapping => "strict_dynamic_mapping_exception",
+            ErrorKind::ResourceAlreadyExists => "resource_already_exists_exception",
+            ErrorKind::IndexNotFound => "index_not_found_exception",
+            ErrorKind::IllegalArgument => "illegal_argument_exception",
+            ErrorKind::VersionConflictEngine => "version_conflict_engine_exception",
+            ErrorKind::DocumentMissing => "document_missing_exception",
+            ErrorKind::Parsing => "parsing_exception",
+            ErrorKind::SearchPhaseExecution => "search_phase_execution_exception",
+            ErrorKind::Other(name) => name,
+        }
+    }
+}
+
+/// An exception reported by the cluster.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ElasticsearchError {
+    pub kind: ErrorKind,
+    pub reason: String,
+    /// The index the exception concerns, when it names one.
+    pub index: Option<String>,
+    /// The exceptions at the bottom of the chain of causes, which usually say
+    /// most about what went wrong.
+    pub root_causes: Vec<ElasticsearchError>,
+    pub caused_by: Option<Box<ElasticsearchError>>,
+}
+
+impl ElasticsearchError {
+    /// The error in the body of an error response. Bodies that aren't in the
+    /// usual shape, such as the empty bodies of `HEAD` requests, give an
+    /// error of an unnamed kind whose reason is the body.
+    pub fn from_response(status: u16, body: &Value) -> ElasticsearchError {
+        if let Some(error) = ElasticsearchError::from_value(&body["error"]) {
+            return error;
+        }
+        let reason = match body {
+            Value::Null => format!("status {}", status),
+            Value::String(s) => s.clone(),
+            other => match &other["error"] {
+                Value::String(s) => s.clone(),
+                _ => other.to_string(),
+            },
+        };
+        ElasticsearchError {
+            kind: ErrorKind::Other(String::new()),
+            reason,
+            index: None,
+            root_causes: Vec::new(),
+            caused_by: None,
+        }
+    }
+
+    /// An error object, as found under `error` in responses and in the items
+    /// of bulk responses.
+    pub fn from_value(error: &Value) -> Option<ElasticsearchError> {
+        let kind = ErrorKind::from_type(error["type"].as_str()?);
+        let root_causes = match &error["root_cause"] {
+            Value::Array(causes) => causes
+                .iter()
+                .filter_map(ElasticsearchError::from_value)
+                .collect(),
+            _ => Vec::new(),
+        };
+        Some(ElasticsearchError {
+            kind,
+            reason: error["reason"].as_str().unwrap_or_default().to_string(),
+            index: error["index"].as_str().map(str::to_string),
+            root_causes,
+            caused_by: ElasticsearchError::from_value(&error["caused_by"]).map(Box::new),
+        })
+    }
+}
+
+impl fmt::Display for ElasticsearchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.kind.as_str().is_empty() {
+            write!(f, "{}: ", self.kind.as_str())?;
+        }
+        f.write_str(&self.reason)?;
+        if let Some(cause) = &self.caused_by {
+            write!(f, ", caused by {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ElasticsearchError {}
+
+/// A failed call to one of the cluster's apis.
+#[derive(PartialEq, Clone, Debug)]
+pub enum ApiError {
+    Transport(TransportError),
+    /// The cluster answered with an error status.
+    Elasticsearch {
+        status: u16,
+        error: ElasticsearchError,
+    },
+    /// A request that couldn't be built, such as one for a mapping using
+    /// undeclared plugins.
+    InvalidRequest(String),
+    UnexpectedResponse(String),
+}
+
+impl ApiError {
+    /// The kind of exception the cluster answered with, if it answered with
+    /// one.
+    pub fn kind(&self) -> Option<&ErrorKind> {
+        match self {
+            ApiError::Elasticsearch { error, .. } => Some(&error.kind),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::Transport(e) => e.fmt(f),
+            ApiError::Elasticsearch { status, error } => {
+                write!(f, "elasticsearch answered {}: {}", status, error)
+            }
+            ApiError::InvalidRequest(message) => write!(f, "invalid request: {}", message),
+            ApiError::UnexpectedResponse(message) => {
+                write!(f, "unexpected response: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<TransportError> for ApiError {
+    fn from(e: TransportError) -> Self {
+        ApiError::Transport(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn error_bodies() {
+        let body = json!({
+            "error": {
+                "root_cause": [{
+                    "type": "resource_already_exists_exception",
+                    "reason": "index [products/abc] already exists",
+                    "index": "products"
+                }],
+                "type": "resource_already_exists_exception",
+                "reason": "index [products/abc] already exists",
+                "index": "products",
+                "caused_by": {"type": "io_exception", "reason": "disk full"}
+            },
+            "status": 400
+        });
+        let error = ElasticsearchError::from_response(400, &body);
+        assert_eq!(error.kind, ErrorKind::ResourceAlreadyExists);
+        assert_eq!(error.index.as_deref(), Some("products"));
+        assert_eq!(error.root_causes.len(), 1);
+        assert_eq!(
+            error.caused_by.as_ref().unwrap().kind,
+            ErrorKind::Other("io_exception".to_string())
+        );
+        assert_eq!(
+            error.to_string(),
+            "resource_already_exists_exception: index [products/abc] already exists, \
+             caused by io_exception: disk full"
+        );
+    }
+
+    #[test]
+    fn unusual_bodies() {
+        let error = ElasticsearchError::from_response(404, &Value::Null);
+        assert_eq!(error.kind, ErrorKind::Other(String::new()));
+        assert_eq!(error.to_string(), "status 404");
+        let error = ElasticsearchError::from_response(500, &json!({"error": "gone"}));
+        assert_eq!(error.to_string(), "gone");
+    }
+}
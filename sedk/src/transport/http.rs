@@ -0,0 +1,58 @@
+use super::{body_from_text, Request, Response, Transport, TransportError};
+use std::time::Duration;
+
+/// A blocking transport over http(s), for a cluster reachable at `base_url`.
+pub struct HttpTransport {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpTransport {
+    pub fn new(base_url: &str) -> HttpTransport {
+        HttpTransport::with_timeout(base_url, Duration::from_secs(30))
+    }
+
+    pub fn with_timeout(base_url: &str, timeout: Duration) -> HttpTransport {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(timeout))
+            .build()
+            .into();
+        HttpTransport {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            agent,
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        let url = format!("{}{}", self.base_url, request.path_and_query());
+        let mut builder = ureq::http::Request::builder()
+            .method(request.method.as_str())
+            .uri(url);
+        let body = match &request.body {
+            Some(body) => {
+                builder = builder.header("content-type", body.content_type());
+                body.to_bytes()
+            }
+            None => Vec::new(),
+        };
+        let http_request = builder
+            .body(body)
+            .map_err(|e| TransportError::Connection(e.to_string()))?;
+        let mut response = self
+            .agent
+            .run(http_request)
+            .map_err(|e| TransportError::Connection(e.to_string()))?;
+        let status = response.status().as_u16();
+        let text = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| TransportError::InvalidResponse(e.to_string()))?;
+        Ok(Response {
+            status,
+            body: body_from_text(text),
+        })
+    }
+}
@@ -0,0 +1,229 @@
+//! The boundary between sedk and an elasticsearch cluster. Anything that can
+//! carry a request and bring back a status and a json body can be plugged in,
+//! blocking or async, and `Client` and `AsyncClient` call the cluster's apis
+//! over it.
+pub mod api;
+#[cfg(feature = "async")]
+mod async_http;
+mod client;
+mod error;
+#[cfg(feature = "blocking")]
+mod http;
+
+#[cfg(feature = "async")]
+pub use self::async_http::AsyncHttpTransport;
+pub use self::client::{AsyncClient, Client};
+pub use self::error::{ApiError, ElasticsearchError, ErrorKind};
+#[cfg(feature = "blocking")]
+pub use self::http::HttpTransport;
+
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Body {
+    Json(Value),
+    /// Newline delimited json, as taken by the bulk and multi search apis.
+    NdJson(Vec<Value>),
+}
+
+impl Body {
+    pub fn content_type(&self) -> &str {
+        match self {
+            Body::Json(_) => "application/json",
+            Body::NdJson(_) => "application/x-ndjson",
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Body::Json(value) => value.to_string().into_bytes(),
+            Body::NdJson(lines) => lines
+                .iter()
+                .map(|line| line.to_string() + "\n")
+                .collect::<String>()
+                .into_bytes(),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Request {
+    pub method: Method,
+    /// The path from the root of the cluster, starting with a slash.
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Option<Body>,
+}
+
+impl Request {
+    pub fn new(method: Method, path: &str) -> Request {
+        Request {
+            method,
+            path: path.to_owned(),
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn with_query(mut self, key: &str, value: &str) -> Request {
+        self.query.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn with_json(mut self, body: Value) -> Request {
+        self.body = Some(Body::Json(body));
+        self
+    }
+
+    pub fn with_ndjson(mut self, lines: Vec<Value>) -> Request {
+        self.body = Some(Body::NdJson(lines));
+        self
+    }
+
+    /// The path and query string, percent encoded.
+    pub fn path_and_query(&self) -> String {
+        if self.query.is_empty() {
+            return self.path.clone();
+        }
+        let query = self
+            .query
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect::<Vec<String>>()
+            .join("&");
+        format!("{}?{}", self.path, query)
+    }
+}
+
+fn encode(component: &str) -> String {
+    let mut encoded = String::with_capacity(component.len());
+    for byte in component.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' | b'*' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// A response from the cluster. Empty bodies, like the ones `HEAD` requests
+/// get, are `Value::Null` and bodies that aren't json are kept as a string.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+fn body_from_text(text: String) -> Value {
+    if text.trim().is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_str(&text).unwrap_or(Value::String(text))
+    }
+}
+
+/// A failure to exchange a request with the cluster at all. Error statuses
+/// are still `Ok` responses.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TransportError {
+    Connection(String),
+    InvalidResponse(String),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransportError::Connection(message) => write!(f, "connection failed: {}", message),
+            TransportError::InvalidResponse(message) => {
+                write!(f, "invalid response: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+pub trait Transport {
+    fn send(&self, request: &Request) -> Result<Response, TransportError>;
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        (**self).send(request)
+    }
+}
+
+/// A transport for async code, which doesn't block a thread while the
+/// cluster answers.
+pub trait AsyncTransport {
+    fn send(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response, TransportError>> + Send;
+}
+
+impl<T: AsyncTransport + Sync + ?Sized> AsyncTransport for &T {
+    fn send(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response, TransportError>> + Send {
+        (**self).send(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn path_and_query() {
+        let request = Request::new(Method::Get, "/my-index/_search")
+            .with_query("q", "title:new york")
+            .with_query("size", "10");
+        assert_eq!(
+            request.path_and_query(),
+            "/my-index/_search?q=title%3Anew%20york&size=10"
+        );
+    }
+
+    #[test]
+    fn ndjson_body() {
+        let body = Body::NdJson(vec![json!({"index": {"_id": "1"}}), json!({"a": 1})]);
+        assert_eq!(
+            String::from_utf8(body.to_bytes()).unwrap(),
+            "{\"index\":{\"_id\":\"1\"}}\n{\"a\":1}\n"
+        );
+    }
+}
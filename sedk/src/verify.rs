@@ -0,0 +1,287 @@
+//! Checks analysis against a real cluster through the `_analyze` api. The
+//! analysis components are sent inline, so no index has to exist, and the
+//! tokens that come back are diffed against local emulation or against the
+//! tokens a caller expects. Run against each new elasticsearch version, this
+//! catches upgrades that change how text is analyzed.
+use crate::analyze::{AnalysisError, Token, TokenDiff};
+use crate::dsl::analysis::{Analyzer, Normalizer};
+use crate::transport::{Method, Request, Transport, TransportError};
+use serde::Deserialize;
+use serde_json::{json, to_value, Map, Value};
+use std::fmt;
+
+/// An analysis component that can be described inline to `_analyze` and
+/// emulated locally.
+pub trait InlineAnalysis {
+    /// The `_analyze` request body for this component, without the text.
+    fn inline_definition(&self) -> Value;
+
+    fn analyze_locally(&self, text: &str) -> Result<Vec<Token>, AnalysisError>;
+}
+
+impl InlineAnalysis for Analyzer {
+    fn inline_definition(&self) -> Value {
+        json!({
+            "char_filter": self
+                .character_filters
+                .iter()
+                .map(|cf| to_value(&cf.character_filter_type).unwrap())
+                .collect::<Vec<Value>>(),
+            "tokenizer": to_value(&self.tokenizer.tokenizer_type).unwrap(),
+            "filter": self
+                .token_filters
+                .iter()
+                .map(|tf| to_value(&tf.filter_type).unwrap())
+                .collect::<Vec<Value>>(),
+        })
+    }
+
+    fn analyze_locally(&self, text: &str) -> Result<Vec<Token>, AnalysisError> {
+        self.analyze(text)
+    }
+}
+
+impl InlineAnalysis for Normalizer {
+    // _analyze treats a request with filters but no tokenizer as a normalizer.
+    // Without any filters it would fall back to the standard analyzer, so the
+    // keyword tokenizer is named instead.
+    fn inline_definition(&self) -> Value {
+        if self.character_filters.is_empty() && self.token_filters.is_empty() {
+            return json!({ "tokenizer": "keyword" });
+        }
+        let mut definition = Map::new();
+        if !self.character_filters.is_empty() {
+            definition.insert(
+                "char_filter".to_string(),
+                self.character_filters
+                    .iter()
+                    .map(|cf| to_value(&cf.character_filter_type).unwrap())
+                    .collect(),
+            );
+        }
+        if !self.token_filters.is_empty() {
+            definition.insert(
+                "filter".to_string(),
+                self.token_filters
+                    .iter()
+                    .map(|tf| to_value(&tf.filter_type).unwrap())
+                    .collect(),
+            );
+        }
+        Value::Object(definition)
+    }
+
+    fn analyze_locally(&self, text: &str) -> Result<Vec<Token>, AnalysisError> {
+        Ok(vec![Token {
+            term: self.normalize(text)?,
+            start_offset: 0,
+            end_offset: text.encode_utf16().count(),
+            token_type: "word".to_string(),
+            position: 0,
+            position_length: 1,
+        }])
+    }
+}
+
+#[derive(Debug)]
+pub enum VerificationError {
+    Transport(TransportError),
+    Analysis(AnalysisError),
+    /// The cluster answered `_analyze` with an error.
+    Rejected {
+        status: u16,
+        body: Value,
+    },
+    UnexpectedResponse(String),
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerificationError::Transport(e) => e.fmt(f),
+            VerificationError::Analysis(e) => e.fmt(f),
+            VerificationError::Rejected { status, body } => {
+                write!(f, "_analyze failed with status {}: {}", status, body)
+            }
+            VerificationError::UnexpectedResponse(message) => {
+                write!(f, "unexpected _analyze response: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+impl From<TransportError> for VerificationError {
+    fn from(e: TransportError) -> Self {
+        VerificationError::Transport(e)
+    }
+}
+
+impl From<AnalysisError> for VerificationError {
+    fn from(e: AnalysisError) -> Self {
+        VerificationError::Analysis(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct AnalyzeResponse {
+    tokens: Vec<Token>,
+}
+
+pub struct Verifier<T> {
+    transport: T,
+}
+
+impl<T: Transport> Verifier<T> {
+    pub fn new(transport: T) -> Verifier<T> {
+        Verifier { transport }
+    }
+
+    /// The tokens the cluster produces for `text`.
+    pub fn remote_tokens<A: InlineAnalysis>(
+        &self,
+        component: &A,
+        text: &str,
+    ) -> Result<Vec<Token>, VerificationError> {
+        let mut body = match component.inline_definition() {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        body.insert("text".to_string(), Value::String(text.to_string()));
+        let request = Request::new(Method::Post, "/_analyze").with_json(Value::Object(body));
+        let response = self.transport.send(&request)?;
+        if !response.is_success() {
+            return Err(VerificationError::Rejected {
+                status: response.status,
+                body: response.body,
+            });
+        }
+        serde_json::from_value::<AnalyzeResponse>(response.body)
+            .map(|r| r.tokens)
+            .map_err(|e| VerificationError::UnexpectedResponse(e.to_string()))
+    }
+
+    /// Diffs local emulation, as expected, against the cluster.
+    pub fn against_local<A: InlineAnalysis>(
+        &self,
+        component: &A,
+        text: &str,
+    ) -> Result<TokenDiff, VerificationError> {
+        let local = component.analyze_locally(text)?;
+        self.against_expected(component, text, &local)
+    }
+
+    /// Diffs `expected` against the tokens the cluster produces.
+    pub fn against_expected<A: InlineAnalysis>(
+        &self,
+        component: &A,
+        text: &str,
+        expected: &[Token],
+    ) -> Result<TokenDiff, VerificationError> {
+        let remote = self.remote_tokens(component, text)?;
+        Ok(TokenDiff::new(&canonical(expected), &canonical(&remote)))
+    }
+}
+
+// the order of tokens sharing a position carries no meaning, and differs
+// between lucene and local emulation for synonym graphs
+fn canonical(tokens: &[Token]) -> Vec<Token> {
+    let mut tokens = tokens.to_vec();
+    tokens.sort_by(|a, b| (a.position, &a.term).cmp(&(b.position, &b.term)));
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::{
+        character_filters::{CharacterFilter, CharacterFilterType},
+        token_processing::{TokenFilter, TokenFilterType},
+        tokenizers::Tokenizer,
+    };
+    use crate::transport::{Body, Response};
+    use std::cell::RefCell;
+
+    struct Canned {
+        response: Response,
+        requests: RefCell<Vec<Request>>,
+    }
+
+    impl Transport for Canned {
+        fn send(&self, request: &Request) -> Result<Response, TransportError> {
+            self.requests.borrow_mut().push(request.clone());
+            Ok(self.response.clone())
+        }
+    }
+
+    fn canned(status: u16, body: Value) -> Canned {
+        Canned {
+            response: Response { status, body },
+            requests: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn analyzer_definition_is_inline() {
+        let analyzer = Analyzer {
+            name: "my_analyzer".to_string(),
+            character_filters: vec![],
+            tokenizer: Tokenizer::default(),
+            token_filters: vec![TokenFilter {
+                name: "lower".to_string(),
+                filter_type: TokenFilterType::Lowercase,
+            }],
+        };
+        let transport = canned(
+            200,
+            json!({"tokens": [
+                {"token": "hello", "start_offset": 0, "end_offset": 5, "type": "word", "position": 0}
+            ]}),
+        );
+        let diff = Verifier::new(&transport)
+            .against_local(&analyzer, "Hello")
+            .unwrap();
+        assert!(diff.is_match(), "{}", diff);
+        assert_eq!(
+            transport.requests.borrow()[0].body,
+            Some(Body::Json(json!({
+                "char_filter": [],
+                "tokenizer": {"type": "char_group", "tokenize_on_chars": ["whitespace"]},
+                "filter": [{"type": "lowercase"}],
+                "text": "Hello"
+            })))
+        );
+    }
+
+    #[test]
+    fn normalizer_definition_is_inline() {
+        let mut normalizer = Normalizer::default();
+        assert_eq!(
+            normalizer.inline_definition(),
+            json!({"tokenizer": "keyword"})
+        );
+        normalizer.character_filters.push(CharacterFilter {
+            name: "dashes".to_string(),
+            character_filter_type: CharacterFilterType::Mapping {
+                mappings: vec![("-".to_string(), "_".to_string())],
+            },
+        });
+        assert_eq!(
+            normalizer.inline_definition(),
+            json!({"char_filter": [{"type": "mapping", "mappings": ["- => _"]}]})
+        );
+    }
+
+    #[test]
+    fn rejected() {
+        let transport = canned(
+            400,
+            json!({"error": {"type": "illegal_argument_exception"}}),
+        );
+        assert!(matches!(
+            Verifier::new(&transport).remote_tokens(&Normalizer::default(), "x"),
+            Err(VerificationError::Rejected { status: 400, .. })
+        ));
+    }
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "async")]
+mod support;
+
+use sedk::transport::{AsyncClient, AsyncHttpTransport, ErrorKind};
+use serde_json::json;
+use support::ReplayServer;
+
+#[tokio::test]
+async fn async_clients_share_the_typed_apis() {
+    let server = ReplayServer::from_fixture("cluster.json");
+    let client = AsyncClient::new(AsyncHttpTransport::new(&server.url()));
+    let response = client
+        .search("listings", json!({"query": {"match": {"title": "desk"}}}))
+        .await
+        .unwrap();
+    assert_eq!(response.hits[0].id, "a-1");
+    let error = client
+        .search("listings", json!({"query": {"match": {"price": "cheap"}}}))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), Some(&ErrorKind::SearchPhaseExecution));
+}
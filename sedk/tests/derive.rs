@@ -0,0 +1,21 @@
+#![cfg(feature = "derive")]
+// the documents are only ever mapped, never read
+#![allow(dead_code)]
+
+// sedk alone is enough to derive mappings
+use sedk::{FieldType, Indexable, IntoFields};
+
+#[derive(IntoFields, Indexable)]
+pub struct Product {
+    price: f64,
+    #[sedk(type = "keyword")]
+    sku: String,
+}
+
+#[test]
+fn derives_are_reexported() {
+    let fields = Product::index_mapping().fields;
+    assert_eq!(fields, Product::into_fields(None));
+    assert_eq!(fields[0].field_type, FieldType::Double);
+    assert!(matches!(fields[1].field_type, FieldType::Keyword { .. }));
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "testing")]
+use sedk::dsl::{
+    character_filters::{CharacterFilter, CharacterFilterType},
+    token_processing::{StemmerLanguage, Synonym, TokenFilter, TokenFilterType},
+    tokenizers::Tokenizer,
+};
+use sedk::testing::{assert_golden, Golden};
+use sedk::{Analyzer, Field, FieldType, IndexMapping, IndexSettings, Normalizer};
+use std::collections::BTreeMap;
+
+fn mapping() -> IndexMapping {
+    let filter = |name: &str, filter_type| TokenFilter {
+        name: name.to_string(),
+        filter_type,
+    };
+    let title = Analyzer {
+        name: "title".to_string(),
+        character_filters: vec![],
+        tokenizer: Tokenizer::default(),
+        token_filters: vec![
+            filter("lowercase", TokenFilterType::Lowercase),
+            filter(
+                "places",
+                TokenFilterType::SynonymGraph {
+                    expand: true,
+                    lenient: false,
+                    synonyms: vec![Synonym::equivalent(&["ny", "new york"])],
+                },
+            ),
+            filter(
+                "porter2",
+                TokenFilterType::Stemmer {
+                    language: StemmerLanguage::Porter2,
+                },
+            ),
+        ],
+    };
+    let sku = Normalizer {
+        name: "sku".to_string(),
+        character_filters: vec![CharacterFilter {
+            name: "dashes".to_string(),
+            character_filter_type: CharacterFilterType::Mapping {
+                mappings: vec![("-".to_string(), "_".to_string())],
+            },
+        }],
+        tokenizer: Tokenizer::default(),
+        token_filters: vec![],
+    };
+    IndexMapping {
+        fields: vec![
+            Field {
+                name: "title".to_string(),
+                field_type: FieldType::Text {
+                    analyzer: title,
+                    search_analyzer: None,
+                },
+                fields: vec![],
+                index: true,
+                copy_to: vec![],
+                meta: BTreeMap::new(),
+            },
+            Field {
+                name: "sku".to_string(),
+                field_type: FieldType::Keyword {
+                    normalizer: sku,
+                    ignore_above: None,
+                },
+                fields: vec![],
+                index: true,
+                copy_to: vec![],
+                meta: BTreeMap::new(),
+            },
+        ],
+        plugins: vec![],
+        settings: IndexSettings::default(),
+        aliases: vec![],
+        dynamic: None,
+    }
+}
+
+#[test]
+fn golden_cases_pass() {
+    assert_golden(&mapping(), "tests/golden/analyzers.toml");
+}
+
+#[test]
+fn failures_show_a_token_diff() {
+    let golden = Golden::parse(
+        r#"
+        [[case]]
+        analyzer = "title"
+        text = "Running dogs"
+        tokens = ["running", "dog"]
+        "#,
+    )
+    .unwrap();
+    let report = golden.run(&mapping());
+    assert!(!report.is_success());
+    assert!(report
+        .to_string()
+        .contains("  - \"running\"\n  + \"run\" @0 [0..7] word\n"));
+}
@@ -0,0 +1,128 @@
+use sedk::analyze::stem;
+use sedk::dsl::token_processing::StemmerLanguage;
+use std::fs::read_to_string;
+
+fn read(directory: &str, file: &str) -> String {
+    read_to_string(format!(
+        "{}/tests/snowball/{}/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        directory,
+        file
+    ))
+    .unwrap()
+}
+
+// stems each word and compares it with the expected stem, failing on every
+// difference `departs` doesn't account for
+fn check<F>(language: StemmerLanguage, name: &str, cases: &[(String, String)], departs: F)
+where
+    F: Fn(&str, &str, &str) -> bool,
+{
+    let mut mismatches = Vec::new();
+    for (word, expected) in cases {
+        let stemmed = stem(&language, word).unwrap();
+        if stemmed != *expected && !departs(word, &stemmed, expected) {
+            mismatches.push((word, stemmed, expected));
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "{} of {} {} words stemmed differently, starting with {:?}",
+        mismatches.len(),
+        cases.len(),
+        name,
+        &mismatches[..mismatches.len().min(10)]
+    );
+}
+
+fn cases(vocabulary: &str, output: &str) -> Vec<(String, String)> {
+    let vocabulary = read(vocabulary, "voc.txt");
+    let output = read(output, "output.txt");
+    assert_eq!(vocabulary.lines().count(), output.lines().count());
+    vocabulary
+        .lines()
+        .zip(output.lines())
+        .map(|(word, expected)| (word.to_string(), expected.to_string()))
+        .collect()
+}
+
+fn conformance(language: StemmerLanguage, directory: &str) {
+    check(
+        language,
+        directory,
+        &cases(directory, directory),
+        |_, _, _| false,
+    );
+}
+
+#[test]
+fn porter2() {
+    conformance(StemmerLanguage::Porter2, "english");
+}
+
+// lucene's PorterStemmer follows Martin Porter's own implementation, which
+// leaves words of one or two letters alone and has bli -> ble and
+// logi -> log in step 2 where snowball's porter has abli -> able.
+#[test]
+fn english() {
+    check(
+        StemmerLanguage::English,
+        "porter",
+        &cases("english", "porter"),
+        |word, stemmed, expected| {
+            (word.chars().count() <= 2 && stemmed == word)
+                || ((expected.ends_with("bli") || expected.ends_with("logi"))
+                    && expected.starts_with(stemmed))
+        },
+    );
+}
+
+// german2 only differs from german in reading ae, oe and ue as umlauts, so
+// it stems german's vocabulary the same way, with the umlauts written out
+// or not. Words already holding one of the pairs are left out, and so are
+// spelled out ü after a vowel, whose u the prelude protects.
+#[test]
+fn german2() {
+    let vowel = |c: char| "aeiouyäöü".contains(c);
+    let mut plain = Vec::new();
+    let mut spelled_out = Vec::new();
+    for (word, expected) in cases("german", "german") {
+        if ["ae", "oe", "ue"].iter().any(|pair| word.contains(pair)) {
+            continue;
+        }
+        let chars: Vec<char> = word.chars().collect();
+        let protected = chars
+            .windows(2)
+            .any(|pair| vowel(pair[0]) && pair[1] == 'ü');
+        if word.contains(|c| "äöü".contains(c)) && !protected {
+            let written_out = word
+                .replace('ä', "ae")
+                .replace('ö', "oe")
+                .replace('ü', "ue");
+            spelled_out.push((written_out, expected.clone()));
+        }
+        plain.push((word, expected));
+    }
+    check(StemmerLanguage::German2, "german", &plain, |_, _, _| false);
+    check(
+        StemmerLanguage::German2,
+        "spelled out german",
+        &spelled_out,
+        |_, _, _| false,
+    );
+}
+
+#[test]
+fn french() {
+    conformance(StemmerLanguage::French, "french");
+}
+
+#[test]
+fn german() {
+    conformance(StemmerLanguage::German, "german");
+}
+
+#[test]
+fn spanish() {
+    conformance(StemmerLanguage::Spanish, "spanish");
+}
@@ -0,0 +1,104 @@
+//! A stand-in for an elasticsearch node which answers every request with a
+//! recorded response, matched on method, path and json body. An ndjson body
+//! is matched as the array of its lines.
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+#[derive(Deserialize, Clone)]
+pub struct Recording {
+    pub method: String,
+    pub path: String,
+    #[serde(default)]
+    pub body: Value,
+    pub status: u16,
+    pub response: Value,
+}
+
+pub struct ReplayServer {
+    address: String,
+}
+
+impl ReplayServer {
+    pub fn from_fixture(name: &str) -> ReplayServer {
+        let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let recordings = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        ReplayServer::start(recordings)
+    }
+
+    pub fn start(recordings: Vec<Recording>) -> ReplayServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let recordings = recordings.clone();
+                thread::spawn(move || serve(stream, &recordings));
+            }
+        });
+        ReplayServer { address }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+}
+
+fn serve(stream: TcpStream, recordings: &[Recording]) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    let body = body_from_bytes(&body);
+
+    let (status, response) = recordings
+        .iter()
+        .find(|r| r.method == method && r.path == path && r.body == body)
+        .map(|r| (r.status, r.response.clone()))
+        .unwrap_or_else(|| {
+            (
+                404,
+                json!({"error": format!("no recording for {} {} {}", method, path, body)}),
+            )
+        });
+    let response = response.to_string();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} Recorded\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+    )
+    .unwrap();
+}
+
+fn body_from_bytes(body: &[u8]) -> Value {
+    if let Ok(value) = serde_json::from_slice(body) {
+        return value;
+    }
+    let lines: Result<Vec<Value>, _> = String::from_utf8_lossy(body)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(serde_json::from_str)
+        .collect();
+    lines.map(Value::Array).unwrap_or(Value::Null)
+}
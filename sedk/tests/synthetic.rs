@@ -0,0 +1,54 @@
+#![cfg(all(feature = "derive", feature = "proptest"))]
+// the documents are only ever mapped, never read
+#![allow(dead_code)]
+
+use proptest::prelude::*;
+use sedk::testing::{documents, FakeCluster};
+use sedk::transport::api::{BulkAction, BulkOperation};
+use sedk::transport::Client;
+use sedk::{Indexable, IntoFields};
+use std::collections::HashMap;
+
+#[derive(IntoFields)]
+pub struct Review {
+    stars: u8,
+    body: String,
+}
+
+#[derive(IntoFields, Indexable)]
+#[sedk(index = "listings", dynamic = "strict")]
+pub struct Listing {
+    #[sedk(type = "keyword", ignore_above = 8)]
+    sku: String,
+    title: String,
+    #[sedk(type = "date", format = "strict_date||epoch_millis")]
+    listed: String,
+    #[sedk(type = "half_float")]
+    weight: f32,
+    #[sedk(nested)]
+    reviews: Vec<Review>,
+    stock: HashMap<String, u16>,
+    #[sedk(type = "dense_vector", dims = 3)]
+    embedding: Vec<f32>,
+}
+
+proptest! {
+    #[test]
+    fn synthetic_documents_fit_their_mapping(document in documents::<Listing>()) {
+        let mapping = Listing::index_mapping();
+        let violations = mapping.check_document(&document);
+        prop_assert!(violations.is_empty(), "{:?}", violations);
+
+        let client = Client::new(FakeCluster::new());
+        client.create_index("listings", &mapping).unwrap();
+        let response = client.bulk(vec![BulkOperation {
+            action: BulkAction::Index,
+            index: "listings".to_string(),
+            id: Some("1".to_string()),
+            routing: None,
+            version: None,
+            source: Some(document),
+        }]).unwrap();
+        prop_assert!(!response.errors, "{:?}", response.items);
+    }
+}
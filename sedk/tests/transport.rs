@@ -0,0 +1,130 @@
+#![cfg(feature = "blocking")]
+mod support;
+
+use pretty_assertions::assert_eq;
+use sedk::dsl::field::{Field, IndexMapping, IndexSettings};
+use sedk::transport::api::{BulkAction, BulkOperation};
+use sedk::transport::{ApiError, Client, ErrorKind, HttpTransport};
+use sedk::Document;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::borrow::Cow;
+use support::ReplayServer;
+
+// cluster.json holds responses in the shape elasticsearch returns them,
+// errors included, replayed by a local stand-in for the cluster.
+fn client(server: &ReplayServer) -> Client<HttpTransport> {
+    Client::new(HttpTransport::new(&server.url()))
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Listing {
+    sku: String,
+    title: String,
+}
+
+impl Listing {
+    fn new(sku: &str, title: &str) -> Listing {
+        Listing {
+            sku: sku.to_string(),
+            title: title.to_string(),
+        }
+    }
+}
+
+impl Document for Listing {
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.sku)
+    }
+}
+
+fn mapping() -> IndexMapping {
+    IndexMapping {
+        fields: vec![
+            Field::keyword("sku").build().unwrap(),
+            Field::text("title").build().unwrap(),
+        ],
+        plugins: vec![],
+        settings: IndexSettings {
+            number_of_shards: Some(1),
+            number_of_replicas: None,
+        },
+        aliases: vec![],
+        dynamic: None,
+    }
+}
+
+#[test]
+fn errors_are_typed() {
+    let server = ReplayServer::from_fixture("cluster.json");
+    let error = client(&server)
+        .create_index("listings", &mapping())
+        .unwrap_err();
+    assert_eq!(error.kind(), Some(&ErrorKind::ResourceAlreadyExists));
+    match error {
+        ApiError::Elasticsearch { status, error } => {
+            assert_eq!(status, 400);
+            assert_eq!(error.index.as_deref(), Some("listings"));
+        }
+        other => panic!("expected an elasticsearch error, got {}", other),
+    }
+}
+
+#[test]
+fn root_causes_explain_failed_searches() {
+    let server = ReplayServer::from_fixture("cluster.json");
+    let error = client(&server)
+        .search("listings", json!({"query": {"match": {"price": "cheap"}}}))
+        .unwrap_err();
+    assert_eq!(error.kind(), Some(&ErrorKind::SearchPhaseExecution));
+    assert_eq!(
+        error.to_string(),
+        "elasticsearch answered 400: search_phase_execution_exception: all shards failed, \
+         caused by number_format_exception: For input string: \"cheap\""
+    );
+    if let ApiError::Elasticsearch { error, .. } = error {
+        assert_eq!(
+            error.root_causes[0].kind,
+            ErrorKind::Other("query_shard_exception".to_string())
+        );
+    }
+}
+
+#[test]
+fn bulk_writes_report_each_failure() {
+    let server = ReplayServer::from_fixture("cluster.json");
+    let response = client(&server)
+        .bulk(vec![
+            BulkOperation::index("listings", &Listing::new("a-1", "Oak desk")).unwrap(),
+            BulkOperation::create("listings", &Listing::new("b-2", "Walnut shelf")).unwrap(),
+            BulkOperation::delete("listings", &Listing::new("c-3", "Pine stool")),
+        ])
+        .unwrap();
+    assert!(response.errors);
+    let actions: Vec<_> = response.items.iter().map(|item| item.action).collect();
+    assert_eq!(
+        actions,
+        vec![BulkAction::Index, BulkAction::Create, BulkAction::Delete]
+    );
+    let failures: Vec<_> = response.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].id.as_deref(), Some("b-2"));
+    assert_eq!(
+        failures[0].error.as_ref().unwrap().kind,
+        ErrorKind::VersionConflictEngine
+    );
+}
+
+#[test]
+fn search_hits_read_as_documents() {
+    let server = ReplayServer::from_fixture("cluster.json");
+    let response = client(&server)
+        .search("listings", json!({"query": {"match": {"title": "desk"}}}))
+        .unwrap();
+    assert_eq!(response.total, Some(1));
+    assert_eq!(response.hits[0].id, "a-1");
+    assert_eq!(
+        response.sources::<Listing>().unwrap(),
+        vec![Listing::new("a-1", "Oak desk")]
+    );
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "blocking")]
+mod support;
+
+use sedk::analyze::{Token, TokenChange};
+use sedk::dsl::{
+    character_filters::{CharacterFilter, CharacterFilterType},
+    token_processing::{StemmerLanguage, Synonym, TokenFilter, TokenFilterType},
+    tokenizers::Tokenizer,
+};
+use sedk::transport::HttpTransport;
+use sedk::verify::Verifier;
+use sedk::{Analyzer, Normalizer};
+use support::ReplayServer;
+
+// analyze.json holds _analyze responses in the shape elasticsearch returns
+// them, replayed by a local stand-in for the cluster.
+fn verifier(server: &ReplayServer) -> Verifier<HttpTransport> {
+    Verifier::new(HttpTransport::new(&server.url()))
+}
+
+fn analyzer(token_filters: Vec<TokenFilterType>) -> Analyzer {
+    Analyzer {
+        name: "my_analyzer".to_string(),
+        character_filters: vec![],
+        tokenizer: Tokenizer::default(),
+        token_filters: token_filters
+            .into_iter()
+            .enumerate()
+            .map(|(i, filter_type)| TokenFilter {
+                name: format!("filter_{}", i),
+                filter_type,
+            })
+            .collect(),
+    }
+}
+
+fn stemming(language: StemmerLanguage) -> Analyzer {
+    analyzer(vec![
+        TokenFilterType::Lowercase,
+        TokenFilterType::Stemmer { language },
+    ])
+}
+
+#[test]
+fn synonym_graph_matches_cluster() {
+    let server = ReplayServer::from_fixture("analyze.json");
+    let analyzer = analyzer(vec![
+        TokenFilterType::Lowercase,
+        TokenFilterType::SynonymGraph {
+            expand: true,
+            lenient: false,
+            synonyms: vec![Synonym::equivalent(&["ny", "new york"])],
+        },
+    ]);
+    let diff = verifier(&server)
+        .against_local(&analyzer, "NY city")
+        .unwrap();
+    assert!(diff.is_match(), "\n{}", diff);
+}
+
+#[test]
+fn stemmer_matches_cluster() {
+    let server = ReplayServer::from_fixture("analyze.json");
+    let diff = verifier(&server)
+        .against_local(&stemming(StemmerLanguage::Porter2), "Running dogs quickly")
+        .unwrap();
+    assert!(diff.is_match(), "\n{}", diff);
+}
+
+#[test]
+fn normalizer_matches_cluster() {
+    let server = ReplayServer::from_fixture("analyze.json");
+    let normalizer = Normalizer {
+        name: "my_normalizer".to_string(),
+        character_filters: vec![CharacterFilter {
+            name: "dashes".to_string(),
+            character_filter_type: CharacterFilterType::Mapping {
+                mappings: vec![("-".to_string(), "_".to_string())],
+            },
+        }],
+        tokenizer: Tokenizer::default(),
+        token_filters: vec![],
+    };
+    let diff = verifier(&server)
+        .against_local(&normalizer, "foo-bar")
+        .unwrap();
+    assert!(diff.is_match(), "\n{}", diff);
+}
+
+#[test]
+fn approximations_are_reported() {
+    let server = ReplayServer::from_fixture("analyze.json");
+    let diff = verifier(&server)
+        .against_local(&stemming(StemmerLanguage::LightEnglish), "buses")
+        .unwrap();
+    let terms: Vec<(char, &str)> = diff
+        .changes
+        .iter()
+        .map(|change| match change {
+            TokenChange::Same(t) => (' ', t.term.as_str()),
+            TokenChange::Missing(t) => ('-', t.term.as_str()),
+            TokenChange::Unexpected(t) => ('+', t.term.as_str()),
+        })
+        .collect();
+    assert_eq!(terms, vec![('-', "buse"), ('+', "bus")]);
+}
+
+#[test]
+fn expected_tokens() {
+    let server = ReplayServer::from_fixture("analyze.json");
+    let expected = vec![Token {
+        term: "bus".to_string(),
+        start_offset: 0,
+        end_offset: 5,
+        token_type: "word".to_string(),
+        position: 0,
+        position_length: 1,
+    }];
+    let diff = verifier(&server)
+        .against_expected(&stemming(StemmerLanguage::LightEnglish), "buses", &expected)
+        .unwrap();
+    assert!(diff.is_match(), "\n{}", diff);
+}
+
+#[test]
+fn unrecorded_requests_are_rejected() {
+    let server = ReplayServer::from_fixture("analyze.json");
+    let result = verifier(&server).remote_tokens(&stemming(StemmerLanguage::Porter2), "unseen");
+    assert!(matches!(
+        result,
+        Err(sedk::verify::VerificationError::Rejected { status: 404, .. })
+    ));
+}
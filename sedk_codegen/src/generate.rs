@@ -0,0 +1,1009 @@
+//! Writing an index out as Rust. Each object becomes a struct deriving
+//! `IntoFields`, with the attributes that make its mapping the one read, and
+//! each analyzer and normalizer becomes a function building it.
+use crate::{CodegenError, Index};
+use sedk::dsl::character_filters::CharacterFilterType;
+use sedk::dsl::token_processing::{Stopwords, Synonym, TokenFilterType};
+use sedk::dsl::tokenizers::TokenizerType;
+use sedk::{Analyzer, Field, FieldType, IntoFields, Normalizer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+type Result<T> = std::result::Result<T, CodegenError>;
+
+// elasticsearch's limit on the length of meta values, within which a
+// description can be a doc comment
+const MAX_DOC_LENGTH: usize = 50;
+
+// the widest line that's kept on one line
+const MAX_WIDTH: usize = 100;
+
+/// The Rust source for `index`, with the document type named `name`.
+pub fn generate(index: &Index, name: &str) -> Result<String> {
+    let mut generator = Generator::default();
+    let root = generator.struct_name(name);
+    generator.analysis(&index.mapping.fields)?;
+    let mut container = Vec::new();
+    if let Some(index) = &index.name {
+        container.push(format!("index = {:?}", index));
+    }
+    match index.mapping.aliases.as_slice() {
+        [] => (),
+        [alias] => container.push(format!("alias = {:?}", alias)),
+        _ => {
+            return Err(CodegenError::unsupported(
+                "aliases",
+                "the derive can only give an index one alias",
+            ))
+        }
+    }
+    let settings = &index.mapping.settings;
+    if let Some(shards) = settings.number_of_shards {
+        container.push(format!("shards = {}", shards));
+    }
+    if let Some(replicas) = settings.number_of_replicas {
+        container.push(format!("replicas = {}", replicas));
+    }
+    if let Some(dynamic) = index.mapping.dynamic {
+        let dynamic = serde_json::to_value(dynamic).unwrap();
+        container.push(format!("dynamic = {}", dynamic));
+    }
+    generator.structure(root, &index.mapping.fields, "", Some(container))?;
+    Ok(generator.source())
+}
+
+#[derive(Default)]
+struct Generator {
+    // the items imported, by the module they're from
+    imports: BTreeMap<&'static str, BTreeSet<&'static str>>,
+    // type names taken, including those of the paths the derive generates
+    type_names: BTreeSet<String>,
+    // the functions building each analyzer and normalizer, by name
+    analyzers: BTreeMap<String, String>,
+    normalizers: BTreeMap<String, String>,
+    function_names: BTreeSet<String>,
+    functions: Vec<String>,
+    structs: Vec<String>,
+}
+
+// A struct's fields. Objects are dotted names in sedk's fields, so those with
+// the same first part are grouped into a struct of their own.
+enum Member<'a> {
+    Leaf(&'a Field),
+    Object(Vec<Field>),
+}
+
+impl Generator {
+    fn import(&mut self, module: &'static str, item: &'static str) {
+        self.imports.entry(module).or_default().insert(item);
+    }
+
+    fn struct_name(&mut self, name: &str) -> String {
+        let name = pascal_case(name);
+        let mut candidate = name.clone();
+        let mut n = 1;
+        // the derive names the paths of `Struct` `StructFields`
+        while self.type_names.contains(&candidate)
+            || self.type_names.contains(&format!("{}Fields", candidate))
+            || RESERVED_TYPES.contains(&candidate.as_str())
+        {
+            n += 1;
+            candidate = format!("{}{}", name, n);
+        }
+        self.type_names.insert(format!("{}Fields", candidate));
+        self.type_names.insert(candidate.clone());
+        candidate
+    }
+
+    fn function_name(&mut self, name: &str, kind: &str) -> String {
+        let mut name = snake_case(name, kind);
+        if is_keyword(&name) || self.function_names.contains(&name) {
+            name = format!("{}_{}", name, kind);
+        }
+        let mut candidate = name.clone();
+        let mut n = 1;
+        while self.function_names.contains(&candidate) {
+            n += 1;
+            candidate = format!("{}_{}", name, n);
+        }
+        self.function_names.insert(candidate.clone());
+        candidate
+    }
+
+    // a function for every analyzer and normalizer besides sedk's defaults
+    fn analysis(&mut self, fields: &[Field]) -> Result<()> {
+        for field in fields {
+            self.analysis(&field.fields)?;
+            match &field.field_type {
+                FieldType::Nested { properties } => self.analysis(properties)?,
+                FieldType::Object { templates } => self.analysis(templates)?,
+                FieldType::Text {
+                    analyzer,
+                    search_analyzer,
+                } => {
+                    for analyzer in std::iter::once(analyzer).chain(search_analyzer) {
+                        self.analyzer(analyzer)?;
+                    }
+                }
+                FieldType::Keyword { normalizer, .. } => self.normalizer(normalizer)?,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn analyzer(&mut self, analyzer: &Analyzer) -> Result<()> {
+        if *analyzer == Analyzer::default() || self.analyzers.contains_key(&analyzer.name) {
+            return Ok(());
+        }
+        if *analyzer == Analyzer::standard() {
+            self.import("sedk", "Analyzer");
+            self.analyzers
+                .insert(analyzer.name.clone(), "Analyzer::standard".to_string());
+            return Ok(());
+        }
+        let function = self.function_name(&analyzer.name, "analyzer");
+        self.import("sedk", "Analyzer");
+        let mut body = format!("    Analyzer::builder({:?})\n", analyzer.name);
+        for filter in &analyzer.character_filters {
+            let filter = self.char_filter(&filter.name, &filter.character_filter_type)?;
+            body.push_str(&step("char_filter", filter));
+        }
+        let tokenizer =
+            self.tokenizer(&analyzer.tokenizer.name, &analyzer.tokenizer.tokenizer_type)?;
+        body.push_str(&step("tokenizer", tokenizer));
+        for filter in &analyzer.token_filters {
+            let filter = self.filter(&filter.name, &filter.filter_type)?;
+            body.push_str(&step("filter", filter));
+        }
+        self.functions.push(format!(
+            "pub fn {}() -> Analyzer {{\n{}        .build()\n        .expect(\"the index's analyzers are valid\")\n}}\n",
+            function, body
+        ));
+        self.analyzers.insert(analyzer.name.clone(), function);
+        Ok(())
+    }
+
+    fn normalizer(&mut self, normalizer: &Normalizer) -> Result<()> {
+        if *normalizer == Normalizer::default() || self.normalizers.contains_key(&normalizer.name) {
+            return Ok(());
+        }
+        if normalizer.tokenizer != Normalizer::default().tokenizer {
+            return Err(CodegenError::unsupported(
+                &format!("normalizer `{}`", normalizer.name),
+                "normalizers can't have a tokenizer",
+            ));
+        }
+        let function = self.function_name(&normalizer.name, "normalizer");
+        self.import("sedk", "Normalizer");
+        let mut body = format!("    Normalizer::builder({:?})\n", normalizer.name);
+        for filter in &normalizer.character_filters {
+            let filter = self.char_filter(&filter.name, &filter.character_filter_type)?;
+            body.push_str(&step("char_filter", filter));
+        }
+        for filter in &normalizer.token_filters {
+            let filter = self.filter(&filter.name, &filter.filter_type)?;
+            body.push_str(&step("filter", filter));
+        }
+        self.functions.push(format!(
+            "pub fn {}() -> Normalizer {{\n{}        .build()\n        .expect(\"the index's normalizers are valid\")\n}}\n",
+            function, body
+        ));
+        self.normalizers.insert(normalizer.name.clone(), function);
+        Ok(())
+    }
+
+    fn tokenizer(&mut self, name: &str, tokenizer_type: &TokenizerType) -> Result<Expr> {
+        self.import("sedk::dsl::tokenizers", "Tokenizer");
+        self.import("sedk::dsl::tokenizers", "TokenizerType");
+        let variant = |name: &str| format!("TokenizerType::{}", name);
+        // plugin components are only variants with their features
+        #[allow(unreachable_patterns)]
+        let tokenizer = match tokenizer_type {
+            TokenizerType::Standard => Expr::atom(variant("Standard")),
+            TokenizerType::Keyword => Expr::atom(variant("Keyword")),
+            TokenizerType::CharacterGroup { tokenize_on_chars } => {
+                self.import("sedk::dsl::tokenizers", "CharacterGroups");
+                Expr::Struct(
+                    variant("CharacterGroup"),
+                    vec![(
+                        "tokenize_on_chars",
+                        Expr::Vec(
+                            tokenize_on_chars
+                                .iter()
+                                .map(|group| Expr::atom(format!("CharacterGroups::{:?}", group)))
+                                .collect(),
+                        ),
+                    )],
+                )
+            }
+            TokenizerType::PathHierarchy { delimiter, reverse } => Expr::Struct(
+                variant("PathHierarchy"),
+                vec![
+                    ("delimiter", Expr::atom(format!("{:?}", delimiter))),
+                    ("reverse", Expr::atom(reverse.to_string())),
+                ],
+            ),
+            _ => return Err(plugin("tokenizer", name)),
+        };
+        Ok(Expr::Call(
+            "Tokenizer::new".to_string(),
+            vec![Expr::str(name), tokenizer],
+        ))
+    }
+
+    fn filter(&mut self, name: &str, filter_type: &TokenFilterType) -> Result<Expr> {
+        self.import("sedk::dsl::token_processing", "TokenFilter");
+        self.import("sedk::dsl::token_processing", "TokenFilterType");
+        let variant = |name: &str| format!("TokenFilterType::{}", name);
+        let boolean = |b: &bool| Expr::atom(b.to_string());
+        let number = |n: &u8| Expr::atom(n.to_string());
+        #[allow(unreachable_patterns)]
+        let filter = match filter_type {
+            TokenFilterType::Lowercase => Expr::atom(variant("Lowercase")),
+            TokenFilterType::Asciifolding { preserve_original } => Expr::Struct(
+                variant("Asciifolding"),
+                vec![("preserve_original", boolean(preserve_original))],
+            ),
+            TokenFilterType::EdgeNgram {
+                min_gram,
+                max_gram,
+                preserve_original,
+            } => Expr::Struct(
+                variant("EdgeNgram"),
+                vec![
+                    ("min_gram", number(min_gram)),
+                    ("max_gram", number(max_gram)),
+                    ("preserve_original", boolean(preserve_original)),
+                ],
+            ),
+            TokenFilterType::Stop {
+                stopwords,
+                ignore_case,
+            } => {
+                self.import("sedk::dsl::token_processing", "Stopwords");
+                let stopwords = match stopwords {
+                    Stopwords::Predefined(language) => Expr::Call(
+                        "Stopwords::Predefined".to_string(),
+                        vec![Expr::string(language)],
+                    ),
+                    Stopwords::List(words) => {
+                        Expr::Call("Stopwords::List".to_string(), vec![strings(words)])
+                    }
+                };
+                Expr::Struct(
+                    variant("Stop"),
+                    vec![
+                        ("stopwords", stopwords),
+                        ("ignore_case", boolean(ignore_case)),
+                    ],
+                )
+            }
+            TokenFilterType::WordDelimiterGraph {
+                generate_word_parts,
+                generate_number_parts,
+                catenate_all,
+                split_on_case_change,
+                split_on_numerics,
+                preserve_original,
+            } => Expr::Struct(
+                variant("WordDelimiterGraph"),
+                vec![
+                    ("generate_word_parts", boolean(generate_word_parts)),
+                    ("generate_number_parts", boolean(generate_number_parts)),
+                    ("catenate_all", boolean(catenate_all)),
+                    ("split_on_case_change", boolean(split_on_case_change)),
+                    ("split_on_numerics", boolean(split_on_numerics)),
+                    ("preserve_original", boolean(preserve_original)),
+                ],
+            ),
+            TokenFilterType::Stemmer { language } => {
+                self.import("sedk::dsl::token_processing", "StemmerLanguage");
+                Expr::Struct(
+                    variant("Stemmer"),
+                    vec![(
+                        "language",
+                        Expr::atom(format!("StemmerLanguage::{:?}", language)),
+                    )],
+                )
+            }
+            TokenFilterType::SynonymGraph {
+                expand,
+                lenient,
+                synonyms,
+            } => {
+                self.import("sedk::dsl::token_processing", "Synonym");
+                Expr::Struct(
+                    variant("SynonymGraph"),
+                    vec![
+                        ("expand", boolean(expand)),
+                        ("lenient", boolean(lenient)),
+                        (
+                            "synonyms",
+                            Expr::Vec(synonyms.iter().map(synonym).collect()),
+                        ),
+                    ],
+                )
+            }
+            TokenFilterType::FlattenGraph => Expr::atom(variant("FlattenGraph")),
+            TokenFilterType::Reverse => Expr::atom(variant("Reverse")),
+            TokenFilterType::Shingle {
+                max_shingle_size,
+                min_shingle_size,
+                output_unigrams,
+                output_unigrams_if_no_shingles,
+                token_separator,
+                filler_token,
+            } => Expr::Struct(
+                variant("Shingle"),
+                vec![
+                    ("max_shingle_size", number(max_shingle_size)),
+                    ("min_shingle_size", number(min_shingle_size)),
+                    ("output_unigrams", boolean(output_unigrams)),
+                    (
+                        "output_unigrams_if_no_shingles",
+                        boolean(output_unigrams_if_no_shingles),
+                    ),
+                    ("token_separator", Expr::string(token_separator)),
+                    ("filler_token", Expr::string(filler_token)),
+                ],
+            ),
+            _ => return Err(plugin("token filter", name)),
+        };
+        Ok(Expr::Call(
+            "TokenFilter::new".to_string(),
+            vec![Expr::str(name), filter],
+        ))
+    }
+
+    fn char_filter(&mut self, name: &str, filter_type: &CharacterFilterType) -> Result<Expr> {
+        self.import("sedk::dsl::character_filters", "CharacterFilter");
+        self.import("sedk::dsl::character_filters", "CharacterFilterType");
+        let variant = |name: &str| format!("CharacterFilterType::{}", name);
+        #[allow(unreachable_patterns)]
+        let filter = match filter_type {
+            CharacterFilterType::HtmlStrip { escaped_tags } => Expr::Struct(
+                variant("HtmlStrip"),
+                vec![("escaped_tags", strings(escaped_tags))],
+            ),
+            CharacterFilterType::Mapping { mappings } => Expr::Struct(
+                variant("Mapping"),
+                vec![(
+                    "mappings",
+                    Expr::Vec(
+                        mappings
+                            .iter()
+                            .map(|(from, to)| {
+                                Expr::Call(
+                                    String::new(),
+                                    vec![Expr::string(from), Expr::string(to)],
+                                )
+                            })
+                            .collect(),
+                    ),
+                )],
+            ),
+            CharacterFilterType::PatternReplace {
+                pattern,
+                replacement,
+                flags,
+            } => {
+                self.import("sedk::dsl::character_filters", "RegexFlag");
+                Expr::Struct(
+                    variant("PatternReplace"),
+                    vec![
+                        ("pattern", Expr::string(pattern)),
+                        ("replacement", Expr::string(replacement)),
+                        (
+                            "flags",
+                            Expr::Vec(
+                                flags
+                                    .iter()
+                                    .map(|flag| Expr::atom(format!("RegexFlag::{:?}", flag)))
+                                    .collect(),
+                            ),
+                        ),
+                    ],
+                )
+            }
+            _ => return Err(plugin("character filter", name)),
+        };
+        Ok(Expr::Call(
+            "CharacterFilter::new".to_string(),
+            vec![Expr::str(name), filter],
+        ))
+    }
+
+    // A struct for `fields`, which are at `path` in the mapping, with the
+    // structs of its objects and nested fields after it. The root struct has
+    // the index's options.
+    fn structure(
+        &mut self,
+        name: String,
+        fields: &[Field],
+        path: &str,
+        container: Option<Vec<String>>,
+    ) -> Result<()> {
+        self.import("serde", "Deserialize");
+        self.import("serde", "Serialize");
+        self.import("sedk", "IntoFields");
+        let position = self.structs.len();
+        self.structs.push(String::new());
+
+        let mut members: Vec<(String, Member)> = Vec::new();
+        for field in fields {
+            let (first, rest) = match field.name.split_once('.') {
+                Some((first, rest)) => (first, Some(rest)),
+                None => (field.name.as_str(), None),
+            };
+            let existing = members.iter_mut().find(|(name, _)| name == first);
+            match (existing, rest) {
+                (None, None) => members.push((first.to_string(), Member::Leaf(field))),
+                (None, Some(rest)) => members.push((
+                    first.to_string(),
+                    Member::Object(vec![relative(field, first, rest)]),
+                )),
+                (Some((_, Member::Object(fields))), Some(rest)) => {
+                    fields.push(relative(field, first, rest))
+                }
+                _ => {
+                    return Err(CodegenError::unsupported(
+                        &format!("{}{}", path, field.name),
+                        "a field can't also be an object",
+                    ))
+                }
+            }
+        }
+
+        let mut idents = BTreeSet::new();
+        let mut body = String::new();
+        for (name, member) in members {
+            let at = format!("{}{}", path, name);
+            if name.is_empty() {
+                return Err(CodegenError::unsupported(&at, "field names can't be empty"));
+            }
+            let ident = field_ident(&name, &mut idents);
+            let mut attributes = Vec::new();
+            if ident.trim_start_matches("r#") != name {
+                attributes.push(format!("#[serde(rename = {:?})]", name));
+            }
+            let ty = match member {
+                Member::Object(fields) => {
+                    let child = self.struct_name(&name);
+                    self.structure(child.clone(), &fields, &format!("{}.", at), None)?;
+                    format!("Option<{}>", child)
+                }
+                Member::Leaf(field) => {
+                    let (docs, options, ty) = self.member(field, &at)?;
+                    if let Some(docs) = docs {
+                        body.push_str(&format!("    /// {}\n", docs));
+                    }
+                    if !options.is_empty() {
+                        attributes.push(format!("#[sedk({})]", options.join(", ")));
+                    }
+                    if matches!(field.field_type, FieldType::Nested { .. }) {
+                        attributes.push("#[serde(default)]".to_string());
+                    }
+                    ty
+                }
+            };
+            for attribute in attributes {
+                body.push_str(&format!("    {}\n", attribute));
+            }
+            body.push_str(&format!("    pub {}: {},\n", ident, ty));
+        }
+
+        let mut derives = vec!["Clone", "Debug", "Serialize", "Deserialize", "IntoFields"];
+        let mut header = String::new();
+        if let Some(container) = container {
+            self.import("sedk", "Indexable");
+            derives.push("Indexable");
+            writeln!(header, "#[derive({})]", derives.join(", ")).unwrap();
+            if !container.is_empty() {
+                writeln!(header, "#[sedk({})]", container.join(", ")).unwrap();
+            }
+        } else {
+            writeln!(header, "#[derive({})]", derives.join(", ")).unwrap();
+        }
+        self.structs[position] = format!("{}pub struct {} {{\n{}}}\n", header, name, body);
+        Ok(())
+    }
+
+    // the doc comment, `#[sedk(...)]` options and type of a field
+    fn member(&mut self, field: &Field, at: &str) -> Result<(Option<String>, Vec<String>, String)> {
+        let unsupported = |reason: &str| Err(CodegenError::unsupported(at, reason));
+        let mut options = Vec::new();
+        let ty = match &field.field_type {
+            FieldType::Nested { properties } => {
+                if !field.fields.is_empty() || !field.index || !field.copy_to.is_empty() {
+                    return unsupported(
+                        "nested fields can't have multi-fields, `index` or `copy_to`",
+                    );
+                }
+                let child = self.struct_name(&field.name);
+                self.structure(child.clone(), properties, &format!("{}.", at), None)?;
+                options.push("nested".to_string());
+                format!("Vec<{}>", child)
+            }
+            FieldType::Object { templates } => {
+                if !field.fields.is_empty()
+                    || !field.index
+                    || !field.copy_to.is_empty()
+                    || !field.meta.is_empty()
+                {
+                    return unsupported(
+                        "maps can't have multi-fields, `index`, `copy_to` or `meta`",
+                    );
+                }
+                let value = match templates.as_slice() {
+                    [template] if template.name == format!("{}.*", field.name) => {
+                        map_value(template)
+                    }
+                    _ => None,
+                };
+                match value {
+                    Some(value) => {
+                        self.import("std::collections", "HashMap");
+                        format!("Option<HashMap<String, {}>>", value)
+                    }
+                    None => {
+                        return unsupported(
+                            "the values of maps must be mapped like a number, boolean or string",
+                        )
+                    }
+                }
+            }
+            // mapped as a String would be, with its keyword multi-field
+            _ if is_plain_string(field) => "Option<String>".to_string(),
+            field_type => {
+                let (ty, mapping) = self.mapping(field_type, at)?;
+                options.extend(mapping);
+                for subfield in &field.fields {
+                    options.push(self.subfield(subfield, &format!("{}.{}", at, subfield.name))?);
+                }
+                format!("Option<{}>", ty)
+            }
+        };
+
+        // `index = false` applies to the field's multi-fields too
+        if field.fields.iter().any(|f| f.index != field.index) {
+            return unsupported("multi-fields must be indexed when their field is");
+        }
+        if !field.index {
+            options.push("index = false".to_string());
+        }
+        for target in &field.copy_to {
+            options.push(format!("copy_to = {:?}", target));
+        }
+
+        // descriptions that fit on a line are written as doc comments
+        let mut docs = None;
+        let mut meta = Vec::new();
+        for (key, value) in &field.meta {
+            let documented = key == "description"
+                && !value.is_empty()
+                && value.trim() == value
+                && !value.contains(['\n', '\r'])
+                && value.chars().count() <= MAX_DOC_LENGTH;
+            if documented {
+                docs = Some(value.clone());
+                continue;
+            }
+            if !is_ident(key) {
+                return unsupported(&format!("meta key `{}` isn't an identifier", key));
+            }
+            meta.push(format!("{} = {:?}", key, value));
+        }
+        if !meta.is_empty() {
+            options.push(format!("meta({})", meta.join(", ")));
+        }
+        Ok((docs, options, ty))
+    }
+
+    // the Rust type of a field of this type, and the options that map it
+    fn mapping(&mut self, field_type: &FieldType, at: &str) -> Result<(String, Vec<String>)> {
+        let typed = |ty: &str| {
+            (
+                ty.to_string(),
+                vec![format!("type = {:?}", field_type.name())],
+            )
+        };
+        Ok(match field_type {
+            FieldType::Text {
+                analyzer,
+                search_analyzer,
+            } => {
+                if search_analyzer.is_some() {
+                    return Err(CodegenError::unsupported(
+                        at,
+                        "the derive can't set search analyzers",
+                    ));
+                }
+                let (ty, mut options) = typed("String");
+                if *analyzer != Analyzer::default() {
+                    options.push(format!("analyzer = {}", self.analyzers[&analyzer.name]));
+                }
+                (ty, options)
+            }
+            FieldType::Keyword {
+                normalizer,
+                ignore_above,
+            } => {
+                let (ty, mut options) = typed("String");
+                if *normalizer != Normalizer::default() {
+                    options.push(format!(
+                        "normalizer = {}",
+                        self.normalizers[&normalizer.name]
+                    ));
+                }
+                if let Some(length) = ignore_above {
+                    options.push(format!("ignore_above = {}", length));
+                }
+                (ty, options)
+            }
+            FieldType::DenseVector { dims } => {
+                let (_, mut options) = typed("Vec<f32>");
+                options.push(format!("dims = {}", dims));
+                ("Vec<f32>".to_string(), options)
+            }
+            FieldType::Long => ("i64".to_string(), Vec::new()),
+            FieldType::Integer => ("i32".to_string(), Vec::new()),
+            FieldType::Short => ("i16".to_string(), Vec::new()),
+            FieldType::Byte => ("i8".to_string(), Vec::new()),
+            FieldType::Double => ("f64".to_string(), Vec::new()),
+            FieldType::Float => ("f32".to_string(), Vec::new()),
+            FieldType::UnsignedLong => ("u64".to_string(), Vec::new()),
+            FieldType::Boolean => ("bool".to_string(), Vec::new()),
+            FieldType::Date { format } => {
+                let (ty, mut options) = typed("String");
+                if let Some(format) = format {
+                    options.push(format!("format = {:?}", format));
+                }
+                (ty, options)
+            }
+            FieldType::Ip => {
+                self.import("std::net", "IpAddr");
+                ("IpAddr".to_string(), Vec::new())
+            }
+            FieldType::Binary => typed("String"),
+            FieldType::HalfFloat => typed("f32"),
+            FieldType::ScaledFloat { scaling_factor } => {
+                let (ty, mut options) = typed("f64");
+                options.push(format!("scaling_factor = {:?}", scaling_factor));
+                (ty, options)
+            }
+            FieldType::Flattened => typed("serde_json::Value"),
+            FieldType::Nested { .. } | FieldType::Object { .. } => {
+                return Err(CodegenError::unsupported(
+                    at,
+                    "objects can't be multi-fields",
+                ))
+            }
+        })
+    }
+
+    fn subfield(&mut self, subfield: &Field, at: &str) -> Result<String> {
+        if !subfield.fields.is_empty() || !subfield.copy_to.is_empty() || !subfield.meta.is_empty()
+        {
+            return Err(CodegenError::unsupported(
+                at,
+                "multi-fields can't have multi-fields, `copy_to` or `meta`",
+            ));
+        }
+        let (_, mut options) = self.mapping(&subfield.field_type, at)?;
+        // the type is left for an analyzer or normalizer to imply
+        let implied = options.get(1).is_some_and(|option| {
+            option.starts_with("analyzer =") || option.starts_with("normalizer =")
+        });
+        if implied {
+            options.remove(0);
+        }
+        Ok(format!(
+            "subfield(name = {:?}, {})",
+            subfield.name,
+            options.join(", ")
+        ))
+    }
+
+    fn source(self) -> String {
+        let mut source = String::from(
+            "// Generated by sedk-codegen. Deriving the mapping of these types gives back the\n\
+             // mapping they were generated from.\n",
+        );
+        for (module, items) in &self.imports {
+            let items: Vec<&str> = items.iter().copied().collect();
+            match items.as_slice() {
+                [item] => writeln!(source, "use {}::{};", module, item).unwrap(),
+                _ => {
+                    let line = format!("use {}::{{{}}};", module, items.join(", "));
+                    if line.len() <= MAX_WIDTH {
+                        writeln!(source, "{}", line).unwrap();
+                    } else {
+                        writeln!(
+                            source,
+                            "use {}::{{\n    {},\n}};",
+                            module,
+                            items.join(",\n    ")
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        }
+        for item in self.functions.iter().chain(&self.structs) {
+            source.push('\n');
+            source.push_str(item);
+        }
+        source
+    }
+}
+
+// types that are imported or in the prelude
+const RESERVED_TYPES: &[&str] = &[
+    "Analyzer",
+    "Normalizer",
+    "IntoFields",
+    "Indexable",
+    "Serialize",
+    "Deserialize",
+    "HashMap",
+    "Option",
+    "Vec",
+    "String",
+    "Box",
+    "Some",
+    "None",
+    "Self",
+];
+
+// a field of an object, named within it
+fn relative(field: &Field, object: &str, rest: &str) -> Field {
+    let prefix = format!("{}.", object);
+    let mut field = Field {
+        name: rest.to_string(),
+        ..field.clone()
+    };
+    if let FieldType::Object { templates } = &mut field.field_type {
+        for template in templates {
+            if let Some(name) = template.name.strip_prefix(&prefix) {
+                template.name = name.to_string();
+            }
+        }
+    }
+    field
+}
+
+// a field mapped as `String` maps itself, a text field with a keyword
+// multi-field
+fn is_plain_string(field: &Field) -> bool {
+    let plain = &String::into_fields(Some(&field.name))[0];
+    let indexed = field.fields.iter().map(|f| Field {
+        index: true,
+        ..f.clone()
+    });
+    field.field_type == plain.field_type && indexed.eq(plain.fields.iter().cloned())
+}
+
+// a type whose values map like a map's templates
+fn map_value(template: &Field) -> Option<&'static str> {
+    let mapping = Some(&template.name[..]);
+    type IntoFieldsFn = fn(Option<&str>) -> Vec<Field>;
+    let candidates: &[(&str, IntoFieldsFn)] = &[
+        ("i64", i64::into_fields),
+        ("i32", i32::into_fields),
+        ("i16", i16::into_fields),
+        ("i8", i8::into_fields),
+        ("f64", f64::into_fields),
+        ("f32", f32::into_fields),
+        ("u64", u64::into_fields),
+        ("bool", bool::into_fields),
+        ("String", String::into_fields),
+    ];
+    candidates
+        .iter()
+        .find(|(_, into_fields)| into_fields(mapping) == [template.clone()])
+        .map(|(ty, _)| *ty)
+}
+
+fn plugin(kind: &str, name: &str) -> CodegenError {
+    CodegenError::unsupported(
+        &format!("{} `{}`", kind, name),
+        "plugin components aren't supported",
+    )
+}
+
+fn step(method: &str, argument: Expr) -> String {
+    let argument = argument.render(8);
+    format!("        .{}({})\n", method, argument)
+}
+
+fn strings(strings: &[String]) -> Expr {
+    Expr::Vec(strings.iter().map(|s| Expr::string(s)).collect())
+}
+
+fn synonym(synonym: &Synonym) -> Expr {
+    let phrases = |phrases: &[String]| {
+        let phrases: Vec<String> = phrases.iter().map(|p| format!("{:?}", p)).collect();
+        Expr::atom(format!("&[{}]", phrases.join(", ")))
+    };
+    if synonym.to.is_empty() {
+        Expr::Call(
+            "Synonym::equivalent".to_string(),
+            vec![phrases(&synonym.from)],
+        )
+    } else {
+        Expr::Call(
+            "Synonym::explicit".to_string(),
+            vec![phrases(&synonym.from), phrases(&synonym.to)],
+        )
+    }
+}
+
+// Rust expressions, laid out on one line when they fit and one part to a
+// line otherwise
+enum Expr {
+    Atom(String),
+    Call(String, Vec<Expr>),
+    Struct(String, Vec<(&'static str, Expr)>),
+    Vec(Vec<Expr>),
+}
+
+impl Expr {
+    fn atom(atom: impl Into<String>) -> Expr {
+        Expr::Atom(atom.into())
+    }
+
+    fn str(s: &str) -> Expr {
+        Expr::Atom(format!("{:?}", s))
+    }
+
+    fn string(s: &str) -> Expr {
+        Expr::Atom(format!("{:?}.to_string()", s))
+    }
+
+    fn inline(&self) -> String {
+        match self {
+            Expr::Atom(atom) => atom.clone(),
+            Expr::Call(function, args) => {
+                let args: Vec<String> = args.iter().map(Expr::inline).collect();
+                format!("{}({})", function, args.join(", "))
+            }
+            Expr::Struct(path, fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value.inline()))
+                    .collect();
+                format!("{} {{ {} }}", path, fields.join(", "))
+            }
+            Expr::Vec(items) if items.is_empty() => "vec![]".to_string(),
+            Expr::Vec(items) => {
+                let items: Vec<String> = items.iter().map(Expr::inline).collect();
+                format!("vec![{}]", items.join(", "))
+            }
+        }
+    }
+
+    // struct expressions are always laid out a field to a line
+    fn has_struct(&self) -> bool {
+        match self {
+            Expr::Atom(_) => false,
+            Expr::Call(_, items) | Expr::Vec(items) => items.iter().any(Expr::has_struct),
+            Expr::Struct(..) => true,
+        }
+    }
+
+    // laid out starting `indent` columns in
+    fn render(&self, indent: usize) -> String {
+        let inline = self.inline();
+        if indent + inline.len() <= MAX_WIDTH && !self.has_struct() {
+            return inline;
+        }
+        let pad = " ".repeat(indent + 4);
+        let close = " ".repeat(indent);
+        match self {
+            Expr::Atom(atom) => atom.clone(),
+            Expr::Call(function, args) => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| format!("{}{},\n", pad, arg.render(indent + 4)))
+                    .collect();
+                format!("{}(\n{}{})", function, args.concat(), close)
+            }
+            Expr::Struct(path, fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(name, value)| {
+                        format!("{}{}: {},\n", pad, name, value.render(indent + 4))
+                    })
+                    .collect();
+                format!("{} {{\n{}{}}}", path, fields.concat(), close)
+            }
+            Expr::Vec(items) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item| format!("{}{},\n", pad, item.render(indent + 4)))
+                    .collect();
+                format!("vec![\n{}{}]", items.concat(), close)
+            }
+        }
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    let mut pascal = String::new();
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            pascal.push(first.to_ascii_uppercase());
+            pascal.extend(chars);
+        }
+    }
+    match pascal.chars().next() {
+        None => "Document".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("Document{}", pascal),
+        Some(_) => pascal,
+    }
+}
+
+// the name of a field or function, which is `fallback` when nothing of the
+// original can be used
+fn snake_case(name: &str, fallback: &str) -> String {
+    let mut snake = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if matches!(previous, Some(p) if p.is_ascii_lowercase() || p.is_ascii_digit()) {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            snake.push(c);
+        } else if !snake.ends_with('_') {
+            snake.push('_');
+        }
+        previous = Some(c);
+    }
+    let snake = snake.trim_matches('_').to_string();
+    match snake.chars().next() {
+        None => fallback.to_string(),
+        Some(c) if c.is_ascii_digit() => format!("{}_{}", fallback, snake),
+        Some(_) => snake,
+    }
+}
+
+fn field_ident(name: &str, taken: &mut BTreeSet<String>) -> String {
+    let snake = snake_case(name, "field");
+    let mut ident = match snake.as_str() {
+        // keywords that can't be raw identifiers
+        "self" | "super" | "crate" => format!("{}_", snake),
+        _ if is_keyword(&snake) => format!("r#{}", snake),
+        _ => snake,
+    };
+    let base = ident.clone();
+    let mut n = 1;
+    while taken.contains(&ident) {
+        n += 1;
+        ident = format!("{}_{}", base, n);
+    }
+    taken.insert(ident.clone());
+    ident
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && s != "_"
+}
+
+fn is_keyword(s: &str) -> bool {
+    KEYWORDS.contains(&s)
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "unsafe",
+    "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+    "priv", "typeof", "unsized", "virtual", "yield",
+];
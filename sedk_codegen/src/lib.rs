@@ -0,0 +1,59 @@
+//! Rust document types generated from the mapping of an existing index, so
+//! that indices whose mappings were written by hand can move onto sedk.
+//!
+//! A mapping is first read into sedk's own [`IndexMapping`], refusing anything
+//! sedk can't represent rather than dropping it, and then written out as
+//! structs deriving `IntoFields` and `Indexable` along with a function for
+//! each analyzer and normalizer. Deriving the mapping of the generated types
+//! gives back the mapping that was read.
+//!
+//! ```
+//! let json = r#"{"mappings": {"properties": {"title": {"type": "keyword"}}}}"#;
+//! let source = sedk_codegen::codegen(json, "Product").unwrap();
+//! assert!(source.contains("pub struct Product {"));
+//! ```
+mod generate;
+mod parse;
+
+pub use generate::generate;
+pub use parse::{parse, Index};
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum CodegenError {
+    Json(serde_json::Error),
+    /// Part of the mapping that sedk can't represent, by where it is in the
+    /// json.
+    Unsupported {
+        at: String,
+        reason: String,
+    },
+}
+
+impl CodegenError {
+    pub(crate) fn unsupported(at: &str, reason: impl Into<String>) -> CodegenError {
+        CodegenError::Unsupported {
+            at: at.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodegenError::Json(e) => write!(f, "could not parse the mapping: {}", e),
+            CodegenError::Unsupported { at, reason } => write!(f, "{}: {}", at, reason),
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+/// The Rust source for the mapping or index in `json`, with the document type
+/// named `name`.
+pub fn codegen(json: &str, name: &str) -> Result<String, CodegenError> {
+    let json = serde_json::from_str(json).map_err(CodegenError::Json)?;
+    generate(&parse(&json)?, name)
+}
@@ -0,0 +1,62 @@
+//! `sedk-codegen <mapping.json> [--name <Type>] [--output <file.rs>]`
+//!
+//! Writes the Rust document types for a mapping, as returned by `GET /<index>`
+//! or sent to create an index. The document type is named after the index,
+//! or the file when the mapping doesn't name it.
+use sedk_codegen::{generate, parse, CodegenError};
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "usage: sedk-codegen <mapping.json> [--name <Type>] [--output <file.rs>]";
+
+fn main() {
+    let mut input = None;
+    let mut name = None;
+    let mut output = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--name" => &mut name,
+            "--output" => &mut output,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if input.is_none() && !arg.starts_with('-') => {
+                input = Some(arg);
+                continue;
+            }
+            _ => fail(&format!("unexpected argument `{}`", arg)),
+        };
+        match args.next() {
+            Some(arg) => *value = Some(arg),
+            None => fail(&format!("`{}` needs a value", arg)),
+        }
+    }
+    let input = input.unwrap_or_else(|| fail("no mapping given"));
+
+    let json = std::fs::read_to_string(&input)
+        .unwrap_or_else(|e| fail(&format!("could not read {}: {}", input, e)));
+    let source = serde_json::from_str(&json)
+        .map_err(CodegenError::Json)
+        .and_then(|json| parse(&json))
+        .and_then(|index| {
+            let name = name.or_else(|| index.name.clone()).unwrap_or_else(|| {
+                let stem = Path::new(&input).file_stem().unwrap_or_default();
+                stem.to_string_lossy().into_owned()
+            });
+            generate(&index, &name)
+        })
+        .unwrap_or_else(|e| fail(&format!("{}: {}", input, e)));
+
+    match output {
+        Some(output) => std::fs::write(&output, source)
+            .unwrap_or_else(|e| fail(&format!("could not write {}: {}", output, e))),
+        None => print!("{}", source),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("sedk-codegen: {}\n{}", message, USAGE);
+    process::exit(1)
+}
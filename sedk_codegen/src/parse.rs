@@ -0,0 +1,960 @@
+//! Reading the json of an index into sedk's model of it. Json can come from
+//! `GET /<index>`, be the body an index is created with, or be a mapping sedk
+//! serialized. Anything sedk can't represent is an error, as leaving it out
+//! would quietly change the index.
+use crate::CodegenError;
+use sedk::dsl::character_filters::{CharacterFilter, CharacterFilterType, RegexFlag};
+use sedk::dsl::token_processing::{
+    StemmerLanguage, Stopwords, Synonym, TokenFilter, TokenFilterType,
+};
+use sedk::dsl::tokenizers::{CharacterGroups, Tokenizer, TokenizerType};
+use sedk::{Analyzer, Dynamic, Field, FieldType, IndexMapping, IndexSettings, Normalizer};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+type Result<T> = std::result::Result<T, CodegenError>;
+
+/// An index read from json, named when the json is the response to
+/// `GET /<index>`.
+#[derive(PartialEq, Eq, Clone)]
+pub struct Index {
+    pub name: Option<String>,
+    pub mapping: IndexMapping,
+}
+
+/// Reads the index in `json`.
+pub fn parse(json: &Value) -> Result<Index> {
+    // `GET /<index>` answers with the index under its name
+    if let Value::Object(map) = json {
+        if let (1, Some((name, index))) = (map.len(), map.iter().next()) {
+            if index.get("mappings").is_some() || index.get("settings").is_some() {
+                return Ok(Index {
+                    name: Some(name.clone()),
+                    mapping: Reader::default().index(index, name)?,
+                });
+            }
+        }
+    }
+    Ok(Index {
+        name: None,
+        mapping: Reader::default().index(json, "")?,
+    })
+}
+
+fn child(at: &str, key: &str) -> String {
+    if at.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", at, key)
+    }
+}
+
+fn object<'a>(at: &str, value: &'a Value) -> Result<&'a Map<String, Value>> {
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => Err(CodegenError::unsupported(at, "expected an object")),
+    }
+}
+
+// The keys of a json object, every one of which must be read before it's
+// finished with, so that settings sedk doesn't know about are reported.
+struct Settings<'a> {
+    at: String,
+    map: &'a Map<String, Value>,
+    read: BTreeSet<&'a str>,
+}
+
+impl<'a> Settings<'a> {
+    fn new(at: &str, value: &'a Value) -> Result<Settings<'a>> {
+        Ok(Settings {
+            at: at.to_string(),
+            map: object(at, value)?,
+            read: BTreeSet::new(),
+        })
+    }
+
+    fn at(&self, key: &str) -> String {
+        child(&self.at, key)
+    }
+
+    fn get(&mut self, key: &str) -> Option<&'a Value> {
+        let (key, value) = self.map.get_key_value(key)?;
+        self.read.insert(key.as_str());
+        Some(value)
+    }
+
+    fn string(&mut self, key: &str) -> Result<Option<&'a str>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(_) => Err(CodegenError::unsupported(
+                &self.at(key),
+                "expected a string",
+            )),
+        }
+    }
+
+    fn string_or(&mut self, key: &str, default: &str) -> Result<String> {
+        Ok(self.string(key)?.unwrap_or(default).to_string())
+    }
+
+    // elasticsearch returns settings as strings, whatever they were set as
+    fn bool(&mut self, key: &str, default: bool) -> Result<bool> {
+        match self.get(key) {
+            None => Ok(default),
+            Some(Value::Bool(b)) => Ok(*b),
+            Some(Value::String(s)) if s == "true" || s == "false" => Ok(s == "true"),
+            Some(_) => Err(CodegenError::unsupported(
+                &self.at(key),
+                "expected a boolean",
+            )),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, key: &str) -> Result<Option<T>> {
+        let number = match self.get(key) {
+            None => return Ok(None),
+            Some(Value::Number(n)) => n.to_string().parse().ok(),
+            Some(Value::String(s)) => s.parse().ok(),
+            Some(_) => None,
+        };
+        match number {
+            Some(n) => Ok(Some(n)),
+            None => Err(CodegenError::unsupported(
+                &self.at(key),
+                "expected a whole number in range",
+            )),
+        }
+    }
+
+    fn char(&mut self, key: &str, default: char) -> Result<char> {
+        let s = match self.string(key)? {
+            None => return Ok(default),
+            Some(s) => s,
+        };
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(CodegenError::unsupported(
+                &self.at(key),
+                "expected a single character",
+            )),
+        }
+    }
+
+    // a string or a list of them
+    fn strings(&mut self, key: &str) -> Result<Vec<String>> {
+        let at = self.at(key);
+        match self.get(key) {
+            None => Ok(Vec::new()),
+            Some(Value::String(s)) => Ok(vec![s.clone()]),
+            Some(Value::Array(values)) => values
+                .iter()
+                .map(|v| match v {
+                    Value::String(s) => Ok(s.clone()),
+                    _ => Err(CodegenError::unsupported(&at, "expected strings")),
+                })
+                .collect(),
+            Some(_) => Err(CodegenError::unsupported(&at, "expected strings")),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self
+            .map
+            .keys()
+            .find(|key| !self.read.contains(key.as_str()))
+        {
+            Some(key) => Err(CodegenError::unsupported(
+                &self.at(key),
+                "sedk has no way to set this",
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+// built in analysis components are read as if they were defined without any
+// settings
+fn builtin() -> Value {
+    Value::Object(Map::new())
+}
+
+#[derive(Default)]
+struct Reader<'a> {
+    // the analysis settings of each kind, by name
+    definitions: BTreeMap<&'static str, (String, &'a Map<String, Value>)>,
+    // the definitions fields have used, directly or through an analyzer
+    used: BTreeSet<(&'static str, String)>,
+    // dynamic templates, by the path of the object they map the values of
+    templates: BTreeMap<String, (String, &'a Value)>,
+}
+
+const KINDS: &[&str] = &[
+    "analyzer",
+    "normalizer",
+    "tokenizer",
+    "filter",
+    "char_filter",
+];
+
+impl<'a> Reader<'a> {
+    fn index(mut self, json: &'a Value, at: &str) -> Result<IndexMapping> {
+        let mut index = Settings::new(at, json)?;
+        let mut settings = IndexSettings::default();
+        if let Some(value) = index.get("settings") {
+            self.settings(value, &index.at("settings"), &mut settings)?;
+        }
+        // sedk writes analysis beside the mappings
+        if let Some(value) = index.get("analysis") {
+            self.analysis(value, &index.at("analysis"))?;
+        }
+        let mut aliases = Vec::new();
+        if let Some(value) = index.get("aliases") {
+            let at = index.at("aliases");
+            for (alias, options) in object(&at, value)? {
+                if !object(&child(&at, alias), options)?.is_empty() {
+                    return Err(CodegenError::unsupported(
+                        &child(&at, alias),
+                        "aliases can't have options",
+                    ));
+                }
+                aliases.push(alias.clone());
+            }
+        }
+        let (fields, dynamic) = match index.get("mappings") {
+            Some(mappings) => self.mappings(mappings, &index.at("mappings"))?,
+            None => return Err(CodegenError::unsupported(at, "there are no mappings")),
+        };
+        index.finish()?;
+        self.check_used()?;
+        Ok(IndexMapping {
+            fields,
+            plugins: Vec::new(),
+            settings,
+            aliases,
+            dynamic,
+        })
+    }
+
+    fn settings(&mut self, value: &'a Value, at: &str, settings: &mut IndexSettings) -> Result<()> {
+        let mut s = Settings::new(at, value)?;
+        // details of the index elasticsearch reports, rather than settings
+        for key in &["creation_date", "uuid", "version", "provided_name"] {
+            s.get(key);
+        }
+        if let Some(index) = s.get("index") {
+            self.settings(index, &s.at("index"), settings)?;
+        }
+        if let Some(shards) = s.number("number_of_shards")? {
+            settings.number_of_shards = Some(shards);
+        }
+        if let Some(replicas) = s.number("number_of_replicas")? {
+            settings.number_of_replicas = Some(replicas);
+        }
+        if let Some(analysis) = s.get("analysis") {
+            self.analysis(analysis, &s.at("analysis"))?;
+        }
+        s.finish()
+    }
+
+    fn analysis(&mut self, value: &'a Value, at: &str) -> Result<()> {
+        let mut s = Settings::new(at, value)?;
+        for kind in KINDS {
+            if let Some(definitions) = s.get(kind) {
+                let at = s.at(kind);
+                if self.definitions.contains_key(kind) {
+                    return Err(CodegenError::unsupported(&at, "defined twice"));
+                }
+                self.definitions
+                    .insert(kind, (at.clone(), object(&at, definitions)?));
+            }
+        }
+        s.finish()
+    }
+
+    // the definition of a named component, or of a built in one
+    fn definition(&mut self, kind: &'static str, name: &str) -> (String, Option<&'a Value>) {
+        self.used.insert((kind, name.to_string()));
+        match self.definitions.get(kind) {
+            Some((at, definitions)) => (child(at, name), definitions.get(name)),
+            None => (format!("{} `{}`", kind, name), None),
+        }
+    }
+
+    // every definition should end up in the mapping sedk creates, which only
+    // has the ones its fields use
+    fn check_used(&self) -> Result<()> {
+        for (kind, (at, definitions)) in &self.definitions {
+            if let Some(name) = definitions
+                .keys()
+                .find(|name| !self.used.contains(&(*kind, name.to_string())))
+            {
+                return Err(CodegenError::unsupported(
+                    &child(at, name),
+                    "isn't used by any field, so sedk wouldn't create it",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn analyzer(&mut self, name: &str) -> Result<Analyzer> {
+        let (at, definition) = self.definition("analyzer", name);
+        let definition = match definition {
+            Some(definition) => definition,
+            None if name == "standard" => return Ok(Analyzer::standard()),
+            None => {
+                return Err(CodegenError::unsupported(
+                    &at,
+                    "built in analyzers other than `standard` can't be used, only custom ones",
+                ))
+            }
+        };
+        let mut s = Settings::new(&at, definition)?;
+        custom(&mut s)?;
+        let tokenizer = match s.string("tokenizer")? {
+            Some(tokenizer) => self.tokenizer(tokenizer)?,
+            None => return Err(CodegenError::unsupported(&at, "analyzers need a tokenizer")),
+        };
+        let analyzer = Analyzer {
+            name: name.to_string(),
+            character_filters: self.char_filters(&mut s)?,
+            tokenizer,
+            token_filters: self.filters(&mut s)?,
+        };
+        s.finish()?;
+        Ok(analyzer)
+    }
+
+    fn normalizer(&mut self, name: &str) -> Result<Normalizer> {
+        let (at, definition) = self.definition("normalizer", name);
+        let definition = definition.ok_or_else(|| {
+            CodegenError::unsupported(&at, "built in normalizers can't be used, only custom ones")
+        })?;
+        let mut s = Settings::new(&at, definition)?;
+        custom(&mut s)?;
+        let normalizer = Normalizer {
+            name: name.to_string(),
+            character_filters: self.char_filters(&mut s)?,
+            token_filters: self.filters(&mut s)?,
+            ..Normalizer::default()
+        };
+        // sedk names the tokenizer its normalizers are given
+        if let Some(tokenizer) = s.string("tokenizer")? {
+            if self.tokenizer(tokenizer)? != normalizer.tokenizer {
+                return Err(CodegenError::unsupported(
+                    &s.at("tokenizer"),
+                    "normalizers can't have a tokenizer",
+                ));
+            }
+        }
+        s.finish()?;
+        Ok(normalizer)
+    }
+
+    fn tokenizer(&mut self, name: &str) -> Result<Tokenizer> {
+        let (at, definition) = self.definition("tokenizer", name);
+        let builtin = builtin();
+        let mut s = Settings::new(&at, definition.unwrap_or(&builtin))?;
+        let kind = s.string("type")?.unwrap_or(name);
+        let tokenizer_type = match kind {
+            "standard" => TokenizerType::Standard,
+            "keyword" => TokenizerType::Keyword,
+            "char_group" => TokenizerType::CharacterGroup {
+                tokenize_on_chars: s
+                    .strings("tokenize_on_chars")?
+                    .iter()
+                    .map(|group| character_group(group, &at))
+                    .collect::<Result<_>>()?,
+            },
+            "path_hierarchy" => TokenizerType::PathHierarchy {
+                delimiter: s.char("delimiter", '/')?,
+                reverse: s.bool("reverse", false)?,
+            },
+            _ => return Err(unknown(&at, "tokenizer", kind)),
+        };
+        s.finish()?;
+        Ok(Tokenizer::new(name, tokenizer_type))
+    }
+
+    fn filters(&mut self, s: &mut Settings) -> Result<Vec<TokenFilter>> {
+        let names = s.strings("filter")?;
+        names.iter().map(|name| self.filter(name)).collect()
+    }
+
+    fn filter(&mut self, name: &str) -> Result<TokenFilter> {
+        let (at, definition) = self.definition("filter", name);
+        let builtin = builtin();
+        let mut s = Settings::new(&at, definition.unwrap_or(&builtin))?;
+        let kind = s.string("type")?.unwrap_or(name);
+        let filter_type = match kind {
+            "lowercase" => TokenFilterType::Lowercase,
+            "asciifolding" => TokenFilterType::Asciifolding {
+                preserve_original: s.bool("preserve_original", false)?,
+            },
+            "edge_ngram" => TokenFilterType::EdgeNgram {
+                min_gram: s.number("min_gram")?.unwrap_or(1),
+                max_gram: s.number("max_gram")?.unwrap_or(2),
+                preserve_original: s.bool("preserve_original", false)?,
+            },
+            "stop" => TokenFilterType::Stop {
+                stopwords: stopwords(&mut s)?,
+                ignore_case: s.bool("ignore_case", false)?,
+            },
+            "word_delimiter_graph" => TokenFilterType::WordDelimiterGraph {
+                generate_word_parts: s.bool("generate_word_parts", true)?,
+                generate_number_parts: s.bool("generate_number_parts", true)?,
+                catenate_all: s.bool("catenate_all", false)?,
+                split_on_case_change: s.bool("split_on_case_change", true)?,
+                split_on_numerics: s.bool("split_on_numerics", true)?,
+                preserve_original: s.bool("preserve_original", false)?,
+            },
+            "stemmer" => {
+                // `name` is an older spelling of `language`
+                let language = match s.string("language")? {
+                    Some(language) => language,
+                    None => s.string("name")?.unwrap_or("english"),
+                };
+                TokenFilterType::Stemmer {
+                    language: stemmer(language, &at)?,
+                }
+            }
+            "synonym_graph" => TokenFilterType::SynonymGraph {
+                expand: s.bool("expand", true)?,
+                lenient: s.bool("lenient", false)?,
+                synonyms: s
+                    .strings("synonyms")?
+                    .iter()
+                    .map(|rule| synonym(rule))
+                    .collect(),
+            },
+            "flatten_graph" => TokenFilterType::FlattenGraph,
+            "reverse" => TokenFilterType::Reverse,
+            "shingle" => TokenFilterType::Shingle {
+                max_shingle_size: s.number("max_shingle_size")?.unwrap_or(2),
+                min_shingle_size: s.number("min_shingle_size")?.unwrap_or(2),
+                output_unigrams: s.bool("output_unigrams", true)?,
+                output_unigrams_if_no_shingles: s.bool("output_unigrams_if_no_shingles", false)?,
+                token_separator: s.string_or("token_separator", " ")?,
+                filler_token: s.string_or("filler_token", "_")?,
+            },
+            _ => return Err(unknown(&at, "token filter", kind)),
+        };
+        s.finish()?;
+        Ok(TokenFilter::new(name, filter_type))
+    }
+
+    fn char_filters(&mut self, s: &mut Settings) -> Result<Vec<CharacterFilter>> {
+        let names = s.strings("char_filter")?;
+        names.iter().map(|name| self.char_filter(name)).collect()
+    }
+
+    fn char_filter(&mut self, name: &str) -> Result<CharacterFilter> {
+        let (at, definition) = self.definition("char_filter", name);
+        let builtin = builtin();
+        let mut s = Settings::new(&at, definition.unwrap_or(&builtin))?;
+        let kind = s.string("type")?.unwrap_or(name);
+        let filter_type = match kind {
+            "html_strip" => CharacterFilterType::HtmlStrip {
+                escaped_tags: s.strings("escaped_tags")?,
+            },
+            "mapping" => CharacterFilterType::Mapping {
+                mappings: s
+                    .strings("mappings")?
+                    .iter()
+                    .map(|mapping| match mapping.split_once("=>") {
+                        Some((from, to)) => Ok((from.trim().to_string(), to.trim().to_string())),
+                        None => Err(CodegenError::unsupported(
+                            &at,
+                            format!("`{}` isn't a mapping", mapping),
+                        )),
+                    })
+                    .collect::<Result<_>>()?,
+            },
+            "pattern_replace" => CharacterFilterType::PatternReplace {
+                pattern: match s.string("pattern")? {
+                    Some(pattern) => pattern.to_string(),
+                    None => return Err(CodegenError::unsupported(&at, "there's no pattern")),
+                },
+                replacement: s.string_or("replacement", "")?,
+                flags: s
+                    .string_or("flags", "")?
+                    .split('|')
+                    .filter(|flag| !flag.is_empty())
+                    .map(|flag| regex_flag(flag, &at))
+                    .collect::<Result<_>>()?,
+            },
+            _ => return Err(unknown(&at, "character filter", kind)),
+        };
+        s.finish()?;
+        Ok(CharacterFilter::new(name, filter_type))
+    }
+
+    // the root of the mappings, which may be under the name of a type in
+    // indices from before types were removed
+    fn mappings(&mut self, value: &'a Value, at: &str) -> Result<(Vec<Field>, Option<Dynamic>)> {
+        let mut s = Settings::new(at, value)?;
+        if let Some(mappings) = s.get("_doc") {
+            s.finish()?;
+            return self.mappings(mappings, &child(at, "_doc"));
+        }
+        let dynamic = match s.get("dynamic") {
+            None => None,
+            Some(dynamic) => Some(match dynamic {
+                Value::Bool(true) => Dynamic::True,
+                Value::Bool(false) => Dynamic::False,
+                Value::String(s) if s == "true" => Dynamic::True,
+                Value::String(s) if s == "false" => Dynamic::False,
+                Value::String(s) if s == "strict" => Dynamic::Strict,
+                Value::String(s) if s == "runtime" => Dynamic::Runtime,
+                _ => {
+                    return Err(CodegenError::unsupported(
+                        &s.at("dynamic"),
+                        "unknown dynamic mapping",
+                    ))
+                }
+            }),
+        };
+        if let Some(templates) = s.get("dynamic_templates") {
+            self.dynamic_templates(templates, &s.at("dynamic_templates"))?;
+        }
+        let mut fields = Vec::new();
+        if let Some(properties) = s.get("properties") {
+            self.properties(properties, &s.at("properties"), "", "", &mut fields)?;
+        }
+        s.finish()?;
+        if let Some((path, (at, _))) = self.templates.iter().next() {
+            return Err(CodegenError::unsupported(
+                at,
+                format!("there's no object at `{}` for the template to map", path),
+            ));
+        }
+        Ok((fields, dynamic))
+    }
+
+    // only templates for every value of an object can be represented, which
+    // are what sedk creates for maps
+    fn dynamic_templates(&mut self, value: &'a Value, at: &str) -> Result<()> {
+        let templates = match value {
+            Value::Array(templates) => templates,
+            _ => return Err(CodegenError::unsupported(at, "expected a list")),
+        };
+        for (i, template) in templates.iter().enumerate() {
+            let at = child(at, &i.to_string());
+            for (name, template) in object(&at, template)? {
+                let at = child(&at, name);
+                let mut s = Settings::new(&at, template)?;
+                let path = match s.string("path_match")?.and_then(|p| p.strip_suffix(".*")) {
+                    Some(path) if !path.contains('*') => path.to_string(),
+                    _ => {
+                        return Err(CodegenError::unsupported(
+                            &at,
+                            "only templates with a `path_match` of an object's values, such as `prices.*`, are supported",
+                        ))
+                    }
+                };
+                let mapping = match s.get("mapping") {
+                    Some(mapping) => mapping,
+                    None => return Err(CodegenError::unsupported(&at, "there's no mapping")),
+                };
+                s.finish()?;
+                self.templates
+                    .insert(path, (child(&at, "mapping"), mapping));
+            }
+        }
+        Ok(())
+    }
+
+    // The fields of an object. Objects are flattened into their parent's
+    // fields with dotted names, as sedk maps structs, while nested fields
+    // start their own, with `path` following the object from the root.
+    fn properties(
+        &mut self,
+        value: &'a Value,
+        at: &str,
+        path: &str,
+        prefix: &str,
+        fields: &mut Vec<Field>,
+    ) -> Result<()> {
+        for (name, definition) in object(at, value)? {
+            let at = child(at, name);
+            let mut s = Settings::new(&at, definition)?;
+            let full_path = format!("{}{}", path, name);
+            let field_name = format!("{}{}", prefix, name);
+            match s.string("type")?.unwrap_or("object") {
+                "object" => match (s.get("properties"), self.templates.remove(&full_path)) {
+                    (Some(properties), None) => self.properties(
+                        properties,
+                        &s.at("properties"),
+                        &format!("{}.", full_path),
+                        &format!("{}.", field_name),
+                        fields,
+                    )?,
+                    (None, Some((template_at, template))) => {
+                        // templates only add keys the object lets in, which
+                        // sedk's maps always do
+                        match s.get("dynamic") {
+                            None | Some(Value::Bool(true)) => (),
+                            Some(Value::String(dynamic)) if dynamic == "true" => (),
+                            Some(_) => {
+                                return Err(CodegenError::unsupported(
+                                    &s.at("dynamic"),
+                                    "maps always take new keys",
+                                ))
+                            }
+                        }
+                        let template =
+                            self.leaf(template, &template_at, &format!("{}.*", field_name))?;
+                        fields.push(Field::new(
+                            &field_name,
+                            FieldType::Object {
+                                templates: vec![template],
+                            },
+                        ));
+                    }
+                    (Some(_), Some((template_at, _))) => {
+                        return Err(CodegenError::unsupported(
+                            &template_at,
+                            "objects with properties can't have templates for their values",
+                        ))
+                    }
+                    (None, None) => {
+                        return Err(CodegenError::unsupported(
+                            &at,
+                            "objects without properties can't be mapped",
+                        ))
+                    }
+                },
+                "nested" => {
+                    let mut properties = Vec::new();
+                    match s.get("properties") {
+                        Some(value) => self.properties(
+                            value,
+                            &s.at("properties"),
+                            &format!("{}.", full_path),
+                            "",
+                            &mut properties,
+                        )?,
+                        None => {
+                            return Err(CodegenError::unsupported(
+                                &at,
+                                "nested fields without properties can't be mapped",
+                            ))
+                        }
+                    }
+                    let mut field = Field::new(&field_name, FieldType::Nested { properties });
+                    field.meta = meta(&mut s)?;
+                    fields.push(field);
+                }
+                kind => {
+                    fields.push(self.leaf_field(kind, &mut s, &field_name, false)?);
+                }
+            }
+            s.finish()?;
+        }
+        Ok(())
+    }
+
+    fn leaf(&mut self, value: &'a Value, at: &str, name: &str) -> Result<Field> {
+        let mut s = Settings::new(at, value)?;
+        let kind = match s.string("type")? {
+            Some(kind) => kind,
+            None => return Err(CodegenError::unsupported(at, "there's no type")),
+        };
+        let field = self.leaf_field(kind, &mut s, name, false)?;
+        s.finish()?;
+        Ok(field)
+    }
+
+    fn leaf_field(
+        &mut self,
+        kind: &str,
+        s: &mut Settings<'a>,
+        name: &str,
+        multi_field: bool,
+    ) -> Result<Field> {
+        let field_type = match kind {
+            "text" => {
+                // text is analyzed by the index's default analyzer unless
+                // told otherwise, which is elasticsearch's standard one when
+                // there isn't one
+                let analyzer = match s.string("analyzer")? {
+                    Some(analyzer) => self.analyzer(analyzer)?,
+                    None if self.has_definition("analyzer", "default") => {
+                        self.analyzer("default")?
+                    }
+                    None => Analyzer::standard(),
+                };
+                FieldType::Text {
+                    analyzer,
+                    search_analyzer: match s.string("search_analyzer")? {
+                        Some(analyzer) => Some(self.analyzer(analyzer)?),
+                        None => None,
+                    },
+                }
+            }
+            "keyword" => FieldType::Keyword {
+                normalizer: match s.string("normalizer")? {
+                    Some(normalizer) => self.normalizer(normalizer)?,
+                    None => Normalizer::default(),
+                },
+                ignore_above: s.number("ignore_above")?,
+            },
+            "binary" => FieldType::Binary,
+            "boolean" => FieldType::Boolean,
+            "date" => FieldType::Date {
+                format: s.string("format")?.map(str::to_string),
+            },
+            "ip" => FieldType::Ip,
+            "dense_vector" => FieldType::DenseVector {
+                dims: match s.number("dims")? {
+                    Some(dims) => dims,
+                    None => return Err(CodegenError::unsupported(&s.at, "dense vectors need `dims`")),
+                },
+            },
+            "long" => FieldType::Long,
+            "integer" => FieldType::Integer,
+            "short" => FieldType::Short,
+            "byte" => FieldType::Byte,
+            "double" => FieldType::Double,
+            "float" => FieldType::Float,
+            "half_float" => FieldType::HalfFloat,
+            "scaled_float" => FieldType::ScaledFloat {
+                scaling_factor: match s.number::<f64>("scaling_factor")? {
+                    Some(factor) if factor > 0.0 && factor.is_finite() => factor,
+                    Some(_) => {
+                        return Err(CodegenError::unsupported(
+                            &s.at("scaling_factor"),
+                            "scaling factors must be positive",
+                        ))
+                    }
+                    None => {
+                        return Err(CodegenError::unsupported(
+                            &s.at,
+                            "scaled floats need a `scaling_factor`",
+                        ))
+                    }
+                },
+            },
+            "unsigned_long" => FieldType::UnsignedLong,
+            "flattened" => FieldType::Flattened,
+            _ => {
+                return Err(CodegenError::unsupported(
+                    &s.at,
+                    format!("`{}` fields aren't supported", kind),
+                ))
+            }
+        };
+        let mut field = Field::new(name, field_type);
+        field.index = s.bool("index", true)?;
+        field.copy_to = s.strings("copy_to")?;
+        field.meta = meta(s)?;
+        if multi_field {
+            // multi-fields can't have their own, though sedk writes them empty
+            if let Some(Value::Object(fields)) = s.get("fields") {
+                if !fields.is_empty() {
+                    return Err(CodegenError::unsupported(
+                        &s.at("fields"),
+                        "multi-fields can't have multi-fields",
+                    ));
+                }
+            }
+            return Ok(field);
+        }
+        if let Some(multi_fields) = s.get("fields") {
+            let at = s.at("fields");
+            for (name, definition) in object(&at, multi_fields)? {
+                let at = child(&at, name);
+                let mut s = Settings::new(&at, definition)?;
+                let kind = match s.string("type")? {
+                    Some(kind) => kind,
+                    None => return Err(CodegenError::unsupported(&at, "there's no type")),
+                };
+                field
+                    .fields
+                    .push(self.leaf_field(kind, &mut s, name, true)?);
+                s.finish()?;
+            }
+        }
+        Ok(field)
+    }
+
+    fn has_definition(&self, kind: &str, name: &str) -> bool {
+        matches!(self.definitions.get(kind), Some((_, definitions)) if definitions.contains_key(name))
+    }
+}
+
+// analyzers and normalizers built from components
+fn custom(s: &mut Settings) -> Result<()> {
+    match s.string("type")? {
+        None | Some("custom") => Ok(()),
+        Some(kind) => Err(CodegenError::unsupported(
+            &s.at("type"),
+            format!(
+                "`{}` can't be configured, only custom ones are supported",
+                kind
+            ),
+        )),
+    }
+}
+
+fn unknown(at: &str, what: &str, kind: &str) -> CodegenError {
+    CodegenError::unsupported(at, format!("sedk has no {} of type `{}`", what, kind))
+}
+
+fn meta(s: &mut Settings) -> Result<BTreeMap<String, String>> {
+    let at = s.at("meta");
+    let mut meta = BTreeMap::new();
+    if let Some(value) = s.get("meta") {
+        for (key, value) in object(&at, value)? {
+            match value {
+                Value::String(value) => meta.insert(key.clone(), value.clone()),
+                _ => return Err(CodegenError::unsupported(&at, "expected strings")),
+            };
+        }
+    }
+    Ok(meta)
+}
+
+fn character_group(group: &str, at: &str) -> Result<CharacterGroups> {
+    let mut chars = group.chars();
+    Ok(match (group, chars.next(), chars.next()) {
+        ("whitespace", _, _) => CharacterGroups::Whitespace,
+        ("letter", _, _) => CharacterGroups::Letter,
+        ("digit", _, _) => CharacterGroups::Digit,
+        ("punctuation", _, _) => CharacterGroups::Punctuation,
+        ("symbol", _, _) => CharacterGroups::Symbol,
+        (_, Some(c), None) => CharacterGroups::Arbitrary(c),
+        _ => {
+            return Err(CodegenError::unsupported(
+                at,
+                format!("unknown character group `{}`", group),
+            ))
+        }
+    })
+}
+
+// predefined lists are named between underscores, and english is the default
+fn stopwords(s: &mut Settings) -> Result<Stopwords> {
+    let at = s.at("stopwords");
+    match s.get("stopwords") {
+        None => Ok(Stopwords::Predefined("english".to_string())),
+        Some(Value::String(list)) => match list.strip_prefix('_').and_then(|l| l.strip_suffix('_'))
+        {
+            Some(language) => Ok(Stopwords::Predefined(language.to_string())),
+            None => Ok(Stopwords::List(vec![list.clone()])),
+        },
+        Some(Value::Array(_)) => Ok(Stopwords::List(s.strings("stopwords")?)),
+        Some(_) => Err(CodegenError::unsupported(&at, "expected strings")),
+    }
+}
+
+fn synonym(rule: &str) -> Synonym {
+    let phrases = |phrases: &str| -> Vec<String> {
+        phrases
+            .split(',')
+            .map(str::trim)
+            .filter(|phrase| !phrase.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    match rule.split_once("=>") {
+        Some((from, to)) => Synonym {
+            from: phrases(from),
+            to: phrases(to),
+        },
+        None => Synonym {
+            from: phrases(rule),
+            to: Vec::new(),
+        },
+    }
+}
+
+fn stemmer(language: &str, at: &str) -> Result<StemmerLanguage> {
+    STEMMERS
+        .iter()
+        .find(|stemmer| serde_json::to_value(stemmer).ok() == Some(Value::from(language)))
+        .cloned()
+        .ok_or_else(|| CodegenError::unsupported(at, format!("unknown stemmer `{}`", language)))
+}
+
+fn regex_flag(flag: &str, at: &str) -> Result<RegexFlag> {
+    FLAGS
+        .iter()
+        .find(|f| String::from(*f) == flag)
+        .cloned()
+        .ok_or_else(|| CodegenError::unsupported(at, format!("unknown regex flag `{}`", flag)))
+}
+
+const FLAGS: &[RegexFlag] = &[
+    RegexFlag::CannonEq,
+    RegexFlag::CaseInsensitive,
+    RegexFlag::Comments,
+    RegexFlag::Dotall,
+    RegexFlag::Literal,
+    RegexFlag::Multiline,
+    RegexFlag::UnicodeCase,
+    RegexFlag::UnicodeCharacterClass,
+    RegexFlag::UnixLines,
+];
+
+const STEMMERS: &[StemmerLanguage] = &[
+    StemmerLanguage::Arabic,
+    StemmerLanguage::Armenian,
+    StemmerLanguage::Basque,
+    StemmerLanguage::Bengali,
+    StemmerLanguage::Brazilian,
+    StemmerLanguage::Bulgarian,
+    StemmerLanguage::Catalan,
+    StemmerLanguage::Czech,
+    StemmerLanguage::Danish,
+    StemmerLanguage::Dutch,
+    StemmerLanguage::DutchKp,
+    StemmerLanguage::English,
+    StemmerLanguage::LightEnglish,
+    StemmerLanguage::Lovins,
+    StemmerLanguage::MinimalEnglish,
+    StemmerLanguage::Porter2,
+    StemmerLanguage::PossessiveEnglish,
+    StemmerLanguage::Estonian,
+    StemmerLanguage::Finnish,
+    StemmerLanguage::LightFinnish,
+    StemmerLanguage::French,
+    StemmerLanguage::LightFrench,
+    StemmerLanguage::MinimalFrench,
+    StemmerLanguage::Galician,
+    StemmerLanguage::MinimalGalician,
+    StemmerLanguage::German,
+    StemmerLanguage::LightGerman,
+    StemmerLanguage::German2,
+    StemmerLanguage::MinimalGerman,
+    StemmerLanguage::Greek,
+    StemmerLanguage::Hindi,
+    StemmerLanguage::Hungarian,
+    StemmerLanguage::LightHungarian,
+    StemmerLanguage::Indonesian,
+    StemmerLanguage::Irish,
+    StemmerLanguage::Italian,
+    StemmerLanguage::LightItalian,
+    StemmerLanguage::Sorani,
+    StemmerLanguage::Latvian,
+    StemmerLanguage::Lithuanian,
+    StemmerLanguage::Norwegian,
+    StemmerLanguage::LightNorwegian,
+    StemmerLanguage::MinimalNorwegian,
+    StemmerLanguage::LightNynorsk,
+    StemmerLanguage::MinimalNynorsk,
+    StemmerLanguage::Portuguese,
+    StemmerLanguage::LightPortuguese,
+    StemmerLanguage::MinimalPortuguese,
+    StemmerLanguage::PortugueseRslp,
+    StemmerLanguage::Romanian,
+    StemmerLanguage::Russian,
+    StemmerLanguage::LightRussian,
+    StemmerLanguage::Spanish,
+    StemmerLanguage::LightSpanish,
+    StemmerLanguage::Swedish,
+    StemmerLanguage::LightSwedish,
+    StemmerLanguage::Turkish,
+];
@@ -0,0 +1,134 @@
+// Generated by sedk-codegen. Deriving the mapping of these types gives back the
+// mapping they were generated from.
+use sedk::{Analyzer, Indexable, IntoFields, Normalizer};
+use sedk::dsl::character_filters::{CharacterFilter, CharacterFilterType};
+use sedk::dsl::token_processing::{
+    StemmerLanguage,
+    Stopwords,
+    Synonym,
+    TokenFilter,
+    TokenFilterType,
+};
+use sedk::dsl::tokenizers::{Tokenizer, TokenizerType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+pub fn path() -> Analyzer {
+    Analyzer::builder("path")
+        .tokenizer(Tokenizer::new(
+            "path_tokenizer",
+            TokenizerType::PathHierarchy {
+                delimiter: '/',
+                reverse: false,
+            },
+        ))
+        .build()
+        .expect("the index's analyzers are valid")
+}
+
+pub fn folded() -> Normalizer {
+    Normalizer::builder("folded")
+        .filter(TokenFilter::new("lowercase", TokenFilterType::Lowercase))
+        .filter(TokenFilter::new(
+            "asciifolding",
+            TokenFilterType::Asciifolding {
+                preserve_original: false,
+            },
+        ))
+        .build()
+        .expect("the index's normalizers are valid")
+}
+
+pub fn product_text() -> Analyzer {
+    Analyzer::builder("product_text")
+        .char_filter(CharacterFilter::new(
+            "html_strip",
+            CharacterFilterType::HtmlStrip {
+                escaped_tags: vec![],
+            },
+        ))
+        .tokenizer(Tokenizer::new("standard", TokenizerType::Standard))
+        .filter(TokenFilter::new("lowercase", TokenFilterType::Lowercase))
+        .filter(TokenFilter::new(
+            "asciifolding",
+            TokenFilterType::Asciifolding {
+                preserve_original: false,
+            },
+        ))
+        .filter(TokenFilter::new(
+            "english_stop",
+            TokenFilterType::Stop {
+                stopwords: Stopwords::Predefined("english".to_string()),
+                ignore_case: false,
+            },
+        ))
+        .filter(TokenFilter::new(
+            "english_stemmer",
+            TokenFilterType::Stemmer {
+                language: StemmerLanguage::LightEnglish,
+            },
+        ))
+        .filter(TokenFilter::new(
+            "product_synonyms",
+            TokenFilterType::SynonymGraph {
+                expand: true,
+                lenient: false,
+                synonyms: vec![
+                    Synonym::equivalent(&["tv", "television"]),
+                    Synonym::explicit(&["laptop"], &["notebook", "laptop"]),
+                ],
+            },
+        ))
+        .build()
+        .expect("the index's analyzers are valid")
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, IntoFields, Indexable)]
+#[sedk(index = "products", alias = "catalog", shards = 3, replicas = 1, dynamic = "strict")]
+pub struct Product {
+    #[sedk(type = "keyword", ignore_above = 64)]
+    pub all_codes: Option<String>,
+    #[sedk(type = "text", analyzer = Analyzer::standard, index = false)]
+    pub body: Option<String>,
+    #[sedk(type = "text", analyzer = Analyzer::standard, subfield(name = "keyword", type = "keyword"))]
+    pub brand: Option<String>,
+    #[sedk(type = "scaled_float", scaling_factor = 100.0)]
+    pub discount: Option<f64>,
+    #[sedk(type = "dense_vector", dims = 3)]
+    pub embedding: Option<Vec<f32>>,
+    #[serde(rename = "inStock")]
+    pub in_stock: Option<bool>,
+    pub listed_from: Option<IpAddr>,
+    #[sedk(type = "text", analyzer = path)]
+    pub path: Option<String>,
+    #[sedk(meta(unit = "EUR"))]
+    pub price: Option<f64>,
+    #[sedk(type = "date", format = "strict_date||epoch_millis")]
+    pub released: Option<String>,
+    pub seller: Option<Seller>,
+    #[sedk(type = "keyword", normalizer = folded, copy_to = "all_codes")]
+    pub sku: Option<String>,
+    pub stock: Option<i32>,
+    pub stock_by_store: Option<HashMap<String, i32>>,
+    /// The name the product is sold under
+    #[sedk(type = "text", analyzer = product_text, subfield(name = "raw", normalizer = folded))]
+    pub title: Option<String>,
+    #[sedk(nested)]
+    #[serde(default)]
+    pub variants: Vec<Variants>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, IntoFields)]
+pub struct Seller {
+    pub id: Option<i64>,
+    #[sedk(type = "text", analyzer = Analyzer::standard)]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, IntoFields)]
+pub struct Variants {
+    #[sedk(type = "keyword")]
+    pub colour: Option<String>,
+    pub size: Option<i16>,
+}
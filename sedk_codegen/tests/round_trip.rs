@@ -0,0 +1,145 @@
+use sedk::{Indexable, Normalizer};
+use sedk_codegen::{codegen, parse};
+use serde_json::{json, Value};
+
+#[allow(dead_code)]
+mod generated {
+    include!("generated/products.rs");
+}
+
+const PRODUCTS: &str = include_str!("fixtures/products.json");
+
+#[test]
+fn generated_source_is_up_to_date() {
+    let source = codegen(PRODUCTS, "Product").unwrap();
+    assert_eq!(source, include_str!("generated/products.rs"));
+}
+
+// a mapping as elasticsearch reads it, with the defaults sedk writes out left
+// implicit: empty multi-fields, sedk's default normalizer, which changes
+// nothing, and text analyzed by the standard analyzer
+fn implicit_defaults(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if map.get("fields") == Some(&json!({})) {
+                map.remove("fields");
+            }
+            if map.get("normalizer") == Some(&json!(Normalizer::default().name)) {
+                map.remove("normalizer");
+            }
+            if map.get("type") == Some(&json!("text"))
+                && map.get("analyzer") == Some(&json!("standard"))
+            {
+                map.remove("analyzer");
+            }
+            map.values_mut().for_each(implicit_defaults);
+        }
+        Value::Array(values) => values.iter_mut().for_each(implicit_defaults),
+        // elasticsearch keeps whole numbers it was given as floats whole
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && n.is_f64() => *value = json!(f as i64),
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+// dotted property names are objects, and a template's name doesn't change
+// what it does, so templates are told apart by what they match
+fn expanded(mappings: &Value) -> Value {
+    fn expand(properties: &Value) -> Value {
+        let mut expanded = serde_json::Map::new();
+        for (name, field) in properties.as_object().unwrap() {
+            let mut field = field.clone();
+            if let Some(properties) = field.get("properties") {
+                field["properties"] = expand(properties);
+            }
+            let mut path: Vec<&str> = name.split('.').collect();
+            let last = path.pop().unwrap();
+            let mut object = &mut expanded;
+            for part in path {
+                object = object
+                    .entry(part)
+                    .or_insert_with(|| json!({"properties": {}}))["properties"]
+                    .as_object_mut()
+                    .unwrap();
+            }
+            object.insert(last.to_owned(), field);
+        }
+        Value::Object(expanded)
+    }
+    let mut mappings = mappings.clone();
+    mappings["properties"] = expand(&mappings["properties"]);
+    if let Some(templates) = mappings.get_mut("dynamic_templates") {
+        for template in templates.as_array_mut().unwrap() {
+            let (_, definition) = template.as_object().unwrap().iter().next().unwrap();
+            let path = definition["path_match"].as_str().unwrap().to_owned();
+            *template = json!({ path: definition.clone() });
+        }
+    }
+    mappings
+}
+
+#[test]
+fn deriving_the_generated_types_gives_back_the_mapping() {
+    let json: Value = serde_json::from_str(PRODUCTS).unwrap();
+    let index = parse(&json).unwrap();
+    assert_eq!(index.name.as_deref(), Some("products"));
+    assert!(generated::Product::index_mapping() == index.mapping);
+
+    let mut derived = serde_json::to_value(generated::Product::index_mapping()).unwrap();
+    implicit_defaults(&mut derived);
+    let products = &json["products"];
+    assert_eq!(
+        expanded(&derived["mappings"]),
+        expanded(&products["mappings"])
+    );
+    assert_eq!(derived["aliases"], products["aliases"]);
+    let settings = &products["settings"]["index"];
+    for setting in &["number_of_shards", "number_of_replicas"] {
+        assert_eq!(
+            derived["settings"][setting].to_string(),
+            settings[setting].as_str().unwrap()
+        );
+    }
+}
+
+#[test]
+fn mappings_sedk_writes_read_back_the_same() {
+    let mapping = generated::Product::index_mapping();
+    let json = serde_json::to_value(&mapping).unwrap();
+    assert!(parse(&json).unwrap().mapping == mapping);
+}
+
+#[test]
+fn unsupported_mappings_are_refused() {
+    let unsupported = [
+        (
+            r#"{"mappings": {"properties": {"point": {"type": "geo_point"}}}}"#,
+            "mappings.properties.point",
+        ),
+        (
+            r#"{"settings": {"analysis": {"analyzer": {"unused": {"tokenizer": "standard"}}}},
+                "mappings": {"properties": {}}}"#,
+            "settings.analysis.analyzer.unused",
+        ),
+        (
+            r#"{"mappings": {"properties": {"title": {"type": "text", "similarity": "boolean"}}}}"#,
+            "mappings.properties.title.similarity",
+        ),
+        (
+            r#"{"mappings": {"properties": {"price": {"type": "scaled_float"}}}}"#,
+            "mappings.properties.price",
+        ),
+        (
+            r#"{"mappings": {
+                "dynamic_templates": [{"tags": {"path_match": "tags.*", "mapping": {"type": "keyword"}}}],
+                "properties": {"tags": {"type": "object", "dynamic": "strict"}}}}"#,
+            "mappings.properties.tags.dynamic",
+        ),
+    ];
+    for (json, at) in &unsupported {
+        let error = codegen(json, "Document").unwrap_err().to_string();
+        assert!(error.starts_with(at), "{}", error);
+    }
+}
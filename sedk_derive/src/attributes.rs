@@ -0,0 +1,840 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, Attribute, Error, Ident, Lit, LitBool, LitFloat, LitInt, LitStr, Meta, Path,
+    Token,
+};
+
+// the names of the field types `type = "..."` accepts, how to build them,
+// and the kind of path they have
+const TYPES: &[(&str, &str, &str)] = &[
+    ("binary", "Binary", "Binary"),
+    ("boolean", "Boolean", "Boolean"),
+    ("keyword", "Keyword", "Keyword"),
+    ("text", "Text", "Text"),
+    ("date", "Date", "Date"),
+    ("ip", "Ip", "Ip"),
+    ("dense_vector", "DenseVector", "DenseVector"),
+    ("long", "Long", "Numeric"),
+    ("integer", "Integer", "Numeric"),
+    ("short", "Short", "Numeric"),
+    ("byte", "Byte", "Numeric"),
+    ("double", "Double", "Numeric"),
+    ("float", "Float", "Numeric"),
+    ("half_float", "HalfFloat", "Numeric"),
+    ("scaled_float", "ScaledFloat", "Numeric"),
+    ("unsigned_long", "UnsignedLong", "Numeric"),
+    ("flattened", "Flattened", "Flattened"),
+];
+
+// elasticsearch's limits on field meta, in entries and in characters
+pub const MAX_META_ENTRIES: usize = 5;
+pub const MAX_META_KEY_LENGTH: usize = 20;
+pub const MAX_META_LENGTH: usize = 50;
+
+// the values of `dynamic = "..."`, and the variants of `Dynamic` they name
+const DYNAMIC: &[(&str, &str)] = &[
+    ("true", "True"),
+    ("false", "False"),
+    ("strict", "Strict"),
+    ("runtime", "Runtime"),
+];
+
+/// The options of the `#[sedk(...)]` attributes on one field.
+#[derive(Default)]
+pub struct FieldAttributes {
+    pub skip: Option<Span>,
+    pub rename: Option<LitStr>,
+    pub mapping: Mapping,
+    pub index: Option<LitBool>,
+    pub copy_to: Vec<LitStr>,
+    pub subfields: Vec<Subfield>,
+    pub nested: Option<Span>,
+    /// The fields a `Document` takes its id, routing key and version from.
+    pub id: Option<Span>,
+    pub routing: Option<Span>,
+    pub version: Option<Span>,
+    /// Entries for the field's meta.
+    pub meta: Vec<(Ident, LitStr)>,
+    /// The first paragraph of the field's doc comment, for its meta.
+    pub description: Option<String>,
+    /// `false` gives the field an untyped path, for types without
+    /// `FieldPaths`.
+    pub paths: Option<LitBool>,
+}
+
+/// How a field, or one of its subfields, is analyzed.
+#[derive(Default)]
+pub struct Mapping {
+    pub field_type: Option<LitStr>,
+    pub analyzer: Option<Path>,
+    pub normalizer: Option<Path>,
+    /// The format of a date field.
+    pub format: Option<LitStr>,
+    /// The length above which a keyword field's values aren't indexed.
+    pub ignore_above: Option<LitInt>,
+    /// The number of dimensions of a dense vector field.
+    pub dims: Option<LitInt>,
+    /// What a scaled float's values are multiplied by before they're stored.
+    pub scaling_factor: Option<LitFloat>,
+}
+
+/// The options of the `#[sedk(...)]` attributes on a struct or enum.
+#[derive(Default)]
+pub struct ContainerAttributes {
+    /// Record the values of a unit-only enum in the field's meta.
+    pub allowed_values: Option<Span>,
+    /// The path to sedk, for crates that re-export it.
+    pub krate: Option<Path>,
+    /// The index an `Indexable` type is stored in, and the settings it's
+    /// created with.
+    pub index: Option<LitStr>,
+    pub alias: Option<LitStr>,
+    pub shards: Option<u32>,
+    pub replicas: Option<u32>,
+    pub dynamic: Option<Ident>,
+    /// `false` leaves out the typed paths, and the `fields()` that reaches
+    /// them.
+    pub paths: Option<LitBool>,
+}
+
+pub struct Subfield {
+    pub name: LitStr,
+    pub mapping: Mapping,
+}
+
+enum FieldOption {
+    Skip(Ident),
+    Rename(Ident, LitStr),
+    Index(Ident, LitBool),
+    CopyTo(LitStr),
+    Subfield(Subfield),
+    Nested(Ident),
+    Document(Ident),
+    Meta(Vec<(Ident, LitStr)>),
+    Paths(Ident, LitBool),
+    Mapping(Ident, MappingOption),
+}
+
+enum ContainerOption {
+    AllowedValues(Ident),
+    Crate(Ident, Path),
+    Index(Ident, LitStr),
+    Alias(Ident, LitStr),
+    Shards(Ident, u32),
+    Replicas(Ident, u32),
+    Dynamic(Ident, Ident),
+    Paths(Ident, LitBool),
+}
+
+enum MappingOption {
+    Type(LitStr),
+    Analyzer(Path),
+    Normalizer(Path),
+    Format(LitStr),
+    IgnoreAbove(LitInt),
+    Dims(LitInt),
+    ScalingFactor(LitFloat),
+}
+
+// a positive number, whole or not, written as a float literal
+fn scaling_factor(input: ParseStream) -> syn::Result<LitFloat> {
+    let (factor, span) = match input.parse()? {
+        Lit::Int(lit) => (lit.base10_parse::<f64>()?, lit.span()),
+        Lit::Float(lit) => (lit.base10_parse::<f64>()?, lit.span()),
+        other => return Err(Error::new(other.span(), "expected a number")),
+    };
+    if !(factor > 0.0 && factor.is_finite()) {
+        return Err(Error::new(span, "scaling factors must be positive"));
+    }
+    Ok(LitFloat::new(&format!("{:?}f64", factor), span))
+}
+
+// `type` is a keyword, so option names are parsed as any identifier
+fn option_name(input: ParseStream) -> syn::Result<Ident> {
+    Ident::parse_any(input)
+}
+
+fn parse_mapping_option(name: &Ident, input: ParseStream) -> syn::Result<Option<MappingOption>> {
+    let option = match name.to_string().as_str() {
+        "type" => {
+            input.parse::<Token![=]>()?;
+            let field_type: LitStr = input.parse()?;
+            if !TYPES.iter().any(|(t, _, _)| field_type.value() == *t) {
+                return Err(Error::new(
+                    field_type.span(),
+                    format!(
+                        "unknown field type `{}`, expected one of: {}",
+                        field_type.value(),
+                        TYPES
+                            .iter()
+                            .map(|(t, _, _)| *t)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ));
+            }
+            MappingOption::Type(field_type)
+        }
+        "analyzer" => {
+            input.parse::<Token![=]>()?;
+            MappingOption::Analyzer(input.parse()?)
+        }
+        "normalizer" => {
+            input.parse::<Token![=]>()?;
+            MappingOption::Normalizer(input.parse()?)
+        }
+        "format" => {
+            input.parse::<Token![=]>()?;
+            MappingOption::Format(input.parse()?)
+        }
+        "ignore_above" | "dims" => {
+            input.parse::<Token![=]>()?;
+            let value: LitInt = input.parse()?;
+            value.base10_parse::<u32>()?;
+            if name == "dims" {
+                MappingOption::Dims(value)
+            } else {
+                MappingOption::IgnoreAbove(value)
+            }
+        }
+        "scaling_factor" => {
+            input.parse::<Token![=]>()?;
+            MappingOption::ScalingFactor(scaling_factor(input)?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(option))
+}
+
+impl Parse for FieldOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = option_name(input)?;
+        if let Some(option) = parse_mapping_option(&name, input)? {
+            return Ok(FieldOption::Mapping(name, option));
+        }
+        match name.to_string().as_str() {
+            "skip" => Ok(FieldOption::Skip(name)),
+            "nested" => Ok(FieldOption::Nested(name)),
+            "id" | "routing" | "version" => Ok(FieldOption::Document(name)),
+            "rename" => {
+                input.parse::<Token![=]>()?;
+                Ok(FieldOption::Rename(name, input.parse()?))
+            }
+            "index" => {
+                input.parse::<Token![=]>()?;
+                Ok(FieldOption::Index(name, input.parse()?))
+            }
+            "paths" => {
+                input.parse::<Token![=]>()?;
+                Ok(FieldOption::Paths(name, input.parse()?))
+            }
+            "copy_to" => {
+                input.parse::<Token![=]>()?;
+                Ok(FieldOption::CopyTo(input.parse()?))
+            }
+            "subfield" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(FieldOption::Subfield(subfield(&name, &content)?))
+            }
+            "meta" => {
+                let content;
+                parenthesized!(content in input);
+                let mut entries = Vec::new();
+                while !content.is_empty() {
+                    let key = option_name(&content)?;
+                    content.parse::<Token![=]>()?;
+                    entries.push((key, content.parse()?));
+                    if !content.is_empty() {
+                        content.parse::<Token![,]>()?;
+                    }
+                }
+                Ok(FieldOption::Meta(entries))
+            }
+            _ => Err(Error::new(
+                name.span(),
+                format!("unknown sedk attribute `{}`", name),
+            )),
+        }
+    }
+}
+
+fn subfield(keyword: &Ident, input: ParseStream) -> syn::Result<Subfield> {
+    let mut name: Option<LitStr> = None;
+    let mut mapping = Mapping::default();
+    while !input.is_empty() {
+        let option = option_name(input)?;
+        if option == "name" {
+            input.parse::<Token![=]>()?;
+            set(&mut name, input.parse()?, &option)?;
+        } else if let Some(m) = parse_mapping_option(&option, input)? {
+            mapping.set(m, &option)?;
+        } else {
+            return Err(Error::new(
+                option.span(),
+                format!("unknown subfield option `{}`", option),
+            ));
+        }
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+    }
+    let name = name.ok_or_else(|| Error::new(keyword.span(), "subfields need a `name`"))?;
+    if mapping.is_empty() {
+        return Err(Error::new(
+            name.span(),
+            "subfields need a `type`, `analyzer` or `normalizer`",
+        ));
+    }
+    mapping.check()?;
+    Ok(Subfield { name, mapping })
+}
+
+fn set<T>(slot: &mut Option<T>, value: T, name: &Ident) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(Error::new(name.span(), format!("duplicate `{}`", name)));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+impl Mapping {
+    fn set(&mut self, option: MappingOption, name: &Ident) -> syn::Result<()> {
+        match option {
+            MappingOption::Type(t) => set(&mut self.field_type, t, name),
+            MappingOption::Analyzer(a) => set(&mut self.analyzer, a, name),
+            MappingOption::Normalizer(n) => set(&mut self.normalizer, n, name),
+            MappingOption::Format(f) => set(&mut self.format, f, name),
+            MappingOption::IgnoreAbove(i) => set(&mut self.ignore_above, i, name),
+            MappingOption::Dims(d) => set(&mut self.dims, d, name),
+            MappingOption::ScalingFactor(f) => set(&mut self.scaling_factor, f, name),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.field_type.is_none()
+            && self.analyzer.is_none()
+            && self.normalizer.is_none()
+            && self.format.is_none()
+            && self.ignore_above.is_none()
+            && self.dims.is_none()
+            && self.scaling_factor.is_none()
+    }
+
+    // analyzers only belong to text fields, normalizers and ignore_above to
+    // keyword fields, formats to date fields, dims to dense vectors and
+    // scaling factors to scaled floats
+    fn check(&self) -> syn::Result<()> {
+        let field_type = self.field_type.as_ref().map(LitStr::value);
+        if let Some(ignore_above) = &self.ignore_above {
+            let keyword = match &field_type {
+                Some(t) => t == "keyword",
+                None => self.normalizer.is_some(),
+            };
+            if !keyword {
+                return Err(Error::new(
+                    ignore_above.span(),
+                    "only keyword fields have `ignore_above`",
+                ));
+            }
+        }
+        match (&self.field_type, &self.dims) {
+            (Some(t), None) if t.value() == "dense_vector" => {
+                return Err(Error::new(t.span(), "dense vectors need `dims`"))
+            }
+            (Some(t), Some(_)) if t.value() == "dense_vector" => (),
+            (_, Some(dims)) => {
+                return Err(Error::new(
+                    dims.span(),
+                    "only dense vectors have `dims`, so it needs `type = \"dense_vector\"`",
+                ))
+            }
+            _ => (),
+        }
+        match (&self.field_type, &self.scaling_factor) {
+            (Some(t), None) if t.value() == "scaled_float" => {
+                return Err(Error::new(t.span(), "scaled floats need a `scaling_factor`"))
+            }
+            (Some(t), Some(_)) if t.value() == "scaled_float" => (),
+            (_, Some(factor)) => {
+                return Err(Error::new(
+                    factor.span(),
+                    "only scaled floats have a `scaling_factor`, so it needs `type = \"scaled_float\"`",
+                ))
+            }
+            _ => (),
+        }
+        if let Some(format) = &self.format {
+            if field_type.as_deref() != Some("date") {
+                return Err(Error::new(
+                    format.span(),
+                    "only date fields have a format, so it needs `type = \"date\"`",
+                ));
+            }
+        }
+        if let (Some(a), Some(_)) = (&self.analyzer, &self.normalizer) {
+            return Err(Error::new_spanned(
+                a,
+                "a field can't have both an analyzer and a normalizer",
+            ));
+        }
+        match (&field_type, &self.analyzer, &self.normalizer) {
+            (Some(t), Some(a), _) if t != "text" => {
+                Err(Error::new_spanned(a, "only text fields have an analyzer"))
+            }
+            (Some(t), _, Some(n)) if t != "keyword" => Err(Error::new_spanned(
+                n,
+                "only keyword fields have a normalizer",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The kind of path of an explicitly typed field.
+    pub fn path_kind(&self) -> Option<Ident> {
+        let field_type = self.field_type.as_ref()?.value();
+        let (_, _, kind) = TYPES.iter().find(|(t, _, _)| *t == field_type)?;
+        Some(Ident::new(kind, Span::call_site()))
+    }
+
+    /// The `FieldType` this mapping describes, if it names one.
+    pub fn field_type(&self) -> Option<TokenStream> {
+        let analyzer = match &self.analyzer {
+            Some(path) => quote! {#path()},
+            None => quote! {_sedk::Analyzer::default()},
+        };
+        let normalizer = match &self.normalizer {
+            Some(path) => quote! {#path()},
+            None => quote! {_sedk::Normalizer::default()},
+        };
+        let name = match &self.field_type {
+            Some(t) => t.value(),
+            None if self.analyzer.is_some() => "text".to_string(),
+            None if self.normalizer.is_some() => "keyword".to_string(),
+            None => return None,
+        };
+        Some(match name.as_str() {
+            "text" => quote! {_sedk::FieldType::Text {analyzer: #analyzer, search_analyzer: None}},
+            "keyword" => {
+                let ignore_above = match &self.ignore_above {
+                    Some(length) => quote! {Some(#length)},
+                    None => quote! {None},
+                };
+                quote! {_sedk::FieldType::Keyword {normalizer: #normalizer, ignore_above: #ignore_above}}
+            }
+            "dense_vector" => {
+                let dims = &self.dims;
+                quote! {_sedk::FieldType::DenseVector {dims: #dims}}
+            }
+            "scaled_float" => {
+                let scaling_factor = &self.scaling_factor;
+                quote! {_sedk::FieldType::ScaledFloat {scaling_factor: #scaling_factor}}
+            }
+            "date" => {
+                let format = match &self.format {
+                    Some(format) => quote! {Some(::std::string::String::from(#format))},
+                    None => quote! {None},
+                };
+                quote! {_sedk::FieldType::Date {format: #format}}
+            }
+            other => {
+                let (_, variant, _) = TYPES.iter().find(|(t, _, _)| *t == other).unwrap();
+                let variant = Ident::new(variant, Span::call_site());
+                quote! {_sedk::FieldType::#variant}
+            }
+        })
+    }
+}
+
+impl FieldAttributes {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<FieldAttributes> {
+        let mut parsed = FieldAttributes::default();
+        let mut errors: Option<Error> = None;
+        for attr in attrs.iter().filter(|a| a.path.is_ident("sedk")) {
+            let options = match attr
+                .parse_args_with(Punctuated::<FieldOption, Token![,]>::parse_terminated)
+            {
+                Ok(options) => options,
+                Err(e) => {
+                    combine(&mut errors, e);
+                    continue;
+                }
+            };
+            for option in options {
+                if let Err(e) = parsed.add(option) {
+                    combine(&mut errors, e);
+                }
+            }
+        }
+        parsed.description = description(attrs);
+        if let Err(e) = parsed.check() {
+            combine(&mut errors, e);
+        }
+        match errors {
+            Some(e) => Err(e),
+            None => Ok(parsed),
+        }
+    }
+
+    /// The field's meta as `(key, value)` pairs, the doc comment giving a
+    /// description unless one is set explicitly.
+    pub fn meta_entries(&self) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = self
+            .meta
+            .iter()
+            .map(|(key, value)| (key.unraw().to_string(), value.value()))
+            .collect();
+        // when there's room, as elasticsearch limits the number of entries
+        if let Some(description) = &self.description {
+            if entries.len() < MAX_META_ENTRIES
+                && !entries.iter().any(|(key, _)| key == "description")
+            {
+                entries.push(("description".to_string(), description.clone()));
+            }
+        }
+        entries
+    }
+
+    fn add(&mut self, option: FieldOption) -> syn::Result<()> {
+        match option {
+            FieldOption::Skip(name) => set(&mut self.skip, name.span(), &name),
+            FieldOption::Nested(name) => set(&mut self.nested, name.span(), &name),
+            FieldOption::Document(name) => {
+                let slot = match name.to_string().as_str() {
+                    "id" => &mut self.id,
+                    "routing" => &mut self.routing,
+                    _ => &mut self.version,
+                };
+                set(slot, name.span(), &name)
+            }
+            FieldOption::Rename(name, rename) => set(&mut self.rename, rename, &name),
+            FieldOption::Index(name, index) => set(&mut self.index, index, &name),
+            FieldOption::Paths(name, paths) => set(&mut self.paths, paths, &name),
+            FieldOption::CopyTo(target) => {
+                self.copy_to.push(target);
+                Ok(())
+            }
+            FieldOption::Subfield(subfield) => {
+                if let Some(existing) = self
+                    .subfields
+                    .iter()
+                    .find(|s| s.name.value() == subfield.name.value())
+                {
+                    let mut error = Error::new(subfield.name.span(), "duplicate subfield");
+                    error.combine(Error::new(existing.name.span(), "first defined here"));
+                    return Err(error);
+                }
+                self.subfields.push(subfield);
+                Ok(())
+            }
+            FieldOption::Meta(entries) => {
+                for (key, value) in entries {
+                    if let Some((existing, _)) = self.meta.iter().find(|(k, _)| *k == key) {
+                        let mut error = Error::new(key.span(), "duplicate meta entry");
+                        error.combine(Error::new(existing.span(), "first defined here"));
+                        return Err(error);
+                    }
+                    self.meta.push((key, value));
+                }
+                Ok(())
+            }
+            FieldOption::Mapping(name, option) => self.mapping.set(option, &name),
+        }
+    }
+
+    /// Whether no options were given.
+    pub fn is_empty(&self) -> bool {
+        self.skip.is_none()
+            && self.rename.is_none()
+            && self.mapping.is_empty()
+            && self.index.is_none()
+            && self.copy_to.is_empty()
+            && self.subfields.is_empty()
+            && self.nested.is_none()
+            && self.id.is_none()
+            && self.routing.is_none()
+            && self.version.is_none()
+            && self.meta.is_empty()
+            && self.paths.is_none()
+    }
+
+    fn check(&self) -> syn::Result<()> {
+        self.mapping.check()?;
+        if let Some(skip) = self.skip {
+            if self.rename.is_some()
+                || !self.mapping.is_empty()
+                || self.index.is_some()
+                || !self.copy_to.is_empty()
+                || !self.subfields.is_empty()
+                || self.nested.is_some()
+                || !self.meta.is_empty()
+                || self.paths.is_some()
+            {
+                // a skipped field can still identify its document
+                return Err(Error::new(
+                    skip,
+                    "skipped fields take no options besides `id`, `routing` and `version`",
+                ));
+            }
+        }
+        self.check_meta()?;
+        if let Some(nested) = self.nested {
+            if !self.mapping.is_empty() || !self.subfields.is_empty() {
+                return Err(Error::new(
+                    nested,
+                    "nested fields take their mapping from their type, and can't have a `type`, `analyzer`, `normalizer` or `subfield`",
+                ));
+            }
+            if self.index.is_some() || !self.copy_to.is_empty() {
+                return Err(Error::new(
+                    nested,
+                    "nested fields can't be combined with `index` or `copy_to`",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // explicit entries must fit elasticsearch's limits, while descriptions
+    // taken from doc comments are shortened to fit or left out
+    fn check_meta(&self) -> syn::Result<()> {
+        for (key, value) in &self.meta {
+            if key.unraw().to_string().chars().count() > MAX_META_KEY_LENGTH {
+                return Err(Error::new(
+                    key.span(),
+                    format!(
+                        "meta keys can't be longer than the {} characters elasticsearch allows",
+                        MAX_META_KEY_LENGTH
+                    ),
+                ));
+            }
+            if value.value().chars().count() > MAX_META_LENGTH {
+                return Err(Error::new(
+                    value.span(),
+                    format!(
+                        "meta values can't be longer than the {} characters elasticsearch allows",
+                        MAX_META_LENGTH
+                    ),
+                ));
+            }
+        }
+        if let Some((key, _)) = self.meta.get(MAX_META_ENTRIES) {
+            return Err(Error::new(
+                key.span(),
+                format!(
+                    "elasticsearch allows at most {} meta entries",
+                    MAX_META_ENTRIES
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// the first paragraph of a doc comment, shortened at a word to fit in meta
+fn description(attrs: &[Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path.is_ident("doc")) {
+        if let Ok(Meta::NameValue(meta)) = attr.parse_meta() {
+            if let Lit::Str(line) = meta.lit {
+                lines.push(line.value());
+            }
+        }
+    }
+    let paragraph: Vec<&str> = lines
+        .iter()
+        .flat_map(|line| line.split('\n'))
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .collect();
+    if paragraph.is_empty() {
+        return None;
+    }
+    let paragraph = paragraph.join(" ");
+    if paragraph.chars().count() <= MAX_META_LENGTH {
+        return Some(paragraph);
+    }
+    let mut description = String::new();
+    for word in paragraph.split(' ') {
+        // leaving room for a space and the ellipsis
+        if description.chars().count() + word.chars().count() + 2 > MAX_META_LENGTH {
+            break;
+        }
+        if !description.is_empty() {
+            description.push(' ');
+        }
+        description.push_str(word);
+    }
+    if description.is_empty() {
+        // a single word longer than the limit
+        description = paragraph.chars().take(MAX_META_LENGTH - 1).collect();
+    }
+    description.push('…');
+    Some(description)
+}
+
+impl Parse for ContainerOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = option_name(input)?;
+        match name.to_string().as_str() {
+            "allowed_values" => Ok(ContainerOption::AllowedValues(name)),
+            "paths" => {
+                input.parse::<Token![=]>()?;
+                Ok(ContainerOption::Paths(name, input.parse()?))
+            }
+            "crate" => {
+                input.parse::<Token![=]>()?;
+                let path: LitStr = input.parse()?;
+                Ok(ContainerOption::Crate(name, path.parse()?))
+            }
+            "index" | "alias" => {
+                input.parse::<Token![=]>()?;
+                let index: LitStr = input.parse()?;
+                check_index_name(&index)?;
+                match name.to_string().as_str() {
+                    "index" => Ok(ContainerOption::Index(name, index)),
+                    _ => Ok(ContainerOption::Alias(name, index)),
+                }
+            }
+            "shards" => {
+                input.parse::<Token![=]>()?;
+                let shards = count(input, "shards")?;
+                match shards.base10_parse()? {
+                    0 => Err(Error::new(
+                        shards.span(),
+                        "an index needs at least one shard",
+                    )),
+                    n => Ok(ContainerOption::Shards(name, n)),
+                }
+            }
+            "replicas" => {
+                input.parse::<Token![=]>()?;
+                let replicas = count(input, "replicas")?;
+                Ok(ContainerOption::Replicas(name, replicas.base10_parse()?))
+            }
+            "dynamic" => {
+                input.parse::<Token![=]>()?;
+                let dynamic: LitStr = input.parse()?;
+                match DYNAMIC.iter().find(|(d, _)| dynamic.value() == *d) {
+                    Some((_, variant)) => Ok(ContainerOption::Dynamic(
+                        name,
+                        Ident::new(variant, dynamic.span()),
+                    )),
+                    None => Err(Error::new(
+                        dynamic.span(),
+                        format!(
+                            "unknown dynamic mapping `{}`, expected one of: {}",
+                            dynamic.value(),
+                            DYNAMIC
+                                .iter()
+                                .map(|(d, _)| *d)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    )),
+                }
+            }
+            _ => Err(Error::new(
+                name.span(),
+                format!("unknown sedk attribute `{}`", name),
+            )),
+        }
+    }
+}
+
+impl ContainerAttributes {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<ContainerAttributes> {
+        let mut parsed = ContainerAttributes::default();
+        for attr in attrs.iter().filter(|a| a.path.is_ident("sedk")) {
+            let options =
+                attr.parse_args_with(Punctuated::<ContainerOption, Token![,]>::parse_terminated)?;
+            for option in options {
+                match option {
+                    ContainerOption::AllowedValues(name) => {
+                        set(&mut parsed.allowed_values, name.span(), &name)?
+                    }
+                    ContainerOption::Crate(name, path) => set(&mut parsed.krate, path, &name)?,
+                    ContainerOption::Index(name, index) => set(&mut parsed.index, index, &name)?,
+                    ContainerOption::Alias(name, alias) => set(&mut parsed.alias, alias, &name)?,
+                    ContainerOption::Shards(name, n) => set(&mut parsed.shards, n, &name)?,
+                    ContainerOption::Replicas(name, n) => set(&mut parsed.replicas, n, &name)?,
+                    ContainerOption::Dynamic(name, d) => set(&mut parsed.dynamic, d, &name)?,
+                    ContainerOption::Paths(name, paths) => set(&mut parsed.paths, paths, &name)?,
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Whether typed paths are generated, as they are unless turned off.
+    pub fn has_paths(&self) -> bool {
+        self.paths.as_ref().is_none_or(LitBool::value)
+    }
+
+    /// Puts the generated impl in a block that names sedk `_sedk`, so that it
+    /// works whatever the user has imported.
+    pub fn in_scope(&self, item: TokenStream) -> TokenStream {
+        let krate = match &self.krate {
+            Some(path) => quote! {#path},
+            None => quote! {::sedk},
+        };
+        quote! {
+            const _: () = {
+                use #krate as _sedk;
+                #item
+            };
+        }
+    }
+}
+
+// a number of shards or replicas, which can't be negative
+fn count(input: ParseStream, what: &str) -> syn::Result<LitInt> {
+    let lit: LitInt = input.parse()?;
+    match lit.base10_parse::<u32>() {
+        Ok(_) => Ok(lit),
+        Err(_) => Err(Error::new(
+            lit.span(),
+            format!(
+                "the number of {} must be a whole number of zero or more",
+                what
+            ),
+        )),
+    }
+}
+
+// elasticsearch's rules for index and alias names
+fn check_index_name(name: &LitStr) -> syn::Result<()> {
+    let value = name.value();
+    let problem = if value.is_empty() {
+        Some("can't be empty".to_string())
+    } else if value == "." || value == ".." {
+        Some("can't be `.` or `..`".to_string())
+    } else if value.len() > 255 {
+        Some("can't be longer than 255 bytes".to_string())
+    } else if value.starts_with(['-', '_', '+']) {
+        Some("can't start with `-`, `_` or `+`".to_string())
+    } else if value.chars().any(|c| c.is_uppercase()) {
+        Some("must be lowercase".to_string())
+    } else {
+        value
+            .chars()
+            .find(|c| "\\/*?\"<>| ,#:".contains(*c))
+            .map(|c| format!("can't contain `{}`", c))
+    };
+    match problem {
+        Some(problem) => Err(Error::new(name.span(), format!("index names {}", problem))),
+        None => Ok(()),
+    }
+}
+
+fn combine(errors: &mut Option<Error>, error: Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
@@ -0,0 +1,121 @@
+use crate::attributes::ContainerAttributes;
+use crate::into_fields::parse_attributes;
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Data, DataStruct, DeriveInput, Error, Fields, GenericArgument, PathArguments, Type};
+
+pub fn impl_document(input: DeriveInput) -> TokenStream {
+    match document(&input) {
+        Ok(document) => document,
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+fn document(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &input.ident;
+    let container = ContainerAttributes::parse(&input.attrs)?;
+    let fields = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(Error::new_spanned(
+                struct_name,
+                "Document can only be derived for structs with named fields",
+            ))
+        }
+    };
+    let attributes = parse_attributes(fields)?;
+
+    // the field each of id, routing and version is taken from
+    let mut id = None;
+    let mut routing = None;
+    let mut version = None;
+    for (field, (attributes, _)) in fields.iter().zip(&attributes) {
+        let marked = [
+            (&mut id, attributes.id, "id"),
+            (&mut routing, attributes.routing, "routing"),
+            (&mut version, attributes.version, "version"),
+        ];
+        for (slot, span, what) in marked {
+            if let Some(span) = span {
+                if slot.is_some() {
+                    return Err(Error::new(
+                        span,
+                        format!("only one field can be the document's {}", what),
+                    ));
+                }
+                *slot = Some(field);
+            }
+        }
+    }
+    let id = id.ok_or_else(|| {
+        Error::new_spanned(struct_name, "Document needs a field marked #[sedk(id)]")
+    })?;
+
+    // conversions are spanned on the field, where a type that can't be
+    // converted is reported
+    let id_name = &id.ident;
+    let id = quote_spanned! {id.ty.span()=> _sedk::AsId::as_id(&self.#id_name)};
+    let routing = routing.map(|field| {
+        let name = &field.ident;
+        let body = match option_of(&field.ty) {
+            Some(inner) => {
+                quote_spanned! {inner.span()=> self.#name.as_ref().map(|r| _sedk::AsId::as_id(r))}
+            }
+            None => quote_spanned! {field.ty.span()=> Some(_sedk::AsId::as_id(&self.#name))},
+        };
+        quote! {
+            fn routing(&self) -> Option<::std::borrow::Cow<'_, str>> {
+                #body
+            }
+        }
+    });
+    let version = version.map(|field| {
+        let name = &field.ident;
+        let body = match option_of(&field.ty) {
+            Some(inner) => quote_spanned! {inner.span()=> self.#name.map(u64::from)},
+            None => quote_spanned! {field.ty.span()=> Some(u64::from(self.#name))},
+        };
+        quote! {
+            // versions already held as u64 are converted too
+            #[allow(clippy::useless_conversion)]
+            fn version(&self) -> Option<u64> {
+                #body
+            }
+        }
+    });
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(container.in_scope(quote! {
+        #[automatically_derived]
+        impl #impl_generics _sedk::Document for #struct_name #ty_generics #where_clause {
+            fn id(&self) -> ::std::borrow::Cow<'_, str> {
+                #id
+            }
+            #routing
+            #version
+        }
+    }))
+}
+
+// the `T` of an `Option<T>`, recognised by name as derives have no types
+fn option_of(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    if last.ident != "Option" {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
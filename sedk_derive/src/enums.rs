@@ -0,0 +1,295 @@
+use crate::attributes::{ContainerAttributes, FieldAttributes, Mapping, MAX_META_LENGTH};
+use crate::into_fields::{field_mappings, json_name, parse_attributes};
+use crate::serde_attributes::{SerdeContainer, SerdeField};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use std::collections::HashMap;
+use syn::{ext::IdentExt, DataEnum, Error, Fields, GenericArgument, PathArguments, Type, Variant};
+
+/// The body of `into_fields` for an enum. Unit-only enums map to a keyword
+/// field and tagged enums to the union of their variants' fields, much as
+/// serde would serialize them.
+pub fn enum_fields(
+    data: &DataEnum,
+    container: &SerdeContainer,
+    attributes: &ContainerAttributes,
+) -> syn::Result<TokenStream> {
+    let variants: Vec<(&Variant, SerdeField)> = data
+        .variants
+        .iter()
+        .map(|v| (v, SerdeField::parse(&v.attrs)))
+        .filter(|(_, serde)| !serde.skip)
+        .collect();
+    let unit_only = variants
+        .iter()
+        .all(|(v, _)| matches!(v.fields, Fields::Unit));
+    if let (Some(span), false) = (
+        attributes.allowed_values,
+        unit_only && container.tag.is_none(),
+    ) {
+        return Err(Error::new(span, "only unit-only enums have allowed values"));
+    }
+    if container.untagged {
+        return Err(Error::new(
+            Span::call_site(),
+            "untagged enums can't be mapped, as their variants may serialize to anything",
+        ));
+    }
+    match &container.tag {
+        None if unit_only => unit_enum(&variants, container, attributes),
+        None => {
+            let (data_variant, _) = variants
+                .iter()
+                .find(|(v, _)| !matches!(v.fields, Fields::Unit))
+                .unwrap();
+            Err(Error::new_spanned(
+                &data_variant.ident,
+                "enums with data need #[serde(tag = \"...\")] to be mapped",
+            ))
+        }
+        Some(tag) => tagged_enum(&variants, container, tag),
+    }
+}
+
+fn variant_name(variant: &Variant, serde: &SerdeField, container: &SerdeContainer) -> String {
+    match (&serde.rename, container.rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(rule)) => rule.apply_to_variant(&variant.ident.unraw().to_string()),
+        (None, None) => variant.ident.unraw().to_string(),
+    }
+}
+
+fn unit_enum(
+    variants: &[(&Variant, SerdeField)],
+    container: &SerdeContainer,
+    attributes: &ContainerAttributes,
+) -> syn::Result<TokenStream> {
+    let meta = match attributes.allowed_values {
+        Some(span) => {
+            let values: Vec<String> = variants
+                .iter()
+                .map(|(v, serde)| variant_name(v, serde, container))
+                .collect();
+            let values = values.join(", ");
+            if values.len() > MAX_META_LENGTH {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "the allowed values are longer than the {} characters elasticsearch allows in field meta",
+                        MAX_META_LENGTH
+                    ),
+                ));
+            }
+            Some(quote! {field.meta.insert("allowed_values".to_owned(), #values.to_owned());})
+        }
+        None => None,
+    };
+    let mutable = meta.as_ref().map(|_| quote! {mut});
+    Ok(quote! {
+        match name {
+            None => Vec::new(),
+            Some(s) => {
+                let #mutable field = _sedk::Field::new(s, _sedk::FieldType::Keyword {normalizer: _sedk::Normalizer::default(), ignore_above: None});
+                #meta
+                vec![field]
+            }
+        }
+    })
+}
+
+fn tagged_enum(
+    variants: &[(&Variant, SerdeField)],
+    container: &SerdeContainer,
+    tag: &str,
+) -> syn::Result<TokenStream> {
+    let name_ident = Ident::new("s", Span::call_site());
+    // the fields of each variant, with the enum's name and with the content's
+    let mut without_name = Vec::new();
+    let mut with_name = Vec::new();
+    let mut seen: HashMap<String, (String, &Ident)> = HashMap::new();
+    for (variant, _) in variants {
+        match &variant.fields {
+            Fields::Unit => (),
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                // serde merges the fields of a newtype's value into the object
+                let inner = &fields.unnamed[0].ty;
+                without_name.push(quote! {vec![<#inner as _sedk::IntoFields>::into_fields(None)]});
+                with_name.push(quote! {vec![<#inner as _sedk::IntoFields>::into_fields(Some(#name_ident))]});
+            }
+            Fields::Unnamed(fields) => {
+                return Err(Error::new_spanned(
+                    fields,
+                    "tuple variants with several fields can't be tagged",
+                ))
+            }
+            Fields::Named(fields) => {
+                let variant_container = SerdeContainer {
+                    rename_all: SerdeContainer::parse(&variant.attrs)
+                        .rename_all
+                        .or(container.rename_all_fields),
+                    ..SerdeContainer::default()
+                };
+                let attributes = parse_attributes(&fields.named)?;
+                for (field, (attributes, serde)) in fields.named.iter().zip(&attributes) {
+                    if attributes.skip.is_some() || serde.skip || serde.flatten {
+                        continue;
+                    }
+                    let name = json_name(field, attributes, serde, variant_container.rename_all);
+                    let signature = signature(&field.ty, attributes);
+                    match seen.get(&name) {
+                        Some((first, first_variant)) if *first != signature => {
+                            let mut error = Error::new_spanned(
+                                field,
+                                format!(
+                                    "`{}` is mapped differently in variant `{}`",
+                                    name, first_variant
+                                ),
+                            );
+                            error.combine(Error::new_spanned(
+                                first_variant,
+                                format!("`{}` is first defined here", name),
+                            ));
+                            return Err(error);
+                        }
+                        Some(_) => (),
+                        None => {
+                            seen.insert(name, (signature, &variant.ident));
+                        }
+                    }
+                }
+                without_name.push(field_mappings(
+                    &fields.named,
+                    &attributes,
+                    &variant_container,
+                    None,
+                ));
+                with_name.push(field_mappings(
+                    &fields.named,
+                    &attributes,
+                    &variant_container,
+                    Some(&name_ident),
+                ));
+            }
+        }
+    }
+
+    if with_name.is_empty() {
+        // only the tag tells unit variants apart
+        return Ok(quote! {
+            match name {
+                None => vec![_sedk::Field::new(#tag, _sedk::FieldType::Keyword {normalizer: _sedk::Normalizer::default(), ignore_above: None})],
+                Some(s) => vec![_sedk::Field::new(&(s.to_owned() + "." + #tag), _sedk::FieldType::Keyword {normalizer: _sedk::Normalizer::default(), ignore_above: None})],
+            }
+        });
+    }
+    let variant_fields = match &container.content {
+        // internally tagged variants share the enum's object with the tag
+        None => quote! {
+            match name {
+                None => {#(fields.extend(#without_name);)*}
+                Some(#name_ident) => {#(fields.extend(#with_name);)*}
+            }
+        },
+        // adjacently tagged variants keep their fields under the content
+        Some(content) => quote! {
+            let content = match name {
+                None => #content.to_owned(),
+                Some(s) => s.to_owned() + "." + #content,
+            };
+            let #name_ident: &str = &content;
+            #(fields.extend(#with_name);)*
+        },
+    };
+    Ok(quote! {
+        let tag = match name {
+            None => #tag.to_owned(),
+            Some(s) => s.to_owned() + "." + #tag,
+        };
+        let mut fields: Vec<Vec<_sedk::Field>> = vec![vec![
+            _sedk::Field::new(&tag, _sedk::FieldType::Keyword {normalizer: _sedk::Normalizer::default(), ignore_above: None})
+        ]];
+        #variant_fields
+        // variants that share a field share its mapping. Those of newtype
+        // variants are only known once mapped, so a field they map
+        // differently is kept twice for `IndexMapping::check_fields` to find.
+        let mut mapped: Vec<_sedk::Field> = Vec::new();
+        for field in fields.into_iter().flatten() {
+            if !mapped.contains(&field) {
+                mapped.push(field);
+            }
+        }
+        mapped
+    })
+}
+
+// what decides how a field is mapped, to compare fields across variants
+fn signature(field_type: &Type, attributes: &FieldAttributes) -> String {
+    let copy_to: Vec<String> = attributes.copy_to.iter().map(|c| c.value()).collect();
+    let subfields: Vec<String> = attributes
+        .subfields
+        .iter()
+        .map(|s| format!("{}: {}", s.name.value(), mapping_signature(&s.mapping)))
+        .collect();
+    let meta: Vec<String> = attributes
+        .meta
+        .iter()
+        .map(|(key, value)| format!("{}={:?}", key, value.value()))
+        .collect();
+    format!(
+        "{} {} {:?} {:?} {:?} {:?} {:?} {}",
+        type_signature(field_type),
+        mapping_signature(&attributes.mapping),
+        attributes.index.as_ref().map(|i| i.value),
+        copy_to,
+        subfields,
+        meta,
+        attributes.description,
+        attributes.nested.is_some()
+    )
+}
+
+fn mapping_signature(mapping: &Mapping) -> String {
+    let analyzer = &mapping.analyzer;
+    let normalizer = &mapping.normalizer;
+    format!(
+        "{:?} {} {} {:?} {:?} {:?} {:?}",
+        mapping.field_type.as_ref().map(|t| t.value()),
+        quote! {#analyzer},
+        quote! {#normalizer},
+        mapping.format.as_ref().map(|f| f.value()),
+        mapping.ignore_above.as_ref().map(|i| i.to_string()),
+        mapping.dims.as_ref().map(|d| d.to_string()),
+        mapping.scaling_factor.as_ref().map(|s| s.to_string()),
+    )
+}
+
+// a type as far as its mapping goes: every field may be missing, so options
+// map like their values, and paths are compared by their last segment so that
+// `String` and `std::string::String` agree
+fn type_signature(field_type: &Type) -> String {
+    let path = match field_type {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return quote! {#field_type}.to_string(),
+    };
+    let last = match path.segments.last() {
+        Some(last) => last,
+        None => return quote! {#field_type}.to_string(),
+    };
+    let arguments: Vec<String> = match &last.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments
+            .args
+            .iter()
+            .map(|argument| match argument {
+                GenericArgument::Type(t) => type_signature(t),
+                other => quote! {#other}.to_string(),
+            })
+            .collect(),
+        PathArguments::Parenthesized(arguments) => vec![quote! {#arguments}.to_string()],
+        PathArguments::None => Vec::new(),
+    };
+    match (last.ident.to_string().as_str(), arguments.as_slice()) {
+        ("Option", [inner]) => inner.clone(),
+        (ident, []) => ident.to_owned(),
+        (ident, arguments) => format!("{}<{}>", ident, arguments.join(", ")),
+    }
+}
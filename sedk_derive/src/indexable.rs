@@ -1,16 +1,67 @@
+use crate::attributes::ContainerAttributes;
+use crate::into_fields::bounded_generics;
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::DeriveInput;
+use syn::{parse_quote, DeriveInput};
 
 pub fn impl_indexable(input: DeriveInput) -> TokenStream {
     let struct_name = input.ident;
+    let attributes = match ContainerAttributes::parse(&input.attrs) {
+        Ok(attributes) => attributes,
+        Err(e) => return e.to_compile_error(),
+    };
+    let generics = bounded_generics(&input.generics, parse_quote!(_sedk::IntoFields));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // the names the index is known by, for callers to create and query it
+    let index_name = attributes.index.as_ref().map(|index| {
+        quote! {pub const INDEX_NAME: &'static str = #index;}
+    });
+    let alias = attributes.alias.as_ref().map(|alias| {
+        quote! {pub const ALIAS: &'static str = #alias;}
+    });
+    let constants = if index_name.is_some() || alias.is_some() {
+        Some(quote! {
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                #index_name
+                #alias
+            }
+        })
+    } else {
+        None
+    };
+
+    let option = |value: Option<TokenStream>| match value {
+        Some(value) => quote! {Some(#value)},
+        None => quote! {None},
+    };
+    let shards = option(attributes.shards.map(|n| quote! {#n}));
+    let replicas = option(attributes.replicas.map(|n| quote! {#n}));
+    let dynamic = option(
+        attributes
+            .dynamic
+            .as_ref()
+            .map(|variant| quote! {_sedk::Dynamic::#variant}),
+    );
+    let aliases = attributes.alias.iter();
+
+    attributes.in_scope(quote! {
+        #constants
 
-    quote! {
         #[automatically_derived]
-        impl Indexable for #struct_name {
-            fn index_mapping() -> IndexMapping {
-                IndexMapping {fields: #struct_name::into_fields(None)}
+        impl #impl_generics _sedk::Indexable for #struct_name #ty_generics #where_clause {
+            fn index_mapping() -> _sedk::IndexMapping {
+                _sedk::IndexMapping {
+                    fields: <Self as _sedk::IntoFields>::into_fields(None),
+                    plugins: Vec::new(),
+                    settings: _sedk::IndexSettings {
+                        number_of_shards: #shards,
+                        number_of_replicas: #replicas,
+                    },
+                    aliases: vec![#(#aliases.to_owned()),*],
+                    dynamic: #dynamic,
+                }
             }
         }
-    }
+    })
 }
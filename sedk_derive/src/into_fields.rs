@@ -1,59 +1,322 @@
+use crate::attributes::{ContainerAttributes, FieldAttributes};
+use crate::enums::enum_fields;
+use crate::paths::{delegated_paths, struct_paths, Paths};
+use crate::serde_attributes::{RenameRule, SerdeContainer, SerdeField};
 use proc_macro2::{TokenStream, Ident, Span};
-use quote::{quote};
-use syn::{Data, DataStruct, DeriveInput, Field, Fields, punctuated::Punctuated};
+use quote::{quote, quote_spanned};
+use syn::{Data, DataStruct, DeriveInput, Error, Field, Fields, FieldsUnnamed, GenericParam, Generics, ext::IdentExt, parse_quote, punctuated::Punctuated, spanned::Spanned};
 
 
-const BREAK_GLASS: &'static str = "IntoFields can only be derived for structs with named fields.";
+const BREAK_GLASS: &str = "IntoFields can only be derived for structs and enums.";
+
+/// The generics of a derived impl, with every type parameter bound by the
+/// trait being derived, such as `IntoFields` so that fields of those types can
+/// be mapped.
+pub fn bounded_generics(generics: &Generics, bound: syn::Path) -> Generics {
+    let mut generics = generics.clone();
+    for param in &mut generics.params {
+        if let GenericParam::Type(type_param) = param {
+            type_param.bounds.push(parse_quote!(#bound));
+        }
+    }
+    generics
+}
 
 pub fn impl_into_fields(input: DeriveInput) -> TokenStream {
-    let struct_name = input.ident;
-    let struct_fields = match input.data {
+    let struct_name = &input.ident;
+    let generics = bounded_generics(&input.generics, parse_quote!(_sedk::IntoFields));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let container = SerdeContainer::parse(&input.attrs);
+    let attributes = match ContainerAttributes::parse(&input.attrs) {
+        Ok(attributes) => attributes,
+        Err(e) => return e.to_compile_error(),
+    };
+    let body = match &input.data {
         // copied from https://blog.turbo.fish/proc-macro-simple-derive/
         Data::Struct(DataStruct {
             fields: Fields::Named(fields),
             ..
-        }) => fields.named,
-        _ => panic!(BREAK_GLASS),
+        }) => match attributes.allowed_values {
+            Some(span) => Err(Error::new(span, "only unit-only enums have allowed values")),
+            None => struct_fields(&input, &fields.named, &container),
+        },
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(fields),
+            ..
+        }) => match attributes.allowed_values {
+            Some(span) => Err(Error::new(span, "only unit-only enums have allowed values")),
+            None => newtype_fields(&input, fields),
+        },
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            struct_token,
+            ..
+        }) => Err(Error::new_spanned(struct_token, "unit structs serialize to null, so have no fields to map")),
+        Data::Enum(data) => enum_fields(data, &container, &attributes).map(|body| {
+            // a tagged enum's fields depend on the variant
+            let paths = match container.tag {
+                None => quote! {_sedk::dsl::paths::FieldPath<_sedk::dsl::paths::kind::Keyword>},
+                Some(_) => quote! {_sedk::dsl::paths::ObjectPath},
+            };
+            (body, delegated_paths(&input, paths))
+        }),
+        Data::Union(data) => Err(Error::new_spanned(data.union_token, BREAK_GLASS)),
     };
-    let name_ident = Ident::new("s", Span::call_site());
-
-    let without_name = field_mappings(&struct_fields, None); // we need the "code" for both branches.
-    let with_name = field_mappings(&struct_fields, Some(&name_ident));
+    let (body, Paths { items, impls }) = match body {
+        Ok((body, _)) if !attributes.has_paths() => (body, Paths::default()),
+        Ok(body) => body,
+        Err(e) => return e.to_compile_error(),
+    };
+    let own_field = own_field(&input, &generics, &container);
 
+    let derived = attributes.in_scope(quote! {
+        #[automatically_derived]
+        impl #impl_generics _sedk::IntoFields for #struct_name #ty_generics #where_clause {
+            fn into_fields(name: Option<&str>) -> Vec<_sedk::Field> {
+                #body
+            }
+        }
 
+        #own_field
+        #impls
+    });
     quote! {
-        #[automatically_derived]
-        impl IntoFields for #struct_name {
-            fn into_fields(name: Option<&str>) -> Vec<Field> {
-                let fields: Vec<Vec<Field>> = match name {
-                    None => #without_name,
-                    Some(#name_ident) => #with_name
-                };
-                fields.into_iter().flatten().collect()
+        #items
+        #derived
+    }
+}
+
+// unit-only enums map to a keyword and newtypes like the type they wrap, so
+// both can have their field changed where they're used
+fn own_field(input: &DeriveInput, generics: &Generics, container: &SerdeContainer) -> Option<TokenStream> {
+    let struct_name = &input.ident;
+    let mut generics = generics.clone();
+    match &input.data {
+        Data::Enum(data) if container.tag.is_none() => {
+            if !data.variants.iter().all(|v| matches!(v.fields, Fields::Unit)) {
+                return None;
             }
         }
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(fields),
+            ..
+        }) => {
+            let field_type = &fields.unnamed.first()?.ty;
+            generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#field_type: _sedk::HasOwnField));
+        }
+        _ => return None,
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Some(quote! {
+        #[automatically_derived]
+        impl #impl_generics _sedk::HasOwnField for #struct_name #ty_generics #where_clause {}
+    })
+}
+
+fn struct_fields<T>(input: &DeriveInput, struct_fields: &Punctuated<Field, T>, container: &SerdeContainer) -> syn::Result<(TokenStream, Paths)> {
+    let attributes = parse_attributes(struct_fields)?;
+    let name_ident = Ident::new("s", Span::call_site());
+
+    let without_name = field_mappings(struct_fields, &attributes, container, None); // we need the "code" for both branches.
+    let with_name = field_mappings(struct_fields, &attributes, container, Some(&name_ident));
+
+    let body = quote! {
+        let fields: Vec<Vec<_sedk::Field>> = match name {
+            None => #without_name,
+            Some(#name_ident) => #with_name
+        };
+        fields.into_iter().flatten().collect()
+    };
+    Ok((body, struct_paths(input, struct_fields, &attributes, container)))
+}
+
+// serde serializes a newtype as the value it wraps, so it maps like it too
+fn newtype_fields(input: &DeriveInput, struct_fields: &FieldsUnnamed) -> syn::Result<(TokenStream, Paths)> {
+    if struct_fields.unnamed.len() != 1 {
+        return Err(Error::new_spanned(
+            struct_fields,
+            "tuple structs with several fields serialize to arrays, which can't be mapped",
+        ));
+    }
+    let field = &struct_fields.unnamed[0];
+    if !FieldAttributes::parse(&field.attrs)?.is_empty() {
+        return Err(Error::new_spanned(
+            field,
+            "newtypes map like the type they wrap; put #[sedk(...)] on the fields that hold them instead",
+        ));
     }
+    let field_type = &field.ty;
+    let body = quote! {<#field_type as _sedk::IntoFields>::into_fields(name)};
+    let paths = quote! {<#field_type as _sedk::dsl::paths::FieldPaths>::Paths};
+    Ok((body, delegated_paths(input, paths)))
 }
 
-fn field_mappings<T>(struct_fields: &Punctuated<Field, T>, with_name: Option<&Ident>) -> TokenStream {
-    let field_mappings = struct_fields.iter().map(|field| {
-        let field_name = match (&field.ident, with_name) {
-            (None, _) => panic!(BREAK_GLASS),
-            (Some(ident), None) => {
-                let as_str = ident.to_string();
-                quote! {Some(#as_str)}
+// every field's attributes, or all of the errors in them
+pub fn parse_attributes<T>(struct_fields: &Punctuated<Field, T>) -> syn::Result<Vec<(FieldAttributes, SerdeField)>> {
+    let mut attributes = Vec::new();
+    let mut errors: Option<Error> = None;
+    for field in struct_fields {
+        let serde = SerdeField::parse(&field.attrs);
+        let parsed = FieldAttributes::parse(&field.attrs).and_then(|a| {
+            // a flattened field's fields belong to this struct
+            if serde.flatten && !a.is_empty() {
+                Err(Error::new_spanned(field, "flattened fields have no mapping of their own"))
+            } else {
+                Ok((a, serde))
+            }
+        });
+        match parsed {
+            Ok(a) => attributes.push(a),
+            Err(e) => match &mut errors {
+                Some(errors) => errors.combine(e),
+                None => errors = Some(e),
             },
-            (Some(ident), Some(s)) => {
-                let as_str = ident.to_string();
-                quote! {
-                    Some(&(#s.to_owned() + "." + #as_str))
-                }
+        }
+    }
+    match errors {
+        Some(e) => Err(e),
+        None => Ok(attributes),
+    }
+}
+
+/// The name of a field in the json its struct serializes to.
+pub fn json_name(field: &Field, attributes: &FieldAttributes, serde: &SerdeField, rename_all: Option<RenameRule>) -> String {
+    match (&field.ident, &attributes.rename, &serde.rename) {
+        (None, _, _) => panic!("{}", BREAK_GLASS),
+        (Some(_), Some(rename), _) => rename.value(),
+        (Some(_), None, Some(rename)) => rename.clone(),
+        (Some(ident), None, None) => {
+            let ident = ident.unraw().to_string();
+            match rename_all {
+                Some(rule) => rule.apply_to_field(&ident),
+                None => ident,
             }
-        };
-        let field_type = &field.ty;
-        quote! {
-            #field_type::into_fields(#field_name)
         }
-    });
-    return quote! {vec![#(#field_mappings),*]}
+    }
+}
+
+/// The fields each struct field maps to, as a `Vec<Vec<Field>>`, with the
+/// struct's own name in `with_name`.
+pub fn field_mappings<T>(
+    struct_fields: &Punctuated<Field, T>,
+    attributes: &[(FieldAttributes, SerdeField)],
+    container: &SerdeContainer,
+    with_name: Option<&Ident>,
+) -> TokenStream {
+    let full_name = |as_str: &str| match with_name {
+        None => quote! {#as_str},
+        Some(s) => quote! {&(#s.to_owned() + "." + #as_str)},
+    };
+    // serde records the name of an internally tagged struct in its tag
+    let tag = container.tag.as_deref().map(|tag| {
+        let field_name = full_name(tag);
+        quote! {vec![_sedk::Field::new(#field_name, _sedk::FieldType::Keyword {normalizer: _sedk::Normalizer::default(), ignore_above: None})]}
+    }).into_iter();
+    let field_mappings = struct_fields
+        .iter()
+        .zip(attributes)
+        .filter(|(_, (attributes, serde))| attributes.skip.is_none() && !serde.skip)
+        .map(|(field, (attributes, serde))| {
+            let field_type = &field.ty;
+            if serde.flatten {
+                let prefix = match with_name {
+                    None => quote! {None},
+                    Some(s) => quote! {Some(#s)},
+                };
+                return quote! {<#field_type as _sedk::IntoFields>::into_fields(#prefix)};
+            }
+            let as_str = json_name(field, attributes, serde, container.rename_all);
+            mapping(field_type, full_name(&as_str), attributes)
+        });
+    quote! {vec![#(#tag,)* #(#field_mappings),*]}
+}
+
+fn mapping(field_type: &syn::Type, field_name: TokenStream, attributes: &FieldAttributes) -> TokenStream {
+    let typed = attributes.mapping.field_type.is_some();
+    let base = if attributes.nested.is_some() {
+        quote! {vec![_sedk::Field::new(name, _sedk::FieldType::Nested {properties: <#field_type as _sedk::IntoFields>::into_fields(None)})]}
+    } else if typed {
+        let mapped = attributes.mapping.field_type();
+        quote! {vec![_sedk::Field::new(name, #mapped)]}
+    } else {
+        quote! {<#field_type as _sedk::IntoFields>::into_fields(Some(name))}
+    };
+
+    let mut modifications = Vec::new();
+    // an analyzer or normalizer alone changes the type of the field the type
+    // maps to, keeping its subfields
+    let retyped = match attributes.mapping.field_type() {
+        Some(mapped) if !typed => Some(quote! {field.field_type = #mapped;}),
+        _ => None,
+    };
+    // the description from a doc comment follows any meta set explicitly
+    let entries = attributes.meta_entries();
+    let (meta, documented) = entries.split_at(attributes.meta.len());
+    let insert = |(key, value): &(String, String)| quote! {field.meta.insert(#key.to_owned(), #value.to_owned());};
+    if retyped.is_some() || !attributes.subfields.is_empty() || !meta.is_empty() {
+        let subfields = attributes.subfields.iter().map(|subfield| {
+            let sub_name = &subfield.name;
+            let sub_type = subfield.mapping.field_type();
+            quote! {field.fields.push(_sedk::Field::new(#sub_name, #sub_type));}
+        });
+        let meta = meta.iter().map(insert);
+        // types mapping only to fields under the name, such as structs,
+        // have no field for these to change
+        let own_field = match (typed, &attributes.nested) {
+            (false, None) => Some(quote_spanned! {field_type.span()=>
+                _sedk::dsl::primitives::has_own_field::<#field_type>();
+            }),
+            _ => None,
+        };
+        modifications.push(quote! {
+            #own_field
+            if let Some(field) = fields.iter_mut().find(|f| f.name == name) {
+                #retyped
+                #(#subfields)*
+                #(#meta)*
+            }
+        });
+    }
+    if !documented.is_empty() {
+        // objects have no meta, so their doc comments are left out
+        let documented = documented.iter().map(insert);
+        modifications.push(quote! {
+            if let Some(field) = fields.iter_mut().find(|f| f.name == name) {
+                #(#documented)*
+            }
+        });
+    }
+    if matches!(&attributes.index, Some(index) if !index.value) {
+        // multi-fields of an unindexed field aren't searchable either
+        modifications.push(quote! {
+            for field in fields.iter_mut() {
+                field.index = false;
+                for subfield in field.fields.iter_mut() {
+                    subfield.index = false;
+                }
+            }
+        });
+    }
+    if !attributes.copy_to.is_empty() {
+        let targets = &attributes.copy_to;
+        modifications.push(quote! {
+            for field in fields.iter_mut() {
+                #(field.copy_to.push(#targets.to_owned());)*
+            }
+        });
+    }
+
+    if modifications.is_empty() && !typed && attributes.nested.is_none() {
+        return quote! {<#field_type as _sedk::IntoFields>::into_fields(Some(#field_name))};
+    }
+    let mutable = if modifications.is_empty() { quote! {} } else { quote! {mut} };
+    quote! {{
+        let name: &str = #field_name;
+        let #mutable fields: Vec<_sedk::Field> = #base;
+        #(#modifications)*
+        fields
+    }}
 }
@@ -1,8 +1,8 @@
 /// # Examples
 /// ```rust
 /// use sedk::{IntoFields, Indexable, IndexMapping, Field, FieldType, Analyzer, Normalizer};
-/// use sedk_derive::{IntoFields, Indexable};
-
+/// use std::collections::BTreeMap;
+///
 /// #[derive(IntoFields)]
 /// pub struct Child {
 ///     integer: i32,
@@ -13,7 +13,7 @@
 /// pub struct Parent {
 ///     nested: Child
 /// }
-
+///
 /// fn main() {
 ///     let fields = Child::into_fields(None);
 ///     assert_eq!(
@@ -22,18 +22,27 @@
 ///             Field {
 ///                 name: "integer".to_owned(), 
 ///                 field_type: FieldType::Integer, 
-///                 fields: Vec::new()
+///                 fields: Vec::new(),
+///                 index: true,
+///                 copy_to: Vec::new(),
+///                 meta: BTreeMap::new()
 ///             },
 ///             Field {
 ///                 name: "string".to_owned(),
-///                 field_type: FieldType::Text {analyzer: Analyzer::default()},
+///                 field_type: FieldType::Text {analyzer: Analyzer::default(), search_analyzer: None},
 ///                 fields: vec![
 ///                     Field {
-///                         name: "string.keyword".to_owned(),
-///                         field_type: FieldType::Keyword {normalizer: Normalizer::default()},
-///                         fields: Vec::new()
+///                         name: "keyword".to_owned(),
+///                         field_type: FieldType::Keyword {normalizer: Normalizer::default(), ignore_above: None},
+///                         fields: Vec::new(),
+///                         index: true,
+///                         copy_to: Vec::new(),
+///                         meta: BTreeMap::new()
 ///                     }
-///                 ]
+///                 ],
+///                 index: true,
+///                 copy_to: Vec::new(),
+///                 meta: BTreeMap::new()
 ///             }
 ///         ]
 ///     );
@@ -44,39 +53,60 @@
 ///             Field {
 ///                 name: "nested.integer".to_owned(), 
 ///                 field_type: FieldType::Integer, 
-///                 fields: Vec::new()
+///                 fields: Vec::new(),
+///                 index: true,
+///                 copy_to: Vec::new(),
+///                 meta: BTreeMap::new()
 ///             },
 ///             Field {
 ///                 name: "nested.string".to_owned(),
-///                 field_type: FieldType::Text {analyzer: Analyzer::default()},
+///                 field_type: FieldType::Text {analyzer: Analyzer::default(), search_analyzer: None},
 ///                 fields: vec![
 ///                     Field {
-///                         name: "nested.string.keyword".to_owned(),
-///                         field_type: FieldType::Keyword {normalizer: Normalizer::default()},
-///                         fields: Vec::new()
+///                         name: "keyword".to_owned(),
+///                         field_type: FieldType::Keyword {normalizer: Normalizer::default(), ignore_above: None},
+///                         fields: Vec::new(),
+///                         index: true,
+///                         copy_to: Vec::new(),
+///                         meta: BTreeMap::new()
 ///                     }
-///                 ]
+///                 ],
+///                 index: true,
+///                 copy_to: Vec::new(),
+///                 meta: BTreeMap::new()
 ///             }
 ///         ]
 ///     );
 /// }
 /// ```
+mod attributes;
+mod document;
+mod enums;
 mod indexable;
 mod into_fields;
+mod paths;
+mod serde_attributes;
+use document::impl_document;
 use indexable::impl_indexable;
 use into_fields::impl_into_fields;
 
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(Indexable)]
+#[proc_macro_derive(Indexable, attributes(sedk))]
 pub fn indexable(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     impl_indexable(input).into()
 }
 
-#[proc_macro_derive(IntoFields)]
+#[proc_macro_derive(IntoFields, attributes(sedk))]
 pub fn into_fields(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     impl_into_fields(input).into()
-}
\ No newline at end of file
+}
+
+#[proc_macro_derive(Document, attributes(sedk))]
+pub fn document(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    impl_document(input).into()
+}
@@ -0,0 +1,135 @@
+//! The typed paths `#[derive(IntoFields)]` generates alongside a mapping,
+//! following the same attributes so that each path names a field the mapping
+//! has.
+use crate::attributes::FieldAttributes;
+use crate::into_fields::{bounded_generics, json_name};
+use crate::serde_attributes::{SerdeContainer, SerdeField};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::punctuated::Punctuated;
+use syn::{ext::IdentExt, parse_quote, spanned::Spanned, DeriveInput, Field, Type};
+
+/// The generated paths, split between the items users name, which go beside
+/// the type, and the impls, which go in the block that names sedk.
+#[derive(Default)]
+pub struct Paths {
+    pub items: TokenStream,
+    pub impls: TokenStream,
+}
+
+// an impl of `FieldPaths` for the derived type
+fn field_paths(input: &DeriveInput, paths: TokenStream) -> TokenStream {
+    let name = &input.ident;
+    let generics = bounded_generics(&input.generics, parse_quote!(_sedk::dsl::paths::FieldPaths));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics _sedk::dsl::paths::FieldPaths for #name #ty_generics #where_clause {
+            type Paths = #paths;
+        }
+    }
+}
+
+/// Types with no fields of their own have the paths of another type.
+pub fn delegated_paths(input: &DeriveInput, paths: TokenStream) -> Paths {
+    Paths {
+        items: TokenStream::new(),
+        impls: field_paths(input, paths),
+    }
+}
+
+/// A struct's paths, a `<Struct>Fields` with an accessor for each field.
+pub fn struct_paths<T>(
+    input: &DeriveInput,
+    struct_fields: &Punctuated<Field, T>,
+    attributes: &[(FieldAttributes, SerdeField)],
+    container: &SerdeContainer,
+) -> Paths {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let paths_name = format_ident!("{}Fields", name.unraw());
+    let doc = format!(
+        "Typed paths to the fields of [`{}`]'s mapping.",
+        name.unraw()
+    );
+    let generics = &input.generics;
+    let where_clause = &generics.where_clause;
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let bounded = bounded_generics(generics, parse_quote!(_sedk::dsl::paths::FieldPaths));
+    let (impl_generics, _, bounded_where) = bounded.split_for_impl();
+    let accessors = struct_fields
+        .iter()
+        .zip(attributes)
+        .filter(|(_, (attributes, serde))| attributes.skip.is_none() && !serde.skip)
+        .map(|(field, (attributes, serde))| {
+            let accessor = field.ident.as_ref().unwrap();
+            let paths = accessor_paths(&field.ty, attributes);
+            let path = if serde.flatten {
+                // a flattened field's fields are the struct's own
+                quote! {self.path.clone()}
+            } else {
+                let json_name = json_name(field, attributes, serde, container.rename_all);
+                quote! {_sedk::dsl::paths::child(&self.path, #json_name)}
+            };
+            quote! {
+                pub fn #accessor(&self) -> #paths {
+                    _sedk::dsl::paths::PathNode::at(#path)
+                }
+            }
+        });
+
+    let items = quote! {
+        #[doc = #doc]
+        #vis struct #paths_name #generics #where_clause {
+            path: ::std::string::String,
+            document: ::std::marker::PhantomData<fn() -> #name #ty_generics>,
+        }
+    };
+    let impls = field_paths(input, quote! {#paths_name #ty_generics});
+    let impls = quote! {
+        #impls
+
+        impl #impl_generics #name #ty_generics #bounded_where {
+            /// Typed paths to the fields of this type's mapping.
+            pub fn fields() -> #paths_name #ty_generics {
+                _sedk::dsl::paths::PathNode::at(::std::string::String::new())
+            }
+        }
+
+        impl #impl_generics #paths_name #ty_generics #bounded_where {
+            #(#accessors)*
+        }
+
+        #[automatically_derived]
+        impl #impl_generics _sedk::dsl::paths::PathNode for #paths_name #ty_generics #bounded_where {
+            fn at(path: ::std::string::String) -> Self {
+                #paths_name {path, document: ::std::marker::PhantomData}
+            }
+
+            fn path(&self) -> &str {
+                &self.path
+            }
+        }
+    };
+    Paths { items, impls }
+}
+
+// the type of path a field has, which its attributes may change
+fn accessor_paths(field_type: &Type, attributes: &FieldAttributes) -> TokenStream {
+    let mapping = &attributes.mapping;
+    let inferred = quote_spanned! {field_type.span()=>
+        <#field_type as _sedk::dsl::paths::FieldPaths>::Paths
+    };
+    if attributes.paths.as_ref().is_some_and(|paths| !paths.value) {
+        quote! {_sedk::dsl::paths::ObjectPath}
+    } else if let Some(kind) = mapping.path_kind() {
+        quote! {_sedk::dsl::paths::FieldPath<_sedk::dsl::paths::kind::#kind>}
+    } else if mapping.analyzer.is_some() {
+        quote! {<#inferred as _sedk::dsl::paths::Retyped>::Text}
+    } else if mapping.normalizer.is_some() {
+        quote! {<#inferred as _sedk::dsl::paths::Retyped>::Keyword}
+    } else {
+        inferred
+    }
+}
@@ -0,0 +1,199 @@
+//! The serde attributes that change the json a document serializes to, so
+//! that mappings use the same names. Attributes serde itself would reject are
+//! ignored here and left for serde to report.
+use syn::{Attribute, Lit, Meta, NestedMeta};
+
+/// `rename_all` conventions, as serde names them.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_str(rule: &str) -> Option<RenameRule> {
+        Some(match rule {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return None,
+        })
+    }
+
+    /// Renames a field, which is written in snake case.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut renamed = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        renamed.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        renamed.push(c);
+                    }
+                }
+                renamed
+            }
+            // as serde does it, so `_private` becomes `private`
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply_to_field(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+
+    /// Renames a variant, which is written in pascal case.
+    pub fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::Pascal => variant.to_string(),
+            RenameRule::Lower => variant.to_ascii_lowercase(),
+            RenameRule::Upper => variant.to_ascii_uppercase(),
+            RenameRule::Camel => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::Snake
+            | RenameRule::ScreamingSnake
+            | RenameRule::Kebab
+            | RenameRule::ScreamingKebab => {
+                let mut snake = String::new();
+                for (i, c) in variant.char_indices() {
+                    if c.is_uppercase() && i > 0 {
+                        snake.push('_');
+                    }
+                    snake.push(c.to_ascii_lowercase());
+                }
+                match self {
+                    RenameRule::Snake => snake,
+                    other => other.apply_to_field(&snake),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SerdeContainer {
+    pub rename_all: Option<RenameRule>,
+    /// How the fields of an enum's struct variants are renamed.
+    pub rename_all_fields: Option<RenameRule>,
+    /// The field an internally tagged struct or enum records its name in.
+    pub tag: Option<String>,
+    /// The field an adjacently tagged enum keeps its variant's data in.
+    pub content: Option<String>,
+    pub untagged: bool,
+}
+
+#[derive(Default)]
+pub struct SerdeField {
+    pub rename: Option<String>,
+    pub skip: bool,
+    pub flatten: bool,
+}
+
+// the options inside every `#[serde(...)]`
+fn serde_options(attrs: &[Attribute]) -> Vec<Meta> {
+    attrs
+        .iter()
+        .filter(|a| a.path.is_ident("serde"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|nested| match nested {
+            NestedMeta::Meta(meta) => Some(meta),
+            NestedMeta::Lit(_) => None,
+        })
+        .collect()
+}
+
+// the serialized half of `name = "..."` or `name(serialize = "...")`
+fn serialize_name(meta: &Meta) -> Option<String> {
+    match meta {
+        Meta::NameValue(nv) => string(&nv.lit),
+        Meta::List(list) => list.nested.iter().find_map(|nested| match nested {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("serialize") => {
+                string(&nv.lit)
+            }
+            _ => None,
+        }),
+        Meta::Path(_) => None,
+    }
+}
+
+fn string(lit: &Lit) -> Option<String> {
+    match lit {
+        Lit::Str(s) => Some(s.value()),
+        _ => None,
+    }
+}
+
+impl SerdeContainer {
+    pub fn parse(attrs: &[Attribute]) -> SerdeContainer {
+        let mut container = SerdeContainer::default();
+        for meta in serde_options(attrs) {
+            let path = meta.path();
+            let rule = || {
+                serialize_name(&meta)
+                    .as_deref()
+                    .and_then(RenameRule::from_str)
+            };
+            if path.is_ident("rename_all") {
+                container.rename_all = rule();
+            } else if path.is_ident("rename_all_fields") {
+                container.rename_all_fields = rule();
+            } else if path.is_ident("tag") {
+                container.tag = serialize_name(&meta);
+            } else if path.is_ident("content") {
+                container.content = serialize_name(&meta);
+            } else if path.is_ident("untagged") {
+                container.untagged = true;
+            }
+        }
+        container
+    }
+}
+
+impl SerdeField {
+    pub fn parse(attrs: &[Attribute]) -> SerdeField {
+        let mut field = SerdeField::default();
+        for meta in serde_options(attrs) {
+            let path = meta.path();
+            if path.is_ident("rename") {
+                field.rename = serialize_name(&meta);
+            } else if path.is_ident("skip") || path.is_ident("skip_serializing") {
+                field.skip = true;
+            } else if path.is_ident("flatten") {
+                field.flatten = true;
+            }
+        }
+        field
+    }
+}
@@ -0,0 +1,190 @@
+// the documents are only ever mapped, never read
+#![allow(dead_code)]
+
+use sedk::{Analyzer, Field, FieldType, IntoFields, Normalizer};
+use std::collections::{BTreeMap, HashMap};
+
+fn english() -> Analyzer {
+    Analyzer {
+        name: "english".to_string(),
+        ..Analyzer::default()
+    }
+}
+
+fn lowercase() -> Normalizer {
+    Normalizer {
+        name: "lowercase".to_string(),
+        ..Normalizer::default()
+    }
+}
+
+#[derive(IntoFields)]
+pub struct Comment {
+    #[sedk(analyzer = english)]
+    body: String,
+}
+
+#[derive(IntoFields)]
+pub struct Post {
+    #[sedk(analyzer = english, subfield(name = "raw", normalizer = lowercase))]
+    title: String,
+    #[sedk(rename = "sku", type = "keyword")]
+    product_code: String,
+    #[sedk(skip)]
+    cache_key: String,
+    #[sedk(index = false, copy_to = "all", copy_to = "numbers")]
+    views: i64,
+    #[sedk(nested)]
+    comment: Comment,
+    author: Comment,
+}
+
+fn text(analyzer: Analyzer) -> FieldType {
+    FieldType::Text {
+        analyzer,
+        search_analyzer: None,
+    }
+}
+
+#[test]
+fn attributes_shape_the_mapping() {
+    let mut title = Field::new("title", text(english()));
+    title.fields = vec![
+        Field::new(
+            "keyword",
+            FieldType::Keyword {
+                normalizer: Normalizer::default(),
+                ignore_above: None,
+            },
+        ),
+        Field::new(
+            "raw",
+            FieldType::Keyword {
+                normalizer: lowercase(),
+                ignore_above: None,
+            },
+        ),
+    ];
+    let sku = Field::new(
+        "sku",
+        FieldType::Keyword {
+            normalizer: Normalizer::default(),
+            ignore_above: None,
+        },
+    );
+    let mut views = Field::new("views", FieldType::Long);
+    views.index = false;
+    views.copy_to = vec!["all".to_string(), "numbers".to_string()];
+    let mut body = Field::new("body", text(english()));
+    body.fields = vec![Field::new(
+        "keyword",
+        FieldType::Keyword {
+            normalizer: Normalizer::default(),
+            ignore_above: None,
+        },
+    )];
+    let comment = Field::new(
+        "comment",
+        FieldType::Nested {
+            properties: vec![body.clone()],
+        },
+    );
+    let mut author = body;
+    author.name = "author.body".to_string();
+
+    assert_eq!(
+        Post::into_fields(None),
+        vec![title, sku, views, comment, author]
+    );
+}
+
+#[test]
+fn prefixes_apply_to_renamed_fields() {
+    let fields = Post::into_fields(Some("post"));
+    let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "post.title",
+            "post.sku",
+            "post.views",
+            "post.comment",
+            "post.author.body"
+        ]
+    );
+}
+
+#[derive(IntoFields)]
+pub struct Listing {
+    tags: Vec<String>,
+    #[sedk(nested)]
+    comments: Vec<Comment>,
+    rating: Option<u32>,
+    #[sedk(type = "flattened")]
+    labels: HashMap<String, String>,
+    prices: BTreeMap<String, f64>,
+}
+
+#[test]
+fn containers_and_maps() {
+    let fields = Listing::into_fields(None);
+    assert_eq!(fields[0], String::into_fields(Some("tags"))[0]);
+    assert!(
+        matches!(&fields[1].field_type, FieldType::Nested { properties } if properties.len() == 1)
+    );
+    assert_eq!(fields[2], Field::new("rating", FieldType::Long));
+    assert_eq!(fields[3], Field::new("labels", FieldType::Flattened));
+    assert_eq!(
+        fields[4],
+        Field::new(
+            "prices",
+            FieldType::Object {
+                templates: vec![Field::new("prices.*", FieldType::Double)]
+            }
+        )
+    );
+}
+
+#[derive(IntoFields)]
+pub struct Visit {
+    #[sedk(type = "date", format = "strict_date||epoch_millis")]
+    day: String,
+    #[sedk(type = "date")]
+    at: String,
+    #[sedk(type = "ip")]
+    client: String,
+    server: std::net::IpAddr,
+    #[sedk(type = "keyword", ignore_above = 256)]
+    referrer: String,
+    #[sedk(type = "dense_vector", dims = 4)]
+    embedding: Vec<f32>,
+    #[sedk(type = "scaled_float", scaling_factor = 100)]
+    spend: f64,
+}
+
+#[test]
+fn dates_addresses_and_vectors() {
+    let fields = Visit::into_fields(None);
+    let format = Some("strict_date||epoch_millis".to_string());
+    assert_eq!(fields[0], Field::new("day", FieldType::Date { format }));
+    assert_eq!(fields[1], Field::new("at", FieldType::Date { format: None }));
+    assert_eq!(fields[2], Field::new("client", FieldType::Ip));
+    assert_eq!(fields[3], Field::new("server", FieldType::Ip));
+    assert_eq!(
+        fields[4],
+        Field::keyword("referrer").ignore_above(256).build().unwrap()
+    );
+    assert_eq!(
+        fields[5],
+        Field::new("embedding", FieldType::DenseVector { dims: 4 })
+    );
+    assert_eq!(
+        fields[6],
+        Field::new(
+            "spend",
+            FieldType::ScaledFloat {
+                scaling_factor: 100.0
+            }
+        )
+    );
+}
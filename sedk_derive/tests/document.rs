@@ -0,0 +1,64 @@
+use sedk::{Document, IntoFields};
+use std::borrow::Cow;
+
+#[derive(IntoFields, Document)]
+pub struct Product {
+    #[sedk(id, type = "keyword")]
+    sku: String,
+    #[sedk(routing)]
+    shop: u32,
+    #[sedk(skip, version)]
+    revision: u32,
+}
+
+#[derive(Document)]
+pub struct Review<'a, T> {
+    #[sedk(id)]
+    id: u64,
+    #[sedk(routing)]
+    product: Option<&'a str>,
+    #[sedk(version)]
+    revision: Option<u64>,
+    body: T,
+}
+
+#[test]
+fn documents_identify_themselves() {
+    let product = Product {
+        sku: "ab-12".to_string(),
+        shop: 7,
+        revision: 3,
+    };
+    assert!(matches!(product.id(), Cow::Borrowed("ab-12")));
+    assert_eq!(product.routing().as_deref(), Some("7"));
+    assert_eq!(product.version(), Some(3));
+    // skipped fields identify documents without being mapped
+    assert_eq!(Product::into_fields(None).len(), 2);
+
+    let review = Review {
+        id: 12,
+        product: Some("ab-12"),
+        revision: None,
+        body: (),
+    };
+    assert_eq!(review.id(), "12");
+    assert_eq!(review.routing().as_deref(), Some("ab-12"));
+    assert_eq!(review.version(), None);
+    assert_eq!(review.body, ());
+}
+
+#[derive(Document)]
+pub struct Minimal {
+    #[sedk(id)]
+    id: String,
+}
+
+#[test]
+fn routing_and_version_are_optional() {
+    let minimal = Minimal {
+        id: "m".to_string(),
+    };
+    assert_eq!(minimal.id(), "m");
+    assert_eq!(minimal.routing(), None);
+    assert_eq!(minimal.version(), None);
+}
@@ -0,0 +1,193 @@
+// the documents are only ever mapped, never read
+#![allow(dead_code)]
+
+use sedk::{
+    ConflictingFields, Field, FieldType, IndexMapping, IndexSettings, IntoFields, Normalizer,
+};
+use serde::Serialize;
+use serde_json::{json, to_value, Value};
+
+fn keyword(name: &str) -> Field {
+    Field::new(
+        name,
+        FieldType::Keyword {
+            normalizer: Normalizer::default(),
+            ignore_above: None,
+        },
+    )
+}
+
+fn names(fields: &[Field]) -> Vec<&str> {
+    fields.iter().map(|f| f.name.as_str()).collect()
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(rename_all = "snake_case")]
+#[sedk(allowed_values)]
+pub enum Status {
+    Draft,
+    InReview,
+    #[serde(rename = "live")]
+    Published,
+    #[serde(skip)]
+    Deleted,
+}
+
+#[derive(Serialize, IntoFields)]
+pub struct Refund {
+    amount: i64,
+    reason: String,
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind", rename_all_fields = "camelCase")]
+pub enum Event {
+    Created {
+        created_by: String,
+        at: i64,
+    },
+    Updated {
+        at: i64,
+        changed_fields: Vec<String>,
+    },
+    Refunded(Refund),
+    Archived,
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "t", content = "c")]
+pub enum Payload {
+    Text(String),
+    Image { url: String, width: u32 },
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "type")]
+pub enum Flag {
+    On,
+    Off,
+}
+
+#[derive(Serialize, IntoFields)]
+pub struct Account {
+    id: i64,
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind")]
+pub enum Owner {
+    Person { id: String },
+    Company(Account),
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind")]
+pub enum Reference {
+    Draft { id: String },
+    Final { id: Option<std::string::String> },
+}
+
+#[test]
+fn unit_enums_are_keywords() {
+    let mut status = keyword("order.status");
+    status.meta.insert(
+        "allowed_values".to_string(),
+        "draft, in_review, live".to_string(),
+    );
+    assert_eq!(Status::into_fields(Some("order.status")), vec![status]);
+    assert_eq!(Status::into_fields(None), vec![]);
+}
+
+#[test]
+fn internally_tagged_enums_union_their_variants() {
+    let fields = Event::into_fields(Some("event"));
+    assert_eq!(
+        names(&fields),
+        vec![
+            "event.kind",
+            "event.createdBy",
+            "event.at",
+            "event.changedFields",
+            "event.amount",
+            "event.reason",
+        ]
+    );
+    assert_eq!(fields[0], keyword("event.kind"));
+    assert_eq!(Flag::into_fields(None), vec![keyword("type")]);
+}
+
+#[test]
+fn adjacently_tagged_enums_nest_their_content() {
+    let fields = Payload::into_fields(None);
+    assert_eq!(names(&fields), vec!["t", "c", "c.url", "c.width"]);
+    assert_eq!(fields[3], Field::new("c.width", FieldType::Long));
+}
+
+#[test]
+fn newtype_variants_conflict_with_other_variants() {
+    let fields = Owner::into_fields(None);
+    assert_eq!(names(&fields), vec!["kind", "id", "id"]);
+    let mapping = IndexMapping {
+        fields,
+        plugins: vec![],
+        settings: IndexSettings::default(),
+        aliases: vec![],
+        dynamic: None,
+    };
+    assert_eq!(
+        mapping.check_fields(),
+        Err(ConflictingFields(vec!["id".to_owned()]))
+    );
+    assert!(to_value(&mapping).is_err());
+}
+
+#[test]
+fn variants_agree_on_mappings_not_types() {
+    assert_eq!(
+        Reference::into_fields(None),
+        [vec![keyword("kind")], String::into_fields(Some("id"))].concat()
+    );
+}
+
+// every key holding a value rather than an object, with its full path
+fn json_paths(value: &Value, prefix: &str, paths: &mut Vec<String>) {
+    if let Value::Object(map) = value {
+        for (key, value) in map {
+            match value {
+                Value::Object(_) => json_paths(value, &format!("{}{}.", prefix, key), paths),
+                _ => paths.push(format!("{}{}", prefix, key)),
+            }
+        }
+    }
+}
+
+#[test]
+fn mappings_cover_serialized_variants() {
+    let mapped = Event::into_fields(None);
+    for event in [
+        Event::Created {
+            created_by: "ana".to_string(),
+            at: 1,
+        },
+        Event::Updated {
+            at: 2,
+            changed_fields: vec!["title".to_string()],
+        },
+        Event::Refunded(Refund {
+            amount: 3,
+            reason: "damaged".to_string(),
+        }),
+        Event::Archived,
+    ] {
+        let mut paths = Vec::new();
+        json_paths(&to_value(&event).unwrap(), "", &mut paths);
+        for path in paths {
+            assert!(
+                names(&mapped).contains(&path.as_str()),
+                "{} isn't mapped",
+                path
+            );
+        }
+    }
+    assert_eq!(to_value(Status::InReview).unwrap(), json!("in_review"));
+}
@@ -1,5 +1,4 @@
-use sedk::{IntoFields, Field};
-use sedk_derive::IntoFields;
+use sedk::IntoFields;
 
 pub struct Child {
     integer: i32,
@@ -12,4 +11,4 @@ pub struct Parent {
 }
 
 fn main() {
-}
\ No newline at end of file
+}
@@ -0,0 +1,34 @@
+use sedk::{Analyzer, IntoFields};
+
+fn english() -> Analyzer {
+    Analyzer::default()
+}
+
+#[derive(IntoFields)]
+pub struct Document {
+    #[sedk(type = "keyword", analyzer = english)]
+    title: String,
+    #[sedk(skip, rename = "text")]
+    body: String,
+    #[sedk(rename = "a", rename = "b")]
+    sku: String,
+    #[sedk(subfield(name = "raw", type = "keyword"), subfield(name = "raw", type = "text"))]
+    tags: String,
+    #[sedk(type = "keyword", format = "strict_date")]
+    released: String,
+    #[sedk(ignore_above = 256)]
+    summary: String,
+    #[sedk(type = "dense_vector")]
+    embedding: Vec<f32>,
+    #[sedk(dims = 3)]
+    position: Vec<f32>,
+    #[sedk(type = "scaled_float")]
+    price: f64,
+    #[sedk(scaling_factor = 10)]
+    discount: f64,
+    #[sedk(type = "scaled_float", scaling_factor = 0)]
+    tax: f64,
+}
+
+fn main() {
+}
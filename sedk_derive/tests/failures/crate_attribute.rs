@@ -0,0 +1,22 @@
+use sedk::IntoFields;
+
+#[derive(IntoFields)]
+#[sedk(crate = "not a path")]
+pub struct Invalid {
+    value: i32,
+}
+
+#[derive(IntoFields)]
+#[sedk(crate = "sedk", crate = "::sedk")]
+pub struct Duplicated {
+    value: i32,
+}
+
+#[derive(IntoFields)]
+#[sedk(crate = "missing")]
+pub struct Missing {
+    value: i32,
+}
+
+fn main() {
+}
@@ -0,0 +1,42 @@
+use sedk::Document;
+
+#[derive(Document)]
+pub struct NoId {
+    name: String,
+}
+
+#[derive(Document)]
+pub struct TwoIds {
+    #[sedk(id)]
+    sku: String,
+    #[sedk(id)]
+    ean: String,
+}
+
+#[derive(Document)]
+pub struct DuplicateRouting {
+    #[sedk(id, routing, routing)]
+    sku: String,
+}
+
+#[derive(Document)]
+pub struct Tuple(#[sedk(id)] String);
+
+#[derive(Document)]
+pub struct FloatVersion {
+    #[sedk(id)]
+    sku: String,
+    #[sedk(version)]
+    version: f64,
+}
+
+pub struct Unidentifiable;
+
+#[derive(Document)]
+pub struct UnidentifiableId {
+    #[sedk(id)]
+    id: Unidentifiable,
+}
+
+fn main() {
+}
@@ -0,0 +1,32 @@
+use sedk::IntoFields;
+use serde::Serialize;
+
+#[derive(Serialize, IntoFields)]
+pub enum Untagged {
+    Empty,
+    Text(String),
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind")]
+pub enum Conflicting {
+    Price { amount: f64 },
+    Count { amount: i64 },
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind")]
+#[sedk(allowed_values)]
+pub enum Tagged {
+    Price { amount: f64 },
+}
+
+#[derive(Serialize, IntoFields)]
+#[sedk(allowed_values)]
+pub enum TooLong {
+    SubmittedForEditorialReview,
+    ReturnedToAuthorWithComments,
+}
+
+fn main() {
+}
@@ -0,0 +1,14 @@
+use sedk::IntoFields;
+
+pub struct Unmapped {
+    value: i32,
+}
+
+#[derive(IntoFields)]
+pub struct Page<T> {
+    items: Vec<T>,
+}
+
+fn main() {
+    Page::<Unmapped>::into_fields(None);
+}
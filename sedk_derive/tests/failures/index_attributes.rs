@@ -0,0 +1,34 @@
+use sedk::{Indexable, IntoFields};
+
+#[derive(IntoFields, Indexable)]
+#[sedk(index = "Products")]
+pub struct Uppercase {
+    price: f64,
+}
+
+#[derive(IntoFields, Indexable)]
+#[sedk(index = "_products", alias = "products live")]
+pub struct InvalidNames {
+    price: f64,
+}
+
+#[derive(IntoFields, Indexable)]
+#[sedk(shards = 0)]
+pub struct NoShards {
+    price: f64,
+}
+
+#[derive(IntoFields, Indexable)]
+#[sedk(replicas = -1)]
+pub struct NegativeReplicas {
+    price: f64,
+}
+
+#[derive(IntoFields, Indexable)]
+#[sedk(dynamic = "sometimes")]
+pub struct UnknownDynamic {
+    price: f64,
+}
+
+fn main() {
+}
@@ -0,0 +1,30 @@
+use sedk::IntoFields;
+
+#[derive(IntoFields)]
+pub struct LongMeta {
+    #[sedk(meta(owner = "the team that looks after search, ingestion and the clusters"))]
+    title: String,
+    #[sedk(meta(the_name_of_the_owning_team = "search"))]
+    body: String,
+}
+
+#[derive(IntoFields)]
+pub struct TooManyEntries {
+    #[sedk(meta(a = "1", b = "2", c = "3", d = "4", e = "5", f = "6"))]
+    title: String,
+}
+
+#[derive(IntoFields)]
+pub struct DuplicateEntries {
+    #[sedk(meta(owner = "search"), meta(owner = "ingestion"))]
+    title: String,
+}
+
+#[derive(IntoFields)]
+pub struct SkippedMeta {
+    #[sedk(skip, meta(owner = "search"))]
+    title: String,
+}
+
+fn main() {
+}
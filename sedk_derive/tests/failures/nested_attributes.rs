@@ -0,0 +1,17 @@
+use sedk::IntoFields;
+
+#[derive(IntoFields)]
+pub struct Comment {
+    body: String,
+}
+
+#[derive(IntoFields)]
+pub struct Post {
+    #[sedk(nested, type = "keyword")]
+    comment: Comment,
+    #[sedk(nested, copy_to = "all")]
+    reply: Comment,
+}
+
+fn main() {
+}
@@ -0,0 +1,28 @@
+use sedk::{IntoFields, Normalizer};
+use serde::Serialize;
+
+fn lowercase() -> Normalizer {
+    Normalizer::default()
+}
+
+#[derive(Serialize, IntoFields)]
+pub struct Author {
+    name: String,
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind")]
+pub enum Reviewer {
+    Person { name: String },
+}
+
+#[derive(IntoFields)]
+pub struct Post {
+    #[sedk(subfield(name = "raw", normalizer = lowercase))]
+    author: Author,
+    #[sedk(meta(owner = "search"))]
+    reviewers: Vec<Reviewer>,
+}
+
+fn main() {
+}
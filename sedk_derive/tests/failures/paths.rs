@@ -0,0 +1,16 @@
+use sedk::dsl::paths::{kind::Keyword, FieldPath};
+use sedk::IntoFields;
+
+#[derive(IntoFields)]
+pub struct Product {
+    title: String,
+    #[sedk(skip)]
+    cache_key: String,
+}
+
+fn term(_path: FieldPath<Keyword>) {}
+
+fn main() {
+    term(Product::fields().title().field());
+    Product::fields().cache_key();
+}
@@ -0,0 +1,17 @@
+use sedk::IntoFields;
+use serde::Serialize;
+
+#[derive(Serialize, IntoFields)]
+pub struct Audit {
+    created_by: String,
+}
+
+#[derive(Serialize, IntoFields)]
+pub struct Product {
+    #[serde(flatten)]
+    #[sedk(rename = "audit")]
+    audit: Audit,
+}
+
+fn main() {
+}
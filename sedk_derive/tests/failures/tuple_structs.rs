@@ -0,0 +1,23 @@
+use sedk::IntoFields;
+
+#[derive(IntoFields)]
+pub struct Point(f64, f64);
+
+#[derive(IntoFields)]
+pub struct Marker;
+
+#[derive(IntoFields)]
+pub struct Sku(#[sedk(type = "keyword")] String);
+
+#[derive(IntoFields)]
+#[sedk(allowed_values)]
+pub struct Code(String);
+
+#[derive(IntoFields)]
+pub union Number {
+    integer: i64,
+    float: f64,
+}
+
+fn main() {
+}
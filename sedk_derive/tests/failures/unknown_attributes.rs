@@ -0,0 +1,16 @@
+use sedk::IntoFields;
+
+#[derive(IntoFields)]
+pub struct Document {
+    #[sedk(analyser = english)]
+    title: String,
+    #[sedk(type = "string")]
+    body: String,
+    #[sedk(subfield(type = "keyword"))]
+    tags: String,
+    #[sedk(subfield(name = "raw", type = "keyword", colour = "red"))]
+    sku: String,
+}
+
+fn main() {
+}
@@ -0,0 +1,41 @@
+// the documents are only ever mapped, never read
+#![allow(dead_code)]
+
+use sedk::{Dynamic, IndexSettings, Indexable, IntoFields};
+
+#[derive(IntoFields, Indexable)]
+#[sedk(index = "products", alias = "products_live", shards = 3, replicas = 1)]
+#[sedk(dynamic = "strict")]
+pub struct Product {
+    price: f64,
+}
+
+#[derive(IntoFields, Indexable)]
+pub struct Unconfigured {
+    price: f64,
+}
+
+#[test]
+fn index_attributes() {
+    assert_eq!(Product::INDEX_NAME, "products");
+    assert_eq!(Product::ALIAS, "products_live");
+    let mapping = Product::index_mapping();
+    assert_eq!(mapping.fields, Product::into_fields(None));
+    assert_eq!(
+        mapping.settings,
+        IndexSettings {
+            number_of_shards: Some(3),
+            number_of_replicas: Some(1),
+        }
+    );
+    assert_eq!(mapping.aliases, vec!["products_live".to_string()]);
+    assert_eq!(mapping.dynamic, Some(Dynamic::Strict));
+}
+
+#[test]
+fn settings_default_to_the_clusters() {
+    let mapping = Unconfigured::index_mapping();
+    assert_eq!(mapping.settings, IndexSettings::default());
+    assert!(mapping.aliases.is_empty());
+    assert_eq!(mapping.dynamic, None);
+}
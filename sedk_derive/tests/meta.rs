@@ -0,0 +1,92 @@
+// the documents are only ever mapped, never read
+#![allow(dead_code)]
+
+use sedk::{data_dictionary, Indexable, IntoFields};
+use std::collections::BTreeMap;
+
+#[derive(IntoFields)]
+pub struct Review {
+    /// Stars out of five.
+    stars: u8,
+}
+
+#[derive(IntoFields, Indexable)]
+pub struct Request {
+    /// How long the request took to serve.
+    ///
+    /// Measured from the first byte received to the last byte sent, so it
+    /// includes time spent queueing.
+    #[sedk(meta(owner = "platform", unit = "ms"))]
+    duration: i64,
+    /// The path that was requested, without its query string or fragment.
+    path: String,
+    /// Set explicitly instead.
+    #[sedk(meta(description = "The response's status"))]
+    status: u16,
+    #[sedk(meta(owner = "platform"))]
+    host: String,
+    /// Reviews left by users.
+    #[sedk(nested)]
+    reviews: Vec<Review>,
+    /// Objects have no meta of their own.
+    review: Review,
+}
+
+fn meta(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn doc_comments_describe_fields() {
+    let fields: BTreeMap<String, BTreeMap<String, String>> = Request::into_fields(None)
+        .into_iter()
+        .map(|field| (field.name, field.meta))
+        .collect();
+    assert_eq!(
+        fields["duration"],
+        meta(&[
+            ("description", "How long the request took to serve."),
+            ("owner", "platform"),
+            ("unit", "ms"),
+        ])
+    );
+    // shortened to the 50 characters elasticsearch allows
+    assert_eq!(
+        fields["path"],
+        meta(&[(
+            "description",
+            "The path that was requested, without its query…"
+        )])
+    );
+    assert_eq!(
+        fields["status"],
+        meta(&[("description", "The response's status")])
+    );
+    assert_eq!(fields["host"], meta(&[("owner", "platform")]));
+    assert_eq!(
+        fields["reviews"],
+        meta(&[("description", "Reviews left by users.")])
+    );
+    assert_eq!(
+        fields["review.stars"],
+        meta(&[("description", "Stars out of five.")])
+    );
+}
+
+#[test]
+fn mappings_render_as_dictionaries() {
+    let dictionary = data_dictionary(&Request::index_mapping());
+    let rows: Vec<&str> = dictionary.lines().collect();
+    assert_eq!(
+        rows[2],
+        "| `duration` | long |  |  | How long the request took to serve. | owner: platform, unit: ms |"
+    );
+    assert_eq!(
+        rows[3],
+        "| `path` | text | sedk_default_normalizer | `path.keyword` (keyword) | The path that was requested, without its query… |  |"
+    );
+    assert!(rows.contains(&"| `reviews.stars` | short |  |  | Stars out of five. |  |"));
+}
@@ -0,0 +1,39 @@
+use sedk::{Field, FieldType, IndexMapping, Indexable, IntoFields};
+
+#[derive(IntoFields)]
+pub struct Product {
+    price: f64,
+}
+
+#[derive(IntoFields, Indexable)]
+pub struct Page<T> {
+    total: u32,
+    items: Vec<T>,
+}
+
+#[derive(IntoFields)]
+pub struct Excerpt<'a, T: Clone, const N: usize>
+where
+    T: Default,
+{
+    title: &'a str,
+    scores: [T; N],
+}
+
+fn main() {
+    assert_eq!(
+        Page::<Product>::into_fields(None),
+        vec![
+            Field::new("total", FieldType::Long),
+            Field::new("items.price", FieldType::Double),
+        ]
+    );
+    assert_eq!(
+        Page::<i32>::index_mapping().fields,
+        Page::<i32>::into_fields(None)
+    );
+    assert_eq!(
+        Excerpt::<i64, 3>::into_fields(Some("excerpt"))[1],
+        Field::new("excerpt.scores", FieldType::Long)
+    );
+}
@@ -0,0 +1,33 @@
+// the derives don't need sedk's types in scope, so their names are free
+use sedk::{Indexable, IntoFields};
+
+pub struct Field;
+
+#[derive(IntoFields, Indexable)]
+pub struct Child {
+    integer: i32,
+}
+
+#[derive(IntoFields, Indexable)]
+pub struct Parent {
+    child: Child,
+    #[sedk(type = "keyword")]
+    code: String,
+}
+
+mod search {
+    // a crate that re-exports sedk under its own name
+    pub use sedk as engine;
+}
+
+#[derive(search::engine::IntoFields)]
+#[sedk(crate = "search::engine")]
+pub struct Reexported {
+    parent: Parent,
+}
+
+fn main() {
+    let _ = Field;
+    assert_eq!(Parent::index_mapping().fields.len(), 2);
+    assert_eq!(Reexported::into_fields(None)[0].name, "parent.child.integer");
+}
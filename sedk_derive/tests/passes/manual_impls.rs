@@ -0,0 +1,48 @@
+use sedk::dsl::paths::{kind::Numeric, FieldPath, PathNode};
+use sedk::{Field, FieldType, IntoFields};
+
+// mapped by hand, so it has no typed paths of its own
+pub struct Point {
+    lat: f64,
+    lon: f64,
+}
+
+impl IntoFields for Point {
+    fn into_fields(name: Option<&str>) -> Vec<Field> {
+        let name = name.unwrap_or("point");
+        vec![
+            Field::new(&format!("{}.lat", name), FieldType::Double),
+            Field::new(&format!("{}.lon", name), FieldType::Double),
+        ]
+    }
+}
+
+#[derive(IntoFields)]
+pub struct Store {
+    name: String,
+    #[sedk(paths = false)]
+    location: Point,
+}
+
+// without paths, the names the derive would generate are free to use
+#[derive(IntoFields)]
+#[sedk(paths = false)]
+pub struct Place {
+    location: Point,
+}
+
+pub struct PlaceFields;
+
+impl Place {
+    pub fn fields() -> PlaceFields {
+        PlaceFields
+    }
+}
+
+fn main() {
+    let lat: FieldPath<Numeric> = Store::fields().location().field("lat");
+    assert_eq!(lat.path(), "location.lat");
+    assert_eq!(Store::fields().name().keyword().path(), "name.keyword");
+    assert_eq!(Place::into_fields(None).len(), 2);
+    let PlaceFields = Place::fields();
+}
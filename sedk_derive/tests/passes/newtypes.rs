@@ -0,0 +1,49 @@
+use sedk::{Field, FieldType, IntoFields, Normalizer};
+
+#[derive(IntoFields)]
+pub struct Sku(String);
+
+#[derive(IntoFields)]
+pub struct Wrapper<T>(Option<T>);
+
+#[derive(IntoFields)]
+pub struct Product {
+    #[sedk(type = "keyword")]
+    sku: Sku,
+    price: Wrapper<f64>,
+}
+
+#[derive(IntoFields)]
+pub enum Status {
+    Draft,
+    Published,
+}
+
+// newtypes and unit enums have a field of their own to change
+#[derive(IntoFields)]
+pub struct Listing {
+    #[sedk(meta(owner = "search"))]
+    skus: Vec<Sku>,
+    #[sedk(meta(owner = "search"))]
+    price: Option<Wrapper<f64>>,
+    #[sedk(meta(owner = "search"))]
+    status: Status,
+}
+
+fn main() {
+    assert_eq!(Sku::into_fields(Some("sku")), String::into_fields(Some("sku")));
+    assert_eq!(
+        Product::into_fields(None),
+        vec![
+            Field::new(
+                "sku",
+                FieldType::Keyword {
+                    normalizer: Normalizer::default(),
+                    ignore_above: None,
+                }
+            ),
+            Field::new("price", FieldType::Double),
+        ]
+    );
+    assert!(Listing::into_fields(None).iter().all(|f| f.meta["owner"] == "search"));
+}
@@ -0,0 +1,29 @@
+use sedk::IntoFields;
+use serde::Serialize;
+
+#[derive(Serialize, IntoFields)]
+pub struct Account {
+    id: i64,
+}
+
+// variants may spell a field's type differently, as long as it maps the same
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind")]
+pub enum Reference {
+    Draft { id: String },
+    Final { id: Option<std::string::String> },
+}
+
+// a newtype's fields are only known once mapped, so both are kept for
+// `IndexMapping::check_fields` to report
+#[derive(Serialize, IntoFields)]
+#[serde(tag = "kind")]
+pub enum Owner {
+    Person { id: String },
+    Company(Account),
+}
+
+fn main() {
+    assert_eq!(Reference::into_fields(None).len(), 2);
+    assert_eq!(Owner::into_fields(None).len(), 3);
+}
@@ -0,0 +1,125 @@
+// the documents are only ever mapped, never read
+#![allow(dead_code)]
+
+use sedk::dsl::paths::{
+    kind::{Date, Keyword, Numeric, Text},
+    FieldPath, PathNode,
+};
+use sedk::{Analyzer, IntoFields, Normalizer};
+use serde::Serialize;
+use std::collections::HashMap;
+
+fn english() -> Analyzer {
+    Analyzer {
+        name: "english".to_string(),
+        ..Analyzer::default()
+    }
+}
+
+fn lowercase() -> Normalizer {
+    Normalizer {
+        name: "lowercase".to_string(),
+        ..Normalizer::default()
+    }
+}
+
+#[derive(IntoFields, Serialize)]
+pub struct Child {
+    integer: i32,
+    string: String,
+}
+
+#[derive(IntoFields, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Audit {
+    #[sedk(type = "date")]
+    created_at: String,
+}
+
+#[derive(IntoFields, Serialize)]
+pub enum Status {
+    Draft,
+    Published,
+}
+
+#[derive(IntoFields, Serialize)]
+pub struct Sku(String);
+
+#[derive(IntoFields, Serialize)]
+pub struct Parent {
+    nested: Child,
+    #[sedk(nested)]
+    children: Vec<Child>,
+    #[sedk(analyzer = english)]
+    title: String,
+    #[sedk(normalizer = lowercase)]
+    code: String,
+    #[serde(rename = "sku")]
+    product_code: Sku,
+    #[sedk(skip)]
+    cache_key: String,
+    #[serde(flatten)]
+    audit: Audit,
+    status: Status,
+    prices: HashMap<String, f64>,
+    r#type: Option<String>,
+}
+
+#[derive(IntoFields)]
+pub struct Page<T> {
+    items: Vec<T>,
+}
+
+// what a query builder taking a path of one type would look like
+fn term(path: FieldPath<Keyword>) -> String {
+    path.to_string()
+}
+
+#[test]
+fn paths_follow_the_mapping() {
+    let fields = Parent::fields();
+    assert_eq!(
+        term(fields.nested().string().keyword()),
+        "nested.string.keyword"
+    );
+    let integer: FieldPath<Numeric> = fields.children().integer();
+    assert_eq!(integer.as_str(), "children.integer");
+    assert_eq!(fields.children().path(), "children");
+
+    let title: FieldPath<Text> = fields.title().field();
+    assert_eq!(title.as_str(), "title");
+    assert_eq!(fields.title().keyword().as_str(), "title.keyword");
+    let code: FieldPath<Keyword> = fields.code().field();
+    assert_eq!(term(code), "code");
+
+    assert_eq!(fields.product_code().field().as_str(), "sku");
+    let created_at: FieldPath<Date> = fields.audit().created_at();
+    assert_eq!(created_at.as_str(), "createdAt");
+    assert_eq!(term(fields.status()), "status");
+    assert_eq!(fields.prices().key("eur").as_str(), "prices.eur");
+    assert_eq!(fields.r#type().field().as_str(), "type");
+
+    let items: FieldPath<Numeric> = Page::<Child>::fields().items().integer();
+    assert_eq!(items.as_str(), "items.integer");
+}
+
+#[test]
+fn every_path_is_mapped() {
+    let mut names = Vec::new();
+    for field in Parent::into_fields(None) {
+        names.push(field.name.clone());
+        let subfields = field.fields.iter();
+        names.extend(subfields.map(|f| format!("{}.{}", field.name, f.name)));
+    }
+    let fields = Parent::fields();
+    let paths = [
+        fields.nested().string().keyword().to_string(),
+        fields.title().keyword().to_string(),
+        fields.product_code().keyword().to_string(),
+        fields.audit().created_at().to_string(),
+        fields.status().to_string(),
+    ];
+    for path in &paths {
+        assert!(names.contains(path), "{} isn't mapped", path);
+    }
+}
@@ -1,5 +1,6 @@
 #[test]
 fn trybuild_tests() {
     let t = trybuild::TestCases::new();
+    t.pass("tests/passes/*.rs");
     t.compile_fail("tests/failures/*.rs");
 }
@@ -0,0 +1,132 @@
+use sedk::{Field, FieldType, IntoFields, Normalizer};
+use serde::Serialize;
+use serde_json::{to_value, Value};
+use std::collections::BTreeSet;
+
+#[derive(Serialize, IntoFields)]
+#[serde(rename_all = "camelCase")]
+pub struct Price {
+    amount_cents: i64,
+    currency_code: String,
+}
+
+#[derive(Serialize, IntoFields)]
+pub struct Audit {
+    created_by: String,
+    #[serde(rename = "ts")]
+    timestamp: i64,
+}
+
+#[derive(Serialize, IntoFields)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub struct Product {
+    product_name: String,
+    #[serde(rename(serialize = "SKU"))]
+    sku: String,
+    r#type: String,
+    _import_batch: i64,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    cache_key: String,
+    #[serde(skip_serializing)]
+    #[allow(dead_code)]
+    secret: String,
+    list_price: Price,
+    #[serde(flatten)]
+    audit: Audit,
+    #[sedk(nested)]
+    discount: Price,
+}
+
+fn sample() -> Product {
+    let price = || Price {
+        amount_cents: 1999,
+        currency_code: "EUR".to_string(),
+    };
+    Product {
+        product_name: "Kettle".to_string(),
+        sku: "KT-1".to_string(),
+        r#type: "appliance".to_string(),
+        _import_batch: 7,
+        cache_key: "kettle:1".to_string(),
+        secret: "hunter2".to_string(),
+        list_price: price(),
+        audit: Audit {
+            created_by: "importer".to_string(),
+            timestamp: 1_600_000_000,
+        },
+        discount: price(),
+    }
+}
+
+// every field holding values, with nested objects' fields in place of them
+fn mapped_paths(fields: &[Field], prefix: &str, paths: &mut BTreeSet<String>) {
+    for field in fields {
+        let path = prefix.to_string() + &field.name;
+        match &field.field_type {
+            FieldType::Nested { properties } => mapped_paths(properties, &(path + "."), paths),
+            _ => {
+                paths.insert(path);
+            }
+        }
+    }
+}
+
+// every key of `value` that holds a value rather than an object of keys
+fn json_paths(value: &Value, prefix: &str, paths: &mut BTreeSet<String>) {
+    if let Value::Object(map) = value {
+        for (key, value) in map {
+            let path = prefix.to_string() + key;
+            match value {
+                Value::Object(_) => json_paths(value, &(path + "."), paths),
+                _ => {
+                    paths.insert(path);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn mapping_matches_serialized_keys() {
+    let mut mapped = BTreeSet::new();
+    mapped_paths(&Product::into_fields(None), "", &mut mapped);
+    let mut serialized = BTreeSet::new();
+    json_paths(&to_value(sample()).unwrap(), "", &mut serialized);
+
+    assert_eq!(mapped, serialized);
+    assert!(!mapped.contains("cacheKey") && !mapped.contains("secret"));
+}
+
+#[test]
+fn serde_names() {
+    let names: Vec<String> = Product::into_fields(Some("product"))
+        .into_iter()
+        .map(|f| f.name)
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "product.kind",
+            "product.productName",
+            "product.SKU",
+            "product.type",
+            "product.importBatch",
+            "product.listPrice.amountCents",
+            "product.listPrice.currencyCode",
+            "product.created_by",
+            "product.ts",
+            "product.discount",
+        ]
+    );
+    assert_eq!(
+        Product::into_fields(None)[0],
+        Field::new(
+            "kind",
+            FieldType::Keyword {
+                normalizer: Normalizer::default(),
+                ignore_above: None,
+            }
+        )
+    );
+}